@@ -0,0 +1,14 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    // Build metadata shown by `ccuse version`
+    let target = std::env::var("TARGET").unwrap_or_default();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    println!("cargo:rustc-env=CCUSE_BUILD_TARGET={target}");
+    println!("cargo:rustc-env=CCUSE_BUILD_TIMESTAMP={timestamp}");
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,180 @@
+use colored::Colorize;
+use std::collections::{BTreeSet, HashMap};
+
+/// Stage of env resolution that produced a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Phase {
+    /// The env stored in the profile being used
+    BaseEnv,
+    /// Taken from this `--inherit-parent-profile` parent
+    InheritedFrom(String),
+    /// Set by a launch option such as `--env` or `--model-override`
+    CliOverride,
+    /// A `${VAR}` reference was expanded
+    TemplateExpansion,
+}
+
+impl Phase {
+    /// Index of the `--env-diff-report` section this phase is listed under.
+    fn report_section(&self) -> Option<usize> {
+        match self {
+            Self::BaseEnv => None,
+            Self::InheritedFrom(_) => Some(0),
+            Self::CliOverride => Some(1),
+            Self::TemplateExpansion => Some(2),
+        }
+    }
+}
+
+/// One env var change made during resolution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvResolutionStep {
+    pub phase: Phase,
+    pub key: String,
+    pub before: Option<String>,
+    pub after: String,
+}
+
+/// Every change made while resolving a session's env, in order.
+#[derive(Debug, Clone, Default)]
+pub struct EnvResolutionTrace {
+    pub steps: Vec<EnvResolutionStep>,
+}
+
+impl EnvResolutionTrace {
+    /// Record a single change.
+    pub fn record(&mut self, phase: Phase, key: &str, before: Option<&str>, after: &str) {
+        self.steps.push(EnvResolutionStep {
+            phase,
+            key: key.to_string(),
+            before: before.map(str::to_string),
+            after: after.to_string(),
+        });
+    }
+
+    /// Record every key whose value differs between `before` and `after`.
+    ///
+    /// Keys are recorded in sorted order; removed keys are not recorded.
+    pub fn record_changes(
+        &mut self,
+        phase: &Phase,
+        before: &HashMap<String, String>,
+        after: &HashMap<String, String>,
+    ) {
+        let keys: BTreeSet<&String> = after.keys().collect();
+        for key in keys {
+            let old = before.get(key);
+            if old != after.get(key) {
+                self.record(phase.clone(), key, old.map(String::as_str), &after[key]);
+            }
+        }
+    }
+
+    /// Append the steps of a later stage.
+    pub fn extend(&mut self, other: EnvResolutionTrace) {
+        self.steps.extend(other.steps);
+    }
+
+    /// Print the inherited, overridden and expanded values, one section each.
+    pub fn print_report(&self) {
+        let sections = [
+            "Inherited from parent profile:",
+            "Overridden by CLI flags:",
+            "Template expansions:",
+        ];
+
+        for (index, title) in sections.into_iter().enumerate() {
+            println!("{}", title.bold());
+            let steps: Vec<&EnvResolutionStep> = self
+                .steps
+                .iter()
+                .filter(|s| s.phase.report_section() == Some(index))
+                .collect();
+            if steps.is_empty() {
+                println!("  {}", "(none)".dimmed());
+            }
+            for step in steps {
+                let before = step
+                    .before
+                    .as_deref()
+                    .map_or_else(|| "(unset)".dimmed(), |value| value.red());
+                let source = match &step.phase {
+                    Phase::InheritedFrom(parent) => format!(" (from {parent})").dimmed(),
+                    _ => "".normal(),
+                };
+                println!(
+                    "  {}: {before} → {}{source}",
+                    step.key.cyan(),
+                    step.after.green()
+                );
+            }
+            println!();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    fn step(phase: Phase, key: &str, before: Option<&str>, after: &str) -> EnvResolutionStep {
+        EnvResolutionStep {
+            phase,
+            key: key.to_string(),
+            before: before.map(str::to_string),
+            after: after.to_string(),
+        }
+    }
+
+    #[test]
+    fn record_changes_lists_added_and_changed_keys_in_order() {
+        let before = env(&[("KEPT", "1"), ("CHANGED", "old"), ("REMOVED", "x")]);
+        let after = env(&[("KEPT", "1"), ("CHANGED", "new"), ("ADDED", "yes")]);
+
+        let mut trace = EnvResolutionTrace::default();
+        trace.record_changes(&Phase::TemplateExpansion, &before, &after);
+
+        assert_eq!(
+            trace.steps,
+            [
+                step(Phase::TemplateExpansion, "ADDED", None, "yes"),
+                step(Phase::TemplateExpansion, "CHANGED", Some("old"), "new"),
+            ]
+        );
+    }
+
+    #[test]
+    fn extend_keeps_the_order_of_stages() {
+        let mut trace = EnvResolutionTrace::default();
+        trace.record(Phase::BaseEnv, "A", None, "1");
+        let mut later = EnvResolutionTrace::default();
+        later.record(Phase::CliOverride, "A", Some("1"), "2");
+
+        trace.extend(later);
+
+        assert_eq!(
+            trace.steps,
+            [
+                step(Phase::BaseEnv, "A", None, "1"),
+                step(Phase::CliOverride, "A", Some("1"), "2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn base_env_is_left_out_of_the_report() {
+        assert_eq!(Phase::BaseEnv.report_section(), None);
+        assert_eq!(
+            Phase::InheritedFrom("team".into()).report_section(),
+            Some(0)
+        );
+        assert_eq!(Phase::CliOverride.report_section(), Some(1));
+        assert_eq!(Phase::TemplateExpansion.report_section(), Some(2));
+    }
+}
@@ -1,27 +1,179 @@
-use crate::config::Profile;
+use crate::claude::env_trace::{EnvResolutionTrace, Phase};
+use crate::claude::limits;
+use crate::claude::notify;
+use crate::claude::overrides;
+use crate::claude::ports::ServiceWait;
+use crate::claude::process::{self, TimeoutPolicy};
+use crate::claude::pty::{self, TypedPrompt};
+use crate::claude::redaction::LogRedactor;
+use crate::claude::tee;
+use crate::claude::terminal;
+use crate::claude::timing::StartupTimer;
+use crate::config::secrets::{self, KEYRING_PREFIX};
+use crate::config::template::expand_templates;
 use crate::config::Storage;
+use crate::config::{LaunchSettings, Profile};
 use crate::error::{Error, Result};
-use std::collections::HashMap;
+use colored::Colorize;
+use std::collections::{BTreeSet, HashMap};
 use std::env;
-use std::process::{Command, Stdio};
-use which::which;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::Instant;
+use which::{which, which_in};
+
+/// Env var holding the name of the profile a session was launched with
+pub const PROFILE_ENV: &str = "CCUSE_PROFILE";
+
+/// Env var holding the display name of that profile, or its name if it has none
+pub const PROFILE_DISPLAY_ENV: &str = "CCUSE_PROFILE_DISPLAY";
+
+/// Vars passed on from the parent environment when launching with a clean env
+pub const CLEAN_ENV_KEEP: [&str; 4] = ["PATH", "HOME", "TERM", "LANG"];
+
+/// Runs its arguments, then removes the file given first, also when the
+/// terminal is closed. The exit status of the command is kept.
+#[cfg(unix)]
+const REMOVE_AFTER_SCRIPT: &str =
+    r#"file=$1; shift; trap 'rm -f -- "$file"' EXIT; trap 'exit 129' HUP INT TERM; "$@""#;
+
+/// Waits for the process given first to exit, then removes the file given second.
+#[cfg(unix)]
+const REMOVE_AFTER_EXIT_SCRIPT: &str =
+    r#"while kill -0 "$1" 2>/dev/null; do sleep 1; done; rm -f -- "$2""#;
+
+/// Options controlling how a profile is launched.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    /// Pass `--dangerously-skip-permissions` to Claude Code
+    pub bypass: bool,
+    /// Start Claude in the background and return without waiting; only
+    /// [`launch_profile`] looks at this
+    pub detach: bool,
+    /// Print the launch plan instead of starting Claude; only
+    /// [`launch_profile`] looks at this
+    pub dry_run: bool,
+    /// Send a desktop notification when the session ends
+    pub notify: bool,
+    /// Stop the session after a time limit
+    pub timeout: Option<TimeoutPolicy>,
+    /// Ad-hoc env vars applied on top of the profile env
+    pub env_overrides: HashMap<String, String>,
+    /// Inherit only these vars from the parent environment instead of all of them
+    pub clean_env: Option<Vec<String>>,
+    /// Value injected as `RUST_LOG`
+    pub log_level: Option<String>,
+    /// Inject `RUST_LOG=trace`
+    pub trace: bool,
+    /// Inject `NODE_DEBUG=mcp,mcp-client`
+    pub debug_mcp: bool,
+    /// Keep log-level vars that are already set instead of overriding them
+    pub keep_existing_log_level: bool,
+    /// Shell command started before Claude (e.g. an SSH tunnel) and killed after it exits
+    pub tunnel_cmd: Option<String>,
+    /// Copy Claude's terminal output to this file
+    pub tee_output: Option<PathBuf>,
+    /// Append to the tee file instead of truncating it
+    pub tee_append: bool,
+    /// Scrubs the tee file; output is written unredacted when `None`
+    pub log_redactor: Option<LogRedactor>,
+    /// Services to wait for before Claude starts, after the tunnel is up
+    pub wait_for: Option<ServiceWait>,
+    /// Cap Claude's CPU usage at this percentage (foreground sessions without tee)
+    pub cpu_limit: Option<u8>,
+    /// Text typed into Claude's input after it starts
+    pub typed_prompt: Option<TypedPrompt>,
+    /// Settings file to pass instead of the profile's own
+    pub settings_path: Option<PathBuf>,
+    /// Settings JSON to pass instead of the profile's own file, e.g. for a
+    /// composed profile; written to a private temporary file for the session
+    pub settings_json: Option<String>,
+    /// Run `claude-<version>` instead of the default executable
+    pub claude_version: Option<String>,
+    /// Fail instead of falling back when `claude-<version>` is missing
+    pub strict_version: bool,
+    /// Run this executable, ignoring `claude_version` and the usual search
+    pub claude_path: Option<PathBuf>,
+    /// Additional arguments passed through to Claude Code
+    pub args: Vec<String>,
+}
 
 pub struct Launcher;
 
+/// Launch the stored profile `name` and wait for Claude Code to exit.
+///
+/// This is the library entry point for embedding ccuse. It flattens the
+/// profile's `extends` chain and expands `${KEY}` references in its env like
+/// `ccuse use` does; `--inherit-parent-profile`, `--env-substitute` and the
+/// other session flags of the CLI are not applied.
+///
+/// With `opts.dry_run` the launch plan is printed, secrets masked, and with
+/// `opts.detach` Claude is started in the background. Both return a success
+/// status once done; use [`Launcher::launch_background`] directly to get the
+/// PID of a detached session.
+///
+/// # Errors
+///
+/// Returns `Error::ProfileNotFound` if there is no such profile, or an error
+/// if Claude Code fails to launch.
+pub fn launch_profile(name: &str, mut opts: LaunchOptions) -> Result<ExitStatus> {
+    // The storage lock is released before Claude starts
+    let mut profile = Storage::new()?
+        .resolve_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+
+    profile.env = expand_templates(&profile.env);
+
+    // Inherited settings are only in a composed copy of the settings file
+    if profile.extends.is_some() && opts.settings_path.is_none() && opts.settings_json.is_none() {
+        opts.settings_json = Some(serde_json::to_string_pretty(&profile)?);
+    }
+    if opts.dry_run {
+        Launcher::print_launch_plan(&profile, &opts, false)?;
+        return Ok(success_status());
+    }
+    if opts.detach {
+        Launcher::launch_background(&profile, &opts)?;
+        return Ok(success_status());
+    }
+    Launcher::launch(&profile, &opts)
+}
+
+/// The status of a launch that did not wait for Claude.
+fn success_status() -> ExitStatus {
+    #[cfg(unix)]
+    use std::os::unix::process::ExitStatusExt;
+    #[cfg(windows)]
+    use std::os::windows::process::ExitStatusExt;
+
+    ExitStatus::from_raw(0)
+}
+
+/// Everything needed to start Claude for a profile.
+struct PreparedLaunch {
+    program: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    /// Must stay alive until Claude has exited, since a temporary copy is removed on drop
+    settings: LaunchSettings,
+}
+
 impl Launcher {
     /// Find the Claude Code executable in the system.
     ///
+    /// With a `version`, only `claude-<version>` is accepted, so callers can
+    /// decide whether to fall back to the default executable.
+    ///
     /// # Errors
     ///
-    /// Returns an error if Claude Code executable cannot be found in PATH or `CLAUDE_CODE_PATH`.
-    pub fn find_claude_executable() -> Result<String> {
-        // Try common locations
-        let candidates = vec!["claude", "claude-code", "Claude Code"];
-
-        for candidate in &candidates {
-            if which(candidate).is_ok() {
-                return Ok((*candidate).to_string());
-            }
+    /// Returns an error if Claude Code executable cannot be found in PATH, `CLAUDE_CODE_PATH`
+    /// or a common install location, or `Error::ClaudeVersionNotFound` if
+    /// `claude-<version>` is not in PATH.
+    pub fn find_claude_executable(version: Option<&str>) -> Result<String> {
+        if let Some(found) = Self::find_in_path(version, env::var_os("PATH"))? {
+            return Ok(found);
         }
 
         // Try environment variable
@@ -31,72 +183,1007 @@ impl Launcher {
             }
         }
 
-        Err(Error::ClaudeNotFound)
+        // npm-global and native installs are often not on PATH
+        install_locations()
+            .into_iter()
+            .find(|path| is_executable(path))
+            .map(|path| path.to_string_lossy().into_owned())
+            .ok_or(Error::ClaudeNotFound)
     }
 
-    /// Launch Claude Code with the specified profile.
+    /// The PATH part of [`Self::find_claude_executable`], searching `search_path`.
     ///
-    /// # Errors
+    /// Returns `None` if no default executable name is in it.
+    fn find_in_path(
+        version: Option<&str>,
+        search_path: Option<OsString>,
+    ) -> Result<Option<String>> {
+        let cwd = env::current_dir().unwrap_or_default();
+        let found = |name: &str| which_in(name, search_path.clone(), &cwd).is_ok();
+
+        if let Some(version) = version {
+            let versioned = format!("claude-{version}");
+            return if found(&versioned) {
+                Ok(Some(versioned))
+            } else {
+                Err(Error::ClaudeVersionNotFound(version.to_string()))
+            };
+        }
+
+        // Try common locations
+        let candidates = ["claude", "claude-code", "Claude Code"];
+        Ok(candidates
+            .into_iter()
+            .find(|candidate| found(candidate))
+            .map(str::to_string))
+    }
+
+    /// Every executable in PATH whose name starts with `claude`, in PATH order.
     ///
-    /// Returns an error if Claude Code cannot be found, settings cannot be found, or the process fails to launch.
-    pub fn launch(profile: &Profile, bypass: bool, args: &[String]) -> Result<()> {
-        let claude_cmd = Self::find_claude_executable()?;
+    /// Executables shadowed by an earlier directory are included.
+    #[must_use]
+    pub fn find_all_claude_executables() -> Vec<PathBuf> {
+        Self::find_all_claude_executables_in(env::var_os("PATH"))
+    }
+
+    fn find_all_claude_executables_in(search_path: Option<OsString>) -> Vec<PathBuf> {
+        let Some(path) = search_path else {
+            return Vec::new();
+        };
+
+        let mut found = Vec::new();
+        for dir in env::split_paths(&path) {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut matches: Vec<PathBuf> = entries
+                .filter_map(std::result::Result::ok)
+                .filter(|entry| entry.file_name().to_string_lossy().starts_with("claude"))
+                .map(|entry| entry.path())
+                .filter(|path| is_executable(path))
+                .collect();
+            matches.sort();
+            for path in matches {
+                if !found.contains(&path) {
+                    found.push(path);
+                }
+            }
+        }
+        found
+    }
 
-        // Create storage to get profile settings path
-        let storage = Storage::new()?;
+    /// Start a tunnel command through the platform shell.
+    fn spawn_tunnel(command: &str) -> Result<Child> {
+        #[cfg(windows)]
+        let mut cmd = {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", command]);
+            cmd
+        };
+        #[cfg(not(windows))]
+        let mut cmd = {
+            use std::os::unix::process::CommandExt;
 
-        // Get profile-specific settings.json path (should already exist)
-        let settings_path = storage.profile_settings_path(&profile.name);
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", command]);
+            // Own process group so stop_tunnel also reaches processes the shell forks
+            cmd.process_group(0);
+            cmd
+        };
 
-        if !settings_path.exists() {
-            return Err(Error::ConfigError(format!(
-                "Settings file not found for profile '{}': {}",
-                profile.name,
-                settings_path.display()
-            )));
+        cmd.stdin(Stdio::null())
+            .spawn()
+            .map_err(|e| Error::LaunchError(format!("Failed to start tunnel command: {e}")))
+    }
+
+    /// Stop a tunnel started by `spawn_tunnel`.
+    fn stop_tunnel(tunnel: &mut Child) {
+        #[cfg(unix)]
+        {
+            use nix::sys::signal::{killpg, Signal};
+            use nix::unistd::Pid;
+
+            if let Ok(pid) = i32::try_from(tunnel.id()) {
+                killpg(Pid::from_raw(pid), Signal::SIGTERM).ok();
+            }
         }
+        tunnel.kill().ok();
+        tunnel.wait().ok();
+    }
 
-        // Build environment - inherit from parent, then override with profile env vars
-        let mut env_vars: HashMap<String, String> = env::vars().collect();
+    /// The environment a process launched with `profile` starts with,
+    /// before any launch options are applied. `base_env` is the environment
+    /// it would otherwise inherit, usually `env::vars()`.
+    ///
+    /// Later sources win: `base_env` without `CLAUDECODE`, then
+    /// `CCUSE_PROFILE` and `CCUSE_PROFILE_DISPLAY` naming the profile, then
+    /// the profile's own env. Keyring references are left unresolved.
+    #[must_use]
+    pub fn build_env(
+        profile: &Profile,
+        base_env: HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        // Start from the inherited env, then override with profile env vars
+        let mut env_vars = base_env;
 
         // Remove CLAUDECODE to allow launching Claude inside another Claude session
         env_vars.remove("CLAUDECODE");
 
+        // Tell hooks and status lines which profile launched them
+        env_vars.insert(PROFILE_ENV.to_string(), profile.name.clone());
+        env_vars.insert(
+            PROFILE_DISPLAY_ENV.to_string(),
+            profile
+                .display_name
+                .clone()
+                .unwrap_or_else(|| profile.name.clone()),
+        );
+
         // Override with profile env vars (these contain the provider configuration)
         for (key, value) in &profile.env {
             env_vars.insert(key.clone(), value.clone());
         }
+        env_vars
+    }
+
+    /// The part of this process's environment Claude inherits: all of it,
+    /// or only the vars named by `opts.clean_env`.
+    #[must_use]
+    pub fn inherited_env(opts: &LaunchOptions) -> HashMap<String, String> {
+        match &opts.clean_env {
+            None => env::vars().collect(),
+            Some(keep) => env::vars().filter(|(key, _)| keep.contains(key)).collect(),
+        }
+    }
+
+    /// Build the environment Claude is started with, and trace what the
+    /// launch options changed on top of the profile env.
+    ///
+    /// This is [`Launcher::build_env`] on top of [`Launcher::inherited_env`],
+    /// followed by the `--env` overrides and finally the log level options.
+    #[must_use]
+    pub fn resolve_env(
+        profile: &Profile,
+        opts: &LaunchOptions,
+    ) -> (HashMap<String, String>, EnvResolutionTrace) {
+        let mut env_vars = Self::build_env(profile, Self::inherited_env(opts));
+        let profile_env = env_vars.clone();
+
+        // Apply ad-hoc overrides from the command line last so they always win
+        for (key, value) in &opts.env_overrides {
+            env_vars.insert(key.clone(), value.clone());
+        }
+
+        overrides::apply_log_overrides(
+            &mut env_vars,
+            opts.log_level.as_deref(),
+            opts.debug_mcp,
+            opts.trace,
+            opts.keep_existing_log_level,
+        );
+
+        let mut trace = EnvResolutionTrace::default();
+        trace.record_changes(&Phase::CliOverride, &profile_env, &env_vars);
+
+        (env_vars, trace)
+    }
+
+    /// Resolve the Claude executable, settings, env and arguments for a launch.
+    fn prepare(profile: &Profile, opts: &LaunchOptions) -> Result<PreparedLaunch> {
+        let found = match &opts.claude_path {
+            Some(path) if is_executable(path) => Ok(path.to_string_lossy().into_owned()),
+            Some(path) => Err(Error::LaunchError(format!(
+                "{} is not an executable file",
+                path.display()
+            ))),
+            None => Self::find_claude_executable(opts.claude_version.as_deref()),
+        };
+        let claude_cmd = match found {
+            Err(Error::ClaudeVersionNotFound(version)) if !opts.strict_version => {
+                eprintln!(
+                    "{} claude-{version} not found in PATH, using the default Claude Code",
+                    "Warning:".yellow()
+                );
+                Self::find_claude_executable(None)?
+            }
+            found => found?,
+        };
+        tracing::debug!(executable = %claude_cmd, "resolved Claude Code");
+
+        let (mut env_vars, _) = Self::resolve_env(profile, opts);
+
+        // Secrets kept in the keyring are only read back now, at spawn time
+        if secrets::has_sentinels(&profile.env) {
+            secrets::resolve_sentinels(&profile.name, &mut env_vars)?;
+        }
+
+        let settings = Self::settings_file(profile, opts, &env_vars)?;
 
         // Build command arguments
         let mut claude_args = Vec::new();
 
         // Add --settings flag to use profile-specific settings
         claude_args.push("--settings".to_string());
-        claude_args.push(settings_path.to_string_lossy().to_string());
+        claude_args.push(settings.path().to_string_lossy().to_string());
+
+        // The profile's own arguments, so the user's can still override them
+        claude_args.extend(profile.launch_args.iter().cloned());
 
         // Add bypass flag if requested
-        if bypass {
+        if opts.bypass {
             claude_args.push("--dangerously-skip-permissions".to_string());
         }
 
         // Add user-provided arguments
-        claude_args.extend(args.iter().cloned());
+        claude_args.extend(opts.args.iter().cloned());
+
+        // Env values can hold secrets, so only their number is logged
+        tracing::debug!(args = ?claude_args, env_vars = env_vars.len(), "prepared launch");
+
+        Ok(PreparedLaunch {
+            program: claude_cmd,
+            args: claude_args,
+            env: env_vars,
+            settings,
+        })
+    }
+
+    /// The settings file passed to Claude Code with `--settings`, with its
+    /// env matching `env_vars`, the env Claude is started with.
+    fn settings_file(
+        profile: &Profile,
+        opts: &LaunchOptions,
+        env_vars: &HashMap<String, String>,
+    ) -> Result<LaunchSettings> {
+        let settings = match (&opts.settings_path, &opts.settings_json) {
+            (Some(path), _) => LaunchSettings::Persistent(path.clone()),
+            (None, Some(json)) => LaunchSettings::temporary(json)?,
+            (None, None) => {
+                // Create storage to get profile settings path
+                let storage = Storage::new()?;
+
+                // Get profile-specific settings.json path (should already exist)
+                if !storage.settings_file_exists(&profile.name) {
+                    return Err(Error::ConfigError(format!(
+                        "Settings file not found for profile '{}': {}",
+                        profile.name,
+                        storage.profile_settings_path(&profile.name).display()
+                    )));
+                }
+                storage.launch_settings(&profile.name)?
+            }
+        };
+
+        let mut content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(settings.path())?)?;
+        if sync_settings_env(&mut content, profile, opts, env_vars) {
+            // The copy holds the profile's env values, so it is private
+            LaunchSettings::temporary(&serde_json::to_string_pretty(&content)?)
+        } else {
+            Ok(settings)
+        }
+    }
+
+    /// Print what [`Launcher::launch`] would run without starting anything.
+    ///
+    /// Values of secret-looking env vars are masked unless `reveal_secrets` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Claude Code or the profile's settings file cannot be found.
+    pub fn print_launch_plan(
+        profile: &Profile,
+        opts: &LaunchOptions,
+        reveal_secrets: bool,
+    ) -> Result<()> {
+        let prepared = Self::prepare(profile, opts)?;
+
+        println!("{}", "Dry run, Claude was not started:".bold());
+        let executable = which(&prepared.program).map_or_else(
+            |_| prepared.program.clone(),
+            |path| path.display().to_string(),
+        );
+        println!("  Executable: {executable}");
+        println!("  Arguments: {}", prepared.args.join(" "));
+        if let Some(tunnel) = &opts.tunnel_cmd {
+            println!("  Tunnel: {tunnel}");
+        }
+        if let Some(keep) = &opts.clean_env {
+            println!("  Inherited from this shell: only {}", keep.join(", "));
+        }
+
+        // Vars Claude would inherit unchanged from this shell are not worth listing
+        let inherited: HashMap<String, String> = env::vars().collect();
+        println!("  Environment (set by ccuse):");
+        let mut keys: Vec<&String> = prepared
+            .env
+            .iter()
+            .filter(|(key, value)| inherited.get(*key) != Some(*value))
+            .map(|(key, _)| key)
+            .collect();
+        keys.sort();
+        for key in keys {
+            let shown = secrets::shown_value(key, &prepared.env[key], reveal_secrets);
+            println!("    {}={shown}", key.yellow());
+        }
+
+        Ok(())
+    }
+
+    /// Print the settings file Claude Code would get with `--settings` to stderr.
+    ///
+    /// Values of secret-looking env vars are masked unless `reveal_secrets` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile's settings file cannot be found or read.
+    pub fn print_settings(
+        profile: &Profile,
+        opts: &LaunchOptions,
+        reveal_secrets: bool,
+    ) -> Result<()> {
+        // Keyring references are left out of the file, so they need not be resolved
+        let (env_vars, _) = Self::resolve_env(profile, opts);
+        let file = Self::settings_file(profile, opts, &env_vars)?;
+        let path = file.path();
+        let mut settings: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+        if !reveal_secrets {
+            if let Some(env) = settings
+                .get_mut("env")
+                .and_then(serde_json::Value::as_object_mut)
+            {
+                for (key, value) in env.iter_mut() {
+                    if let Some(text) = value.as_str() {
+                        *value = secrets::shown_value(key, text, false).into();
+                    }
+                }
+            }
+        }
+
+        eprintln!("{} {}", "Settings:".bold(), path.display());
+        eprintln!("{}", serde_json::to_string_pretty(&settings)?);
+        Ok(())
+    }
+
+    /// Launch Claude Code with the specified profile.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Claude Code cannot be found, settings cannot be found, or the process fails to launch.
+    pub fn launch(profile: &Profile, opts: &LaunchOptions) -> Result<ExitStatus> {
+        StartupTimer::checkpoint("launch entered");
+        let _span = tracing::debug_span!("launch", profile = %profile.name).entered();
+        let prepared = Self::prepare(profile, opts)?;
+
+        let tee_file = opts
+            .tee_output
+            .as_deref()
+            .map(|path| tee::TeeFile::start(path, opts.tee_append, opts.log_redactor.clone()))
+            .transpose()?;
 
-        // Launch process
-        let mut cmd = Command::new(&claude_cmd);
-        cmd.args(&claude_args)
-            .envs(&env_vars)
+        // Launch process, under a PTY when teeing output
+        let mut cmd = match &tee_file {
+            Some(tee_file) => tee::wrap_command(&prepared.program, &prepared.args, tee_file)?,
+            None => {
+                let mut cmd = Command::new(&prepared.program);
+                cmd.args(&prepared.args);
+                cmd
+            }
+        };
+        cmd.env_clear()
+            .envs(&prepared.env)
             .stdin(Stdio::inherit())
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit());
 
-        let mut child = cmd.spawn().map_err(|e| Error::LaunchError(e.to_string()))?;
+        let mut tunnel = opts
+            .tunnel_cmd
+            .as_deref()
+            .map(Self::spawn_tunnel)
+            .transpose()?;
+
+        if let Some(wait) = &opts.wait_for {
+            if let Err(e) = wait.wait() {
+                if let Some(tunnel) = tunnel.as_mut() {
+                    Self::stop_tunnel(tunnel);
+                }
+                if let Some(tee_file) = tee_file {
+                    tee_file.finish();
+                }
+                return Err(e);
+            }
+        }
+
+        let started = Instant::now();
+
+        // A typed prompt needs Claude on a PTY that ccuse relays itself
+        if let Some(prompt) = &opts.typed_prompt {
+            let result = pty::run_with_typed_prompt(cmd, prompt);
+            if let Some(tunnel) = tunnel.as_mut() {
+                Self::stop_tunnel(tunnel);
+            }
+            if let Some(tee_file) = tee_file {
+                tee_file.finish();
+            }
+            let status = result?;
+            Self::notify_finished(profile, opts, started, status);
+            return Ok(status);
+        }
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                if let Some(tunnel) = tunnel.as_mut() {
+                    Self::stop_tunnel(tunnel);
+                }
+                if let Some(tee_file) = tee_file {
+                    tee_file.finish();
+                }
+                return Err(Error::LaunchError(e.to_string()));
+            }
+        };
+        StartupTimer::checkpoint("claude spawned");
+        StartupTimer::report();
+
+        if let Some(percent) = opts.cpu_limit {
+            // Claude is already running; an unlimited session beats none
+            if let Err(e) = limits::limit_cpu(child.id(), percent) {
+                eprintln!("{} {e}", "Warning:".yellow());
+            }
+        }
 
         // Wait for the child to complete so ccuse keeps the terminal alive
-        child
-            .wait()
-            .map_err(|e| Error::LaunchError(e.to_string()))?;
+        let waited = match &opts.timeout {
+            Some(policy) => process::wait_with_timeout(&mut child, policy),
+            None => child.wait().map_err(|e| Error::LaunchError(e.to_string())),
+        };
 
-        Ok(())
+        if let Some(tunnel) = tunnel.as_mut() {
+            Self::stop_tunnel(tunnel);
+        }
+        if let Some(tee_file) = tee_file {
+            tee_file.finish();
+        }
+        if opts.cpu_limit.is_some() {
+            limits::release_cpu_limit(child.id());
+        }
+        let status = waited?;
+
+        Self::notify_finished(profile, opts, started, status);
+
+        Ok(status)
+    }
+
+    /// Send the end-of-session notification if requested.
+    fn notify_finished(
+        profile: &Profile,
+        opts: &LaunchOptions,
+        started: Instant,
+        status: ExitStatus,
+    ) {
+        if opts.notify {
+            let body = notify::session_summary(&profile.name, started.elapsed(), status.code());
+            // Notification failures are not fatal; the session already ended
+            if let Err(e) = notify::send_notification("ccuse", &body) {
+                tracing::warn!("{e}");
+            }
+        }
+    }
+
+    /// Start Claude detached from the terminal in its own session and return its PID.
+    ///
+    /// ccuse does not wait for the session, so timeouts, tunnels and
+    /// notifications do not apply. stdin is `/dev/null` so Claude does not
+    /// compete with the shell for keyboard input once ccuse returns; stdout
+    /// and stderr stay inherited.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Claude Code cannot be found or fails to start.
+    pub fn launch_background(profile: &Profile, opts: &LaunchOptions) -> Result<u32> {
+        let prepared = Self::prepare(profile, opts)?;
+        if let Some(wait) = &opts.wait_for {
+            wait.wait()?;
+        }
+
+        let mut cmd = Command::new(&prepared.program);
+        cmd.args(&prepared.args)
+            .env_clear()
+            .envs(&prepared.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            // New session: Claude survives ccuse exiting and the terminal closing
+            // SAFETY: setsid is async-signal-safe and touches no parent state
+            unsafe {
+                cmd.pre_exec(|| {
+                    nix::unistd::setsid()
+                        .map(|_| ())
+                        .map_err(std::io::Error::from)
+                });
+            }
+        }
+
+        let child = cmd.spawn().map_err(|e| Error::LaunchError(e.to_string()))?;
+
+        // Claude may read its settings after ccuse has returned
+        if let Some(path) = prepared.settings.into_kept_temporary()? {
+            remove_after_exit(child.id(), &path);
+        }
+
+        Ok(child.id())
+    }
+
+    /// Open Claude in a new terminal emulator window.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Claude Code or a terminal emulator cannot be found.
+    pub fn launch_in_new_terminal(profile: &Profile, opts: &LaunchOptions) -> Result<()> {
+        let prepared = Self::prepare(profile, opts)?;
+        if let Some(wait) = &opts.wait_for {
+            wait.wait()?;
+        }
+
+        // The terminal outlives ccuse, so the shell running Claude removes a temporary copy
+        let Some(settings_path) = prepared.settings.into_kept_temporary()? else {
+            return terminal::open_in_new_terminal(
+                &prepared.program,
+                &prepared.args,
+                &prepared.env,
+            );
+        };
+        let (program, args) = remove_after(&settings_path, prepared.program, prepared.args);
+        let opened = terminal::open_in_new_terminal(&program, &args, &prepared.env);
+        if opened.is_err() {
+            fs::remove_file(&settings_path).ok();
+        }
+        opened
+    }
+}
+
+/// Wrap `program args` in a shell that removes `path` when they exit.
+#[cfg(unix)]
+fn remove_after(path: &Path, program: String, args: Vec<String>) -> (String, Vec<String>) {
+    let mut wrapped = vec![
+        "-c".to_string(),
+        REMOVE_AFTER_SCRIPT.to_string(),
+        "ccuse".to_string(),
+        path.to_string_lossy().into_owned(),
+        program,
+    ];
+    wrapped.extend(args);
+    ("sh".to_string(), wrapped)
+}
+
+#[cfg(not(unix))]
+fn remove_after(path: &Path, program: String, args: Vec<String>) -> (String, Vec<String>) {
+    warn_not_removed(path);
+    (program, args)
+}
+
+/// Remove `path` once the detached process `pid` has exited, from a shell
+/// in its own session so it outlives ccuse and the terminal.
+#[cfg(unix)]
+fn remove_after_exit(pid: u32, path: &Path) {
+    use std::os::unix::process::CommandExt;
+
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", REMOVE_AFTER_EXIT_SCRIPT, "ccuse"])
+        .arg(pid.to_string())
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // SAFETY: setsid is async-signal-safe and touches no parent state
+    unsafe {
+        cmd.pre_exec(|| {
+            nix::unistd::setsid()
+                .map(|_| ())
+                .map_err(std::io::Error::from)
+        });
+    }
+    if cmd.spawn().is_err() {
+        warn_not_removed(path);
+    }
+}
+
+#[cfg(not(unix))]
+fn remove_after_exit(_pid: u32, path: &Path) {
+    warn_not_removed(path);
+}
+
+fn warn_not_removed(path: &Path) {
+    eprintln!(
+        "{} the private settings copy {} is not removed when Claude exits; delete it afterwards",
+        "Warning:".yellow(),
+        path.display()
+    );
+}
+
+/// Give every env var of the `settings` file, the profile and the `--env`
+/// overrides its value from `env_vars`, returning whether anything changed.
+///
+/// Claude Code applies the settings file's `env` over its own environment,
+/// so values resolved at launch (template expansion, `--env`, port
+/// forwarding, ...) must be in the file as well. Keyring references are
+/// removed instead, so the resolved secrets only reach Claude's environment.
+fn sync_settings_env(
+    settings: &mut serde_json::Value,
+    profile: &Profile,
+    opts: &LaunchOptions,
+    env_vars: &HashMap<String, String>,
+) -> bool {
+    let Some(settings) = settings.as_object_mut() else {
+        return false;
+    };
+    let Some(env) = settings
+        .entry("env")
+        .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()))
+        .as_object_mut()
+    else {
+        return false;
+    };
+
+    let is_sentinel = |value: Option<&str>| value.is_some_and(|v| v.starts_with(KEYRING_PREFIX));
+    let keys: BTreeSet<String> = env
+        .keys()
+        .chain(profile.env.keys())
+        .chain(opts.env_overrides.keys())
+        .cloned()
+        .collect();
+
+    let mut changed = false;
+    for key in keys {
+        let current = env.get(&key).and_then(serde_json::Value::as_str);
+        let secret = is_sentinel(profile.env.get(&key).map(String::as_str)) || is_sentinel(current);
+        match env_vars.get(&key).filter(|_| !secret) {
+            Some(value) if current == Some(value.as_str()) => {}
+            Some(value) => {
+                env.insert(key, value.clone().into());
+                changed = true;
+            }
+            None => changed |= env.remove(&key).is_some(),
+        }
+    }
+    changed
+}
+
+/// Where Claude Code installs itself outside of PATH, most specific first.
+fn install_locations() -> Vec<PathBuf> {
+    let mut locations = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        locations.push(home.join(".claude").join("local").join("claude"));
+        locations.push(home.join(".npm-global").join("bin").join("claude"));
+    }
+    locations.push(PathBuf::from("/usr/local/bin/claude"));
+    if cfg!(target_os = "macos") {
+        locations.push(PathBuf::from("/opt/homebrew/bin/claude"));
+        locations.push(PathBuf::from(
+            "/Applications/Claude Code.app/Contents/MacOS/claude",
+        ));
+    }
+    locations
+}
+
+#[cfg(unix)]
+fn is_executable(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &std::path::Path) -> bool {
+    path.is_file()
+        && path.extension().is_some_and(|ext| {
+            ["exe", "cmd", "bat"]
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    fn profile(env: &[(&str, &str)]) -> Profile {
+        Profile {
+            name: "work".to_string(),
+            env: base_env(env),
+            ..Profile::default()
+        }
+    }
+
+    #[test]
+    fn build_env_profile_values_override_inherited_ones() {
+        let base = base_env(&[
+            ("ANTHROPIC_BASE_URL", "https://inherited.example.com"),
+            ("PATH", "/usr/bin"),
+        ]);
+        let profile = profile(&[("ANTHROPIC_BASE_URL", "https://profile.example.com")]);
+
+        let env = Launcher::build_env(&profile, base);
+
+        assert_eq!(env["ANTHROPIC_BASE_URL"], "https://profile.example.com");
+        assert_eq!(env["PATH"], "/usr/bin");
+    }
+
+    #[test]
+    fn build_env_removes_claudecode() {
+        let base = base_env(&[("CLAUDECODE", "1"), ("HOME", "/home/user")]);
+
+        let env = Launcher::build_env(&profile(&[]), base);
+
+        assert!(!env.contains_key("CLAUDECODE"));
+        assert_eq!(env["HOME"], "/home/user");
+    }
+
+    #[test]
+    fn build_env_names_the_profile() {
+        let base = base_env(&[(PROFILE_ENV, "parent-session")]);
+
+        let env = Launcher::build_env(&profile(&[]), base.clone());
+        assert_eq!(env[PROFILE_ENV], "work");
+        assert_eq!(env[PROFILE_DISPLAY_ENV], "work");
+
+        let named = Profile {
+            display_name: Some("Work account".to_string()),
+            ..profile(&[])
+        };
+        let env = Launcher::build_env(&named, base);
+        assert_eq!(env[PROFILE_DISPLAY_ENV], "Work account");
+    }
+
+    #[test]
+    fn resolve_env_traces_cli_overrides() {
+        let profile = profile(&[("ANTHROPIC_MODEL", "sonnet"), ("API_TIMEOUT_MS", "1000")]);
+        let opts = LaunchOptions {
+            clean_env: Some(Vec::new()),
+            env_overrides: base_env(&[("ANTHROPIC_MODEL", "opus"), ("API_TIMEOUT_MS", "1000")]),
+            log_level: Some("debug".to_string()),
+            ..LaunchOptions::default()
+        };
+
+        let (env, trace) = Launcher::resolve_env(&profile, &opts);
+
+        assert_eq!(env["ANTHROPIC_MODEL"], "opus");
+        let steps: Vec<_> = trace
+            .steps
+            .iter()
+            .map(|s| {
+                (
+                    &s.phase,
+                    s.key.as_str(),
+                    s.before.as_deref(),
+                    s.after.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (
+                    &Phase::CliOverride,
+                    "ANTHROPIC_MODEL",
+                    Some("sonnet"),
+                    "opus"
+                ),
+                (&Phase::CliOverride, "RUST_LOG", None, "debug"),
+            ]
+        );
+    }
+
+    /// A stand-in for Claude Code running the shell script `body`.
+    #[cfg(unix)]
+    fn fake_claude(dir: &Path, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("claude");
+        fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    fn fake_claude_opts(claude: PathBuf) -> LaunchOptions {
+        LaunchOptions {
+            claude_path: Some(claude),
+            settings_json: Some("{}".to_string()),
+            ..LaunchOptions::default()
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn launch_returns_the_exact_exit_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let claude = fake_claude(dir.path(), "exit 42");
+
+        let status = Launcher::launch(&profile(&[]), &fake_claude_opts(claude)).unwrap();
+
+        assert_eq!(status.code(), Some(42));
+        assert!(!process::is_success_exit_code(42, &[]));
+        assert!(process::is_success_exit_code(42, &[3, 42]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn claude_gets_the_overrides_in_its_settings_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let seen = dir.path().join("seen.json");
+        // $1 is --settings, $2 the file
+        let claude = fake_claude(dir.path(), &format!("cp \"$2\" '{}'", seen.display()));
+        let stored = profile(&[
+            ("ANTHROPIC_BASE_URL", "https://stored.example.com"),
+            ("ANTHROPIC_MODEL", "sonnet"),
+        ]);
+        let opts = LaunchOptions {
+            settings_json: Some(serde_json::to_string(&stored).unwrap()),
+            env_overrides: base_env(&[
+                ("ANTHROPIC_BASE_URL", "https://override.example.com"),
+                ("API_TIMEOUT_MS", "600000"),
+            ]),
+            ..fake_claude_opts(claude)
+        };
+
+        let status = Launcher::launch(&stored, &opts).unwrap();
+
+        assert!(status.success());
+        let settings: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&seen).unwrap()).unwrap();
+        assert_eq!(
+            settings["env"],
+            serde_json::json!({
+                "ANTHROPIC_BASE_URL": "https://override.example.com",
+                "ANTHROPIC_MODEL": "sonnet",
+                "API_TIMEOUT_MS": "600000",
+            })
+        );
+    }
+
+    #[test]
+    fn sync_settings_env_leaves_matching_settings_alone() {
+        let profile = profile(&[("ANTHROPIC_MODEL", "sonnet")]);
+        let mut settings = serde_json::json!({ "env": { "ANTHROPIC_MODEL": "sonnet" } });
+        let env = base_env(&[("ANTHROPIC_MODEL", "sonnet"), ("HOME", "/home/user")]);
+
+        assert!(!sync_settings_env(
+            &mut settings,
+            &profile,
+            &LaunchOptions::default(),
+            &env
+        ));
+        assert_eq!(
+            settings,
+            serde_json::json!({ "env": { "ANTHROPIC_MODEL": "sonnet" } })
+        );
+    }
+
+    #[test]
+    fn sync_settings_env_removes_keyring_references_and_dropped_vars() {
+        let profile = profile(&[
+            ("ANTHROPIC_AUTH_TOKEN", "keyring:ANTHROPIC_AUTH_TOKEN"),
+            ("ANTHROPIC_MODEL", "opus"),
+        ]);
+        let mut settings = serde_json::json!({
+            "env": {
+                "ANTHROPIC_AUTH_TOKEN": "keyring:ANTHROPIC_AUTH_TOKEN",
+                "ANTHROPIC_MODEL": "${MODEL}",
+                "CLAUDE_CODE_EXPERIMENTAL_FAST_MODE": "1",
+            },
+            "permissions": { "enabled": true },
+        });
+        // The secret as resolved from the keyring; the flag was disabled
+        let env = base_env(&[
+            ("ANTHROPIC_AUTH_TOKEN", "sk-ant-secret"),
+            ("ANTHROPIC_MODEL", "opus"),
+        ]);
+
+        assert!(sync_settings_env(
+            &mut settings,
+            &profile,
+            &LaunchOptions::default(),
+            &env
+        ));
+        assert_eq!(
+            settings,
+            serde_json::json!({
+                "env": { "ANTHROPIC_MODEL": "opus" },
+                "permissions": { "enabled": true },
+            })
+        );
+    }
+
+    /// A PATH of two directories holding stand-in executables, plus one
+    /// non-executable file.
+    #[cfg(unix)]
+    fn mock_path(dir: &Path) -> OsString {
+        let first = dir.join("first");
+        let second = dir.join("second");
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+        for (bin, name) in [
+            (&first, "claude-4"),
+            (&first, "claude-beta"),
+            (&second, "claude"),
+            (&second, "claude-3"),
+            (&second, "claude-4"),
+        ] {
+            let claude = fake_claude(dir, "exit 0");
+            fs::rename(claude, bin.join(name)).unwrap();
+        }
+        fs::write(second.join("claude-notes.txt"), "").unwrap();
+        env::join_paths([first, second]).unwrap()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_in_path_picks_the_requested_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = mock_path(dir.path());
+
+        for version in ["3", "4", "beta"] {
+            assert_eq!(
+                Launcher::find_in_path(Some(version), Some(path.clone())).unwrap(),
+                Some(format!("claude-{version}"))
+            );
+        }
+        assert_eq!(
+            Launcher::find_in_path(None, Some(path.clone())).unwrap(),
+            Some("claude".to_string())
+        );
+        assert!(matches!(
+            Launcher::find_in_path(Some("5"), Some(path)),
+            Err(Error::ClaudeVersionNotFound(v)) if v == "5"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_in_path_without_default_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = mock_path(dir.path());
+        fs::remove_file(dir.path().join("second/claude")).unwrap();
+
+        assert_eq!(Launcher::find_in_path(None, Some(path)).unwrap(), None);
+        assert_eq!(Launcher::find_in_path(None, None).unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_all_lists_every_versioned_executable_in_path_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = mock_path(dir.path());
+
+        let found: Vec<_> = Launcher::find_all_claude_executables_in(Some(path))
+            .into_iter()
+            .map(|p| p.strip_prefix(dir.path()).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            found,
+            [
+                "first/claude-4",
+                "first/claude-beta",
+                "second/claude",
+                "second/claude-3",
+                "second/claude-4",
+            ]
+            .map(PathBuf::from)
+        );
+        assert!(Launcher::find_all_claude_executables_in(None).is_empty());
     }
 }
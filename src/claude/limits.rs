@@ -0,0 +1,292 @@
+use crate::error::{Error, Result};
+
+/// Cap the CPU usage of the process `child_pid` at `percent` of the machine.
+///
+/// - Linux with the `cgroups` feature: a cgroup v2 at
+///   `/sys/fs/cgroup/ccuse/<pid>` whose `cpu.max` enforces a hard limit.
+///   Needs write access to `/sys/fs/cgroup` (root or a delegated subtree).
+/// - Windows: a Job Object with a hard CPU rate cap.
+/// - Elsewhere: the process is reniced, which only lowers its priority.
+///
+/// Processes the child starts afterwards inherit the limit.
+///
+/// # Errors
+///
+/// Returns [`Error::CpuLimitError`] if `percent` is not in 1–100 or the
+/// limit cannot be applied.
+pub fn limit_cpu(child_pid: u32, percent: u8) -> Result<()> {
+    if !(1..=100).contains(&percent) {
+        return Err(Error::CpuLimitError(format!(
+            "CPU limit must be between 1 and 100, got {percent}"
+        )));
+    }
+
+    platform::limit_cpu(child_pid, percent)
+}
+
+/// Remove whatever [`limit_cpu`] set up once the process has exited.
+pub fn release_cpu_limit(child_pid: u32) {
+    platform::release_cpu_limit(child_pid);
+}
+
+/// Whether [`limit_cpu`] only lowers priority instead of enforcing a hard cap.
+#[must_use]
+pub const fn is_priority_only() -> bool {
+    !cfg!(any(all(feature = "cgroups", target_os = "linux"), windows))
+}
+
+#[cfg(all(feature = "cgroups", target_os = "linux"))]
+mod platform {
+    use crate::error::{Error, Result};
+    use std::fs;
+    use std::path::Path;
+
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+    /// Scheduling period written to `cpu.max`, in microseconds.
+    const CPU_PERIOD_US: u64 = 100_000;
+
+    fn write(path: &Path, content: &str) -> Result<()> {
+        fs::write(path, content)
+            .map_err(|e| Error::CpuLimitError(format!("Cannot write {}: {e}", path.display())))
+    }
+
+    pub fn limit_cpu(child_pid: u32, percent: u8) -> Result<()> {
+        limit_cpu_in(Path::new(CGROUP_ROOT), child_pid, percent)
+    }
+
+    pub fn release_cpu_limit(child_pid: u32) {
+        release_cpu_limit_in(Path::new(CGROUP_ROOT), child_pid);
+    }
+
+    /// [`limit_cpu`] for the cgroup v2 hierarchy mounted at `root`.
+    pub(super) fn limit_cpu_in(root: &Path, child_pid: u32, percent: u8) -> Result<()> {
+        if !root.join("cgroup.controllers").exists() {
+            return Err(Error::CpuLimitError(format!(
+                "cgroup v2 is not mounted at {}",
+                root.display()
+            )));
+        }
+
+        let parent = root.join("ccuse");
+        fs::create_dir_all(&parent).map_err(|e| {
+            Error::CpuLimitError(format!("Cannot create {}: {e}", parent.display()))
+        })?;
+        // Let the per-session cgroups below ccuse/ use the cpu controller
+        write(&root.join("cgroup.subtree_control"), "+cpu")?;
+        write(&parent.join("cgroup.subtree_control"), "+cpu")?;
+
+        let session = parent.join(child_pid.to_string());
+        fs::create_dir_all(&session).map_err(|e| {
+            Error::CpuLimitError(format!("Cannot create {}: {e}", session.display()))
+        })?;
+
+        let cpus = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+        let quota = CPU_PERIOD_US * u64::from(percent) * cpus as u64 / 100;
+        write(
+            &session.join("cpu.max"),
+            &format!("{quota} {CPU_PERIOD_US}"),
+        )?;
+        write(&session.join("cgroup.procs"), &child_pid.to_string())
+    }
+
+    pub(super) fn release_cpu_limit_in(root: &Path, child_pid: u32) {
+        // Only succeeds once the cgroup is empty, which it is after the session
+        fs::remove_dir(root.join("ccuse").join(child_pid.to_string())).ok();
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use crate::error::{Error, Result};
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectCpuRateControlInformation,
+        SetInformationJobObject, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
+        JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0, JOB_OBJECT_CPU_RATE_CONTROL_ENABLE,
+        JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+    };
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, PROCESS_SET_QUOTA, PROCESS_TERMINATE,
+    };
+
+    fn last_error(what: &str) -> Error {
+        Error::CpuLimitError(format!("{what}: {}", std::io::Error::last_os_error()))
+    }
+
+    pub fn limit_cpu(child_pid: u32, percent: u8) -> Result<()> {
+        // SAFETY: plain Win32 calls; every handle is checked and closed below.
+        // The job lives on while the process is assigned to it.
+        unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job.is_null() {
+                return Err(last_error("CreateJobObjectW failed"));
+            }
+
+            // CpuRate is in hundredths of a percent of the whole machine
+            let info = JOBOBJECT_CPU_RATE_CONTROL_INFORMATION {
+                ControlFlags: JOB_OBJECT_CPU_RATE_CONTROL_ENABLE
+                    | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+                Anonymous: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0 {
+                    CpuRate: u32::from(percent) * 100,
+                },
+            };
+            let result = if SetInformationJobObject(
+                job,
+                JobObjectCpuRateControlInformation,
+                std::ptr::addr_of!(info).cast(),
+                std::mem::size_of::<JOBOBJECT_CPU_RATE_CONTROL_INFORMATION>() as u32,
+            ) == 0
+            {
+                Err(last_error("SetInformationJobObject failed"))
+            } else {
+                let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, child_pid);
+                if process.is_null() {
+                    Err(last_error("OpenProcess failed"))
+                } else {
+                    let assigned = AssignProcessToJobObject(job, process);
+                    CloseHandle(process);
+                    if assigned == 0 {
+                        Err(last_error("AssignProcessToJobObject failed"))
+                    } else {
+                        Ok(())
+                    }
+                }
+            };
+
+            CloseHandle(job);
+            result
+        }
+    }
+
+    pub fn release_cpu_limit(_child_pid: u32) {}
+}
+
+#[cfg(all(unix, not(all(feature = "cgroups", target_os = "linux"))))]
+mod platform {
+    use crate::error::{Error, Result};
+    use nix::libc;
+
+    /// Highest nice value, used for the lowest limits.
+    const MAX_NICE: i32 = 19;
+
+    pub fn limit_cpu(child_pid: u32, percent: u8) -> Result<()> {
+        // 100% keeps the default priority, 1% gets the lowest
+        let nice = (100 - i32::from(percent)) * MAX_NICE / 100;
+        // SAFETY: setpriority has no memory-safety preconditions
+        let result =
+            unsafe { libc::setpriority(libc::PRIO_PROCESS, child_pid as libc::id_t, nice) };
+        if result == -1 {
+            return Err(Error::CpuLimitError(format!(
+                "Cannot renice process {child_pid}: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn release_cpu_limit(_child_pid: u32) {}
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use crate::error::{Error, Result};
+
+    pub fn limit_cpu(_child_pid: u32, _percent: u8) -> Result<()> {
+        Err(Error::CpuLimitError(
+            "--cpu-limit is not supported on this platform".into(),
+        ))
+    }
+
+    pub fn release_cpu_limit(_child_pid: u32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_must_be_between_1_and_100() {
+        for percent in [0, 101, 255] {
+            assert!(matches!(
+                limit_cpu(std::process::id(), percent),
+                Err(Error::CpuLimitError(_))
+            ));
+        }
+    }
+
+    #[cfg(all(feature = "cgroups", target_os = "linux"))]
+    #[test]
+    fn cgroup_files_are_written_for_the_session() {
+        use std::fs;
+
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        // limit_cpu refuses hierarchies that are not cgroup v2
+        assert!(platform::limit_cpu_in(root, 4242, 50).is_err());
+        fs::write(root.join("cgroup.controllers"), "cpu io memory").unwrap();
+
+        platform::limit_cpu_in(root, 4242, 50).unwrap();
+
+        let session = root.join("ccuse/4242");
+        let cpus = std::thread::available_parallelism().unwrap().get() as u64;
+        assert_eq!(
+            fs::read_to_string(session.join("cpu.max")).unwrap(),
+            format!("{} 100000", 50_000 * cpus)
+        );
+        assert_eq!(
+            fs::read_to_string(session.join("cgroup.procs")).unwrap(),
+            "4242"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("ccuse/cgroup.subtree_control")).unwrap(),
+            "+cpu"
+        );
+
+        // A real cgroup directory is empty once the process is gone
+        for file in ["cpu.max", "cgroup.procs"] {
+            fs::remove_file(session.join(file)).unwrap();
+        }
+        platform::release_cpu_limit_in(root, 4242);
+        assert!(!session.exists());
+    }
+
+    /// Needs root and cgroup v2 mounted at /sys/fs/cgroup.
+    #[cfg(all(feature = "cgroups", target_os = "linux"))]
+    #[test]
+    #[ignore]
+    fn limits_a_real_process() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        let result = limit_cpu(child.id(), 10);
+        let procs =
+            std::fs::read_to_string(format!("/sys/fs/cgroup/ccuse/{}/cgroup.procs", child.id()));
+        child.kill().unwrap();
+        child.wait().unwrap();
+        release_cpu_limit(child.id());
+
+        result.unwrap();
+        assert_eq!(procs.unwrap().trim(), child.id().to_string());
+    }
+
+    #[cfg(all(unix, not(all(feature = "cgroups", target_os = "linux"))))]
+    #[test]
+    fn renices_the_process() {
+        use nix::libc;
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        let result = limit_cpu(child.id(), 50);
+        // SAFETY: getpriority has no memory-safety preconditions
+        let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, child.id() as libc::id_t) };
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        result.unwrap();
+        // Half the CPU maps to half the nice range
+        assert_eq!(nice, 9);
+    }
+}
@@ -1,3 +1,14 @@
+pub mod env_trace;
 pub mod launcher;
+pub mod limits;
+pub mod notify;
+pub mod overrides;
+pub mod ports;
+pub mod process;
+pub mod pty;
+pub mod redaction;
+pub mod tee;
+pub mod terminal;
+pub mod timing;
 
-pub use launcher::Launcher;
+pub use launcher::{launch_profile, LaunchOptions, Launcher};
@@ -0,0 +1,154 @@
+use crate::error::{Error, Result};
+use std::process::Command;
+use std::time::Duration;
+
+/// Send a desktop notification using the platform's native mechanism.
+///
+/// - macOS: `osascript -e 'display notification ...'`
+/// - Linux: `notify-send`
+/// - Windows: `BurntToast` if installed, otherwise a message box via PowerShell
+///
+/// # Errors
+///
+/// Returns an error if the notification command cannot be run or exits non-zero.
+pub fn send_notification(title: &str, body: &str) -> Result<()> {
+    run_notification(notification_command(title, body))
+}
+
+fn run_notification(mut cmd: Command) -> Result<()> {
+    let status = cmd
+        .status()
+        .map_err(|e| Error::NotifyError(e.to_string()))?;
+
+    if !status.success() {
+        return Err(Error::NotifyError(format!(
+            "notification command exited with code: {}",
+            status.code().unwrap_or(1)
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn notification_command(title: &str, body: &str) -> Command {
+    osascript_command(title, body)
+}
+
+#[cfg(any(target_os = "macos", test))]
+fn osascript_command(title: &str, body: &str) -> Command {
+    let script = format!(
+        "display notification \"{}\" with title \"{}\"",
+        escape_applescript(body),
+        escape_applescript(title)
+    );
+    let mut cmd = Command::new("osascript");
+    cmd.arg("-e").arg(script);
+    cmd
+}
+
+#[cfg(any(target_os = "macos", test))]
+fn escape_applescript(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(target_os = "windows")]
+fn notification_command(title: &str, body: &str) -> Command {
+    let title = title.replace('\'', "''");
+    let body = body.replace('\'', "''");
+    let script = format!(
+        "try {{ New-BurntToastNotification -Text '{title}','{body}' -ErrorAction Stop }} \
+         catch {{ Add-Type -AssemblyName System.Windows.Forms; \
+         [System.Windows.Forms.MessageBox]::Show('{body}', '{title}') | Out-Null }}"
+    );
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-Command", &script]);
+    cmd
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn notification_command(title: &str, body: &str) -> Command {
+    notify_send_command(title, body)
+}
+
+#[cfg(any(not(any(target_os = "macos", target_os = "windows")), test))]
+fn notify_send_command(title: &str, body: &str) -> Command {
+    let mut cmd = Command::new("notify-send");
+    cmd.arg(title).arg(body);
+    cmd
+}
+
+/// Build the notification body for a finished Claude session.
+#[must_use]
+pub fn session_summary(profile_name: &str, elapsed: Duration, exit_code: Option<i32>) -> String {
+    let secs = elapsed.as_secs();
+    let mut body = format!(
+        "Claude session finished (profile: {profile_name}, duration: {}m {}s)",
+        secs / 60,
+        secs % 60
+    );
+
+    match exit_code {
+        Some(0) => {}
+        Some(code) => body.push_str(&format!("\nClaude exited with code {code}.")),
+        None => body.push_str("\nClaude was terminated by a signal."),
+    }
+
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn notify_send_gets_title_and_body_as_arguments() {
+        let cmd = notify_send_command("Done", "it's \"finished\"");
+        assert_eq!(cmd.get_program(), "notify-send");
+        assert_eq!(args(&cmd), ["Done", "it's \"finished\""]);
+    }
+
+    #[test]
+    fn osascript_escapes_quotes_and_backslashes() {
+        let cmd = osascript_command("Say \"hi\"", r"C:\path");
+        assert_eq!(cmd.get_program(), "osascript");
+        assert_eq!(
+            args(&cmd),
+            [
+                "-e",
+                r#"display notification "C:\\path" with title "Say \"hi\"""#
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_notification_command_is_an_error() {
+        assert!(run_notification(Command::new("true")).is_ok());
+        assert!(matches!(
+            run_notification(Command::new("false")),
+            Err(Error::NotifyError(_))
+        ));
+        assert!(matches!(
+            run_notification(Command::new("ccuse-no-such-notifier")),
+            Err(Error::NotifyError(_))
+        ));
+    }
+
+    #[test]
+    fn session_summary_reports_duration_and_exit_code() {
+        let elapsed = Duration::from_secs(125);
+        assert_eq!(
+            session_summary("work", elapsed, Some(0)),
+            "Claude session finished (profile: work, duration: 2m 5s)"
+        );
+        assert!(session_summary("work", elapsed, Some(3)).ends_with("\nClaude exited with code 3."));
+        assert!(session_summary("work", elapsed, None).ends_with("terminated by a signal."));
+    }
+}
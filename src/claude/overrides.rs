@@ -0,0 +1,526 @@
+use crate::error::{Error, Result};
+use colored::Colorize;
+use regex::Regex;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+/// Validate a `--log-level` value.
+///
+/// Accepts a plain level or any valid `RUST_LOG` filter expression
+/// such as `info,hyper=warn`.
+///
+/// # Errors
+///
+/// Returns an error if the value is neither.
+pub fn validate_log_level(level: &str) -> Result<()> {
+    if LOG_LEVELS.contains(&level.to_lowercase().as_str()) || EnvFilter::try_new(level).is_ok() {
+        return Ok(());
+    }
+
+    Err(Error::ConfigError(format!(
+        "Invalid log level '{level}': expected one of {} or a RUST_LOG filter expression",
+        LOG_LEVELS.join(", ")
+    )))
+}
+
+/// Inject log-level env vars for the Claude session.
+///
+/// `trace` is shorthand for `RUST_LOG=trace` and takes precedence over
+/// `log_level`; `debug_mcp` sets `NODE_DEBUG=mcp,mcp-client`. With
+/// `no_override`, variables already present in `env` are left untouched.
+pub fn apply_log_overrides(
+    env: &mut HashMap<String, String>,
+    log_level: Option<&str>,
+    debug_mcp: bool,
+    trace: bool,
+    no_override: bool,
+) {
+    let mut set = |key: &str, value: &str| {
+        if no_override && env.contains_key(key) {
+            return;
+        }
+        env.insert(key.to_string(), value.to_string());
+    };
+
+    if trace {
+        set("RUST_LOG", "trace");
+    } else if let Some(level) = log_level {
+        set("RUST_LOG", level);
+    }
+
+    if debug_mcp {
+        set("NODE_DEBUG", "mcp,mcp-client");
+    }
+}
+
+/// Env var used for the resource group when the config does not set one.
+pub const DEFAULT_RESOURCE_GROUP_ENV_KEY: &str = "ANTHROPIC_RESOURCE_GROUP";
+
+/// Tag the session with a cost-allocation resource group.
+pub fn inject_resource_group(env: &mut HashMap<String, String>, group: Option<&str>, key: &str) {
+    if let Some(group) = group {
+        env.insert(key.to_string(), group.to_string());
+    }
+}
+
+/// A `--port-forward <local_port>[:<remote_url>]` mapping.
+#[derive(Debug, Clone)]
+pub struct PortForwardMapping {
+    pub local_port: u16,
+    /// Endpoint reached through the tunnel [default: the profile's `ANTHROPIC_BASE_URL`]
+    pub remote_url: Option<Url>,
+}
+
+impl FromStr for PortForwardMapping {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (port, remote) = match s.split_once(':') {
+            Some((port, remote)) => (port, Some(remote)),
+            None => (s, None),
+        };
+
+        let local_port = port
+            .parse::<u16>()
+            .ok()
+            .filter(|p| *p != 0)
+            .ok_or_else(|| {
+                Error::ConfigError(format!(
+                    "Invalid port-forward local port '{port}': expected 1-65535"
+                ))
+            })?;
+
+        let remote_url = remote
+            .map(|r| {
+                Url::parse(r).map_err(|e| {
+                    Error::ConfigError(format!("Invalid port-forward remote URL '{r}': {e}"))
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            local_port,
+            remote_url,
+        })
+    }
+}
+
+/// Point `ANTHROPIC_BASE_URL` at a local tunnel port.
+///
+/// The host and port of the remote URL are replaced with
+/// `localhost:<local_port>`, keeping scheme and path. The original URL is
+/// kept in `CCUSE_ORIGINAL_BASE_URL` for debugging.
+///
+/// # Errors
+///
+/// Returns an error if there is no URL to rewrite, or it cannot be parsed or
+/// is not an `http`/`https` URL.
+pub fn apply_port_forward(
+    env: &mut HashMap<String, String>,
+    mapping: &PortForwardMapping,
+) -> Result<()> {
+    let original = match &mapping.remote_url {
+        Some(url) => url.clone(),
+        None => {
+            let base = env.get("ANTHROPIC_BASE_URL").ok_or_else(|| {
+                Error::ConfigError(
+                    "--port-forward needs ANTHROPIC_BASE_URL or a remote URL (<port>:<url>)".into(),
+                )
+            })?;
+            Url::parse(base).map_err(|e| {
+                Error::ConfigError(format!("Invalid ANTHROPIC_BASE_URL '{base}': {e}"))
+            })?
+        }
+    };
+
+    if !matches!(original.scheme(), "http" | "https") {
+        return Err(Error::ConfigError(format!(
+            "Cannot port-forward '{original}': only http and https URLs are supported"
+        )));
+    }
+
+    let mut rewritten = original.clone();
+    rewritten
+        .set_host(Some("localhost"))
+        .and_then(|()| {
+            rewritten
+                .set_port(Some(mapping.local_port))
+                .map_err(|()| url::ParseError::InvalidPort)
+        })
+        .map_err(|e| Error::ConfigError(format!("Cannot rewrite '{original}': {e}")))?;
+
+    env.insert(
+        "CCUSE_ORIGINAL_BASE_URL".to_string(),
+        original.as_str().trim_end_matches('/').to_string(),
+    );
+    env.insert(
+        "ANTHROPIC_BASE_URL".to_string(),
+        rewritten.as_str().trim_end_matches('/').to_string(),
+    );
+
+    Ok(())
+}
+
+/// Prefix of the env vars that toggle Claude Code experimental features.
+pub const EXPERIMENTAL_FLAG_PREFIX: &str = "CLAUDE_EXPERIMENTAL_";
+
+/// Env var for an experimental flag name, e.g. `fast_mode` -> `CLAUDE_EXPERIMENTAL_FAST_MODE`.
+///
+/// Flag names are case-insensitive on input and uppercased for the env key.
+///
+/// # Errors
+///
+/// Returns an error if the uppercased name does not match `[A-Z_][A-Z0-9_]*`.
+pub fn experimental_flag_env_key(flag: &str) -> Result<String> {
+    static FLAG_NAME: OnceLock<Regex> = OnceLock::new();
+    let pattern = FLAG_NAME.get_or_init(|| Regex::new(r"^[A-Z_][A-Z0-9_]*$").expect("valid regex"));
+
+    let name = flag.trim().to_ascii_uppercase();
+    if !pattern.is_match(&name) {
+        return Err(Error::ConfigError(format!(
+            "Invalid experimental flag '{flag}': use letters, digits and underscores"
+        )));
+    }
+
+    Ok(format!("{EXPERIMENTAL_FLAG_PREFIX}{name}"))
+}
+
+/// Set each experimental flag's env var to `1`, or `0` when `disable` is set.
+///
+/// # Errors
+///
+/// Returns an error if a flag name is invalid; `env` is left unchanged.
+pub fn apply_experimental_flags(
+    env: &mut HashMap<String, String>,
+    flags: &[String],
+    disable: bool,
+) -> Result<()> {
+    let keys = flags
+        .iter()
+        .map(|flag| experimental_flag_env_key(flag))
+        .collect::<Result<Vec<_>>>()?;
+
+    let value = if disable { "0" } else { "1" };
+    for key in keys {
+        env.insert(key, value.to_string());
+    }
+
+    Ok(())
+}
+
+/// Env vars Claude Code reads its model from, in `ccuse profile get-model` order.
+pub const MODEL_ENV_KEYS: [&str; 4] = [
+    "ANTHROPIC_MODEL",
+    "ANTHROPIC_DEFAULT_OPUS_MODEL",
+    "ANTHROPIC_DEFAULT_SONNET_MODEL",
+    "ANTHROPIC_DEFAULT_HAIKU_MODEL",
+];
+
+/// Env var that selects `model`, chosen by its family name.
+///
+/// Haiku, Sonnet and Opus models set the matching `ANTHROPIC_DEFAULT_*_MODEL`;
+/// anything else sets `ANTHROPIC_MODEL`. Matching is case-insensitive.
+#[must_use]
+pub fn model_to_env_key(model: &str) -> &'static str {
+    let model = model.to_lowercase();
+    if model.contains("haiku") {
+        "ANTHROPIC_DEFAULT_HAIKU_MODEL"
+    } else if model.contains("sonnet") {
+        "ANTHROPIC_DEFAULT_SONNET_MODEL"
+    } else if model.contains("opus") {
+        "ANTHROPIC_DEFAULT_OPUS_MODEL"
+    } else {
+        "ANTHROPIC_MODEL"
+    }
+}
+
+/// Whether `model` looks like a Claude model id, e.g. `claude-sonnet-4-5`,
+/// `anthropic/claude-opus-4` or a Bedrock `us.anthropic.claude-...` id.
+#[must_use]
+pub fn is_known_model(model: &str) -> bool {
+    static KNOWN_MODEL: OnceLock<Regex> = OnceLock::new();
+    KNOWN_MODEL
+        .get_or_init(|| {
+            Regex::new(r"(?i)^([a-z0-9-]+[./])*(claude-|haiku|sonnet|opus)")
+                .expect("known model pattern is valid")
+        })
+        .is_match(model)
+}
+
+/// Set the env var for `model` in `env`, warning if it does not look like a Claude model.
+///
+/// Returns the key that was set.
+pub fn apply_model_override(env: &mut HashMap<String, String>, model: &str) -> &'static str {
+    if !is_known_model(model) {
+        eprintln!(
+            "{} '{model}' does not look like a Claude model; setting it anyway",
+            "Warning:".yellow()
+        );
+    }
+    let key = model_to_env_key(model);
+    env.insert(key.to_string(), model.to_string());
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn log_level_sets_rust_log() {
+        let mut vars = env(&[]);
+        apply_log_overrides(&mut vars, Some("debug"), false, false, false);
+        assert_eq!(vars, env(&[("RUST_LOG", "debug")]));
+    }
+
+    #[test]
+    fn trace_shorthand_wins_over_log_level() {
+        let mut vars = env(&[]);
+        apply_log_overrides(&mut vars, Some("warn"), false, true, false);
+        assert_eq!(vars, env(&[("RUST_LOG", "trace")]));
+    }
+
+    #[test]
+    fn debug_mcp_shorthand_sets_node_debug() {
+        let mut vars = env(&[]);
+        apply_log_overrides(&mut vars, None, true, false, false);
+        assert_eq!(vars, env(&[("NODE_DEBUG", "mcp,mcp-client")]));
+    }
+
+    #[test]
+    fn existing_values_are_kept_with_no_override() {
+        let existing = env(&[("RUST_LOG", "info"), ("NODE_DEBUG", "http")]);
+
+        let mut vars = existing.clone();
+        apply_log_overrides(&mut vars, None, true, true, true);
+        assert_eq!(vars, existing);
+
+        let mut vars = existing;
+        apply_log_overrides(&mut vars, None, true, true, false);
+        assert_eq!(vars["RUST_LOG"], "trace");
+        assert_eq!(vars["NODE_DEBUG"], "mcp,mcp-client");
+    }
+
+    #[test]
+    fn no_override_still_fills_unset_vars() {
+        let mut vars = env(&[("RUST_LOG", "info")]);
+        apply_log_overrides(&mut vars, None, true, true, true);
+        assert_eq!(vars["RUST_LOG"], "info");
+        assert_eq!(vars["NODE_DEBUG"], "mcp,mcp-client");
+    }
+
+    #[test]
+    fn log_levels_and_filter_expressions_are_valid() {
+        for level in [
+            "error",
+            "WARN",
+            "info",
+            "debug",
+            "trace",
+            "info,hyper=warn",
+            "my_crate=debug",
+        ] {
+            assert!(validate_log_level(level).is_ok(), "{level}");
+        }
+        // A bare word is a valid RUST_LOG target, so only syntax errors fail
+        for level in ["info,=", "debug=verbose", "=debug"] {
+            assert!(validate_log_level(level).is_err(), "{level}");
+        }
+    }
+
+    #[test]
+    fn resource_group_is_injected_under_the_given_key() {
+        let mut vars = env(&[]);
+        inject_resource_group(&mut vars, Some("team-a"), DEFAULT_RESOURCE_GROUP_ENV_KEY);
+        assert_eq!(vars, env(&[("ANTHROPIC_RESOURCE_GROUP", "team-a")]));
+
+        let mut vars = env(&[]);
+        inject_resource_group(&mut vars, Some("team-b"), "COST_CENTER");
+        assert_eq!(vars, env(&[("COST_CENTER", "team-b")]));
+    }
+
+    #[test]
+    fn no_resource_group_leaves_env_alone() {
+        let mut vars = env(&[("ANTHROPIC_RESOURCE_GROUP", "from-profile-env")]);
+        inject_resource_group(&mut vars, None, DEFAULT_RESOURCE_GROUP_ENV_KEY);
+        assert_eq!(vars["ANTHROPIC_RESOURCE_GROUP"], "from-profile-env");
+    }
+
+    fn forward(mapping: &str, base_url: Option<&str>) -> Result<HashMap<String, String>> {
+        let mut vars = env(&[]);
+        if let Some(base_url) = base_url {
+            vars.insert("ANTHROPIC_BASE_URL".to_string(), base_url.to_string());
+        }
+        apply_port_forward(&mut vars, &mapping.parse()?)?;
+        Ok(vars)
+    }
+
+    #[test]
+    fn port_forward_rewrites_host_and_port_keeping_scheme_and_path() {
+        for (base, expected) in [
+            ("https://api.example.com", "https://localhost:8443"),
+            ("https://api.example.com/", "https://localhost:8443"),
+            (
+                "https://api.example.com:9000/v1/proxy",
+                "https://localhost:8443/v1/proxy",
+            ),
+            (
+                "http://10.0.0.5/api?region=eu",
+                "http://localhost:8443/api?region=eu",
+            ),
+            (
+                "https://user:pw@api.example.com/v1",
+                "https://user:pw@localhost:8443/v1",
+            ),
+            ("http://[::1]:8080/v1", "http://localhost:8443/v1"),
+        ] {
+            let vars = forward("8443", Some(base)).unwrap();
+            assert_eq!(vars["ANTHROPIC_BASE_URL"], expected, "{base}");
+            assert_eq!(
+                vars["CCUSE_ORIGINAL_BASE_URL"],
+                base.trim_end_matches('/'),
+                "{base}"
+            );
+        }
+    }
+
+    #[test]
+    fn port_forward_remote_url_wins_over_the_profile() {
+        let vars = forward(
+            "9000:https://gateway.example.com/anthropic",
+            Some("https://api.example.com"),
+        )
+        .unwrap();
+        assert_eq!(
+            vars["ANTHROPIC_BASE_URL"],
+            "https://localhost:9000/anthropic"
+        );
+        assert_eq!(
+            vars["CCUSE_ORIGINAL_BASE_URL"],
+            "https://gateway.example.com/anthropic"
+        );
+    }
+
+    #[test]
+    fn port_forward_needs_a_url_and_a_valid_port() {
+        assert!(forward("8443", None).is_err());
+        assert!(forward("8443", Some("not a url")).is_err());
+        assert!(forward("0", Some("https://api.example.com")).is_err());
+        assert!(forward("70000", Some("https://api.example.com")).is_err());
+        assert!(forward("http", Some("https://api.example.com")).is_err());
+        assert!(forward("8443:not a url", Some("https://api.example.com")).is_err());
+    }
+
+    #[test]
+    fn port_forward_rejects_non_http_urls() {
+        assert!(forward("8443", Some("file:///tmp/socket")).is_err());
+        assert!(forward("8443", Some("unix:/tmp/socket")).is_err());
+    }
+
+    #[test]
+    fn experimental_flag_names_become_uppercase_env_keys() {
+        for (flag, key) in [
+            ("fast_mode", "CLAUDE_EXPERIMENTAL_FAST_MODE"),
+            ("Fast_Mode", "CLAUDE_EXPERIMENTAL_FAST_MODE"),
+            ("  tools2 ", "CLAUDE_EXPERIMENTAL_TOOLS2"),
+            ("_private", "CLAUDE_EXPERIMENTAL__PRIVATE"),
+        ] {
+            assert_eq!(experimental_flag_env_key(flag).unwrap(), key, "{flag}");
+        }
+    }
+
+    #[test]
+    fn experimental_flag_names_are_validated() {
+        // Non-ASCII letters must not sneak in through uppercasing ("ß" -> "SS")
+        for flag in ["", "2fast", "fast-mode", "fast mode", "a=b", "straße", "ıd"] {
+            assert!(experimental_flag_env_key(flag).is_err(), "{flag:?}");
+        }
+    }
+
+    #[test]
+    fn experimental_flags_are_enabled_or_disabled() {
+        let flags = vec!["fast_mode".to_string(), "TOOLS".to_string()];
+        let mut vars = env(&[("CLAUDE_EXPERIMENTAL_FAST_MODE", "0")]);
+        apply_experimental_flags(&mut vars, &flags, false).unwrap();
+        assert_eq!(
+            vars,
+            env(&[
+                ("CLAUDE_EXPERIMENTAL_FAST_MODE", "1"),
+                ("CLAUDE_EXPERIMENTAL_TOOLS", "1")
+            ])
+        );
+
+        apply_experimental_flags(&mut vars, &flags[..1], true).unwrap();
+        assert_eq!(vars["CLAUDE_EXPERIMENTAL_FAST_MODE"], "0");
+        assert_eq!(vars["CLAUDE_EXPERIMENTAL_TOOLS"], "1");
+    }
+
+    #[test]
+    fn invalid_experimental_flag_leaves_env_unchanged() {
+        let flags = vec!["fast_mode".to_string(), "bad-flag".to_string()];
+        let mut vars = HashMap::new();
+        assert!(apply_experimental_flags(&mut vars, &flags, false).is_err());
+        assert!(vars.is_empty());
+    }
+
+    #[test]
+    fn model_family_selects_the_env_key() {
+        for (model, key) in [
+            ("claude-3-5-haiku-20241022", "ANTHROPIC_DEFAULT_HAIKU_MODEL"),
+            ("claude-haiku-4-5", "ANTHROPIC_DEFAULT_HAIKU_MODEL"),
+            ("claude-sonnet-4-5", "ANTHROPIC_DEFAULT_SONNET_MODEL"),
+            (
+                "us.anthropic.claude-3-7-sonnet-20250219-v1:0",
+                "ANTHROPIC_DEFAULT_SONNET_MODEL",
+            ),
+            ("Claude-Opus-4-1", "ANTHROPIC_DEFAULT_OPUS_MODEL"),
+            ("anthropic/claude-opus-4", "ANTHROPIC_DEFAULT_OPUS_MODEL"),
+            ("opus", "ANTHROPIC_DEFAULT_OPUS_MODEL"),
+            ("claude-2.1", "ANTHROPIC_MODEL"),
+            ("kimi-k2", "ANTHROPIC_MODEL"),
+            ("", "ANTHROPIC_MODEL"),
+        ] {
+            assert_eq!(model_to_env_key(model), key, "{model}");
+        }
+    }
+
+    #[test]
+    fn known_model_ids_are_recognised() {
+        for model in [
+            "claude-sonnet-4-5",
+            "anthropic/claude-opus-4",
+            "us.anthropic.claude-3-7-sonnet-20250219-v1:0",
+            "HAIKU",
+        ] {
+            assert!(is_known_model(model), "{model}");
+        }
+        for model in ["gpt-4o", "kimi-k2", "my-claude", ""] {
+            assert!(!is_known_model(model), "{model}");
+        }
+    }
+
+    #[test]
+    fn model_override_sets_the_selected_key_only() {
+        let mut vars = env(&[("ANTHROPIC_MODEL", "claude-2.1")]);
+        let key = apply_model_override(&mut vars, "claude-opus-4-1");
+        assert_eq!(key, "ANTHROPIC_DEFAULT_OPUS_MODEL");
+        assert_eq!(
+            vars,
+            env(&[
+                ("ANTHROPIC_MODEL", "claude-2.1"),
+                ("ANTHROPIC_DEFAULT_OPUS_MODEL", "claude-opus-4-1")
+            ])
+        );
+    }
+}
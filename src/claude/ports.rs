@@ -0,0 +1,272 @@
+use crate::error::{Error, Result};
+use colored::Colorize;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Default `--port-timeout` in seconds.
+pub const DEFAULT_PORT_TIMEOUT_SECS: u64 = 30;
+
+/// Pause between connection attempts.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How often a progress dot is printed while waiting.
+const DOT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Wait until something accepts connections on `127.0.0.1:<port>`.
+///
+/// Returns `false` if the port is still closed after `timeout`.
+///
+/// # Errors
+///
+/// Returns an error if progress cannot be written to the terminal.
+pub fn wait_for_tcp_port(port: u16, timeout: Duration) -> Result<bool> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    wait_until(timeout, || {
+        TcpStream::connect_timeout(&addr, POLL_INTERVAL).is_ok()
+    })
+}
+
+/// Wait until an `http://` URL answers with a non-error status (below 400).
+///
+/// Returns `false` if it has not after `timeout`.
+///
+/// # Errors
+///
+/// Returns an error if the URL is not a valid `http://` URL.
+pub fn wait_for_url(url: &str, timeout: Duration) -> Result<bool> {
+    let parsed = parse_http_url(url)?;
+    wait_until(timeout, || http_ready(&parsed))
+}
+
+fn parse_http_url(url: &str) -> Result<Url> {
+    let parsed =
+        Url::parse(url).map_err(|e| Error::ConfigError(format!("Invalid URL '{url}': {e}")))?;
+    if parsed.scheme() != "http" {
+        return Err(Error::ConfigError(format!(
+            "--wait-for-url only supports http:// URLs, got '{url}'"
+        )));
+    }
+    Ok(parsed)
+}
+
+/// Ports and URLs that must be ready before Claude starts.
+#[derive(Debug, Clone, Default)]
+pub struct ServiceWait {
+    pub ports: Vec<u16>,
+    pub urls: Vec<String>,
+    /// How long to wait for each port or URL
+    pub timeout: Duration,
+    /// Fail instead of warning when a target is not ready in time
+    pub fail_if_unavailable: bool,
+}
+
+impl ServiceWait {
+    /// Wait for every port and URL in turn.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::PortWaitTimeout`] or [`Error::UrlWaitTimeout`] when a
+    /// target is not ready and `fail_if_unavailable` is set, or an error for
+    /// an invalid URL.
+    pub fn wait(&self) -> Result<()> {
+        // Reject bad URLs before spending time on the ports
+        for url in &self.urls {
+            parse_http_url(url)?;
+        }
+
+        for &port in &self.ports {
+            println!("Waiting for port {port}...");
+            if !wait_for_tcp_port(port, self.timeout)? {
+                if self.fail_if_unavailable {
+                    return Err(Error::PortWaitTimeout(port));
+                }
+                eprintln!(
+                    "{} port {port} is still closed after {}s, launching anyway",
+                    "Warning:".yellow(),
+                    self.timeout.as_secs()
+                );
+            }
+        }
+
+        for url in &self.urls {
+            println!("Waiting for {url}...");
+            if !wait_for_url(url, self.timeout)? {
+                if self.fail_if_unavailable {
+                    return Err(Error::UrlWaitTimeout(url.clone()));
+                }
+                eprintln!(
+                    "{} {url} is still not ready after {}s, launching anyway",
+                    "Warning:".yellow(),
+                    self.timeout.as_secs()
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Poll `ready` every [`POLL_INTERVAL`] until it succeeds or `timeout` passes,
+/// printing a dot every [`DOT_INTERVAL`].
+fn wait_until(timeout: Duration, mut ready: impl FnMut() -> bool) -> Result<bool> {
+    let started = Instant::now();
+    let mut last_dot = started;
+    let mut printed = false;
+
+    let result = loop {
+        if ready() {
+            break true;
+        }
+        if started.elapsed() >= timeout {
+            break false;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+
+        if last_dot.elapsed() >= DOT_INTERVAL {
+            print!(".");
+            io::stdout().flush()?;
+            last_dot = Instant::now();
+            printed = true;
+        }
+    };
+
+    if printed {
+        println!();
+    }
+    Ok(result)
+}
+
+/// Send one `GET` and report whether the status is below 400.
+fn http_ready(url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let port = url.port_or_known_default().unwrap_or(80);
+    let Some(addr) = (host, port)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut a| a.next())
+    else {
+        return false;
+    };
+    let Ok(mut stream) = TcpStream::connect_timeout(&addr, POLL_INTERVAL) else {
+        return false;
+    };
+    stream.set_read_timeout(Some(POLL_INTERVAL)).ok();
+
+    let path = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    };
+    let request =
+        format!("GET {path} HTTP/1.1\r\nHost: {host}:{port}\r\nConnection: close\r\n\r\n");
+    if stream.write_all(request.as_bytes()).is_err() {
+        return false;
+    }
+
+    // "HTTP/1.1 200 OK" - the status code is the second word
+    let mut head = [0; 32];
+    let Ok(n) = stream.read(&mut head) else {
+        return false;
+    };
+    String::from_utf8_lossy(&head[..n])
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .is_some_and(|code| code < 400)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// A port that nothing listens on yet.
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[test]
+    fn waits_for_a_port_that_opens_later() {
+        let port = free_port();
+        let delay = Duration::from_millis(700);
+        let server = thread::spawn(move || {
+            thread::sleep(delay);
+            let listener = TcpListener::bind(("127.0.0.1", port)).unwrap();
+            // Keep listening until the poll has connected
+            listener.accept().unwrap();
+        });
+
+        let started = Instant::now();
+        assert!(wait_for_tcp_port(port, Duration::from_secs(5)).unwrap());
+        assert!(started.elapsed() >= delay);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn gives_up_on_a_closed_port() {
+        let port = free_port();
+        let started = Instant::now();
+        assert!(!wait_for_tcp_port(port, Duration::from_secs(1)).unwrap());
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn service_wait_fails_only_when_asked() {
+        let port = free_port();
+        let mut wait = ServiceWait {
+            ports: vec![port],
+            timeout: Duration::from_millis(100),
+            ..ServiceWait::default()
+        };
+        assert!(wait.wait().is_ok());
+
+        wait.fail_if_unavailable = true;
+        assert!(matches!(wait.wait(), Err(Error::PortWaitTimeout(p)) if p == port));
+    }
+
+    #[test]
+    fn waits_for_a_url_to_stop_failing() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            // Not ready on the first request, ready on the second
+            for status in ["503 Service Unavailable", "200 OK"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = io::BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                assert_eq!(request_line, "GET /health?full=1 HTTP/1.1\r\n");
+                write!(stream, "HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n").unwrap();
+            }
+        });
+
+        let url = format!("http://127.0.0.1:{port}/health?full=1");
+        assert!(wait_for_url(&url, Duration::from_secs(5)).unwrap());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn only_http_urls_are_accepted() {
+        for url in ["https://localhost:8080", "ftp://localhost", "not a url"] {
+            assert!(wait_for_url(url, Duration::ZERO).is_err(), "{url}");
+        }
+        let wait = ServiceWait {
+            ports: vec![free_port()],
+            urls: vec!["https://localhost".into()],
+            timeout: Duration::from_secs(5),
+            ..ServiceWait::default()
+        };
+        // The URL is rejected before any port is waited for
+        let started = Instant::now();
+        assert!(matches!(wait.wait(), Err(Error::ConfigError(_))));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
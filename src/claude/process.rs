@@ -0,0 +1,211 @@
+use crate::config::{Signal, TimeoutAction};
+use crate::error::{Error, Result};
+use colored::Colorize;
+use std::process::{Child, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Default time Claude gets to exit cleanly after a timeout signal.
+pub const DEFAULT_TIMEOUT_GRACE: Duration = Duration::from_secs(10);
+
+/// How a running session should be stopped once its timeout elapses.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeoutPolicy {
+    pub timeout: Duration,
+    pub action: TimeoutAction,
+    pub signal: Signal,
+    pub grace: Duration,
+}
+
+/// Check whether a Claude exit code counts as success.
+///
+/// Zero is always a success; `additional_success` lists extra codes that
+/// should be treated the same way.
+#[must_use]
+pub fn is_success_exit_code(code: i32, additional_success: &[i32]) -> bool {
+    code == 0 || additional_success.contains(&code)
+}
+
+/// Send a signal to a child process.
+///
+/// # Errors
+///
+/// Returns an error if the signal cannot be delivered.
+#[cfg(unix)]
+pub fn send_signal_to_child(child: &mut Child, signal: Signal) -> Result<()> {
+    use nix::sys::signal::{kill, Signal as NixSignal};
+    use nix::unistd::Pid;
+
+    let nix_signal = match signal {
+        Signal::Term => NixSignal::SIGTERM,
+        Signal::Int => NixSignal::SIGINT,
+        Signal::Hup => NixSignal::SIGHUP,
+        Signal::Quit => NixSignal::SIGQUIT,
+    };
+
+    let pid = i32::try_from(child.id())
+        .map_err(|_| Error::LaunchError(format!("Invalid child pid: {}", child.id())))?;
+
+    kill(Pid::from_raw(pid), nix_signal).map_err(|e| Error::LaunchError(e.to_string()))
+}
+
+/// Send a signal to a child process.
+///
+/// Windows has no POSIX signals, so this terminates the process instead.
+///
+/// # Errors
+///
+/// Returns an error if the process cannot be terminated.
+#[cfg(windows)]
+pub fn send_signal_to_child(child: &mut Child, _signal: Signal) -> Result<()> {
+    eprintln!(
+        "{} Signals are not supported on Windows; terminating Claude instead",
+        "Warning:".yellow()
+    );
+    child.kill().map_err(|e| Error::LaunchError(e.to_string()))
+}
+
+/// Poll a child until it exits or `timeout` elapses.
+///
+/// Returns `None` if the child is still running after `timeout`.
+///
+/// # Errors
+///
+/// Returns an error if the child's status cannot be queried.
+pub fn wait_until(child: &mut Child, timeout: Duration) -> Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child
+            .try_wait()
+            .map_err(|e| Error::LaunchError(e.to_string()))?
+        {
+            return Ok(Some(status));
+        }
+
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Wait for a child, stopping it according to `policy` if it outlives the timeout.
+///
+/// With [`TimeoutAction::Signal`] the child first receives `policy.signal` and
+/// is only killed if it is still running after `policy.grace`.
+///
+/// # Errors
+///
+/// Returns an error if the child cannot be waited on, signalled, or killed.
+pub fn wait_with_timeout(child: &mut Child, policy: &TimeoutPolicy) -> Result<ExitStatus> {
+    if let Some(status) = wait_until(child, policy.timeout)? {
+        return Ok(status);
+    }
+
+    eprintln!(
+        "{} Claude session timed out after {}s",
+        "Warning:".yellow(),
+        policy.timeout.as_secs()
+    );
+
+    if policy.action == TimeoutAction::Signal {
+        send_signal_to_child(child, policy.signal)?;
+
+        if let Some(status) = wait_until(child, policy.grace)? {
+            return Ok(status);
+        }
+
+        eprintln!(
+            "{} Claude did not exit within {}s grace period, killing it",
+            "Warning:".yellow(),
+            policy.grace.as_secs()
+        );
+    }
+
+    child
+        .kill()
+        .map_err(|e| Error::LaunchError(e.to_string()))?;
+    child.wait().map_err(|e| Error::LaunchError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_and_listed_codes_are_success() {
+        assert!(is_success_exit_code(0, &[]));
+        assert!(is_success_exit_code(130, &[130]));
+        assert!(!is_success_exit_code(1, &[130]));
+    }
+
+    #[cfg(unix)]
+    mod unix {
+        use super::*;
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::Command;
+
+        fn sh(script: &str) -> Child {
+            Command::new("sh").args(["-c", script]).spawn().unwrap()
+        }
+
+        fn policy(action: TimeoutAction, grace: Duration) -> TimeoutPolicy {
+            TimeoutPolicy {
+                timeout: Duration::from_millis(300),
+                action,
+                signal: Signal::Term,
+                grace,
+            }
+        }
+
+        #[test]
+        fn exit_before_the_timeout_is_returned() {
+            let mut child = sh("exit 3");
+            let status = wait_with_timeout(
+                &mut child,
+                &policy(TimeoutAction::Kill, DEFAULT_TIMEOUT_GRACE),
+            )
+            .unwrap();
+            assert_eq!(status.code(), Some(3));
+        }
+
+        #[test]
+        fn kill_action_kills_at_the_timeout() {
+            let mut child = sh("exec sleep 30");
+            let started = Instant::now();
+            let status = wait_with_timeout(
+                &mut child,
+                &policy(TimeoutAction::Kill, DEFAULT_TIMEOUT_GRACE),
+            )
+            .unwrap();
+            assert_eq!(status.signal(), Some(9));
+            assert!(started.elapsed() < Duration::from_secs(5));
+        }
+
+        #[test]
+        fn signal_action_lets_the_child_exit_within_the_grace_period() {
+            let mut child = sh("trap 'exit 7' TERM; while :; do sleep 0.05; done");
+            let status = wait_with_timeout(
+                &mut child,
+                &policy(TimeoutAction::Signal, Duration::from_secs(5)),
+            )
+            .unwrap();
+            assert_eq!(status.code(), Some(7));
+        }
+
+        #[test]
+        fn signal_action_kills_after_the_grace_period() {
+            let mut child = sh("trap '' TERM; while :; do sleep 0.05; done");
+            let grace = Duration::from_millis(500);
+            let started = Instant::now();
+            let status =
+                wait_with_timeout(&mut child, &policy(TimeoutAction::Signal, grace)).unwrap();
+            assert_eq!(status.signal(), Some(9));
+            assert!(started.elapsed() >= Duration::from_millis(300) + grace);
+        }
+    }
+}
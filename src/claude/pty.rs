@@ -0,0 +1,247 @@
+use crate::error::{Error, Result};
+use std::process::{Command, ExitStatus};
+use std::time::Duration;
+
+/// Default `--typing-speed` in characters per second.
+pub const DEFAULT_TYPING_SPEED: u32 = 30;
+
+/// Text typed into Claude's input once the session has started.
+#[derive(Debug, Clone)]
+pub struct TypedPrompt {
+    pub text: String,
+    /// Pause between characters
+    pub delay: Duration,
+}
+
+impl TypedPrompt {
+    /// Build a prompt typed at `chars_per_second`, or with a fixed `delay_ms` when given.
+    #[must_use]
+    pub fn new(text: String, chars_per_second: u32, delay_ms: Option<u64>) -> Self {
+        let delay = delay_ms.map_or_else(
+            || Duration::from_secs_f64(1.0 / f64::from(chars_per_second.max(1))),
+            Duration::from_millis,
+        );
+        Self { text, delay }
+    }
+}
+
+/// Run `cmd` on a pseudo-terminal, type `prompt` into it, then relay the
+/// real terminal until the command exits.
+///
+/// Typing starts shortly after the command first draws output, so the
+/// characters arrive once Claude's input box is ready. The session stays
+/// fully interactive and follows terminal resizes.
+///
+/// # Errors
+///
+/// Returns an error if the PTY cannot be set up or the command fails to start.
+#[cfg(all(feature = "pty", unix))]
+pub fn run_with_typed_prompt(cmd: Command, prompt: &TypedPrompt) -> Result<ExitStatus> {
+    unix::run(cmd, prompt)
+}
+
+/// Run `cmd` on a pseudo-terminal and type `prompt` into it.
+///
+/// # Errors
+///
+/// Always fails: typing a prompt needs the `pty` feature on a Unix platform.
+#[cfg(not(all(feature = "pty", unix)))]
+pub fn run_with_typed_prompt(_cmd: Command, _prompt: &TypedPrompt) -> Result<ExitStatus> {
+    Err(Error::LaunchError(
+        "--stdin-prompt requires the `pty` feature on a Unix platform".into(),
+    ))
+}
+
+#[cfg(all(feature = "pty", unix))]
+mod unix {
+    use super::{Error, Result, TypedPrompt};
+    use nix::libc;
+    use nix::pty::{openpty, Winsize};
+    use nix::sys::termios::{cfmakeraw, tcgetattr, tcsetattr, SetArg, Termios};
+    use std::fs::File;
+    use std::io::{self, Read, Write};
+    use std::os::fd::{AsRawFd, OwnedFd};
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, ExitStatus, Stdio};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    /// Pause after Claude's first output before typing starts.
+    const TYPING_START_DELAY: Duration = Duration::from_millis(500);
+    /// How often the real terminal size is checked for changes.
+    const RESIZE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    nix::ioctl_read_bad!(get_winsize, libc::TIOCGWINSZ, Winsize);
+    nix::ioctl_write_ptr_bad!(set_winsize, libc::TIOCSWINSZ, Winsize);
+
+    /// Restores the terminal mode when dropped.
+    struct RawModeGuard(Termios);
+
+    impl Drop for RawModeGuard {
+        fn drop(&mut self) {
+            tcsetattr(io::stdin(), SetArg::TCSANOW, &self.0).ok();
+        }
+    }
+
+    fn terminal_size() -> Option<Winsize> {
+        let mut size = Winsize {
+            ws_row: 0,
+            ws_col: 0,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        // SAFETY: TIOCGWINSZ only writes into `size`
+        unsafe { get_winsize(io::stdin().as_raw_fd(), &mut size) }
+            .ok()
+            .map(|_| size)
+    }
+
+    fn pty_error(e: impl std::fmt::Display) -> Error {
+        Error::LaunchError(format!("PTY setup failed: {e}"))
+    }
+
+    pub(super) fn run(mut cmd: Command, prompt: &TypedPrompt) -> Result<ExitStatus> {
+        let pty = openpty(terminal_size().as_ref(), None).map_err(pty_error)?;
+        let slave: OwnedFd = pty.slave;
+
+        cmd.stdin(Stdio::from(slave.try_clone()?))
+            .stdout(Stdio::from(slave.try_clone()?))
+            .stderr(Stdio::from(slave));
+
+        // SAFETY: setsid and ioctl are async-signal-safe
+        unsafe {
+            cmd.pre_exec(|| {
+                // Make the PTY the controlling terminal of a new session
+                nix::unistd::setsid().map_err(io::Error::from)?;
+                if libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+
+        let mut child = cmd.spawn().map_err(|e| Error::LaunchError(e.to_string()))?;
+        // Drop our slave handles so reading the master ends when the child exits
+        drop(cmd);
+
+        let guard = tcgetattr(io::stdin()).ok().map(|original| {
+            let mut raw = original.clone();
+            cfmakeraw(&mut raw);
+            tcsetattr(io::stdin(), SetArg::TCSANOW, &raw).ok();
+            RawModeGuard(original)
+        });
+
+        let master = File::from(pty.master);
+        let started = Arc::new(AtomicBool::new(false));
+        let finished = Arc::new(AtomicBool::new(false));
+
+        // Human input → Claude
+        let mut input = master.try_clone()?;
+        thread::spawn(move || {
+            io::copy(&mut io::stdin(), &mut input).ok();
+        });
+
+        // Typed prompt → Claude, once it has drawn its UI
+        let mut typer = master.try_clone()?;
+        let text = prompt.text.clone();
+        let delay = prompt.delay;
+        let typing_started = Arc::clone(&started);
+        thread::spawn(move || {
+            while !typing_started.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(50));
+            }
+            thread::sleep(TYPING_START_DELAY);
+            let mut buf = [0; 4];
+            for c in text.chars() {
+                if typer.write_all(c.encode_utf8(&mut buf).as_bytes()).is_err() {
+                    return;
+                }
+                thread::sleep(delay);
+            }
+        });
+
+        // Real terminal size → PTY
+        let resize_master = master.try_clone()?;
+        let resize_finished = Arc::clone(&finished);
+        thread::spawn(move || {
+            let mut last = terminal_size().map(|s| (s.ws_row, s.ws_col));
+            while !resize_finished.load(Ordering::Relaxed) {
+                thread::sleep(RESIZE_POLL_INTERVAL);
+                let Some(size) = terminal_size() else {
+                    continue;
+                };
+                if last != Some((size.ws_row, size.ws_col)) {
+                    last = Some((size.ws_row, size.ws_col));
+                    // SAFETY: TIOCSWINSZ only reads `size`
+                    unsafe { set_winsize(resize_master.as_raw_fd(), &size) }.ok();
+                }
+            }
+        });
+
+        // Claude → real terminal, until the PTY closes
+        let mut output = master;
+        let mut stdout = io::stdout();
+        let mut buf = [0; 8192];
+        loop {
+            match output.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    started.store(true, Ordering::Relaxed);
+                    if stdout
+                        .write_all(&buf[..n])
+                        .and_then(|()| stdout.flush())
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+
+        finished.store(true, Ordering::Relaxed);
+        drop(guard);
+
+        child.wait().map_err(|e| Error::LaunchError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typing_speed_sets_the_delay() {
+        let prompt = TypedPrompt::new("hi".into(), DEFAULT_TYPING_SPEED, None);
+        assert_eq!(prompt.delay, Duration::from_secs_f64(1.0 / 30.0));
+        // A zero speed would divide by zero; it types one character a second
+        let prompt = TypedPrompt::new("hi".into(), 0, None);
+        assert_eq!(prompt.delay, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn fixed_delay_wins_over_typing_speed() {
+        let prompt = TypedPrompt::new("hi".into(), 1000, Some(120));
+        assert_eq!(prompt.delay, Duration::from_millis(120));
+    }
+
+    #[cfg(all(feature = "pty", unix))]
+    #[test]
+    fn typed_characters_reach_the_subprocess() {
+        let dir = tempfile::tempdir().unwrap();
+        let received = dir.path().join("received");
+        // Typing starts after the first output; no echo keeps the test output quiet
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(format!(
+            "stty -echo; echo ready; IFS= read -r line; printf '%s' \"$line\" > '{}'",
+            received.display()
+        ));
+        let prompt = TypedPrompt::new("hello, wörld!\r".into(), DEFAULT_TYPING_SPEED, Some(1));
+
+        let status = run_with_typed_prompt(cmd, &prompt).unwrap();
+
+        assert!(status.success());
+        assert_eq!(std::fs::read_to_string(&received).unwrap(), "hello, wörld!");
+    }
+}
@@ -0,0 +1,143 @@
+use crate::error::{Error, Result};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+/// Patterns redacted from every session log, even without `--log-redact-patterns`.
+pub const DEFAULT_REDACT_PATTERNS: &[&str] = &[
+    // Anthropic API keys
+    r"sk-ant-api[A-Za-z0-9_-]+",
+];
+
+/// Replacement for every redacted match.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Regexes scrubbed from session logs before they are written to disk.
+#[derive(Debug, Clone)]
+pub struct LogRedactor {
+    patterns: Vec<Regex>,
+}
+
+impl LogRedactor {
+    /// Build a redactor from the default patterns plus `extra`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern is not a valid regex.
+    pub fn new<S: AsRef<str>>(extra: impl IntoIterator<Item = S>) -> Result<Self> {
+        let mut patterns = DEFAULT_REDACT_PATTERNS
+            .iter()
+            .map(|p| Regex::new(p).expect("default redaction pattern is valid"))
+            .collect::<Vec<_>>();
+
+        for pattern in extra {
+            let pattern = pattern.as_ref();
+            patterns.push(Regex::new(pattern).map_err(|e| {
+                Error::ConfigError(format!("Invalid redaction pattern '{pattern}': {e}"))
+            })?);
+        }
+
+        Ok(Self { patterns })
+    }
+}
+
+/// Read redaction patterns from a file, one per line.
+///
+/// Blank lines and lines starting with `#` are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+pub fn read_patterns_file(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| Error::ConfigError(format!("Cannot read {}: {e}", path.display())))?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Replace every match of the redactor's patterns in `line` with [`REDACTED`].
+#[must_use]
+pub fn redact_line(redactor: &LogRedactor, line: &str) -> String {
+    redactor
+        .patterns
+        .iter()
+        .fold(line.to_string(), |line, pattern| {
+            pattern.replace_all(&line, REDACTED).into_owned()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "sk-ant-api03-AbC_d-123xyz";
+
+    #[test]
+    fn api_keys_are_redacted_from_log_lines() {
+        let redactor = LogRedactor::new(Vec::<String>::new()).unwrap();
+        let fixtures = [
+            (
+                format!("export ANTHROPIC_API_KEY={KEY}\r\n"),
+                "export ANTHROPIC_API_KEY=[REDACTED]\r\n",
+            ),
+            (
+                format!(r#"{{"headers":{{"x-api-key":"{KEY}"}}}}"#),
+                r#"{"headers":{"x-api-key":"[REDACTED]"}}"#,
+            ),
+            (
+                format!("\x1b[32m{KEY}\x1b[0m and {KEY}"),
+                "\x1b[32m[REDACTED]\x1b[0m and [REDACTED]",
+            ),
+            (
+                "no secrets, just sk-ant and api".to_string(),
+                "no secrets, just sk-ant and api",
+            ),
+        ];
+
+        for (line, expected) in &fixtures {
+            assert_eq!(redact_line(&redactor, line), *expected);
+        }
+    }
+
+    #[test]
+    fn extra_patterns_are_redacted_too() {
+        let redactor = LogRedactor::new([r"Bearer \S+", r"[\w.]+@example\.com"]).unwrap();
+        assert_eq!(
+            redact_line(
+                &redactor,
+                &format!("Authorization: Bearer abc.def for jo.doe@example.com, key {KEY}")
+            ),
+            "Authorization: [REDACTED] for [REDACTED], key [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn invalid_patterns_are_config_errors() {
+        assert!(matches!(
+            LogRedactor::new(["unclosed("]),
+            Err(Error::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn pattern_files_skip_blank_lines_and_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("patterns");
+        fs::write(
+            &path,
+            "# tokens\nBearer \\S+\n\n   \n  ghp_[A-Za-z0-9]+  \n#x\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_patterns_file(&path).unwrap(),
+            [r"Bearer \S+", "ghp_[A-Za-z0-9]+"]
+        );
+        assert!(read_patterns_file(&dir.path().join("missing")).is_err());
+    }
+}
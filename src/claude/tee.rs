@@ -0,0 +1,293 @@
+use crate::claude::redaction::LogRedactor;
+use crate::error::{Error, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread::JoinHandle;
+
+/// Build a command that runs `program` under a pseudo-terminal and copies
+/// everything it prints to `tee`'s file.
+///
+/// The PTY is provided by `script(1)`, which already handles raw mode,
+/// `SIGWINCH` and interactive prompts, so Claude behaves exactly as it would
+/// without the tee.
+///
+/// # Errors
+///
+/// Returns an error if `script` is not installed or tee is unsupported on
+/// this platform or build.
+pub fn wrap_command(program: &str, args: &[String], tee: &TeeFile) -> Result<Command> {
+    // script writes to the FIFO; the writer thread fills the real file
+    script_command(program, args, &tee.fifo)
+}
+
+/// Tee file fed through a FIFO.
+///
+/// `script(1)` writes to the FIFO and a background thread copies it line by
+/// line into the real file, flushing after every line. The writer drops the
+/// start and done lines util-linux `script` adds even with `-q`, so the file
+/// holds exactly what the terminal showed, and redacts each line when given a
+/// redactor. Only the file is redacted; the terminal shows Claude's output
+/// unchanged.
+pub struct TeeFile {
+    fifo: PathBuf,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl TeeFile {
+    /// Create the FIFO and start copying it into `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` cannot be opened or the FIFO cannot be created.
+    #[cfg(all(feature = "tty", unix))]
+    pub fn start(path: &Path, append: bool, redactor: Option<LogRedactor>) -> Result<Self> {
+        use crate::claude::redaction::redact_line;
+        use std::fs::OpenOptions;
+        use std::io::{BufReader, Write};
+
+        let mut output = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)
+            .map_err(|e| Error::TeeError(format!("Cannot open {}: {e}", path.display())))?;
+
+        let fifo = std::env::temp_dir().join(format!("ccuse-tee-{}.fifo", std::process::id()));
+        std::fs::remove_file(&fifo).ok();
+        nix::unistd::mkfifo(
+            &fifo,
+            nix::sys::stat::Mode::S_IRUSR | nix::sys::stat::Mode::S_IWUSR,
+        )
+        .map_err(|e| Error::TeeError(format!("Cannot create {}: {e}", fifo.display())))?;
+
+        let reader_path = fifo.clone();
+        let writer = std::thread::spawn(move || {
+            // Blocks until script opens the FIFO, or dropping the tee file unblocks it
+            let Ok(file) = std::fs::File::open(&reader_path) else {
+                return;
+            };
+            copy_typescript(BufReader::new(file), |line| {
+                let written = match &redactor {
+                    Some(redactor) => {
+                        let redacted = redact_line(redactor, &String::from_utf8_lossy(line));
+                        output.write_all(redacted.as_bytes())
+                    }
+                    None => output.write_all(line),
+                };
+                written.and_then(|()| output.flush()).is_ok()
+            });
+        });
+
+        Ok(Self {
+            fifo,
+            writer: Some(writer),
+        })
+    }
+
+    /// Create the FIFO and start copying it into `path`.
+    ///
+    /// # Errors
+    ///
+    /// Always fails: tee needs the `tty` feature on a Unix platform.
+    #[cfg(not(all(feature = "tty", unix)))]
+    pub fn start(_path: &Path, _append: bool, _redactor: Option<LogRedactor>) -> Result<Self> {
+        Err(Error::TeeError(
+            "--tee-output requires the `tty` feature on Linux or macOS".into(),
+        ))
+    }
+
+    /// Wait for the remaining output to be written and remove the FIFO.
+    ///
+    /// Dropping the tee file does the same, so the FIFO is also cleaned up
+    /// when the launch fails before Claude starts.
+    pub fn finish(self) {
+        drop(self);
+    }
+}
+
+impl Drop for TeeFile {
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            #[cfg(all(feature = "tty", unix))]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+
+                // If script never opened the FIFO the writer is still blocked
+                // in open(); opening and closing the write end lets it see EOF.
+                // The non-blocking open fails until the writer has reached
+                // open(), which it may not have yet when the launch fails early
+                while !writer.is_finished()
+                    && std::fs::OpenOptions::new()
+                        .write(true)
+                        .custom_flags(nix::libc::O_NONBLOCK)
+                        .open(&self.fifo)
+                        .is_err()
+                {
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                }
+            }
+            writer.join().ok();
+        }
+        std::fs::remove_file(&self.fifo).ok();
+    }
+}
+
+/// Lines util-linux `script` writes around the session in its typescript
+#[cfg(all(feature = "tty", unix))]
+const SCRIPT_STARTED: &[u8] = b"Script started on ";
+#[cfg(all(feature = "tty", unix))]
+const SCRIPT_DONE: &[u8] = b"Script done on ";
+
+/// Pass each line of a typescript to `write_line`, minus the start and done
+/// lines `script` adds; stops early once `write_line` returns false.
+#[cfg(all(feature = "tty", unix))]
+fn copy_typescript(mut reader: impl std::io::BufRead, mut write_line: impl FnMut(&[u8]) -> bool) {
+    let mut line = Vec::new();
+    let mut first = true;
+    // script starts its done line with a newline of its own, so each line's
+    // newline is held back until we know the done line does not follow
+    let mut held_newline = false;
+    while matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+        let is_header = std::mem::take(&mut first) && line.starts_with(SCRIPT_STARTED);
+        if line.starts_with(SCRIPT_DONE) {
+            held_newline = false;
+        } else if !is_header {
+            if std::mem::take(&mut held_newline) && !write_line(b"\n") {
+                return;
+            }
+            if line.last() == Some(&b'\n') {
+                line.pop();
+                held_newline = true;
+            }
+            if !line.is_empty() && !write_line(&line) {
+                return;
+            }
+        }
+        line.clear();
+    }
+    if held_newline {
+        write_line(b"\n");
+    }
+}
+
+#[cfg(all(feature = "tty", target_os = "linux"))]
+fn script_command(program: &str, args: &[String], path: &Path) -> Result<Command> {
+    let script = which::which("script")
+        .map_err(|_| Error::TeeError("`script` (util-linux) not found in PATH".into()))?;
+
+    let command_line = std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .map(shell_quote)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut cmd = Command::new(script);
+    // -e returns Claude's exit code, -f flushes after every write
+    cmd.args(["-q", "-e", "-f"]);
+    cmd.arg("-c").arg(command_line).arg(path);
+    Ok(cmd)
+}
+
+#[cfg(all(feature = "tty", target_os = "macos"))]
+fn script_command(program: &str, args: &[String], path: &Path) -> Result<Command> {
+    let script =
+        which::which("script").map_err(|_| Error::TeeError("`script` not found in PATH".into()))?;
+
+    let mut cmd = Command::new(script);
+    // -F flushes after every write
+    cmd.args(["-q", "-F"]);
+    cmd.arg(path).arg(program).args(args);
+    Ok(cmd)
+}
+
+#[cfg(not(all(feature = "tty", any(target_os = "linux", target_os = "macos"))))]
+fn script_command(_program: &str, _args: &[String], _path: &Path) -> Result<Command> {
+    Err(Error::TeeError(
+        "--tee-output requires the `tty` feature on Linux or macOS".into(),
+    ))
+}
+
+/// Quote `arg` for a POSIX shell command line.
+#[cfg(all(feature = "tty", target_os = "linux"))]
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+#[cfg(all(test, feature = "tty", unix))]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::process::Stdio;
+
+    fn copied(typescript: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        copy_typescript(Cursor::new(typescript), |line| {
+            out.extend_from_slice(line);
+            true
+        });
+        out
+    }
+
+    #[test]
+    fn copy_typescript_drops_script_start_and_done_lines() {
+        let typescript = b"Script started on 2026-10-18 [COMMAND=\"claude\"]\n\
+            a\r\n\n\nb\r\n\nScript done on 2026-10-18 [COMMAND_EXIT_CODE=\"0\"]\n";
+        assert_eq!(copied(typescript), b"a\r\n\n\nb\r\n");
+
+        let unterminated = b"Script started on 2026-10-18\nprompt> \nScript done on 2026-10-18\n";
+        assert_eq!(copied(unterminated), b"prompt> ");
+    }
+
+    #[test]
+    fn copy_typescript_keeps_output_without_script_lines() {
+        assert_eq!(
+            copied(b"a\r\nScript started on\r\n\n"),
+            b"a\r\nScript started on\r\n\n"
+        );
+        assert_eq!(copied(b"no newline"), b"no newline");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn tee_file_matches_terminal_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tee.log");
+        let run = |tee: &TeeFile, text: &str| {
+            let output = wrap_command("printf", &[text.to_string()], tee)
+                .unwrap()
+                .stdin(Stdio::null())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{output:?}");
+            output.stdout
+        };
+
+        // The FIFO is named after the process, so the cases run in turn
+        let tee = TeeFile::start(&path, false, None).unwrap();
+        let first = run(&tee, r"plain\n\033[31mred\033[0m\n\ndone");
+        tee.finish();
+        assert!(first.starts_with(b"plain\r\n\x1b[31mred"));
+        assert_eq!(std::fs::read(&path).unwrap(), first);
+
+        let tee = TeeFile::start(&path, true, None).unwrap();
+        let second = run(&tee, r"again\n");
+        tee.finish();
+        assert_eq!(std::fs::read(&path).unwrap(), [first, second].concat());
+
+        let redactor = LogRedactor::new(["hunter2"]).unwrap();
+        let tee = TeeFile::start(&path, false, Some(redactor)).unwrap();
+        let shown = run(&tee, r"password hunter2\n");
+        tee.finish();
+        assert_eq!(shown, b"password hunter2\r\n");
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(!written.contains("hunter2"), "{written}");
+        assert!(written.starts_with("password "), "{written}");
+
+        // A launch that fails before script runs just drops the tee file
+        let tee = TeeFile::start(&path, false, None).unwrap();
+        let fifo = tee.fifo.clone();
+        drop(tee);
+        assert!(!fifo.exists());
+    }
+}
@@ -0,0 +1,144 @@
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+
+/// Open `cmd args` in a new terminal emulator window and return without waiting.
+///
+/// On Linux the first available of `$TERMINAL`, `x-terminal-emulator`,
+/// `gnome-terminal`, `konsole` and `xterm` is used; it inherits `env`.
+/// Terminal.app on macOS starts a fresh login shell, so the variables in
+/// `env` that differ from ccuse's own environment are exported on the
+/// command line instead.
+///
+/// # Errors
+///
+/// Returns an error if no terminal emulator is available or it fails to start.
+#[cfg(target_os = "linux")]
+pub fn open_in_new_terminal(
+    cmd: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+) -> Result<()> {
+    let candidates = std::env::var("TERMINAL").ok().into_iter().chain(
+        ["x-terminal-emulator", "gnome-terminal", "konsole", "xterm"]
+            .into_iter()
+            .map(str::to_string),
+    );
+
+    for terminal in candidates {
+        if which::which(&terminal).is_err() {
+            continue;
+        }
+
+        // gnome-terminal deprecated -e in favour of `--`
+        let exec_flag = if terminal.ends_with("gnome-terminal") {
+            "--"
+        } else {
+            "-e"
+        };
+
+        let mut command = Command::new(&terminal);
+        command.arg(exec_flag).arg(cmd).args(args);
+        return spawn_detached(command, env);
+    }
+
+    Err(Error::LaunchError(
+        "No terminal emulator found; set $TERMINAL".into(),
+    ))
+}
+
+/// Open `cmd args` in a new terminal emulator window and return without waiting.
+///
+/// # Errors
+///
+/// Returns an error if Terminal.app cannot be scripted.
+#[cfg(target_os = "macos")]
+pub fn open_in_new_terminal(
+    cmd: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+) -> Result<()> {
+    let mut parts = Vec::new();
+
+    if let Ok(cwd) = std::env::current_dir() {
+        parts.push(format!("cd {} &&", shell_quote(&cwd.to_string_lossy())));
+    }
+    parts.push("env".to_string());
+    if std::env::var_os("CLAUDECODE").is_some() {
+        parts.push("-u CLAUDECODE".to_string());
+    }
+    let mut changed: Vec<_> = env
+        .iter()
+        .filter(|(key, value)| std::env::var(key).ok().as_ref() != Some(*value))
+        .collect();
+    changed.sort();
+    parts.extend(
+        changed
+            .into_iter()
+            .map(|(key, value)| shell_quote(&format!("{key}={value}"))),
+    );
+    parts.push(shell_quote(cmd));
+    parts.extend(args.iter().map(|arg| shell_quote(arg)));
+
+    let command_line = parts.join(" ").replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "tell application \"Terminal\"\n  activate\n  do script \"{command_line}\"\nend tell"
+    );
+
+    let mut command = Command::new("osascript");
+    command.args(["-e", &script]);
+    spawn_detached(command, &HashMap::new())
+}
+
+/// Open `cmd args` in a new terminal emulator window and return without waiting.
+///
+/// # Errors
+///
+/// Returns an error if the console window fails to start.
+#[cfg(windows)]
+pub fn open_in_new_terminal(
+    cmd: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+) -> Result<()> {
+    let mut command = Command::new("cmd");
+    // The empty string is the window title expected by `start`
+    command.args(["/C", "start", ""]).arg(cmd).args(args);
+    spawn_detached(command, env)
+}
+
+/// Open `cmd args` in a new terminal emulator window and return without waiting.
+///
+/// # Errors
+///
+/// Always fails: no terminal emulator integration for this platform.
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn open_in_new_terminal(
+    _cmd: &str,
+    _args: &[String],
+    _env: &HashMap<String, String>,
+) -> Result<()> {
+    Err(Error::LaunchError(
+        "--new-terminal is not supported on this platform".into(),
+    ))
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+fn spawn_detached(mut command: Command, env: &HashMap<String, String>) -> Result<()> {
+    if !env.is_empty() {
+        command.env_clear().envs(env);
+    }
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| Error::LaunchError(format!("Failed to open a new terminal: {e}")))
+}
+
+/// Quote `arg` for a POSIX shell command line.
+#[cfg(target_os = "macos")]
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
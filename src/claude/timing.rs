@@ -0,0 +1,85 @@
+use colored::Colorize;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+static CHECKPOINTS: Mutex<Vec<(&'static str, Instant)>> = Mutex::new(Vec::new());
+
+/// Records how long ccuse spends between `ccuse use` and spawning Claude.
+///
+/// The timer is process-global so checkpoints can be recorded from any
+/// layer without threading state through; it is a no-op until enabled.
+pub struct StartupTimer;
+
+impl StartupTimer {
+    /// Start measuring, recording a "start" checkpoint.
+    pub fn enable(json_output: bool) {
+        ENABLED.store(true, Ordering::Relaxed);
+        JSON_OUTPUT.store(json_output, Ordering::Relaxed);
+        Self::checkpoint("start");
+    }
+
+    /// Record a labelled checkpoint at the current instant.
+    pub fn checkpoint(label: &'static str) {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Ok(mut checkpoints) = CHECKPOINTS.lock() {
+            checkpoints.push((label, Instant::now()));
+        }
+    }
+
+    /// Print the startup breakdown to stderr so Claude's stdout stays clean.
+    pub fn report() {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(checkpoints) = CHECKPOINTS.lock() else {
+            return;
+        };
+        let Some(&(_, start)) = checkpoints.first() else {
+            return;
+        };
+
+        let rows: Vec<(&str, f64, f64)> = checkpoints
+            .iter()
+            .scan(start, |prev, &(label, at)| {
+                let delta = at.duration_since(*prev).as_secs_f64() * 1000.0;
+                *prev = at;
+                Some((
+                    label,
+                    at.duration_since(start).as_secs_f64() * 1000.0,
+                    delta,
+                ))
+            })
+            .collect();
+
+        if JSON_OUTPUT.load(Ordering::Relaxed) {
+            let entries: Vec<_> = rows
+                .iter()
+                .map(|(label, cumulative, delta)| {
+                    json!({ "label": label, "cumulative_ms": cumulative, "delta_ms": delta })
+                })
+                .collect();
+            eprintln!("{}", json!({ "startup": entries }));
+            return;
+        }
+
+        let width = rows
+            .iter()
+            .map(|(label, _, _)| label.len())
+            .max()
+            .unwrap_or(0);
+        eprintln!("{}", "Startup breakdown:".bold());
+        eprintln!(
+            "  {:width$}  {:>12}  {:>12}",
+            "checkpoint", "cumulative", "delta"
+        );
+        for (label, cumulative, delta) in &rows {
+            eprintln!("  {label:width$}  {cumulative:>10.2}ms  {delta:>10.2}ms");
+        }
+    }
+}
@@ -1,4 +1,15 @@
-use clap::{Parser, Subcommand};
+use crate::claude::ports::DEFAULT_PORT_TIMEOUT_SECS;
+use crate::claude::pty::DEFAULT_TYPING_SPEED;
+use crate::cli::commands::usage_cmd::DEFAULT_CACHE_TTL_SECS;
+use crate::cli::completions::Shell;
+use crate::cli::env_export::ExportShell;
+use crate::config::env_copy::MergeStrategy;
+use crate::config::format::ImportFormat;
+use crate::config::sorting::{ListFormat, ProfileSort};
+use crate::config::{DisplayNameStrategy, Signal, TimeoutAction};
+use crate::db::DEFAULT_APP_TYPE;
+use clap::{ArgAction, ArgGroup, Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "ccuse")]
@@ -9,62 +20,927 @@ pub struct Args {
 
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Disable colored output, as does setting NO_COLOR
+    #[arg(long = "no-color", global = true)]
+    pub no_color: bool,
+
+    /// Use this configuration directory instead of the default [env: CCUSE_CONFIG_DIR]
+    #[arg(long = "config-dir", value_name = "PATH", global = true)]
+    pub config_dir: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Use a profile to launch Claude Code
-    Use {
-        /// Profile name to use
-        name: String,
+    Use(Box<UseArgs>),
+
+    /// Update profiles from CC-Switch database
+    Update(UpdateArgs),
+
+    /// List all available profiles
+    List(ListArgs),
 
-        /// Skip permissions check (equivalent to --dangerously-skip-permissions)
-        #[arg(short = 'b', long = "bypass", global = false)]
-        bypass: bool,
+    /// Show raw CC-Switch database contents without importing
+    #[command(name = "audit-ccswitch")]
+    AuditCcSwitch {
+        /// Show the full settings_config of a single CC-Switch profile
+        #[arg(long = "profile-name", value_name = "NAME")]
+        profile_name: Option<String>,
 
-        /// Additional arguments to pass to Claude Code
-        #[arg(allow_hyphen_values = true)]
-        args: Vec<String>,
+        /// Emit raw rows as JSON
+        #[arg(long = "json")]
+        json: bool,
+
+        /// List all table names in the database
+        #[arg(long = "all-tables")]
+        all_tables: bool,
     },
 
-    /// Update profiles from CC-Switch database
-    Update,
+    /// Add a new profile, in the editor unless flags describe it completely
+    Add(AddArgs),
 
-    /// List all available profiles
-    List,
+    /// Import profiles from a JSON, TOML or dotenv file
+    Import {
+        /// Path of the file to import
+        path: PathBuf,
+
+        /// File format
+        #[arg(long = "format", value_enum, default_value_t = ImportFormat::Auto)]
+        format: ImportFormat,
 
-    /// Add a new profile interactively
-    Add,
+        /// Print the detected format and exit
+        #[arg(long = "detect-only")]
+        detect_only: bool,
+
+        /// Parse the file and report errors without saving
+        #[arg(long = "validate")]
+        validate: bool,
+
+        /// Save the imported profile under this name instead
+        #[arg(long = "rename", value_name = "NAME")]
+        rename: Option<String>,
+
+        /// Add this tag to every imported profile (repeatable)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Append this Claude Code argument to every imported profile's launch args (repeatable)
+        #[arg(long = "launch-arg", value_name = "ARG", allow_hyphen_values = true)]
+        launch_args: Vec<String>,
+    },
 
     /// Remove a profile
     Remove {
         /// Name of the profile to remove
+        #[arg(conflicts_with_all = ["category", "pattern"])]
         name: Option<String>,
 
         /// Remove all profiles and delete the data file
-        #[arg(long = "all", short = 'a')]
+        #[arg(long = "all", short = 'a', conflicts_with_all = ["name", "category", "pattern"])]
         all: bool,
+
+        /// Remove every profile in this category (case-insensitive)
+        #[arg(long = "category", value_name = "NAME")]
+        category: Option<String>,
+
+        /// Remove every profile whose name matches this glob, e.g. 'test-*'
+        #[arg(long = "match", value_name = "GLOB")]
+        pattern: Option<String>,
+    },
+
+    /// Export a profile as a standalone JSON file
+    Export {
+        /// Name of the profile
+        name: String,
+
+        /// File to write [default: stdout]
+        output: Option<PathBuf>,
+
+        /// Replace ANTHROPIC_AUTH_TOKEN and ANTHROPIC_API_KEY with a placeholder
+        #[arg(long = "redact-secrets")]
+        redact_secrets: bool,
+    },
+
+    /// Show every field of a single profile
+    Show {
+        /// Name of the profile
+        name: String,
+
+        /// Print secret env values in full instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
+
+    /// Open a profile's settings in the editor and save them after validating
+    Edit {
+        /// Name of the profile
+        name: String,
+    },
+
+    /// Find profiles by name, display name, category, env key or base URL
+    Search {
+        /// Text to look for, ignoring case
+        query: String,
+
+        /// Only match env var keys and the ANTHROPIC_BASE_URL value
+        #[arg(long = "env-only")]
+        env_only: bool,
     },
 
     /// Rename a profile
     Rename {
         /// Current name of the profile
-        old_name: String,
+        #[arg(required_unless_present = "regex", conflicts_with = "regex")]
+        old_name: Option<String>,
 
         /// New name for the profile
-        new_name: String,
+        #[arg(required_unless_present = "regex")]
+        new_name: Option<String>,
+
+        /// Rename every profile whose name matches this regex
+        #[arg(long = "regex", value_name = "PATTERN", requires = "replace")]
+        regex: Option<String>,
+
+        /// Replacement for --regex matches; $1 or ${name} insert capture groups
+        #[arg(long = "replace", value_name = "REPLACEMENT", requires = "regex")]
+        replace: Option<String>,
+    },
+
+    /// Duplicate a profile under a new name
+    Clone {
+        /// Name of the profile to copy
+        source: String,
+
+        /// Name of the new profile
+        dest: String,
+    },
+
+    /// Inspect and modify individual profiles
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommands,
+    },
+
+    /// Print a profile's env vars as shell exports, or manage them
+    #[command(args_conflicts_with_subcommands = true)]
+    Env {
+        /// Profile whose env vars to print, e.g. for `eval "$(ccuse env work)"`
+        name: Option<String>,
+
+        /// Shell syntax to print [default: detected from $SHELL]
+        #[arg(long = "format", value_enum)]
+        format: Option<ExportShell>,
+
+        #[command(subcommand)]
+        command: Option<EnvCommands>,
+    },
+
+    /// Run any command with a profile's env vars, e.g. `ccuse exec work -- ./script.sh`
+    Exec {
+        /// Profile whose env to use
+        name: String,
+
+        /// Command to run and its arguments
+        #[arg(last = true, required = true, value_name = "COMMAND")]
+        command: Vec<String>,
+    },
+
+    /// Inspect Claude experimental feature flags
+    ExperimentalFlags {
+        #[command(subcommand)]
+        command: ExperimentalFlagsCommands,
+    },
+
+    /// Inspect API calls logged by `use --capture-api-calls`
+    #[command(args_conflicts_with_subcommands = true)]
+    ApiLog {
+        /// Session id to show; lists sessions when omitted
+        session_id: Option<String>,
+
+        #[command(subcommand)]
+        command: Option<ApiLogCommands>,
+    },
+
+    /// Show the profile most recently launched with `ccuse use`
+    Current,
+
+    /// Summarize the stored profiles by source, category and settings
+    Stats {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Count the recorded launches per resource group instead
+        #[arg(long = "by-resource-group")]
+        by_resource_group: bool,
+    },
+
+    /// Show the profiles recently launched with `ccuse use`, most recent first
+    History {
+        /// Delete the launch history
+        #[arg(long)]
+        clear: bool,
+    },
+
+    /// Show or set the profile `ccuse use` launches when no name is given
+    Default {
+        /// Profile to make the default [default: show the current default]
+        name: Option<String>,
+
+        /// Unset the default profile
+        #[arg(long = "clear", conflicts_with = "name")]
+        clear: bool,
+    },
+
+    /// Report profile directories and profile names that do not match up
+    Prune {
+        /// Remove empty directories, re-register misnamed profiles and drop names without a profile
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Restore profiles from a backup taken by `remove --all`
+    Restore {
+        /// Backup to restore, e.g. 20250101-120000 [default: list backups]
+        timestamp: Option<String>,
+    },
+
+    /// Move plaintext tokens and keys from settings.json into the OS keyring
+    MigrateSecrets {
+        /// Profile to migrate [default: all profiles]
+        name: Option<String>,
     },
 
     /// Show configuration directory
-    ConfigDir,
+    ConfigDir {
+        /// Also open it in the file manager
+        #[arg(long)]
+        open: bool,
+    },
+
+    /// Check for common setup problems and how to fix them
+    Doctor,
+
+    /// Check that one or all profiles are valid
+    Validate {
+        /// Profile to check [default: all profiles]
+        name: Option<String>,
+    },
 
     /// Install shell completions interactively
-    Completions,
+    Completions {
+        /// Shell to generate completions for [default: detected from $SHELL]
+        #[arg(long = "shell", value_enum)]
+        shell: Option<Shell>,
+
+        /// Print the script to stdout instead of installing it
+        #[arg(long = "print")]
+        print: bool,
+
+        /// Write a static script with the current profile names baked in
+        #[arg(long = "generate-from-config", alias = "static")]
+        generate_from_config: bool,
+
+        /// Overwrite the installed script without prompting, e.g. after upgrading; fails if none is installed
+        #[arg(long = "regenerate", conflicts_with_all = ["print", "generate_from_config"])]
+        regenerate: bool,
+    },
+
+    /// Show version and build information
+    Version {
+        /// Check GitHub for a newer release
+        #[arg(long = "check-update")]
+        check_update: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List Claude Code executables in PATH with their versions
+    ListClaudeVersions,
 
     /// Analyze Claude Code token usage with ccusage
     Usage {
+        /// Reuse ccusage output cached within --cache-ttl, caching new output
+        #[arg(long = "cache", conflicts_with = "offline")]
+        cache: bool,
+
+        /// Seconds a cached result stays valid for --cache
+        #[arg(long = "cache-ttl", value_name = "SECS", default_value_t = DEFAULT_CACHE_TTL_SECS, requires = "cache")]
+        cache_ttl: u64,
+
+        /// Only print cached ccusage output, failing if there is none
+        #[arg(long = "offline")]
+        offline: bool,
+
+        /// Run ccusage@<VERSION> with bunx/npx [env: CCUSE_CCUSAGE_VERSION] [default: latest]
+        #[arg(long = "ccusage-version", value_name = "VERSION")]
+        ccusage_version: Option<String>,
+
+        /// Do not use a ccusage installed in PATH
+        #[arg(long = "no-local")]
+        no_local: bool,
+
         /// Additional arguments to pass to ccusage
         #[arg(allow_hyphen_values = true, trailing_var_arg = true)]
         args: Vec<String>,
     },
 }
+
+#[derive(Subcommand)]
+pub enum ApiLogCommands {
+    /// Show request totals, token usage and latency percentiles
+    Stats {
+        /// Session id
+        session_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum EnvCommands {
+    /// Copy env vars from one profile to another
+    Copy {
+        /// Profile to copy from
+        src: String,
+
+        /// Profile to copy to
+        dst: String,
+
+        /// Only copy these keys [default: all]
+        #[arg(long = "keys", value_name = "KEY1,KEY2", value_delimiter = ',')]
+        keys: Vec<String>,
+
+        /// How to handle keys that already exist in the destination
+        #[arg(long = "merge-strategy", value_enum, default_value_t = MergeStrategy::Overwrite)]
+        merge_strategy: MergeStrategy,
+
+        /// Remove the copied keys from the source profile
+        #[arg(long = "move")]
+        move_keys: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExperimentalFlagsCommands {
+    /// List the experimental flags a profile sets
+    List {
+        /// Profile name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileCommands {
+    /// Import a profile from an http(s) or file:// URL
+    ImportFromUrl {
+        /// URL of a JSON or TOML profile
+        url: String,
+
+        /// Profile name [default: from the content or the URL file name]
+        #[arg(long = "name")]
+        name: Option<String>,
+
+        /// Extra request header, e.g. "Authorization: Bearer ..." (repeatable)
+        #[arg(long = "headers", value_name = "KEY:VALUE")]
+        headers: Vec<String>,
+
+        /// Expected SHA-256 of the downloaded content
+        #[arg(long = "verify-checksum", value_name = "SHA256")]
+        verify_checksum: Option<String>,
+    },
+
+    /// List ${VAR} template variables referenced by a profile's env values
+    TemplateVars {
+        /// Profile name
+        name: String,
+
+        /// Exit with an error if any template variable is undefined
+        #[arg(long = "check")]
+        check: bool,
+
+        /// Show the final expanded env values
+        #[arg(long = "resolve")]
+        resolve: bool,
+    },
+
+    /// Gzip-compress a profile's settings.json to save space
+    Compress {
+        /// Profile name
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
+
+        /// Compress all profiles
+        #[arg(long = "all", short = 'a', conflicts_with = "name")]
+        all: bool,
+    },
+
+    /// Convert a compressed profile back to a plain settings.json
+    Decompress {
+        /// Profile name
+        name: String,
+    },
+
+    /// Set the default cost-allocation resource group of a profile
+    SetResourceGroup {
+        /// Profile name
+        profile: String,
+
+        /// Resource group name
+        #[arg(required_unless_present = "clear")]
+        group: Option<String>,
+
+        /// Remove the resource group instead
+        #[arg(long = "clear", conflicts_with = "group")]
+        clear: bool,
+    },
+
+    /// Set a profile's model without knowing which env var to use
+    SetModel {
+        /// Profile name
+        profile: String,
+
+        /// Model id, e.g. claude-sonnet-4-5
+        model: String,
+    },
+
+    /// Show the models a profile configures
+    GetModel {
+        /// Profile name
+        profile: String,
+    },
+
+    /// Change the name shown by `ccuse list` without renaming the profile
+    RenameDisplay {
+        /// Profile name
+        name: String,
+
+        /// New display name
+        #[arg(required_unless_present = "clear_display_name")]
+        new_display_name: Option<String>,
+
+        /// Remove the display name so the profile name is shown again
+        #[arg(long = "clear-display-name", conflicts_with = "new_display_name")]
+        clear_display_name: bool,
+    },
+}
+
+#[derive(clap::Args)]
+pub struct UseArgs {
+    /// Profile name to use [default: the profile set with `ccuse default`]
+    pub name: Option<String>,
+
+    /// Skip permissions check (equivalent to --dangerously-skip-permissions)
+    #[arg(short = 'b', long = "bypass", global = false)]
+    pub bypass: bool,
+
+    /// Send a desktop notification when the Claude session ends
+    #[arg(long = "notify")]
+    pub notify: bool,
+
+    /// Stop the Claude session after this many seconds
+    #[arg(long = "timeout", value_name = "SECS")]
+    pub timeout: Option<u64>,
+
+    /// What to do when the timeout is reached [default: profile setting or kill]
+    #[arg(long = "timeout-action", value_enum, requires = "timeout")]
+    pub timeout_action: Option<TimeoutAction>,
+
+    /// Signal sent with --timeout-action signal [default: SIGTERM]
+    #[arg(long = "timeout-signal", value_enum, requires = "timeout")]
+    pub timeout_signal: Option<Signal>,
+
+    /// Seconds to wait after the timeout signal before killing Claude
+    #[arg(long = "timeout-grace", value_name = "SECS", requires = "timeout")]
+    pub timeout_grace: Option<u64>,
+
+    /// Expand $VAR references in profile env values from the current shell env
+    #[arg(long = "env-substitute")]
+    pub env_substitute: bool,
+
+    /// Warn about $VAR references that are not set (with --env-substitute)
+    #[arg(long = "warn-undefined", requires = "env_substitute")]
+    pub warn_undefined: bool,
+
+    /// Inject RUST_LOG=<level> into the session [default: profile log_level]
+    #[arg(long = "log-level", value_name = "LEVEL")]
+    pub log_level: Option<String>,
+
+    /// Shorthand for --log-level trace
+    #[arg(long = "trace", conflicts_with = "log_level")]
+    pub trace: bool,
+
+    /// Inject NODE_DEBUG=mcp,mcp-client to debug MCP servers
+    #[arg(long = "debug-mcp")]
+    pub debug_mcp: bool,
+
+    /// Do not override RUST_LOG/NODE_DEBUG if they are already set
+    #[arg(long = "no-override-existing-log-level")]
+    pub no_override_existing_log_level: bool,
+
+    /// Merge another profile underneath this one for the session (repeatable, applied left to right)
+    #[arg(long = "inherit-parent-profile", value_name = "NAME")]
+    pub inherit_parent_profile: Vec<String>,
+
+    /// Only pass PATH, HOME, TERM and LANG from this shell, plus the profile env
+    #[arg(long = "clean-env", visible_alias = "profile-env-only")]
+    pub clean_env: bool,
+
+    /// Also pass this var from this shell with --clean-env (repeatable)
+    #[arg(long = "keep-env", value_name = "VAR", requires = "clean_env")]
+    pub keep_env: Vec<String>,
+
+    /// Tag the session with a cost-allocation resource group [default: profile resource_group]
+    #[arg(long = "resource-group", value_name = "NAME")]
+    pub resource_group: Option<String>,
+
+    /// Route ANTHROPIC_BASE_URL through localhost:<port> [default: profile port_forward]
+    #[arg(long = "port-forward", value_name = "LOCAL_PORT[:REMOTE_URL]")]
+    pub port_forward: Option<String>,
+
+    /// Command that opens the tunnel; started before Claude and killed after
+    #[arg(long = "tunnel-cmd", value_name = "CMD")]
+    pub tunnel_cmd: Option<String>,
+
+    /// Wait for Claude in this terminal (default)
+    #[arg(long = "foreground", conflicts_with_all = ["background", "new_terminal"])]
+    pub foreground: bool,
+
+    /// Start Claude detached in its own session, print its PID and exit
+    ///
+    /// stdin is not connected; stdout and stderr still go to this terminal.
+    #[arg(
+        long = "background",
+        visible_alias = "detach",
+        conflicts_with_all = ["new_terminal", "timeout", "tunnel_cmd", "tee_output", "capture_exit_code", "capture_api_calls"]
+    )]
+    pub background: bool,
+
+    /// Open Claude in a new terminal emulator window and exit
+    #[arg(
+        long = "new-terminal",
+        conflicts_with_all = ["timeout", "tunnel_cmd", "tee_output", "capture_exit_code", "capture_api_calls"]
+    )]
+    pub new_terminal: bool,
+
+    /// Also write Claude's terminal output to this file [default: profile tee_output_file]
+    #[arg(long = "tee-output", value_name = "FILE")]
+    pub tee_output: Option<PathBuf>,
+
+    /// Append to the --tee-output file; pass false to truncate it
+    #[arg(long = "tee-append", action = ArgAction::Set, default_value_t = true, value_name = "BOOL")]
+    pub tee_append: bool,
+
+    /// Extra regexes scrubbed from the --tee-output file (Anthropic API keys always are)
+    #[arg(
+        long = "log-redact-patterns",
+        value_name = "REGEX,...",
+        value_delimiter = ','
+    )]
+    pub log_redact_patterns: Vec<String>,
+
+    /// Load extra redaction regexes from a file, one per line
+    #[arg(long = "log-redact-patterns-file", value_name = "PATH")]
+    pub log_redact_patterns_file: Option<PathBuf>,
+
+    /// Enable Claude experimental features as CLAUDE_EXPERIMENTAL_<FLAG>=1 (adds to profile flags)
+    #[arg(
+        long = "experimental-flags",
+        value_name = "FLAG,...",
+        value_delimiter = ','
+    )]
+    pub experimental_flags: Vec<String>,
+
+    /// Set the experimental flags to 0 instead of 1
+    #[arg(long = "no-experimental-flags")]
+    pub no_experimental_flags: bool,
+
+    /// Override an environment variable for this session (repeatable)
+    #[arg(long = "env", value_name = "KEY=VALUE")]
+    pub env: Vec<String>,
+
+    /// Override environment variables from an inline JSON object
+    #[arg(long = "env-from-json", value_name = "JSON")]
+    pub env_from_json: Option<String>,
+
+    /// Override environment variables from a JSON file
+    #[arg(long = "env-from-json-file", value_name = "PATH")]
+    pub env_from_json_file: Option<PathBuf>,
+
+    /// Type this text into Claude's input after it starts, like a human would
+    #[arg(
+        long = "stdin-prompt",
+        value_name = "TEXT",
+        conflicts_with_all = ["stdin_prompt_file", "tee_output", "timeout", "background", "new_terminal"]
+    )]
+    pub stdin_prompt: Option<String>,
+
+    /// Like --stdin-prompt, reading the text from a file
+    #[arg(
+        long = "stdin-prompt-file",
+        value_name = "FILE",
+        conflicts_with_all = ["tee_output", "timeout", "background", "new_terminal"]
+    )]
+    pub stdin_prompt_file: Option<PathBuf>,
+
+    /// Characters typed per second for --stdin-prompt
+    #[arg(long = "typing-speed", value_name = "CPS", default_value_t = DEFAULT_TYPING_SPEED)]
+    pub typing_speed: u32,
+
+    /// Fixed delay between typed characters in milliseconds (overrides --typing-speed)
+    #[arg(long = "typing-delay", value_name = "MS")]
+    pub typing_delay: Option<u64>,
+
+    /// Use this model; sets ANTHROPIC_DEFAULT_{HAIKU,SONNET,OPUS}_MODEL or ANTHROPIC_MODEL by its name
+    #[arg(long = "model-override", value_name = "MODEL")]
+    pub model_override: Option<String>,
+
+    /// Print the executable, arguments and env Claude would get, without launching it
+    ///
+    /// Secret-looking env values are masked unless --verbose is also given.
+    #[arg(long = "dry-run", conflicts_with = "capture_api_calls")]
+    pub dry_run: bool,
+
+    /// Print the settings.json passed to Claude with --settings to stderr before launching
+    ///
+    /// Secret-looking env values are masked unless --verbose is also given.
+    #[arg(long = "print-settings")]
+    pub print_settings: bool,
+
+    /// Print which env vars were inherited, overridden or expanded before launching
+    #[arg(long = "env-diff-report")]
+    pub env_diff_report: bool,
+
+    /// Like --env-diff-report, but exit without launching Claude
+    #[arg(long = "env-diff-report-only")]
+    pub env_diff_report_only: bool,
+
+    /// Also make this profile the default for `ccuse use` without a name
+    #[arg(long = "set-default", conflicts_with_all = ["dry_run", "env_diff_report_only"])]
+    pub set_default: bool,
+
+    /// Run claude-<VERSION> from PATH instead of claude [default: profile claude_version]
+    #[arg(long = "claude-version", value_name = "VERSION")]
+    pub claude_version: Option<String>,
+
+    /// Run the Claude Code executable at PATH instead of searching for one
+    #[arg(long = "claude-path", value_name = "PATH", conflicts_with_all = ["claude_version", "strict_version"])]
+    pub claude_path: Option<PathBuf>,
+
+    /// Fail instead of falling back to claude when claude-<VERSION> is missing
+    #[arg(long = "strict-version")]
+    pub strict_version: bool,
+
+    /// Wait until 127.0.0.1:<PORT> accepts connections before launching (repeatable)
+    #[arg(long = "wait-for-port", value_name = "PORT")]
+    pub wait_for_port: Vec<u16>,
+
+    /// Wait until an http:// URL responds without an error status (repeatable)
+    #[arg(long = "wait-for-url", value_name = "URL")]
+    pub wait_for_url: Vec<String>,
+
+    /// Seconds to wait for each port or URL
+    #[arg(long = "port-timeout", value_name = "SECS", default_value_t = DEFAULT_PORT_TIMEOUT_SECS)]
+    pub port_timeout: u64,
+
+    /// Abort instead of launching when a port or URL is not ready in time
+    #[arg(long = "fail-if-port-unavailable")]
+    pub fail_if_port_unavailable: bool,
+
+    /// Cap Claude's CPU usage at this percentage of the machine [default: profile cpu_limit_percent]
+    #[arg(
+        long = "cpu-limit",
+        value_name = "PERCENT",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+        conflicts_with_all = ["background", "new_terminal", "tee_output", "stdin_prompt", "stdin_prompt_file"]
+    )]
+    pub cpu_limit: Option<u8>,
+
+    /// Export the session as an OpenTelemetry span to $OTEL_EXPORTER_OTLP_ENDPOINT (requires the otel feature)
+    #[arg(long = "otel")]
+    pub otel: bool,
+
+    /// Attribute added to the --otel session span (repeatable)
+    #[arg(long = "metric-tag", value_name = "KEY=VALUE", requires = "otel")]
+    pub metric_tags: Vec<String>,
+
+    /// Log every API request through a local proxy (requires the api-capture feature)
+    #[arg(long = "capture-api-calls")]
+    pub capture_api_calls: bool,
+
+    /// Print how long profile resolution and Claude startup take
+    #[arg(long = "measure-startup")]
+    pub measure_startup: bool,
+
+    /// Like --measure-startup, but print the breakdown as JSON
+    #[arg(long = "measure-startup-json")]
+    pub measure_startup_json: bool,
+
+    /// Exit with Claude's exit code even if --success-exit-codes lists it
+    #[arg(long = "capture-exit-code")]
+    pub capture_exit_code: bool,
+
+    /// Additional Claude exit codes to treat as success (ccuse exits with 0)
+    #[arg(
+        long = "success-exit-codes",
+        value_name = "CODES",
+        value_delimiter = ','
+    )]
+    pub success_exit_codes: Vec<i32>,
+
+    /// Additional arguments to pass to Claude Code
+    #[arg(allow_hyphen_values = true)]
+    pub args: Vec<String>,
+}
+
+#[derive(clap::Args)]
+pub struct AddArgs {
+    /// Pre-fill the profile from a built-in preset (anthropic, openrouter, deepseek)
+    #[arg(long = "preset", value_name = "NAME")]
+    pub preset: Option<String>,
+
+    /// Keep tokens and keys in the OS keyring instead of settings.json (requires the keyring feature)
+    #[arg(long = "use-keyring")]
+    pub use_keyring: bool,
+
+    /// Profile name, instead of asking for it
+    #[arg(long = "name", value_name = "NAME")]
+    pub name: Option<String>,
+
+    /// Set an environment variable (repeatable)
+    #[arg(long = "env", value_name = "KEY=VALUE")]
+    pub env: Vec<String>,
+
+    /// Set ANTHROPIC_BASE_URL
+    #[arg(long = "base-url", value_name = "URL")]
+    pub base_url: Option<String>,
+
+    /// Set ANTHROPIC_AUTH_TOKEN
+    #[arg(long = "token", value_name = "TOKEN")]
+    pub token: Option<String>,
+
+    /// Set the profile category
+    #[arg(long = "category", value_name = "CATEGORY")]
+    pub category: Option<String>,
+
+    /// Tag the profile (repeatable)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Pass this argument to Claude Code on every launch (repeatable), e.g. --launch-arg=--model
+    #[arg(long = "launch-arg", value_name = "ARG", allow_hyphen_values = true)]
+    pub launch_args: Vec<String>,
+
+    /// Save the profile from the flags without opening the editor
+    ///
+    /// Implied when --name, a base URL and a token are all given.
+    #[arg(long = "no-edit")]
+    pub no_edit: bool,
+}
+
+#[derive(clap::Args)]
+pub struct UpdateArgs {
+    /// CC-Switch app type to sync providers of, e.g. codex or gemini
+    #[arg(long = "app-type", value_name = "TYPE", default_value = DEFAULT_APP_TYPE)]
+    pub app_type: String,
+
+    /// CC-Switch database to read (default: $CC_SWITCH_DB_PATH or ~/.cc-switch/cc-switch.db)
+    #[arg(
+        long = "db-path",
+        value_name = "PATH",
+        conflicts_with = "apply_diff_file"
+    )]
+    pub db_path: Option<PathBuf>,
+
+    /// How to derive display names from CC-Switch names (saved as the new default)
+    #[arg(long = "sync-display-names", value_enum, value_name = "STRATEGY")]
+    pub sync_display_names: Option<DisplayNameStrategy>,
+
+    /// Regenerate the static completion script after syncing profiles
+    #[arg(long = "output-on-update")]
+    pub output_on_update: bool,
+
+    /// Show what would change without saving anything
+    #[arg(long = "dry-run", visible_alias = "diff")]
+    pub dry_run: bool,
+
+    /// Also write the --dry-run preview to a JSON file
+    #[arg(long = "output-diff-file", value_name = "PATH", requires = "dry_run")]
+    pub output_diff_file: Option<PathBuf>,
+
+    /// Exit with code 1 if --dry-run finds changes
+    #[arg(long = "fail-on-changes", requires = "dry_run")]
+    pub fail_on_changes: bool,
+
+    /// Keep env vars added locally to CC-Switch profiles instead of replacing the whole env
+    #[arg(long = "merge")]
+    pub merge: bool,
+
+    /// Apply exactly the changes in a file written by --output-diff-file
+    #[arg(
+        long = "apply-diff-file",
+        value_name = "PATH",
+        conflicts_with_all = ["dry_run", "sync_display_names", "merge"]
+    )]
+    pub apply_diff_file: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+#[command(group(ArgGroup::new("env_values").args(["keys", "json"]).multiple(true)))]
+pub struct ListArgs {
+    /// Only show profiles defining this env var (case-insensitive)
+    #[arg(long = "env-key", value_name = "KEY", conflicts_with = "env_regex")]
+    pub env_key: Option<String>,
+
+    /// Only show profiles with an env var key matching this regex
+    #[arg(long = "env-regex", value_name = "PATTERN")]
+    pub env_regex: Option<String>,
+
+    /// Only show profiles with an env var set to exactly this value
+    #[arg(
+        long = "env-value",
+        value_name = "VALUE",
+        conflicts_with = "env_contains"
+    )]
+    pub env_value: Option<String>,
+
+    /// Only show profiles with an env var value containing this substring
+    #[arg(long = "env-contains", value_name = "SUBSTRING")]
+    pub env_contains: Option<String>,
+
+    /// Only show profiles in this resource group
+    #[arg(long = "resource-group", value_name = "NAME")]
+    pub resource_group: Option<String>,
+
+    /// Print only the number of matching profiles
+    #[arg(long = "count")]
+    pub count: bool,
+
+    /// Show profile directories that are missing their settings.json
+    #[arg(long = "orphaned")]
+    pub orphaned: bool,
+
+    /// Show directories whose settings.json does not load as that profile
+    #[arg(long = "orphaned-dirs")]
+    pub orphaned_dirs: bool,
+
+    /// Repair the inconsistencies found by --orphaned and --orphaned-dirs
+    #[arg(long = "fix-orphaned")]
+    pub fix_orphaned: bool,
+
+    /// Show the on-disk size of each profile
+    #[arg(long = "show-sizes")]
+    pub show_sizes: bool,
+
+    /// Show each profile's env var names, with secret values masked
+    #[arg(long = "keys")]
+    pub keys: bool,
+
+    /// Show secret values in full with --keys or --json
+    #[arg(long = "show-secrets", requires = "env_values")]
+    pub show_secrets: bool,
+
+    /// Print the profiles as a JSON array, with secret values masked; same as --format json
+    #[arg(
+        long = "json",
+        conflicts_with_all = ["format", "count", "orphaned", "orphaned_dirs", "fix_orphaned", "by_template", "group_by_category"]
+    )]
+    pub json: bool,
+
+    /// Output format; json ignores --by-template and --group-by-category
+    #[arg(long = "format", value_enum, value_name = "FORMAT", default_value_t = ListFormat::List)]
+    pub format: ListFormat,
+
+    /// Only show profiles created from a preset
+    #[arg(long = "template-only")]
+    pub template_only: bool,
+
+    /// Only show profiles created from this preset
+    #[arg(long = "template", value_name = "PRESET")]
+    pub template: Option<String>,
+
+    /// Group profiles under a header per preset
+    #[arg(long = "by-template", conflicts_with = "group_by_category")]
+    pub by_template: bool,
+
+    /// Only show profiles in this category (case-insensitive)
+    #[arg(long = "category", value_name = "NAME")]
+    pub category: Option<String>,
+
+    /// Group profiles under a header per category
+    #[arg(long = "group-by-category")]
+    pub group_by_category: bool,
+
+    /// Only show profiles with this tag (case-insensitive; repeat to require several)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Order of the listed profiles
+    #[arg(long = "sort", value_enum, value_name = "KEY", default_value_t = ProfileSort::Name)]
+    pub sort: ProfileSort,
+
+    /// Reverse the sort order
+    #[arg(long = "reverse")]
+    pub reverse: bool,
+}
@@ -1,127 +1,151 @@
-use crate::config::{Profile, Storage};
+use crate::cli::editor::edit_file;
+use crate::cli::env_overrides::parse_env_pair;
+use crate::cli::AddArgs;
+use crate::config::presets::{find_preset, PRESETS};
+use crate::config::validation::{validate_profile_env, validate_profile_name};
+use crate::config::{secrets, Profile, ProfileSource, Storage};
 use crate::error::Result;
 use chrono::Utc;
 use colored::Colorize;
 use inquire::Text;
 use serde_json::json;
-use std::env;
+use std::collections::HashMap;
 use std::fs;
-use std::process::Command;
 
-/// Get the system's default text editor
-fn get_editor() -> String {
-    // Try environment variables first
-    if let Ok(editor) = env::var("VISUAL") {
-        return editor;
-    }
-    if let Ok(editor) = env::var("EDITOR") {
-        return editor;
-    }
+const AUTH_TOKEN_KEY: &str = "ANTHROPIC_AUTH_TOKEN";
+const BASE_URL_KEY: &str = "ANTHROPIC_BASE_URL";
 
-    // Platform-specific defaults
-    #[cfg(target_os = "windows")]
-    {
-        "notepad.exe".to_string()
+/// Add a new profile, optionally starting from a preset.
+///
+/// The env from the preset, `--env`, `--base-url` and `--token` pre-fills the
+/// template opened in the editor. With `--no-edit`, or when the name, base
+/// URL and token are all given, the profile is saved without the editor.
+///
+/// With `use_keyring`, secret env values are stored in the OS keyring and
+/// settings.json only keeps `keyring:` references to them.
+///
+/// The editor works on a private draft outside the profile directories, and
+/// the storage lock is only held to check the name and to save, so other
+/// commands keep working while the editor is open.
+///
+/// # Errors
+///
+/// Returns an error if the preset is unknown, profile already exists, user input fails, or profile cannot be saved.
+pub fn add_profile(add_args: &AddArgs) -> Result<()> {
+    let preset = add_args
+        .preset
+        .as_deref()
+        .map(|name| {
+            find_preset(name).ok_or_else(|| {
+                let available: Vec<_> = PRESETS.iter().map(|p| p.name).collect();
+                crate::error::Error::ConfigError(format!(
+                    "Unknown preset '{name}'. Available: {}",
+                    available.join(", ")
+                ))
+            })
+        })
+        .transpose()?;
+
+    // Later sources win: preset, then --env, then --base-url and --token
+    let mut env: HashMap<String, String> = preset
+        .map(|preset| {
+            preset
+                .env
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    for pair in &add_args.env {
+        let (key, value) = parse_env_pair(pair)?;
+        env.insert(key, value);
     }
-    #[cfg(target_os = "macos")]
-    {
-        "open -e".to_string()
+    if let Some(base_url) = &add_args.base_url {
+        env.insert(BASE_URL_KEY.to_string(), base_url.clone());
     }
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
-    {
-        // Try common editors on Linux
-        for editor in ["nano", "vim", "vi"] {
-            if which::which(editor).is_ok() {
-                return editor.to_string();
-            }
-        }
-        "vi".to_string()
+    if let Some(token) = &add_args.token {
+        env.insert(AUTH_TOKEN_KEY.to_string(), token.clone());
     }
-}
 
-/// Add a new profile interactively.
-///
-/// # Errors
-///
-/// Returns an error if profile already exists, user input fails, or profile cannot be saved.
-pub fn add_profile() -> Result<()> {
-    let storage = Storage::new()?;
+    let complete = [AUTH_TOKEN_KEY, BASE_URL_KEY]
+        .iter()
+        .all(|key| env.get(*key).is_some_and(|value| !value.is_empty()));
+    if add_args.no_edit || (add_args.name.is_some() && complete) {
+        return add_without_editor(
+            &Storage::new_exclusive()?,
+            add_args,
+            preset.map(|p| p.name),
+            env,
+        );
+    }
 
     // Get profile name
-    let name = Text::new("Profile name:").prompt()?;
+    let name = match &add_args.name {
+        Some(name) => name.clone(),
+        None => Text::new("Profile name:").prompt()?,
+    };
 
-    // Check if already exists
-    if storage.get_profile(&name)?.is_some() {
-        return Err(crate::error::Error::ProfileAlreadyExists(name));
-    }
+    validate_profile_name(&name)?;
+
+    // Check if already exists, and create the draft next to the profiles;
+    // it holds the token, so it is private and removed when dropped
+    let draft = {
+        let storage = Storage::new()?;
+        if storage.profile_exists_fast(&name)? {
+            return Err(crate::error::Error::ProfileAlreadyExists(name));
+        }
+        tempfile::Builder::new()
+            .prefix(".add-")
+            .suffix(".json")
+            .tempfile_in(storage.config_dir())?
+            .into_temp_path()
+    };
 
     // Create minimal template - only requires token and base_url
-    let template = json!({
+    for key in [AUTH_TOKEN_KEY, BASE_URL_KEY] {
+        env.entry(key.to_string()).or_default();
+    }
+    let env: serde_json::Map<String, serde_json::Value> = env
+        .into_iter()
+        .map(|(key, value)| (key, json!(value)))
+        .collect();
+    let mut template = json!({
         "name": name,
         "display_name": name,
         "source": "manual",
-        "env": {
-            "ANTHROPIC_AUTH_TOKEN": "",
-            "ANTHROPIC_BASE_URL": ""
-        }
+        "env": env
     });
+    if let Some(category) = &add_args.category {
+        template["category"] = json!(category);
+    }
+    // Always present, so the editor shows where tags and launch args go
+    template["tags"] = json!(add_args.tags);
+    template["launch_args"] = json!(add_args.launch_args);
 
-    // Create settings.json in profile directory
-    let settings_path = storage.ensure_profile_settings_dir(&name)?;
     let original_content = serde_json::to_string_pretty(&template)?;
-    fs::write(&settings_path, &original_content)?;
+    fs::write(&draft, &original_content)?;
 
     println!("\n{} Opening editor to configure profile...", "→".cyan());
-    println!("{} {}", "File:".bold(), settings_path.display());
+    println!("{} {}", "File:".bold(), draft.display());
     println!(
         "{} Save and close the editor when done. If you want to cancel, delete all content and save.\n",
         "Tip:".yellow()
     );
 
     // Open editor
-    let editor = get_editor();
-    let editor_parts: Vec<&str> = editor.split_whitespace().collect();
-    let (cmd, args) = if editor_parts.len() > 1 {
-        (editor_parts[0], &editor_parts[1..])
-    } else {
-        (editor_parts[0], &[][..])
-    };
-
-    let status = Command::new(cmd)
-        .args(args)
-        .arg(&settings_path)
-        .status()
-        .map_err(|e| {
-            fs::remove_file(&settings_path).ok();
-            storage.profile_settings_dir(&name).exists().then(|| {
-                fs::remove_dir_all(storage.profile_settings_dir(&name)).ok();
-            });
-            crate::error::Error::ConfigError(format!("Failed to open editor: {e}"))
-        })?;
-
-    if !status.success() {
-        fs::remove_file(&settings_path).ok();
-        fs::remove_dir_all(storage.profile_settings_dir(&name)).ok();
-        return Err(crate::error::Error::ConfigError(
-            "Editor exited with error".into(),
-        ));
-    }
+    let changed = edit_file(&draft)?;
 
     // Read and parse the edited file
-    let content = fs::read_to_string(&settings_path)?;
+    let content = fs::read_to_string(&draft)?;
 
     // Check if user deleted content (cancelled)
     if content.trim().is_empty() {
-        fs::remove_file(&settings_path).ok();
-        fs::remove_dir_all(storage.profile_settings_dir(&name)).ok();
         println!("{}", "Profile creation cancelled.".yellow());
         return Ok(());
     }
 
     // Check if content unchanged (user didn't edit)
-    if content.trim() == original_content.trim() {
-        fs::remove_file(&settings_path).ok();
-        fs::remove_dir_all(storage.profile_settings_dir(&name)).ok();
+    if !changed || content.trim() == original_content.trim() {
         println!(
             "{}",
             "No changes made. Profile creation cancelled.".yellow()
@@ -130,11 +154,8 @@ pub fn add_profile() -> Result<()> {
     }
 
     // Parse the edited content and merge with defaults
-    let user_json: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
-        fs::remove_file(&settings_path).ok();
-        fs::remove_dir_all(storage.profile_settings_dir(&name)).ok();
-        crate::error::Error::ConfigError(format!("Invalid JSON: {e}"))
-    })?;
+    let user_json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| crate::error::Error::ConfigError(format!("Invalid JSON: {e}")))?;
 
     // Create default values for missing fields
     let default_json = json!({
@@ -149,6 +170,8 @@ pub fn add_profile() -> Result<()> {
         "always_thinking_enabled": null,
         "api_timeout_ms": null,
         "category": null,
+        "tags": [],
+        "launch_args": [],
         "source": "manual",
         "created_at": Utc::now(),
         "updated_at": Utc::now()
@@ -164,16 +187,20 @@ pub fn add_profile() -> Result<()> {
         "always_thinking_enabled": user_json.get("always_thinking_enabled").or_else(|| default_json.get("always_thinking_enabled")),
         "api_timeout_ms": user_json.get("api_timeout_ms").or_else(|| default_json.get("api_timeout_ms")),
         "category": user_json.get("category").or_else(|| default_json.get("category")),
+        "tags": user_json.get("tags").unwrap_or_else(|| default_json.get("tags").unwrap()),
+        "launch_args": user_json.get("launch_args").unwrap_or_else(|| default_json.get("launch_args").unwrap()),
         "source": user_json.get("source").unwrap_or_else(|| default_json.get("source").unwrap()),
         "created_at": user_json.get("created_at").unwrap_or_else(|| default_json.get("created_at").unwrap()),
         "updated_at": Utc::now()
     });
 
-    let profile: Profile = serde_json::from_value(merged_json).map_err(|e| {
-        fs::remove_file(&settings_path).ok();
-        fs::remove_dir_all(storage.profile_settings_dir(&name)).ok();
-        crate::error::Error::ConfigError(format!("Invalid JSON: {e}"))
-    })?;
+    let mut profile: Profile = serde_json::from_value(merged_json)
+        .map_err(|e| crate::error::Error::ConfigError(format!("Invalid JSON: {e}")))?;
+
+    profile.template_source = preset.map(|p| p.name.to_string());
+    // Trim and deduplicate what was typed in the editor
+    let tags = std::mem::take(&mut profile.tags);
+    profile.add_tags(tags);
 
     // Validate that both token and base_url are provided
     let has_token = profile
@@ -188,25 +215,94 @@ pub fn add_profile() -> Result<()> {
         .unwrap_or(false);
 
     if !has_token {
-        fs::remove_file(&settings_path).ok();
-        fs::remove_dir_all(storage.profile_settings_dir(&name)).ok();
         return Err(crate::error::Error::ConfigError(
             "ANTHROPIC_AUTH_TOKEN is required".into(),
         ));
     }
 
     if !has_base_url {
-        fs::remove_file(&settings_path).ok();
-        fs::remove_dir_all(storage.profile_settings_dir(&name)).ok();
         return Err(crate::error::Error::ConfigError(
             "ANTHROPIC_BASE_URL is required".into(),
         ));
     }
 
-    // Save profile (the profile is already saved to settings.json earlier,
-    // but we need to ensure it's properly saved with all fields)
-    let settings_path = storage.ensure_profile_settings_dir(&name)?;
-    fs::write(&settings_path, serde_json::to_string_pretty(&profile)?)?;
+    validate_profile_env(&profile)?;
+
+    if add_args.use_keyring {
+        let moved = secrets::move_secrets_to_keyring(&mut profile)?;
+        if !moved.is_empty() {
+            println!(
+                "{} Stored {} in the OS keyring",
+                "→".cyan(),
+                moved.join(", ")
+            );
+        }
+    }
+
+    // Checked again under the lock, in case the name was taken meanwhile
+    Storage::new_exclusive()?.add_profile(profile)?;
+
+    println!(
+        "{}",
+        format!("✓ Profile '{name}' created successfully!").green()
+    );
+
+    Ok(())
+}
+
+/// Build and save a profile from the `add` flags alone.
+fn add_without_editor(
+    storage: &Storage,
+    add_args: &AddArgs,
+    template_source: Option<&str>,
+    mut env: HashMap<String, String>,
+) -> Result<()> {
+    let Some(name) = add_args.name.clone() else {
+        return Err(crate::error::Error::ConfigError(
+            "--name is required with --no-edit".into(),
+        ));
+    };
+
+    validate_profile_name(&name)?;
+    if storage.profile_exists_fast(&name)? {
+        return Err(crate::error::Error::ProfileAlreadyExists(name));
+    }
+
+    // Presets leave values for the editor to fill in; drop the ones still empty
+    env.retain(|_, value| !value.is_empty());
+    for (key, flag) in [(AUTH_TOKEN_KEY, "--token"), (BASE_URL_KEY, "--base-url")] {
+        if !env.contains_key(key) {
+            return Err(crate::error::Error::ConfigError(format!(
+                "{key} is required; pass {flag} or --env {key}=..."
+            )));
+        }
+    }
+
+    let mut profile = Profile {
+        name: name.clone(),
+        display_name: Some(name.clone()),
+        env,
+        category: add_args.category.clone(),
+        source: Some(ProfileSource::Manual),
+        template_source: template_source.map(str::to_string),
+        launch_args: add_args.launch_args.clone(),
+        ..Profile::default()
+    };
+    profile.add_tags(&add_args.tags);
+    validate_profile_env(&profile)?;
+
+    if add_args.use_keyring {
+        let moved = secrets::move_secrets_to_keyring(&mut profile)?;
+        if !moved.is_empty() {
+            println!(
+                "{} Stored {} in the OS keyring",
+                "→".cyan(),
+                moved.join(", ")
+            );
+        }
+    }
+
+    storage.add_profile(profile)?;
 
     println!(
         "{}",
@@ -0,0 +1,100 @@
+use crate::cli::ApiLogCommands;
+use crate::config::Storage;
+use crate::error::{Error, Result};
+use crate::proxy::{api_log_path, compute_stats, list_sessions, read_api_log};
+use colored::Colorize;
+
+/// Run `ccuse api-log`: list sessions, show one session, or its stats.
+///
+/// # Errors
+///
+/// Returns an error if the session log does not exist or cannot be parsed.
+pub fn run_api_log_command(
+    session_id: Option<&str>,
+    command: Option<&ApiLogCommands>,
+) -> Result<()> {
+    let storage = Storage::new()?;
+
+    match (command, session_id) {
+        (Some(ApiLogCommands::Stats { session_id }), _) => show_stats(&storage, session_id),
+        (None, Some(session_id)) => show_session(&storage, session_id),
+        (None, None) => {
+            let sessions = list_sessions(storage.config_dir())?;
+            if sessions.is_empty() {
+                println!(
+                    "{}",
+                    "No API logs found. Run 'ccuse use <name> --capture-api-calls' to record one."
+                        .yellow()
+                );
+                return Ok(());
+            }
+            println!("{}", "Captured sessions:".bold());
+            for session in sessions {
+                println!("  {session}");
+            }
+            Ok(())
+        }
+    }
+}
+
+fn load_session(storage: &Storage, session_id: &str) -> Result<Vec<crate::proxy::ApiCallRecord>> {
+    let path = api_log_path(storage.config_dir(), session_id);
+    if !path.exists() {
+        return Err(Error::ProxyError(format!(
+            "No API log for session '{session_id}'"
+        )));
+    }
+    read_api_log(&path)
+}
+
+fn show_session(storage: &Storage, session_id: &str) -> Result<()> {
+    let records = load_session(storage, session_id)?;
+
+    println!("{} {}\n", "API calls for session".bold(), session_id.bold());
+    for record in &records {
+        let status = if record.status >= 400 {
+            record.status.to_string().red()
+        } else {
+            record.status.to_string().green()
+        };
+        let tokens = match (record.input_tokens, record.output_tokens) {
+            (None, None) => String::new(),
+            (input, output) => format!(
+                "  tokens {}/{}",
+                input.map_or("-".to_string(), |t| t.to_string()),
+                output.map_or("-".to_string(), |t| t.to_string())
+            ),
+        };
+        println!(
+            "  {} {} {} {status} {}ms  {}B/{}B{tokens}",
+            record.timestamp.format("%H:%M:%S"),
+            record.method,
+            record.url,
+            record.duration_ms,
+            record.request_bytes,
+            record.response_bytes,
+        );
+    }
+
+    Ok(())
+}
+
+fn show_stats(storage: &Storage, session_id: &str) -> Result<()> {
+    let stats = compute_stats(&load_session(storage, session_id)?);
+
+    println!("{} {}\n", "API stats for session".bold(), session_id.bold());
+    println!(
+        "  Requests:      {} ({} failed)",
+        stats.requests, stats.errors
+    );
+    println!("  Sent:          {} bytes", stats.request_bytes);
+    println!("  Received:      {} bytes", stats.response_bytes);
+    println!("  Input tokens:  {}", stats.input_tokens);
+    println!("  Output tokens: {}", stats.output_tokens);
+    println!(
+        "  Latency:       p50 {}ms, p90 {}ms, p99 {}ms",
+        stats.p50_ms, stats.p90_ms, stats.p99_ms
+    );
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use crate::db::{CcSwitchDb, RawProfile};
+use crate::error::{Error, Result};
+use chrono::DateTime;
+use colored::Colorize;
+
+/// Show raw CC-Switch database contents without importing anything.
+///
+/// # Errors
+///
+/// Returns an error if the CC-Switch database cannot be read or the named profile does not exist.
+pub fn audit_ccswitch(profile_name: Option<&str>, json: bool, all_tables: bool) -> Result<()> {
+    let ccswitch = CcSwitchDb::new()?;
+
+    if all_tables {
+        let tables = ccswitch.list_tables()?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&tables)?);
+        } else {
+            println!("{}", "CC-Switch tables:".bold());
+            for table in &tables {
+                println!("  {table}");
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(name) = profile_name {
+        let raw = ccswitch
+            .get_raw_profile_by_name(name)?
+            .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&raw)?);
+        } else {
+            print_raw_profile(&raw);
+            println!("    {}", "settings_config:".bold());
+            for line in pretty_settings(&raw.settings_config_json).lines() {
+                println!("      {line}");
+            }
+        }
+        return Ok(());
+    }
+
+    let rows = ccswitch.list_all_profiles_raw()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    if rows.is_empty() {
+        println!("{}", "No providers found in CC-Switch database.".yellow());
+        return Ok(());
+    }
+
+    println!("{}", "CC-Switch providers:".bold());
+    println!();
+    for raw in &rows {
+        print_raw_profile(raw);
+        println!("    settings_config: {}", raw.settings_config_json);
+        println!();
+    }
+
+    Ok(())
+}
+
+fn print_raw_profile(raw: &RawProfile) {
+    let created = DateTime::from_timestamp_millis(raw.created_at_ms)
+        .map_or_else(|| raw.created_at_ms.to_string(), |dt| dt.to_rfc3339());
+
+    println!("  {} ({})", raw.name.green(), raw.app_type.cyan());
+    println!("    id: {}", raw.id);
+    println!("    created_at: {created}");
+}
+
+/// Pretty-print settings JSON, or return it unchanged if it does not parse.
+fn pretty_settings(settings_config: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(settings_config) {
+        Ok(value) => {
+            serde_json::to_string_pretty(&value).unwrap_or_else(|_| settings_config.to_string())
+        }
+        Err(e) => format!("{settings_config}\n(invalid JSON: {e})"),
+    }
+}
@@ -0,0 +1,47 @@
+use crate::claude::Launcher;
+use crate::error::Result;
+use colored::Colorize;
+use std::process::{Command, Stdio};
+
+/// List the Claude Code executables in PATH with their `--version` output.
+///
+/// # Errors
+///
+/// This command does not fail; executables that cannot report a version are
+/// listed without one.
+pub fn list_claude_versions() -> Result<()> {
+    let executables = Launcher::find_all_claude_executables();
+
+    if executables.is_empty() {
+        println!("{}", "No claude* executables found in PATH.".yellow());
+        return Ok(());
+    }
+
+    for path in executables {
+        let version = Command::new(&path)
+            .arg("--version")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .next()
+                    .map(|line| line.trim().to_string())
+            })
+            .filter(|line| !line.is_empty());
+
+        let name = path
+            .file_name()
+            .map_or_else(String::new, |n| n.to_string_lossy().to_string());
+        match version {
+            Some(version) => println!("  {} {version}", name.green()),
+            None => println!("  {} {}", name.green(), "(no version)".dimmed()),
+        }
+        println!("    {}", path.display());
+    }
+
+    Ok(())
+}
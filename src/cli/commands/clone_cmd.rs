@@ -0,0 +1,45 @@
+use crate::config::validation::validate_profile_name;
+use crate::config::{secrets, ProfileSource, Storage};
+use crate::error::{Error, Result};
+use chrono::Utc;
+use colored::Colorize;
+
+/// Duplicate a profile under a new name.
+///
+/// The copy gets its own directory and settings.json, so editing one
+/// profile never affects the other. The copy is a manual profile even when
+/// the source came from CC-Switch.
+///
+/// # Errors
+///
+/// Returns an error if the source does not exist, the destination name is
+/// taken, or the copy cannot be saved.
+pub fn clone_profile(source: &str, dest: &str) -> Result<()> {
+    validate_profile_name(dest)?;
+    let storage = Storage::new_exclusive()?;
+
+    let Some(mut profile) = storage.get_profile(source)? else {
+        return Err(Error::ProfileNotFound(source.into()));
+    };
+
+    if storage.profile_exists_fast(dest)? {
+        return Err(Error::ProfileAlreadyExists(dest.into()));
+    }
+
+    secrets::copy_secrets(source, dest, &profile.env)?;
+
+    // A display name copied as-is would make both profiles look the same in `ccuse list`
+    profile.name = dest.to_string();
+    profile.display_name = profile.display_name.map(|_| dest.to_string());
+    profile.source = Some(ProfileSource::Manual);
+    profile.created_at = Utc::now();
+
+    storage.add_profile(profile)?;
+
+    println!(
+        "{}",
+        format!("✓ Profile '{source}' cloned to '{dest}'").green()
+    );
+
+    Ok(())
+}
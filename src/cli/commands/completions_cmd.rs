@@ -0,0 +1,104 @@
+use crate::cli::completions::Shell;
+use crate::cli::CompletionInstaller;
+use crate::config::Storage;
+use crate::error::{Error, Result};
+use colored::Colorize;
+
+/// Print a completion script for `shell` (default: the detected one) to stdout.
+///
+/// With `static_profiles`, the stored profile names are baked in instead of
+/// being looked up with `ccuse list`.
+///
+/// # Errors
+///
+/// Returns an error if the shell cannot be detected or profiles cannot be loaded.
+pub fn print_completions(shell: Option<Shell>, static_profiles: bool) -> Result<()> {
+    let shell = shell
+        .or_else(Shell::detect)
+        .ok_or_else(|| Error::ConfigError("Unable to detect shell type; pass --shell".into()))?;
+
+    let script = if static_profiles {
+        let storage = Storage::new()?;
+        let names: Vec<String> = storage
+            .load_profiles()?
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        shell.completion_with_profiles(&names)
+    } else {
+        shell.completion()
+    };
+    print!("{script}");
+
+    Ok(())
+}
+
+/// Write a completion script with the stored profile names baked in, for
+/// `shell` or the detected shell.
+///
+/// # Errors
+///
+/// Returns an error if profiles cannot be loaded or the script cannot be written.
+pub fn install_static_completions(shell: Option<Shell>) -> Result<()> {
+    let storage = Storage::new()?;
+    let names: Vec<String> = storage
+        .load_profiles()?
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+    let path = CompletionInstaller::install_static(shell, &names)
+        .map_err(|e| Error::ConfigError(format!("Failed to write completions: {e}")))?;
+    println!(
+        "{} Wrote static completions for {} profile(s) to {}",
+        "✓".green(),
+        names.len(),
+        path.display().to_string().cyan()
+    );
+
+    Ok(())
+}
+
+/// Overwrite the completion script already installed for `shell` (default:
+/// the detected one) without prompting, e.g. from a post-upgrade hook.
+///
+/// A static script is regenerated with the current profile names, so it stays static.
+///
+/// # Errors
+///
+/// Returns an error if the shell cannot be detected, no script is installed,
+/// profiles cannot be loaded or the script cannot be written.
+pub fn regenerate_completions(shell: Option<Shell>) -> Result<()> {
+    let mut plan =
+        CompletionInstaller::plan(shell).map_err(|e| Error::ConfigError(e.to_string()))?;
+    if !plan.exists {
+        return Err(Error::ConfigError(format!(
+            "No {} completions installed at {}; run 'ccuse completions' to install them",
+            plan.shell.name(),
+            plan.target_path.display()
+        )));
+    }
+
+    if plan.installed_is_static() {
+        let storage = Storage::new()?;
+        let names: Vec<String> = storage
+            .load_profiles()?
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        plan.script = plan.shell.completion_with_profiles(&names);
+    }
+    plan.write()
+        .map_err(|e| Error::ConfigError(format!("Failed to write completions: {e}")))?;
+    println!(
+        "{} Regenerated {} completions at {}",
+        "✓".green(),
+        plan.shell.name(),
+        plan.target_path.display().to_string().cyan()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::config::storage::resolve_config_dir;
+use crate::config::Storage;
+use crate::error::Result;
+use colored::Colorize;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Program that opens a directory in the platform file manager.
+#[cfg(target_os = "macos")]
+const FILE_MANAGER: &str = "open";
+#[cfg(windows)]
+const FILE_MANAGER: &str = "explorer";
+#[cfg(not(any(target_os = "macos", windows)))]
+const FILE_MANAGER: &str = "xdg-open";
+
+/// Print the configuration directory, and open it in the file manager if `open` is set.
+///
+/// If the file manager cannot be started, a warning is printed and the
+/// command still succeeds, since the path was printed.
+///
+/// # Errors
+///
+/// Returns an error if the configuration directory cannot be determined, or
+/// created when `open` is set.
+pub fn show_config_dir(open: bool) -> Result<()> {
+    if !open {
+        println!("{}", resolve_config_dir()?.display());
+        return Ok(());
+    }
+
+    // Storage creates the directory, so there is always something to open
+    let storage = Storage::new()?;
+    let config_dir = storage.config_dir();
+    println!("{}", config_dir.display());
+
+    if let Err(e) = open_in_file_manager(config_dir) {
+        eprintln!(
+            "{} could not run '{FILE_MANAGER}' to open the directory: {e}",
+            "Warning:".yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Start the file manager on `dir` without waiting for it.
+fn open_in_file_manager(dir: &Path) -> std::io::Result<()> {
+    Command::new(FILE_MANAGER)
+        .arg(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
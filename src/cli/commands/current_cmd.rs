@@ -0,0 +1,32 @@
+use crate::config::Storage;
+use crate::error::Result;
+use colored::Colorize;
+
+/// Show the profile most recently launched with `ccuse use`.
+///
+/// # Errors
+///
+/// Returns an error if the state or the profile cannot be read.
+pub fn show_current_profile() -> Result<()> {
+    let storage = Storage::new()?;
+
+    let Some(name) = storage.load_last_used()? else {
+        println!(
+            "{}",
+            "No profile has been used yet. Run 'ccuse use <name>' to launch one.".yellow()
+        );
+        return Ok(());
+    };
+
+    match storage.get_profile(&name)? {
+        Some(profile) => match profile.display_name.as_deref() {
+            Some(display_name) if display_name != name => {
+                println!("{} ({display_name})", name.green().bold());
+            }
+            _ => println!("{}", name.green().bold()),
+        },
+        None => println!("{} {}", name.bold(), "(profile no longer exists)".dimmed()),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use crate::config::Storage;
+use crate::error::{Error, Result};
+use colored::Colorize;
+
+/// Show, set or clear the profile `ccuse use` launches when no name is given.
+///
+/// # Errors
+///
+/// Returns an error if the profile does not exist or state.json cannot be read or written.
+pub fn run_default_command(name: Option<&str>, clear: bool) -> Result<()> {
+    if clear {
+        let storage = Storage::new_exclusive()?;
+        match storage.load_default_profile()? {
+            Some(previous) => {
+                storage.save_default_profile(None)?;
+                println!(
+                    "{}",
+                    format!("✓ Default profile '{previous}' cleared.").green()
+                );
+            }
+            None => println!("{}", "No default profile is set.".yellow()),
+        }
+        return Ok(());
+    }
+
+    let Some(name) = name else {
+        let storage = Storage::new()?;
+        match storage.load_default_profile()? {
+            Some(default) if storage.profile_exists_fast(&default)? => {
+                println!("{}", default.green().bold())
+            }
+            Some(default) => println!(
+                "{} {}",
+                default.bold(),
+                "(profile no longer exists)".dimmed()
+            ),
+            None => println!(
+                "{}",
+                "No default profile is set. Run 'ccuse default <name>' to set one.".yellow()
+            ),
+        }
+        return Ok(());
+    };
+
+    let storage = Storage::new_exclusive()?;
+    if !storage.profile_exists_fast(name)? {
+        return Err(Error::ProfileNotFound(name.into()));
+    }
+    storage.save_default_profile(Some(name))?;
+    println!(
+        "{}",
+        format!("✓ Default profile set to '{name}'. 'ccuse use' now launches it.").green()
+    );
+
+    Ok(())
+}
+
+/// Resolve the profile `ccuse use` should launch: `name` if given, otherwise
+/// the default profile.
+///
+/// # Errors
+///
+/// Returns an error if no name is given and no default profile is set.
+pub fn resolve_profile_name(storage: &Storage, name: Option<&str>) -> Result<String> {
+    if let Some(name) = name {
+        return Ok(name.to_string());
+    }
+    storage.load_default_profile()?.ok_or_else(|| {
+        Error::ConfigError(
+            "No profile given and no default profile is set. Run 'ccuse use <name>', \
+             or set a default with 'ccuse default <name>'."
+                .into(),
+        )
+    })
+}
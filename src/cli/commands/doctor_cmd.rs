@@ -0,0 +1,194 @@
+use crate::claude::Launcher;
+use crate::cli::commands::validate_cmd::profile_problems;
+use crate::config::storage::resolve_config_dir;
+use crate::config::Storage;
+use crate::db::CcSwitchDb;
+use crate::error::Result;
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+use which::which;
+
+/// Outcome of one `doctor` check.
+enum Check {
+    Pass(String),
+    /// Something only optional features need
+    Warn(String, String),
+    /// Something every launch needs; makes `doctor` exit with code 1
+    Fail(String, String),
+}
+
+impl Check {
+    fn print(&self) {
+        match self {
+            Self::Pass(message) => println!("  {} {message}", "✓".green()),
+            Self::Warn(message, fix) => {
+                println!("  {} {message}", "!".yellow());
+                println!("    {} {fix}", "→".cyan());
+            }
+            Self::Fail(message, fix) => {
+                println!("  {} {message}", "✗".red());
+                println!("    {} {fix}", "→".cyan());
+            }
+        }
+    }
+}
+
+/// Check the setup ccuse depends on and print what to fix.
+///
+/// Exits with code 1 when a critical check fails: Claude Code cannot be
+/// found, the config directory is not writable, or a profile is invalid.
+/// A missing CC-Switch database or bunx/npx only produce warnings.
+///
+/// # Errors
+///
+/// This command does not fail; problems are reported as failed checks.
+pub fn run_doctor() -> Result<()> {
+    let mut checks = vec![check_claude(), check_config_dir()];
+    checks.extend(check_profiles());
+    checks.push(check_ccswitch());
+    checks.push(check_ccusage_runners());
+
+    for check in &checks {
+        check.print();
+    }
+
+    let failed = checks
+        .iter()
+        .filter(|check| matches!(check, Check::Fail(..)))
+        .count();
+    let warned = checks
+        .iter()
+        .filter(|check| matches!(check, Check::Warn(..)))
+        .count();
+
+    println!();
+    if failed > 0 {
+        println!(
+            "{}",
+            format!("{failed} check(s) failed, {warned} warning(s).").red()
+        );
+        std::process::exit(1);
+    }
+    println!(
+        "{}",
+        format!("✓ All critical checks passed, {warned} warning(s).").green()
+    );
+    Ok(())
+}
+
+fn check_claude() -> Check {
+    match Launcher::find_claude_executable(None) {
+        Ok(program) => {
+            let location =
+                which(&program).map_or_else(|_| program.clone(), |path| path.display().to_string());
+            Check::Pass(format!("Claude Code found: {location}"))
+        }
+        Err(e) => Check::Fail(
+            e.to_string(),
+            "Install Claude Code, add it to PATH, or set CLAUDE_CODE_PATH".into(),
+        ),
+    }
+}
+
+fn check_config_dir() -> Check {
+    let dir = match resolve_config_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            return Check::Fail(
+                e.to_string(),
+                "Set CCUSE_CONFIG_DIR or pass --config-dir".into(),
+            )
+        }
+    };
+
+    match probe_writable(&dir) {
+        Ok(()) => Check::Pass(format!("Config directory is writable: {}", dir.display())),
+        Err(e) => Check::Fail(
+            format!("Config directory {} is not writable: {e}", dir.display()),
+            "Fix its permissions, or point CCUSE_CONFIG_DIR at another directory".into(),
+        ),
+    }
+}
+
+/// Create `dir` if needed and write and remove a scratch file in it.
+fn probe_writable(dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(format!(".doctor-{}", std::process::id()));
+    fs::write(&probe, b"")?;
+    fs::remove_file(probe)
+}
+
+/// One check per stored profile, so every broken one is listed.
+fn check_profiles() -> Vec<Check> {
+    let storage = match Storage::new() {
+        Ok(storage) => storage,
+        Err(e) => {
+            return vec![Check::Fail(
+                format!("Cannot open the profile store: {e}"),
+                "Fix the config directory first".into(),
+            )]
+        }
+    };
+    let mut names = match storage.profile_dir_names() {
+        Ok(names) => names,
+        Err(e) => {
+            return vec![Check::Fail(
+                format!("Cannot list profiles: {e}"),
+                "Fix the config directory first".into(),
+            )]
+        }
+    };
+    names.retain(|name| storage.settings_file_exists(name));
+    names.sort();
+
+    if names.is_empty() {
+        return vec![Check::Warn(
+            "No profiles yet".into(),
+            "Create one with 'ccuse add' or import them with 'ccuse update'".into(),
+        )];
+    }
+
+    names
+        .into_iter()
+        .map(|name| {
+            let problems = profile_problems(&storage, &name);
+            if problems.is_empty() {
+                Check::Pass(format!("Profile '{name}' is valid"))
+            } else {
+                Check::Fail(
+                    format!("Profile '{name}' is invalid: {}", problems.join("; ")),
+                    format!("Fix it with 'ccuse edit {name}'"),
+                )
+            }
+        })
+        .collect()
+}
+
+fn check_ccswitch() -> Check {
+    match CcSwitchDb::open(None) {
+        Ok(_) => Check::Pass("CC-Switch database found".into()),
+        Err(e) => Check::Warn(
+            e.to_string(),
+            "Only needed for 'ccuse update'; set CC_SWITCH_DB_PATH if it lives elsewhere".into(),
+        ),
+    }
+}
+
+fn check_ccusage_runners() -> Check {
+    let found: Vec<&str> = ["ccusage", "bunx", "npx"]
+        .into_iter()
+        .filter(|program| which(program).is_ok())
+        .collect();
+    if found.is_empty() {
+        Check::Warn(
+            "Neither ccusage, bunx nor npx is in PATH".into(),
+            "Install Bun or Node.js to use 'ccuse usage'".into(),
+        )
+    } else {
+        Check::Pass(format!(
+            "'ccuse usage' can run ccusage with {}",
+            found.join(", ")
+        ))
+    }
+}
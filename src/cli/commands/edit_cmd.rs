@@ -0,0 +1,116 @@
+use crate::cli::editor::edit_file;
+use crate::config::storage::{migrations, write_atomic};
+use crate::config::validation::validate_profile_env;
+use crate::config::{Profile, Storage};
+use crate::error::{Error, Result};
+use colored::Colorize;
+use inquire::Confirm;
+use std::fs;
+
+/// Working copy edited in place of the profile's settings, kept in the
+/// profile directory until the edit is saved
+const EDIT_FILE: &str = "settings.edit.json";
+
+/// Open a profile's settings in the editor, then validate and save them.
+///
+/// The editor works on a copy, so the profile stays usable and unlocked
+/// while it is open. Invalid edits can be reopened; declined ones are kept
+/// for the next `ccuse edit`.
+///
+/// # Errors
+///
+/// Returns an error if the profile does not exist, the editor fails, or the profile cannot be saved.
+pub fn edit_profile(name: &str) -> Result<()> {
+    let storage = Storage::new()?;
+    if !storage.profile_exists_fast(name)? {
+        return Err(Error::ProfileNotFound(name.into()));
+    }
+
+    let original = storage.read_settings(name)?;
+    let working = storage.profile_settings_dir(name).join(EDIT_FILE);
+    let resume = working.exists()
+        && Confirm::new(&format!(
+            "Continue the unsaved edit of '{name}' from last time?"
+        ))
+        .with_default(true)
+        .prompt()?;
+    if !resume {
+        write_atomic(&working, &original)?;
+    }
+    // Release the storage lock while the editor is open
+    drop(storage);
+
+    println!("{} {}", "File:".bold(), working.display());
+    println!(
+        "{} Save and close the editor when done. If you want to cancel, delete all content and save.\n",
+        "Tip:".yellow()
+    );
+
+    let profile = loop {
+        // A resumed copy already differs from the stored settings, so those
+        // are compared below rather than relying on what the editor changed
+        edit_file(&working).map_err(|e| {
+            // Keep the copy only if it holds edits worth resuming
+            if fs::read_to_string(&working).is_ok_and(|content| content == original) {
+                fs::remove_file(&working).ok();
+            }
+            e
+        })?;
+        let content = fs::read_to_string(&working)?;
+
+        if content.trim().is_empty() {
+            fs::remove_file(&working).ok();
+            println!("{}", "Edit cancelled.".yellow());
+            return Ok(());
+        }
+        if content.trim() == original.trim() {
+            fs::remove_file(&working).ok();
+            println!("{}", "No changes made.".yellow());
+            return Ok(());
+        }
+
+        match parse_edited(name, &content) {
+            Ok(profile) => break profile,
+            Err(e) => {
+                eprintln!("{} {e}", "Error:".red().bold());
+                let reopen = Confirm::new("Reopen the editor to fix it?")
+                    .with_default(true)
+                    .prompt()?;
+                if !reopen {
+                    println!(
+                        "{} Your edits are kept in {}; run 'ccuse edit {name}' to continue.",
+                        "→".cyan(),
+                        working.display()
+                    );
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    let storage = Storage::new_exclusive()?;
+    storage.update_profile(profile)?;
+    fs::remove_file(&working).ok();
+
+    println!("{}", format!("✓ Profile '{name}' updated.").green());
+
+    Ok(())
+}
+
+/// Parse and validate edited settings.
+fn parse_edited(name: &str, content: &str) -> Result<Profile> {
+    let invalid = |e: serde_json::Error| Error::ConfigError(format!("Invalid JSON: {e}"));
+
+    let mut settings: serde_json::Value = serde_json::from_str(content).map_err(invalid)?;
+    migrations::migrate(&mut settings)?;
+    let profile: Profile = serde_json::from_value(settings).map_err(invalid)?;
+
+    if profile.name != name {
+        return Err(Error::ConfigError(format!(
+            "\"name\" must stay '{name}'; use 'ccuse rename' to rename the profile"
+        )));
+    }
+    validate_profile_env(&profile)?;
+
+    Ok(profile)
+}
@@ -0,0 +1,76 @@
+use crate::cli::env_export::{export_lines, ExportShell};
+use crate::cli::EnvCommands;
+use crate::config::env_copy::copy_env_vars;
+use crate::config::template::expand_templates;
+use crate::config::{secrets, Storage};
+use crate::error::{Error, Result};
+use colored::Colorize;
+
+/// Run a `ccuse env` subcommand, or print the exports for profile `name`.
+///
+/// # Errors
+///
+/// Returns an error if the subcommand fails or the profile does not exist.
+pub fn run_env_command(
+    name: Option<&str>,
+    format: Option<ExportShell>,
+    command: Option<&EnvCommands>,
+) -> Result<()> {
+    let Some(command) = command else {
+        let name = name.ok_or_else(|| {
+            Error::ConfigError("specify a profile name or a subcommand such as 'copy'".into())
+        })?;
+        return print_exports(name, format.unwrap_or_else(ExportShell::detect));
+    };
+
+    match command {
+        EnvCommands::Copy {
+            src,
+            dst,
+            keys,
+            merge_strategy,
+            move_keys,
+        } => {
+            let storage = Storage::new_exclusive()?;
+            let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+            let keys = (!keys.is_empty()).then_some(keys.as_slice());
+
+            let result = copy_env_vars(&storage, src, dst, keys, *merge_strategy, *move_keys)?;
+
+            let verb = if *move_keys { "Moved" } else { "Copied" };
+            println!(
+                "{}",
+                format!(
+                    "✓ {verb} {} env var(s) from '{src}' to '{dst}'",
+                    result.copied.len()
+                )
+                .green()
+            );
+            for key in &result.copied {
+                println!("  {} {key}", "+".green());
+            }
+            for key in &result.skipped {
+                println!("  {} {key} (already set in '{dst}')", "-".yellow());
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Print the profile's env vars, with `${VAR}` references expanded as
+/// `ccuse use` would, in `shell` syntax.
+fn print_exports(name: &str, shell: ExportShell) -> Result<()> {
+    let storage = Storage::new()?;
+    let profile = storage
+        .resolve_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+
+    let mut env = profile.env;
+    secrets::resolve_sentinels(name, &mut env)?;
+    for line in export_lines(&expand_templates(&env), shell) {
+        println!("{line}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use crate::claude::Launcher;
+use crate::config::template::expand_templates;
+use crate::config::{secrets, Storage};
+use crate::error::{Error, Result};
+use std::env;
+use std::process::Command;
+
+/// Run `command` with the env `ccuse use` would give Claude for profile `name`.
+///
+/// stdio is inherited, and ccuse exits with the command's exit code when it
+/// is not 0 (1 if it was killed by a signal).
+///
+/// # Errors
+///
+/// Returns an error if the profile does not exist, a keyring secret cannot be read, or the command cannot be started.
+pub fn exec_with_profile(name: &str, command: &[String]) -> Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(Error::ConfigError("No command given".into()));
+    };
+
+    // The storage lock is released before the command starts
+    let mut profile = Storage::new()?
+        .resolve_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+    profile.env = expand_templates(&profile.env);
+
+    let mut env_vars = Launcher::build_env(&profile, env::vars().collect());
+    if secrets::has_sentinels(&profile.env) {
+        secrets::resolve_sentinels(&profile.name, &mut env_vars)?;
+    }
+
+    let status = Command::new(program)
+        .args(args)
+        .env_clear()
+        .envs(&env_vars)
+        .status()
+        .map_err(|e| Error::CommandError(program.clone(), e.to_string()))?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
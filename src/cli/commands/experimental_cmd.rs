@@ -0,0 +1,62 @@
+use crate::claude::overrides::{experimental_flag_env_key, EXPERIMENTAL_FLAG_PREFIX};
+use crate::cli::ExperimentalFlagsCommands;
+use crate::config::Storage;
+use crate::error::{Error, Result};
+use colored::Colorize;
+use std::collections::BTreeMap;
+
+/// Run a `ccuse experimental-flags` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the subcommand fails.
+pub fn run_experimental_flags_command(command: &ExperimentalFlagsCommands) -> Result<()> {
+    match command {
+        ExperimentalFlagsCommands::List { name } => list_experimental_flags(name),
+    }
+}
+
+/// List the experimental flags a profile enables, from both its
+/// `experimental_flags` defaults and `CLAUDE_EXPERIMENTAL_*` env vars.
+///
+/// # Errors
+///
+/// Returns an error if the profile does not exist or has an invalid flag name.
+pub fn list_experimental_flags(name: &str) -> Result<()> {
+    let storage = Storage::new()?;
+    let profile = storage
+        .resolve_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+
+    // env key -> (value, origin)
+    let mut flags: BTreeMap<String, (String, &str)> = profile
+        .env
+        .iter()
+        .filter(|(key, _)| key.starts_with(EXPERIMENTAL_FLAG_PREFIX))
+        .map(|(key, value)| (key.clone(), (value.clone(), "env")))
+        .collect();
+    for flag in &profile.experimental_flags {
+        flags.insert(
+            experimental_flag_env_key(flag)?,
+            ("1".to_string(), "experimental_flags"),
+        );
+    }
+
+    if flags.is_empty() {
+        println!(
+            "{}",
+            format!("Profile '{name}' sets no experimental flags.").yellow()
+        );
+        return Ok(());
+    }
+
+    println!("Experimental flags for '{}':\n", name.bold());
+    for (key, (value, origin)) in &flags {
+        let flag = key
+            .trim_start_matches(EXPERIMENTAL_FLAG_PREFIX)
+            .to_lowercase();
+        println!("  {} {}={} ({origin})", flag.cyan(), key, value);
+    }
+
+    Ok(())
+}
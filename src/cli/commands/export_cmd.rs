@@ -0,0 +1,49 @@
+use crate::claude::redaction::REDACTED;
+use crate::config::format::ProfileExport;
+use crate::config::Storage;
+use crate::error::{Error, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+
+/// Env vars replaced by `--redact-secrets`.
+const SECRET_ENV_KEYS: [&str; 2] = ["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_API_KEY"];
+
+/// Export a profile as a standalone JSON file, or to stdout without `output`.
+///
+/// With `redact_secrets`, API credentials are replaced by a placeholder.
+///
+/// # Errors
+///
+/// Returns an error if the profile does not exist or the file cannot be written.
+pub fn export_profile(name: &str, output: Option<&Path>, redact_secrets: bool) -> Result<()> {
+    let storage = Storage::new()?;
+    let mut profile = storage
+        .resolve_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+    // Inherited settings are included, so the export stands on its own
+    profile.extends = None;
+
+    if redact_secrets {
+        for key in SECRET_ENV_KEYS {
+            if let Some(value) = profile.env.get_mut(key) {
+                *value = REDACTED.to_string();
+            }
+        }
+    }
+
+    let content = serde_json::to_string_pretty(&ProfileExport::new(&profile))?;
+
+    match output {
+        Some(path) => {
+            fs::write(path, content + "\n")?;
+            println!(
+                "{}",
+                format!("✓ Profile '{name}' exported to {}", path.display()).green()
+            );
+        }
+        None => println!("{content}"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use crate::config::Storage;
+use crate::error::Result;
+use colored::Colorize;
+
+/// Show the profiles recently launched with `ccuse use`, most recent first,
+/// or delete the history if `clear` is set.
+///
+/// # Errors
+///
+/// Returns an error if the history cannot be read or removed.
+pub fn show_history(clear: bool) -> Result<()> {
+    if clear {
+        Storage::new_exclusive()?.clear_history()?;
+        println!("{}", "✓ Launch history cleared.".green());
+        return Ok(());
+    }
+
+    let storage = Storage::new()?;
+    let history = storage.load_history()?;
+    if history.is_empty() {
+        println!(
+            "{}",
+            "No launches recorded yet. Run 'ccuse use <name>' to launch a profile.".yellow()
+        );
+        return Ok(());
+    }
+
+    let existing = storage.profile_dir_names()?;
+    for record in history.iter().rev() {
+        let when = record.timestamp.format("%Y-%m-%d %H:%M:%S UTC");
+        if existing.contains(&record.name) {
+            println!("  {}  {}", when.to_string().dimmed(), record.name.green());
+        } else {
+            println!(
+                "  {}  {} {}",
+                when.to_string().dimmed(),
+                record.name,
+                "(profile no longer exists)".dimmed()
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use crate::config::format::{detect_import_format, parse_import, ImportFormat};
+use crate::config::validation::{validate_profile_env, validate_profile_name};
+use crate::config::{ProfileSource, Storage};
+use crate::error::{Error, Result};
+use chrono::Utc;
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+
+/// Import profiles from a file.
+///
+/// With [`ImportFormat::Auto`] the format is detected from the file content.
+/// Imported profiles are marked as manual with fresh timestamps and get
+/// `tags` and `launch_args` added; `rename` stores a single imported profile
+/// under a different name.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed, or a profile already exists.
+pub fn import_profile(
+    path: &Path,
+    format: ImportFormat,
+    detect_only: bool,
+    validate: bool,
+    rename: Option<&str>,
+    tags: &[String],
+    launch_args: &[String],
+) -> Result<()> {
+    let content = fs::read_to_string(path)?;
+
+    let format = match format {
+        ImportFormat::Auto => detect_import_format(&content),
+        explicit => explicit,
+    };
+
+    if detect_only {
+        println!("{format}");
+        return Ok(());
+    }
+
+    let fallback_name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| Error::ConfigError(format!("Invalid file name: {}", path.display())))?;
+
+    let mut profiles = parse_import(&content, format, fallback_name)?;
+
+    if let Some(empty) = profiles.iter().find(|p| p.env.is_empty()) {
+        return Err(Error::ConfigError(format!(
+            "Profile '{}' has no env vars to import",
+            empty.name
+        )));
+    }
+    for profile in &profiles {
+        validate_profile_env(profile)?;
+    }
+
+    if let Some(new_name) = rename {
+        let [profile] = profiles.as_mut_slice() else {
+            return Err(Error::ConfigError(format!(
+                "--rename needs a file with one profile, found {}",
+                profiles.len()
+            )));
+        };
+        profile.name = new_name.to_string();
+    }
+    for profile in &profiles {
+        validate_profile_name(&profile.name)?;
+    }
+
+    if validate {
+        println!(
+            "{}",
+            format!(
+                "✓ {} is valid {format} ({} profile(s))",
+                path.display(),
+                profiles.len()
+            )
+            .green()
+        );
+        for profile in &profiles {
+            println!("  {} ({} env vars)", profile.name, profile.env.len());
+        }
+        return Ok(());
+    }
+
+    let storage = Storage::new_exclusive()?;
+
+    let now = Utc::now();
+    for mut profile in profiles {
+        profile.source = Some(ProfileSource::Manual);
+        profile.created_at = now;
+        profile.add_tags(tags);
+        profile.launch_args.extend(launch_args.iter().cloned());
+
+        let name = profile.name.clone();
+        storage.add_profile(profile)?;
+        println!(
+            "{}",
+            format!("✓ Profile '{name}' imported successfully!").green()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,271 @@
+use crate::config::format::{detect_import_format, parse_import};
+use crate::config::validation::{validate_profile_env, validate_profile_name};
+use crate::config::Storage;
+use crate::error::{Error, Result};
+use colored::Colorize;
+use inquire::Text;
+use sha2::{Digest, Sha256};
+use std::fs;
+use url::Url;
+
+/// Time limit for fetching a remote profile.
+#[cfg(feature = "remote-import")]
+const FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Fetch the content of a profile from a `file://` or (with the
+/// `remote-import` feature) `http(s)://` URL.
+///
+/// # Errors
+///
+/// Returns an error if the URL is invalid or unsupported, the request fails,
+/// or the body is not UTF-8.
+pub fn fetch_remote_profile(url: &str, headers: &[(String, String)]) -> Result<String> {
+    let parsed =
+        Url::parse(url).map_err(|e| Error::ConfigError(format!("Invalid URL '{url}': {e}")))?;
+
+    match parsed.scheme() {
+        "file" => {
+            let path = parsed
+                .to_file_path()
+                .map_err(|()| Error::ConfigError(format!("Invalid file URL '{url}'")))?;
+            Ok(fs::read_to_string(path)?)
+        }
+        "http" | "https" => fetch_http(url, headers),
+        scheme => Err(Error::ConfigError(format!(
+            "Unsupported URL scheme '{scheme}': use http, https or file"
+        ))),
+    }
+}
+
+#[cfg(feature = "remote-import")]
+fn fetch_http(url: &str, headers: &[(String, String)]) -> Result<String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| Error::ConfigError(e.to_string()))?;
+
+    let mut request = client.get(url);
+    for (key, value) in headers {
+        request = request.header(key, value);
+    }
+
+    request
+        .send()
+        .and_then(reqwest::blocking::Response::error_for_status)
+        .and_then(reqwest::blocking::Response::text)
+        .map_err(|e| Error::ConfigError(format!("Failed to fetch {url}: {e}")))
+}
+
+#[cfg(not(feature = "remote-import"))]
+fn fetch_http(_url: &str, _headers: &[(String, String)]) -> Result<String> {
+    Err(Error::ConfigError(
+        "Fetching http(s) URLs requires the `remote-import` feature; file:// URLs always work"
+            .into(),
+    ))
+}
+
+/// Parse a `--headers` value of the form `Key: Value`.
+///
+/// # Errors
+///
+/// Returns an error if there is no `:` separator or the key is empty.
+pub fn parse_header(header: &str) -> Result<(String, String)> {
+    match header.split_once(':') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(Error::ConfigError(format!(
+            "Invalid header '{header}': expected KEY:VALUE"
+        ))),
+    }
+}
+
+/// Profile name suggested by the last URL path segment, e.g. `work` for `.../work.json`.
+fn name_from_url(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let segment = parsed.path_segments()?.rev().find(|s| !s.is_empty())?;
+    let stem = segment.split('.').next()?;
+    (!stem.is_empty()).then(|| stem.to_string())
+}
+
+/// Import profiles from a URL.
+///
+/// # Errors
+///
+/// Returns an error if fetching, checksum verification or parsing fails, or a profile already exists.
+pub fn import_profile_from_url(
+    url: &str,
+    name: Option<&str>,
+    headers: &[String],
+    verify_checksum: Option<&str>,
+) -> Result<()> {
+    let headers = headers
+        .iter()
+        .map(|h| parse_header(h))
+        .collect::<Result<Vec<_>>>()?;
+
+    let content = fetch_remote_profile(url, &headers)?;
+
+    if let Some(expected) = verify_checksum {
+        let actual = format!("{:x}", Sha256::digest(content.as_bytes()));
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            return Err(Error::ConfigError(format!(
+                "Checksum mismatch for {url}: expected {expected}, got {actual}"
+            )));
+        }
+    }
+
+    // An empty fallback marks profiles whose content does not name them
+    let mut profiles = parse_import(&content, detect_import_format(&content), "")?;
+
+    if name.is_some() && profiles.len() > 1 {
+        return Err(Error::ConfigError(format!(
+            "--name cannot be used when the URL contains {} profiles",
+            profiles.len()
+        )));
+    }
+
+    for profile in &mut profiles {
+        if let Some(name) = name {
+            profile.name = name.to_string();
+        } else if profile.name.is_empty() {
+            profile.name = match name_from_url(url) {
+                Some(suggested) => suggested,
+                None => Text::new("Profile name:").prompt()?,
+            };
+        }
+    }
+
+    for profile in &profiles {
+        validate_profile_name(&profile.name)?;
+        validate_profile_env(profile)?;
+    }
+
+    let storage = Storage::new_exclusive()?;
+    for profile in profiles {
+        let name = profile.name.clone();
+        storage.add_profile(profile)?;
+        println!(
+            "{}",
+            format!("✓ Profile '{name}' imported from {url}").green()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetches_file_urls() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("work.json");
+        fs::write(&path, r#"{"env":{}}"#).unwrap();
+        let url = Url::from_file_path(&path).unwrap();
+
+        assert_eq!(
+            fetch_remote_profile(url.as_str(), &[]).unwrap(),
+            r#"{"env":{}}"#
+        );
+        assert!(fetch_remote_profile(url.join("missing.json").unwrap().as_str(), &[]).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_and_unsupported_urls() {
+        for url in ["not a url", "ftp://example.com/work.json", "data:,{}"] {
+            assert!(
+                matches!(fetch_remote_profile(url, &[]), Err(Error::ConfigError(_))),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_headers() {
+        assert_eq!(
+            parse_header("Authorization: Bearer a:b").unwrap(),
+            ("Authorization".to_string(), "Bearer a:b".to_string())
+        );
+        assert_eq!(
+            parse_header(" X-Empty :").unwrap(),
+            ("X-Empty".to_string(), String::new())
+        );
+        assert!(parse_header("no separator").is_err());
+        assert!(parse_header(": value").is_err());
+    }
+
+    #[test]
+    fn suggests_names_from_the_last_path_segment() {
+        assert_eq!(
+            name_from_url("https://example.com/profiles/work.json").as_deref(),
+            Some("work")
+        );
+        assert_eq!(
+            name_from_url("https://example.com/raw/team-proxy/").as_deref(),
+            Some("team-proxy")
+        );
+        assert_eq!(name_from_url("https://example.com/"), None);
+        assert_eq!(name_from_url("https://example.com/.hidden"), None);
+    }
+
+    #[cfg(feature = "remote-import")]
+    mod http {
+        use super::super::*;
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+        use std::thread::JoinHandle;
+
+        /// Serve one response locally, returning its URL and the request headers.
+        fn serve_once(status: &str, body: &'static str) -> (String, JoinHandle<Vec<String>>) {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!(
+                "http://{}/profiles/work.json",
+                listener.local_addr().unwrap()
+            );
+            let status = status.to_string();
+            let server = std::thread::spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                let request: Vec<String> = BufReader::new(stream.try_clone().unwrap())
+                    .lines()
+                    .map(std::result::Result::unwrap)
+                    .take_while(|line| !line.is_empty())
+                    .collect();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+                request
+            });
+            (url, server)
+        }
+
+        #[test]
+        fn fetches_http_urls_with_headers() {
+            let (url, server) = serve_once("200 OK", r#"{"env":{"ANTHROPIC_MODEL":"opus"}}"#);
+            let headers = [("Authorization".to_string(), "Bearer token".to_string())];
+
+            let content = fetch_remote_profile(&url, &headers).unwrap();
+
+            assert_eq!(content, r#"{"env":{"ANTHROPIC_MODEL":"opus"}}"#);
+            let request = server.join().unwrap();
+            assert_eq!(request[0], "GET /profiles/work.json HTTP/1.1");
+            assert!(request
+                .iter()
+                .any(|h| h.eq_ignore_ascii_case("authorization: Bearer token")));
+        }
+
+        #[test]
+        fn http_errors_fail_the_fetch() {
+            let (url, server) = serve_once("404 Not Found", "missing");
+            assert!(matches!(
+                fetch_remote_profile(&url, &[]),
+                Err(Error::ConfigError(_))
+            ));
+            server.join().unwrap();
+        }
+    }
+}
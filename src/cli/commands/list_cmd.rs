@@ -1,49 +1,434 @@
-use crate::config::Storage;
+use crate::cli::ListArgs;
+use crate::config::filtering::{filter_by_env, matching_env_entries, EnvFilter};
+use crate::config::orphans::{find_orphaned_profiles, OrphanReport};
+use crate::config::secrets::shown_value;
+use crate::config::sorting::{
+    group_profiles_by_category, group_profiles_by_template, sort_profiles, ListFormat,
+};
+use crate::config::{Profile, Storage};
+use crate::db::DEFAULT_APP_TYPE;
 use crate::error::Result;
 use colored::Colorize;
+use inquire::Confirm;
+use std::fs;
+use std::io::{self, Write};
+
+/// Widest name `--format table` prints before truncating it with an ellipsis.
+const MAX_TABLE_NAME_WIDTH: usize = 32;
 
 /// List all available profiles.
 ///
 /// # Errors
 ///
-/// Returns an error if profiles cannot be loaded from storage.
-pub fn list_profiles() -> Result<()> {
-    let storage = Storage::new()?;
+/// Returns an error if profiles cannot be loaded from storage or a filter is invalid.
+pub fn list_profiles(list_args: &ListArgs) -> Result<()> {
+    let storage = if list_args.fix_orphaned {
+        Storage::new_exclusive()?
+    } else {
+        Storage::new()?
+    };
+
+    if list_args.orphaned || list_args.orphaned_dirs || list_args.fix_orphaned {
+        return list_orphaned(&storage, list_args);
+    }
+
     let profiles = storage.load_profiles()?;
 
+    let key_filter = match (&list_args.env_key, &list_args.env_regex) {
+        (Some(key), _) => Some(EnvFilter::Exact(key.clone())),
+        (None, Some(pattern)) => Some(EnvFilter::regex(pattern)?),
+        (None, None) => None,
+    };
+    let value_filter = match (&list_args.env_value, &list_args.env_contains) {
+        (Some(value), _) => Some(EnvFilter::Exact(value.clone())),
+        (None, Some(substring)) => Some(EnvFilter::Contains(substring.clone())),
+        (None, None) => None,
+    };
+    let env_filtered = key_filter.is_some() || value_filter.is_some();
+
+    let mut profiles = filter_by_env(&profiles, key_filter.as_ref(), value_filter.as_ref());
+
+    if let Some(group) = &list_args.resource_group {
+        profiles.retain(|p| p.resource_group.as_ref() == Some(group));
+    }
+    if list_args.template_only {
+        profiles.retain(|p| p.template_source.is_some());
+    }
+    if let Some(template) = &list_args.template {
+        profiles.retain(|p| {
+            p.template_source
+                .as_deref()
+                .is_some_and(|t| t.eq_ignore_ascii_case(template))
+        });
+    }
+    if let Some(category) = &list_args.category {
+        profiles.retain(|p| {
+            p.category
+                .as_deref()
+                .is_some_and(|c| c.eq_ignore_ascii_case(category))
+        });
+    }
+    profiles.retain(|p| list_args.tags.iter().all(|tag| p.has_tag(tag)));
+    sort_profiles(&mut profiles, list_args.sort);
+    if list_args.reverse {
+        profiles.reverse();
+    }
+
+    let filtered = env_filtered
+        || list_args.resource_group.is_some()
+        || list_args.template_only
+        || list_args.template.is_some()
+        || list_args.category.is_some()
+        || !list_args.tags.is_empty();
+
+    if list_args.count {
+        println!("{}", profiles.len());
+        return Ok(());
+    }
+
+    let format = if list_args.json {
+        ListFormat::Json
+    } else {
+        list_args.format
+    };
+    if format == ListFormat::Json {
+        return print_json(&profiles, list_args.show_secrets);
+    }
+
     if profiles.is_empty() {
-        println!("{}", "No profiles found. Run 'ccuse update' to sync from CC-Switch or 'ccuse add' to create one.".yellow());
+        if filtered {
+            println!("{}", "No profiles match the given filters.".yellow());
+        } else {
+            println!("{}", "No profiles found. Run 'ccuse update' to sync from CC-Switch or 'ccuse add' to create one.".yellow());
+        }
+        return Ok(());
+    }
+
+    let filters = (key_filter.as_ref(), value_filter.as_ref());
+    let print_group = |group: &[&Profile]| {
+        if format == ListFormat::Table {
+            print_table(&storage, group);
+            println!();
+        } else {
+            for profile in group {
+                print_profile(&storage, profile, list_args, filters);
+            }
+        }
+    };
+
+    if format == ListFormat::List {
+        println!("{}", "Available profiles:".bold());
+        println!();
+    }
+
+    if list_args.by_template {
+        for (template, group) in group_profiles_by_template(profiles.iter().copied()) {
+            let header =
+                template.map_or_else(|| "No template".to_string(), |t| format!("Template: {t}"));
+            println!("{}", header.bold().underline());
+            println!();
+            print_group(&group);
+        }
         return Ok(());
     }
 
-    println!("{}", "Available profiles:".bold());
+    if list_args.group_by_category {
+        for (category, group) in group_profiles_by_category(profiles.iter().copied()) {
+            println!(
+                "{}",
+                category.unwrap_or("(uncategorized)").bold().underline()
+            );
+            println!();
+            print_group(&group);
+        }
+        return Ok(());
+    }
+
+    if format == ListFormat::Table {
+        print_table(&storage, &profiles);
+    } else {
+        print_group(&profiles);
+    }
+
+    Ok(())
+}
+
+/// Print profiles as an aligned table of name, source, category, env count and timeout.
+fn print_table(storage: &Storage, profiles: &[&Profile]) {
+    let header = ["NAME", "SOURCE", "CATEGORY", "ENV", "TIMEOUT"].map(String::from);
+    let rows: Vec<[String; 5]> = profiles
+        .iter()
+        .map(|profile| {
+            let name = profile.display_name.as_ref().unwrap_or(&profile.name);
+            let source = match &profile.source {
+                Some(crate::config::ProfileSource::CcSwitch) => "ccswitch",
+                Some(crate::config::ProfileSource::Manual) => "manual",
+                None => "-",
+            };
+            [
+                truncate(name, MAX_TABLE_NAME_WIDTH),
+                source.to_string(),
+                profile.category.clone().unwrap_or_else(|| "-".into()),
+                env_count(storage, profile).to_string(),
+                profile
+                    .api_timeout_ms
+                    .map_or_else(|| "-".into(), |timeout| format!("{timeout}ms")),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.clone().map(|cell| cell.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    // Pad before coloring, since the escape codes would count towards the width
+    let pad = |row: &[String; 5]| -> Vec<String> {
+        row.iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect()
+    };
+    println!("{}", pad(&header).join("  ").trim_end().bold());
+    for row in &rows {
+        let cells = pad(row);
+        let rest = cells[1..].join("  ");
+        println!("{}  {}", cells[0].green(), rest.trim_end());
+    }
+}
+
+/// `text` cut to at most `max` characters, ending in an ellipsis if it was cut.
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(max - 1).collect();
+    cut.push('…');
+    cut
+}
+
+/// Number of env vars a profile launches with, including inherited ones.
+fn env_count(storage: &Storage, profile: &Profile) -> usize {
+    match &profile.extends {
+        None => profile.env.len(),
+        Some(_) => match storage.resolve_profile(&profile.name) {
+            Ok(Some(resolved)) => resolved.env.len(),
+            Ok(None) | Err(_) => profile.env.len(),
+        },
+    }
+}
+
+/// Print the profiles as a JSON array of their stored form, without any other output.
+fn print_json(profiles: &[&Profile], show_secrets: bool) -> Result<()> {
+    let profiles: Vec<Profile> = profiles
+        .iter()
+        .map(|profile| {
+            let mut profile = (*profile).clone();
+            for (key, value) in &mut profile.env {
+                *value = shown_value(key, value, show_secrets);
+            }
+            profile
+        })
+        .collect();
+    let json = serde_json::to_string_pretty(&profiles)?;
+
+    // Scripts may stop reading early, e.g. `ccuse list --json | head`
+    match writeln!(io::stdout().lock(), "{json}") {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Print one profile entry of `ccuse list`.
+fn print_profile(
+    storage: &Storage,
+    profile: &Profile,
+    list_args: &ListArgs,
+    (key_filter, value_filter): (Option<&EnvFilter>, Option<&EnvFilter>),
+) {
+    let name = profile.display_name.as_ref().unwrap_or(&profile.name);
+
+    // Only show source if explicitly set
+    let source_str = match &profile.source {
+        Some(crate::config::ProfileSource::CcSwitch) => Some("ccswitch".cyan()),
+        Some(crate::config::ProfileSource::Manual) => Some("manual".blue()),
+        None => None,
+    };
+
+    let compressed = if storage.is_profile_compressed(&profile.name) {
+        " 📦"
+    } else {
+        ""
+    };
+
+    match source_str {
+        Some(colored) => println!("  {} ({}){compressed}", name.green(), colored),
+        None => println!("  {}{compressed}", name.green()),
+    }
+
+    if name != &profile.name {
+        println!("    Name: {}", profile.name);
+    }
+
+    if let Some(group) = &profile.resource_group {
+        println!("    Resource group: {group}");
+    }
+
+    // Profiles synced for other CC-Switch app types record it as their category
+    if let Some(category) = profile
+        .category
+        .as_deref()
+        .filter(|c| *c != DEFAULT_APP_TYPE)
+    {
+        println!("    Category: {category}");
+    }
+
+    if !profile.tags.is_empty() {
+        println!("    Tags: {}", profile.tags.join(", "));
+    }
+
+    if let Some(template) = &profile.template_source {
+        println!("    Template: {template}");
+    }
+
+    if list_args.show_sizes {
+        if let Ok(size) = storage.profile_size_on_disk(&profile.name) {
+            println!("    Size on disk: {size} bytes");
+        }
+    }
+
+    if let Some(parent) = &profile.extends {
+        println!("    Extends: {parent}");
+    }
+
+    let env_count = env_count(storage, profile);
+    if env_count > 0 {
+        println!("    Environment variables: {env_count}");
+    }
+
+    if list_args.keys {
+        let mut keys: Vec<&String> = profile.env.keys().collect();
+        keys.sort();
+        for key in keys {
+            let shown = shown_value(key, &profile.env[key], list_args.show_secrets);
+            println!("      {}={shown}", key.yellow());
+        }
+    }
+
+    if key_filter.is_some() || value_filter.is_some() {
+        for (key, value) in matching_env_entries(profile, key_filter, value_filter) {
+            println!("    {}={}", key.yellow().bold(), value);
+        }
+    }
+
+    if let Some(timeout) = profile.api_timeout_ms {
+        println!("    API timeout: {timeout}ms");
+    }
+
     println!();
+}
 
-    for profile in &profiles {
-        let name = profile.display_name.as_ref().unwrap_or(&profile.name);
+/// Report (and optionally repair) broken profile directories.
+fn list_orphaned(storage: &Storage, list_args: &ListArgs) -> Result<()> {
+    let report = find_orphaned_profiles(storage)?;
 
-        // Only show source if explicitly set
-        let source_str = match &profile.source {
-            Some(crate::config::ProfileSource::CcSwitch) => Some("ccswitch".cyan()),
-            Some(crate::config::ProfileSource::Manual) => Some("manual".blue()),
-            None => None,
-        };
+    // --fix-orphaned alone acts on both categories
+    let show_all = !list_args.orphaned && !list_args.orphaned_dirs;
+    let show_missing = list_args.orphaned || show_all;
+    let show_dirs = list_args.orphaned_dirs || show_all;
 
-        match source_str {
-            Some(colored) => println!("  {} ({})", name.green(), colored),
-            None => println!("  {}", name.green()),
+    if list_args.count {
+        let mut count = 0;
+        if show_missing {
+            count += report.missing_files.len();
+        }
+        if show_dirs {
+            count += report.orphaned_dirs.len();
         }
+        println!("{count}");
+        return Ok(());
+    }
 
-        if !profile.env.is_empty() {
-            let env_count = profile.env.len();
-            println!("    Environment variables: {env_count}");
+    if show_missing {
+        if report.missing_files.is_empty() {
+            println!("{}", "No profiles with missing settings.json.".green());
+        } else {
+            println!("{}", "Profiles missing settings.json:".bold());
+            for name in &report.missing_files {
+                println!("  {}", name.red());
+            }
         }
+    }
 
-        if let Some(timeout) = profile.api_timeout_ms {
-            println!("    API timeout: {timeout}ms");
+    if show_dirs {
+        if report.orphaned_dirs.is_empty() {
+            println!("{}", "No orphaned profile directories.".green());
+        } else {
+            println!("{}", "Orphaned profile directories:".bold());
+            for dir in &report.orphaned_dirs {
+                println!("  {}", dir.display().to_string().red());
+            }
         }
+    }
 
+    if list_args.fix_orphaned && !report.is_empty() {
         println!();
+        fix_orphaned(storage, &report, show_missing, show_dirs)?;
+    }
+
+    Ok(())
+}
+
+pub(super) fn fix_orphaned(
+    storage: &Storage,
+    report: &OrphanReport,
+    fix_missing: bool,
+    fix_dirs: bool,
+) -> Result<()> {
+    if fix_missing {
+        // Directories without settings.json hold nothing ccuse can load
+        for name in &report.missing_files {
+            fs::remove_dir_all(storage.profile_settings_dir(name))?;
+            println!("{} Removed empty profile '{name}'", "✓".green());
+        }
+    }
+
+    if !fix_dirs {
+        return Ok(());
+    }
+
+    for dir in &report.orphaned_dirs {
+        let Some(dir_name) = dir.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        // A parseable profile stored under another name is re-registered under its directory name
+        if let Ok(Some(mut profile)) = storage.get_profile(dir_name) {
+            let old_name = std::mem::replace(&mut profile.name, dir_name.to_string());
+            storage.update_profile(profile)?;
+            println!(
+                "{} Renamed profile in {} from '{old_name}' to '{dir_name}'",
+                "✓".green(),
+                dir.display()
+            );
+            continue;
+        }
+
+        let confirm = Confirm::new(&format!(
+            "settings.json in '{}' cannot be parsed. Delete this directory?",
+            dir.display()
+        ))
+        .with_default(false)
+        .prompt()?;
+
+        if confirm {
+            fs::remove_dir_all(dir)?;
+            println!("{} Deleted {}", "✓".green(), dir.display());
+        } else {
+            println!("{} Kept {}", "-".yellow(), dir.display());
+        }
     }
 
     Ok(())
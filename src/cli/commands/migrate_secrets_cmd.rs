@@ -0,0 +1,47 @@
+use crate::config::Storage;
+use crate::error::Result;
+use colored::Colorize;
+
+/// Move plaintext secret env values of one or all profiles into the OS keyring.
+///
+/// # Errors
+///
+/// Returns an error if a profile does not exist, the keyring is unavailable,
+/// or a profile cannot be saved.
+pub fn migrate_secrets(name: Option<&str>) -> Result<()> {
+    let storage = Storage::new_exclusive()?;
+
+    let names = match name {
+        Some(name) => vec![name.to_string()],
+        None => {
+            let mut names: Vec<String> = storage
+                .load_profiles()?
+                .into_iter()
+                .map(|profile| profile.name)
+                .collect();
+            names.sort();
+            names
+        }
+    };
+
+    let mut total = 0;
+    for name in &names {
+        let moved = storage.move_secrets_to_keyring(name)?;
+        if moved.is_empty() {
+            continue;
+        }
+        total += moved.len();
+        println!("{} {name}: {}", "✓".green(), moved.join(", "));
+    }
+
+    if total == 0 {
+        println!("{}", "No plaintext secrets to migrate.".yellow());
+    } else {
+        println!(
+            "{}",
+            format!("Moved {total} secret(s) into the OS keyring.").green()
+        );
+    }
+
+    Ok(())
+}
@@ -1,15 +1,66 @@
 pub mod add_cmd;
+pub mod api_log_cmd;
+pub mod audit_cmd;
+pub mod claude_versions_cmd;
+pub mod clone_cmd;
+pub mod completions_cmd;
+pub mod config_dir_cmd;
+pub mod current_cmd;
+pub mod default_cmd;
+pub mod doctor_cmd;
+pub mod edit_cmd;
+pub mod env_cmd;
+pub mod exec_cmd;
+pub mod experimental_cmd;
+pub mod export_cmd;
+pub mod history_cmd;
+pub mod import_cmd;
+pub mod import_url_cmd;
 pub mod list_cmd;
+pub mod migrate_secrets_cmd;
+pub mod profile_cmd;
+pub mod prune_cmd;
 pub mod remove_cmd;
 pub mod rename_cmd;
+pub mod restore_cmd;
+pub mod search_cmd;
+pub mod show_cmd;
+pub mod stats_cmd;
 pub mod update_cmd;
 pub mod usage_cmd;
 pub mod use_cmd;
+pub mod validate_cmd;
+pub mod version_cmd;
 
 pub use add_cmd::add_profile;
+pub use api_log_cmd::run_api_log_command;
+pub use audit_cmd::audit_ccswitch;
+pub use claude_versions_cmd::list_claude_versions;
+pub use clone_cmd::clone_profile;
+pub use completions_cmd::{install_static_completions, print_completions, regenerate_completions};
+pub use config_dir_cmd::show_config_dir;
+pub use current_cmd::show_current_profile;
+pub use default_cmd::run_default_command;
+pub use doctor_cmd::run_doctor;
+pub use edit_cmd::edit_profile;
+pub use env_cmd::run_env_command;
+pub use exec_cmd::exec_with_profile;
+pub use experimental_cmd::run_experimental_flags_command;
+pub use export_cmd::export_profile;
+pub use history_cmd::show_history;
+pub use import_cmd::import_profile;
 pub use list_cmd::list_profiles;
-pub use remove_cmd::{remove_all_profiles, remove_profile};
-pub use rename_cmd::rename_profile;
+pub use migrate_secrets_cmd::migrate_secrets;
+pub use profile_cmd::run_profile_command;
+pub use prune_cmd::prune_profiles;
+pub use remove_cmd::{remove_all_profiles, remove_matching_profiles, remove_profile};
+pub use rename_cmd::{rename_profile, rename_profiles_by_regex};
+pub use restore_cmd::restore_backup;
+pub use search_cmd::search_profiles;
+pub use show_cmd::show_profile;
+pub use stats_cmd::show_stats;
 pub use update_cmd::update_profiles;
 pub use usage_cmd::run_ccusage;
 pub use use_cmd::use_profile;
+pub use validate_cmd::validate_profiles;
+pub use version_cmd::show_version;
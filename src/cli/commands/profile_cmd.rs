@@ -0,0 +1,340 @@
+use crate::claude::overrides::{apply_model_override, MODEL_ENV_KEYS};
+use crate::cli::commands::import_url_cmd::import_profile_from_url;
+use crate::cli::ProfileCommands;
+use crate::config::template::{expand_templates, template_var_sources, TemplateVarSource};
+use crate::config::Storage;
+use crate::error::{Error, Result};
+use colored::Colorize;
+
+/// Run a `ccuse profile` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the subcommand fails.
+pub fn run_profile_command(command: &ProfileCommands) -> Result<()> {
+    match command {
+        ProfileCommands::ImportFromUrl {
+            url,
+            name,
+            headers,
+            verify_checksum,
+        } => import_profile_from_url(url, name.as_deref(), headers, verify_checksum.as_deref()),
+        ProfileCommands::TemplateVars {
+            name,
+            check,
+            resolve,
+        } => template_vars(name, *check, *resolve),
+        ProfileCommands::Compress { name, all } => compress_profiles(name.as_deref(), *all),
+        ProfileCommands::Decompress { name } => decompress_profile(name),
+        ProfileCommands::SetResourceGroup {
+            profile,
+            group,
+            clear,
+        } => set_resource_group(profile, if *clear { None } else { group.as_deref() }),
+        ProfileCommands::SetModel { profile, model } => set_model(profile, model),
+        ProfileCommands::GetModel { profile } => get_model(profile),
+        ProfileCommands::RenameDisplay {
+            name,
+            new_display_name,
+            clear_display_name,
+        } => rename_display(
+            name,
+            if *clear_display_name {
+                None
+            } else {
+                new_display_name.as_deref()
+            },
+        ),
+    }
+}
+
+/// Store `model` in the env var Claude Code reads for its family.
+///
+/// # Errors
+///
+/// Returns an error if the profile does not exist or cannot be saved.
+pub fn set_model(name: &str, model: &str) -> Result<()> {
+    let storage = Storage::new_exclusive()?;
+
+    let mut profile = storage
+        .get_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+
+    let key = apply_model_override(&mut profile.env, model);
+    storage.update_profile(profile)?;
+
+    println!("{}", format!("✓ Set {key}={model} in '{name}'").green());
+
+    Ok(())
+}
+
+/// Print the model env vars a profile sets.
+///
+/// # Errors
+///
+/// Returns an error if the profile does not exist.
+pub fn get_model(name: &str) -> Result<()> {
+    let storage = Storage::new()?;
+
+    let profile = storage
+        .get_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+
+    let models: Vec<(&str, &String)> = MODEL_ENV_KEYS
+        .iter()
+        .filter_map(|key| profile.env.get(*key).map(|model| (*key, model)))
+        .collect();
+
+    if models.is_empty() {
+        println!(
+            "{}",
+            format!("Profile '{name}' sets no model; Claude Code uses its default.").yellow()
+        );
+        return Ok(());
+    }
+
+    for (key, model) in models {
+        println!("{} ({key})", model.cyan());
+    }
+
+    Ok(())
+}
+
+/// Set or clear the display name of a profile, leaving its directory untouched.
+///
+/// # Errors
+///
+/// Returns an error if the profile does not exist or cannot be saved.
+pub fn rename_display(name: &str, display_name: Option<&str>) -> Result<()> {
+    let storage = Storage::new_exclusive()?;
+    rename_display_in(&storage, name, display_name)
+}
+
+fn rename_display_in(storage: &Storage, name: &str, display_name: Option<&str>) -> Result<()> {
+    let mut profile = storage
+        .get_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+
+    profile.display_name = display_name.map(str::to_string);
+    storage.update_profile(profile)?;
+
+    match display_name {
+        Some(display_name) => println!(
+            "{}",
+            format!("✓ Display name of '{name}' set to '{display_name}'").green()
+        ),
+        None => println!("{}", format!("✓ Display name of '{name}' cleared").green()),
+    }
+
+    Ok(())
+}
+
+/// Set or clear the default resource group of a profile.
+///
+/// # Errors
+///
+/// Returns an error if the profile does not exist or cannot be saved.
+pub fn set_resource_group(name: &str, group: Option<&str>) -> Result<()> {
+    let storage = Storage::new_exclusive()?;
+
+    let mut profile = storage
+        .get_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+
+    profile.resource_group = group.map(str::to_string);
+    storage.update_profile(profile)?;
+
+    match group {
+        Some(group) => println!(
+            "{}",
+            format!("✓ Resource group of '{name}' set to '{group}'").green()
+        ),
+        None => println!(
+            "{}",
+            format!("✓ Resource group of '{name}' cleared").green()
+        ),
+    }
+
+    Ok(())
+}
+
+/// Compress one or all profiles.
+///
+/// # Errors
+///
+/// Returns an error if a profile does not exist or cannot be rewritten.
+pub fn compress_profiles(name: Option<&str>, all: bool) -> Result<()> {
+    let storage = Storage::new_exclusive()?;
+
+    let names: Vec<String> = if all {
+        storage
+            .load_profiles()?
+            .into_iter()
+            .map(|p| p.name)
+            .collect()
+    } else {
+        name.map(str::to_string).into_iter().collect()
+    };
+
+    for name in &names {
+        if storage.is_profile_compressed(name) {
+            println!("{} '{name}' is already compressed", "-".yellow());
+            continue;
+        }
+
+        let before = storage.profile_size_on_disk(name).unwrap_or(0);
+        storage.compress_profile(name)?;
+        let after = storage.profile_size_on_disk(name)?;
+        println!(
+            "{}",
+            format!("✓ Compressed '{name}' ({before} → {after} bytes)").green()
+        );
+    }
+
+    Ok(())
+}
+
+/// Decompress a profile back to plain settings.json.
+///
+/// # Errors
+///
+/// Returns an error if the profile does not exist or cannot be rewritten.
+pub fn decompress_profile(name: &str) -> Result<()> {
+    let storage = Storage::new_exclusive()?;
+
+    if !storage.is_profile_compressed(name) {
+        if !storage.profile_exists_fast(name)? {
+            return Err(Error::ProfileNotFound(name.into()));
+        }
+        println!("{} '{name}' is not compressed", "-".yellow());
+        return Ok(());
+    }
+
+    storage.decompress_profile(name)?;
+    println!("{}", format!("✓ Decompressed '{name}'").green());
+
+    Ok(())
+}
+
+/// List the `${VAR}` template variables a profile's env values depend on,
+/// including those of the profiles it `extends`.
+///
+/// # Errors
+///
+/// Returns an error if the profile or one of its parents does not exist, or
+/// if `check` is set and any referenced variable is undefined.
+pub fn template_vars(name: &str, check: bool, resolve: bool) -> Result<()> {
+    let storage = Storage::new()?;
+
+    // Also rejects missing parents and cycles, so the walk below ends
+    let profile = storage
+        .resolve_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+
+    let mut chain = Vec::new();
+    let mut next = Some(name.to_string());
+    while let Some(current) = next {
+        let link = storage
+            .get_profile(&current)?
+            .ok_or_else(|| Error::ProfileNotFound(current.clone()))?;
+        next = link.extends.clone();
+        chain.push(link);
+    }
+
+    let vars = template_var_sources(&chain);
+    if vars.is_empty() {
+        println!("{}", "No template variables referenced.".yellow());
+    } else {
+        println!("{}", "Template variables:".bold());
+    }
+
+    let mut undefined = 0;
+    for (var, source) in &vars {
+        match source {
+            TemplateVarSource::Own => println!("  {} {var}", "✓".green()),
+            TemplateVarSource::Inherited(parent) => {
+                println!("  {} {var} {}", "↑".cyan(), format!("({parent})").dimmed());
+            }
+            TemplateVarSource::Undefined => {
+                undefined += 1;
+                println!("  {} {}", "✗".red(), var.red());
+            }
+        }
+    }
+
+    if resolve && !profile.env.is_empty() {
+        let expanded = expand_templates(&profile.env);
+        let mut keys: Vec<&String> = expanded.keys().collect();
+        keys.sort();
+
+        println!();
+        println!("{}", "Resolved values:".bold());
+        for key in keys {
+            println!("  {}={}", key.cyan(), expanded[key]);
+        }
+    }
+
+    if check && undefined > 0 {
+        return Err(Error::ConfigError(format!(
+            "{undefined} undefined template variable(s) in profile '{name}'"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Profile;
+
+    fn storage_with(dir: &std::path::Path, display_name: Option<&str>) -> Storage {
+        let storage = Storage::in_dir(dir);
+        storage
+            .add_profile(Profile {
+                name: "work".into(),
+                display_name: display_name.map(String::from),
+                ..Profile::default()
+            })
+            .unwrap();
+        storage
+    }
+
+    #[test]
+    fn rename_display_sets_only_the_display_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage_with(dir.path(), None);
+
+        rename_display_in(&storage, "work", Some("Work (EU)")).unwrap();
+
+        let profile = storage.get_profile("work").unwrap().unwrap();
+        assert_eq!(profile.name, "work");
+        assert_eq!(profile.display_name.as_deref(), Some("Work (EU)"));
+        assert!(dir.path().join("work/settings.json").exists());
+        assert_eq!(storage.profile_dir_names().unwrap(), ["work"]);
+    }
+
+    #[test]
+    fn rename_display_replaces_and_clears_an_existing_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage_with(dir.path(), Some("Old"));
+
+        rename_display_in(&storage, "work", Some("New")).unwrap();
+        let profile = storage.get_profile("work").unwrap().unwrap();
+        assert_eq!(profile.display_name.as_deref(), Some("New"));
+
+        rename_display_in(&storage, "work", None).unwrap();
+        let profile = storage.get_profile("work").unwrap().unwrap();
+        assert_eq!(profile.display_name, None);
+    }
+
+    #[test]
+    fn rename_display_of_a_missing_profile_is_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        assert!(matches!(
+            rename_display_in(&storage, "missing", Some("x")),
+            Err(Error::ProfileNotFound(_))
+        ));
+    }
+}
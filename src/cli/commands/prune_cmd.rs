@@ -0,0 +1,77 @@
+use super::list_cmd::fix_orphaned;
+use crate::config::orphans::NameReference;
+use crate::config::Storage;
+use crate::error::Result;
+use colored::Colorize;
+
+/// Report profile directories without a loadable profile and profile names
+/// without a directory, and repair them with `fix`.
+///
+/// # Errors
+///
+/// Returns an error if the config directory cannot be scanned, user
+/// confirmation fails, or a repair cannot be written.
+pub fn prune_profiles(fix: bool) -> Result<()> {
+    let storage = if fix {
+        Storage::new_exclusive()?
+    } else {
+        Storage::new()?
+    };
+    let found = storage.find_inconsistencies()?;
+
+    if found.is_empty() {
+        println!("{}", "No inconsistencies found.".green());
+        return Ok(());
+    }
+
+    if !found.orphans.missing_files.is_empty() {
+        println!("{}", "Profiles missing settings.json:".bold());
+        for name in &found.orphans.missing_files {
+            println!("  {}", name.red());
+        }
+    }
+    if !found.orphans.orphaned_dirs.is_empty() {
+        println!("{}", "Orphaned profile directories:".bold());
+        for dir in &found.orphans.orphaned_dirs {
+            println!("  {}", dir.display().to_string().red());
+        }
+    }
+    if !found.dangling_names.is_empty() {
+        println!("{}", "Profile names without a profile:".bold());
+        for dangling in &found.dangling_names {
+            println!(
+                "  {} ({})",
+                dangling.name.red(),
+                describe(&dangling.referenced_by)
+            );
+        }
+    }
+
+    if !fix {
+        println!();
+        println!("Run 'ccuse prune --fix' to repair these.");
+        return Ok(());
+    }
+
+    println!();
+    fix_orphaned(&storage, &found.orphans, true, true)?;
+    for dangling in &found.dangling_names {
+        storage.drop_dangling_name(dangling)?;
+        println!(
+            "{} Dropped '{}' as the {}",
+            "✓".green(),
+            dangling.name,
+            describe(&dangling.referenced_by)
+        );
+    }
+
+    Ok(())
+}
+
+fn describe(reference: &NameReference) -> String {
+    match reference {
+        NameReference::DefaultProfile => "default profile".to_string(),
+        NameReference::LastUsed => "last used profile".to_string(),
+        NameReference::ExtendedBy(child) => format!("parent of '{child}'"),
+    }
+}
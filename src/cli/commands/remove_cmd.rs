@@ -1,3 +1,4 @@
+use crate::config::filtering::glob_regex;
 use crate::config::Storage;
 use crate::error::Result;
 use colored::Colorize;
@@ -9,10 +10,10 @@ use inquire::Confirm;
 ///
 /// Returns an error if profile does not exist, user confirmation fails, or profile cannot be removed.
 pub fn remove_profile(name: &str) -> Result<()> {
-    let storage = Storage::new()?;
+    let storage = Storage::new_exclusive()?;
 
     // Check if profile exists
-    if storage.get_profile(name)?.is_none() {
+    if !storage.profile_exists_fast(name)? {
         return Err(crate::error::Error::ProfileNotFound(name.into()));
     }
 
@@ -35,6 +36,78 @@ pub fn remove_profile(name: &str) -> Result<()> {
         format!("Profile '{name}' removed successfully.").green()
     );
 
+    if storage.load_default_profile()?.as_deref() == Some(name) {
+        storage.save_default_profile(None)?;
+        println!(
+            "{} '{name}' was the default profile; no default is set now.",
+            "→".cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// Remove every profile in `category` whose name matches the glob `pattern`,
+/// after one confirmation for the whole list.
+///
+/// # Errors
+///
+/// Returns an error if the pattern is invalid, user confirmation fails, or a profile cannot be removed.
+pub fn remove_matching_profiles(category: Option<&str>, pattern: Option<&str>) -> Result<()> {
+    let storage = Storage::new_exclusive()?;
+    let pattern = pattern.map(glob_regex).transpose()?;
+
+    let mut names: Vec<String> = storage
+        .load_profiles()?
+        .into_iter()
+        .filter(|p| {
+            category.map_or(true, |category| {
+                p.category
+                    .as_deref()
+                    .is_some_and(|c| c.eq_ignore_ascii_case(category))
+            })
+        })
+        .filter(|p| pattern.as_ref().map_or(true, |re| re.is_match(&p.name)))
+        .map(|p| p.name)
+        .collect();
+    names.sort();
+
+    if names.is_empty() {
+        println!("{}", "No profiles match.".yellow());
+        return Ok(());
+    }
+
+    println!("{}", "Profiles to remove:".bold());
+    for name in &names {
+        println!("  {name}");
+    }
+    let confirm = Confirm::new(&format!(
+        "Are you sure you want to delete these {} profile(s)?",
+        names.len()
+    ))
+    .with_default(false)
+    .prompt()?;
+
+    if !confirm {
+        println!("{}", "Deletion cancelled.".yellow());
+        return Ok(());
+    }
+
+    let default = storage.load_default_profile()?;
+    for name in &names {
+        storage.remove_profile(name)?;
+    }
+
+    println!("{}", format!("Removed {} profile(s).", names.len()).green());
+
+    if let Some(default) = default.filter(|default| names.contains(default)) {
+        storage.save_default_profile(None)?;
+        println!(
+            "{} '{default}' was the default profile; no default is set now.",
+            "→".cyan()
+        );
+    }
+
     Ok(())
 }
 
@@ -44,7 +117,7 @@ pub fn remove_profile(name: &str) -> Result<()> {
 ///
 /// Returns an error if user confirmation fails or profiles cannot be removed.
 pub fn remove_all_profiles() -> Result<()> {
-    let storage = Storage::new()?;
+    let storage = Storage::new_exclusive()?;
 
     // Confirm deletion
     let confirm =
@@ -57,9 +130,10 @@ pub fn remove_all_profiles() -> Result<()> {
         return Ok(());
     }
 
-    storage.remove_all_profiles()?;
+    let backup = storage.remove_all_profiles()?;
 
     println!("{}", "All profiles removed and data file deleted.".green());
+    println!("Backup saved to {}", backup.display().to_string().cyan());
 
     Ok(())
 }
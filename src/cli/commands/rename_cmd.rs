@@ -1,6 +1,10 @@
-use crate::config::Storage;
-use crate::error::Result;
+use crate::config::validation::validate_profile_name;
+use crate::config::{secrets, Storage};
+use crate::error::{Error, Result};
 use colored::Colorize;
+use inquire::Confirm;
+use regex::Regex;
+use std::collections::HashSet;
 use std::fs;
 
 /// Rename a profile.
@@ -9,18 +13,91 @@ use std::fs;
 ///
 /// Returns an error if old profile does not exist, new name already exists, or profile cannot be updated.
 pub fn rename_profile(old_name: &str, new_name: &str) -> Result<()> {
-    let storage = Storage::new()?;
+    validate_profile_name(new_name)?;
+    let storage = Storage::new_exclusive()?;
+    rename_in(&storage, old_name, new_name)
+}
+
+/// Rename every profile whose name matches `pattern`, replacing each match
+/// with `replacement` (`$1`, `${name}` refer to capture groups).
+///
+/// The old → new mapping is previewed and applied after one confirmation.
+/// Nothing is renamed if a new name is invalid, two profiles would get the
+/// same name, or a new name is already taken.
+///
+/// # Errors
+///
+/// Returns an error if the pattern is invalid, the renames would collide, user confirmation fails, or a profile cannot be renamed.
+pub fn rename_profiles_by_regex(pattern: &str, replacement: &str) -> Result<()> {
+    let re = Regex::new(pattern)
+        .map_err(|e| Error::ConfigError(format!("Invalid regex '{pattern}': {e}")))?;
+    let storage = Storage::new_exclusive()?;
+
+    let mut names = storage.profile_dir_names()?;
+    names.retain(|name| storage.settings_file_exists(name));
+    names.sort();
+
+    let renames: Vec<(String, String)> = names
+        .iter()
+        .filter(|name| re.is_match(name))
+        .map(|name| (name.clone(), re.replace_all(name, replacement).into_owned()))
+        .filter(|(old, new)| old != new)
+        .collect();
+
+    if renames.is_empty() {
+        println!("{}", "No profile names would change.".yellow());
+        return Ok(());
+    }
+
+    let mut targets = HashSet::new();
+    for (old, new) in &renames {
+        validate_profile_name(new)?;
+        if !targets.insert(new.as_str()) {
+            return Err(Error::ConfigError(format!(
+                "More than one profile would be renamed to '{new}', nothing was renamed"
+            )));
+        }
+        if names.contains(new) {
+            return Err(Error::ConfigError(format!(
+                "'{old}' would be renamed to '{new}', which already exists; nothing was renamed"
+            )));
+        }
+    }
+
+    println!("{}", "Profiles to rename:".bold());
+    for (old, new) in &renames {
+        println!("  {old} {} {new}", "→".cyan());
+    }
+    let confirm = Confirm::new(&format!("Rename these {} profile(s)?", renames.len()))
+        .with_default(false)
+        .prompt()?;
 
+    if !confirm {
+        println!("{}", "Rename cancelled.".yellow());
+        return Ok(());
+    }
+
+    for (old, new) in &renames {
+        rename_in(&storage, old, new)?;
+    }
+
+    Ok(())
+}
+
+fn rename_in(storage: &Storage, old_name: &str, new_name: &str) -> Result<()> {
     // Check if old profile exists
     let Some(mut profile) = storage.get_profile(old_name)? else {
         return Err(crate::error::Error::ProfileNotFound(old_name.into()));
     };
 
     // Check if new name already exists
-    if storage.get_profile(new_name)?.is_some() {
+    if storage.profile_exists_fast(new_name)? {
         return Err(crate::error::Error::ProfileAlreadyExists(new_name.into()));
     }
 
+    // Keyring entries are keyed by profile name, so they move first
+    secrets::copy_secrets(old_name, new_name, &profile.env)?;
+
     // Rename the profile directory
     let old_dir = storage.profile_settings_dir(old_name);
     let new_dir = storage.profile_settings_dir(new_name);
@@ -34,15 +111,14 @@ pub fn rename_profile(old_name: &str, new_name: &str) -> Result<()> {
         fs::rename(&old_dir, &new_dir)?;
     }
 
-    // Update profile name in settings.json
-    profile.name = new_name.to_string();
-    if profile.display_name.is_some() {
-        profile.display_name = Some(new_name.to_string());
+    // Update profile name in settings.json, keeping the name users know it by
+    if profile.display_name.is_none() {
+        profile.display_name = Some(old_name.to_string());
     }
+    profile.name = new_name.to_string();
 
     // Save updated profile to new location
-    let settings_path = storage.ensure_profile_settings_dir(new_name)?;
-    fs::write(&settings_path, serde_json::to_string_pretty(&profile)?)?;
+    storage.update_profile(profile.clone())?;
 
     // Remove old profile directory
     let old_dir = storage.profile_settings_dir(old_name);
@@ -50,6 +126,25 @@ pub fn rename_profile(old_name: &str, new_name: &str) -> Result<()> {
         fs::remove_dir_all(&old_dir)?;
     }
 
+    if storage.load_default_profile()?.as_deref() == Some(old_name) {
+        storage.save_default_profile(Some(new_name))?;
+    }
+
+    // Keep profiles that extend this one pointing at it
+    for mut child in storage.load_profiles()? {
+        if child.extends.as_deref() == Some(old_name) {
+            child.extends = Some(new_name.to_string());
+            storage.update_profile(child)?;
+        }
+    }
+
+    if let Err(e) = secrets::delete_secrets(old_name, &profile.env) {
+        eprintln!(
+            "{} keyring entries of '{old_name}' were not deleted: {e}",
+            "Warning:".yellow()
+        );
+    }
+
     println!(
         "{}",
         format!("Profile '{old_name}' renamed to '{new_name}' successfully.").green()
@@ -57,3 +152,115 @@ pub fn rename_profile(old_name: &str, new_name: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Profile;
+    use chrono::{DateTime, Utc};
+    use std::path::Path;
+
+    fn add(storage: &Storage, name: &str, display_name: Option<&str>, extends: Option<&str>) {
+        storage
+            .add_profile(Profile {
+                name: name.into(),
+                display_name: display_name.map(String::from),
+                extends: extends.map(String::from),
+                ..Profile::default()
+            })
+            .unwrap();
+    }
+
+    fn dirs(dir: &Path) -> Vec<String> {
+        let mut names = Storage::in_dir(dir).profile_dir_names().unwrap();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn rename_keeps_the_old_name_as_display_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        add(&storage, "work", None, None);
+
+        rename_in(&storage, "work", "work-eu").unwrap();
+
+        let profile = storage.get_profile("work-eu").unwrap().unwrap();
+        assert_eq!(profile.name, "work-eu");
+        assert_eq!(profile.display_name.as_deref(), Some("work"));
+        assert_eq!(dirs(dir.path()), ["work-eu"]);
+    }
+
+    #[test]
+    fn rename_keeps_an_existing_display_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        add(&storage, "work", Some("Work Proxy"), None);
+
+        rename_in(&storage, "work", "proxy").unwrap();
+
+        let profile = storage.get_profile("proxy").unwrap().unwrap();
+        assert_eq!(profile.display_name.as_deref(), Some("Work Proxy"));
+    }
+
+    #[test]
+    fn rename_follows_the_default_and_children() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        add(&storage, "base", None, None);
+        add(&storage, "child", None, Some("base"));
+        storage.save_default_profile(Some("base")).unwrap();
+
+        rename_in(&storage, "base", "root").unwrap();
+
+        assert_eq!(
+            storage.load_default_profile().unwrap().as_deref(),
+            Some("root")
+        );
+        let child = storage.get_profile("child").unwrap().unwrap();
+        assert_eq!(child.extends.as_deref(), Some("root"));
+    }
+
+    #[test]
+    fn rename_needs_an_existing_source_and_a_free_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        add(&storage, "work", None, None);
+        add(&storage, "home", None, None);
+
+        assert!(matches!(
+            rename_in(&storage, "missing", "new"),
+            Err(Error::ProfileNotFound(_))
+        ));
+        assert!(matches!(
+            rename_in(&storage, "work", "home"),
+            Err(Error::ProfileAlreadyExists(_))
+        ));
+        assert_eq!(dirs(dir.path()), ["home", "work"]);
+    }
+
+    #[test]
+    fn rename_advances_updated_at_but_not_created_at() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        let long_ago: DateTime<Utc> = "2024-01-02T03:04:05Z".parse().unwrap();
+        fs::create_dir(dir.path().join("work")).unwrap();
+        fs::write(
+            dir.path().join("work/settings.json"),
+            serde_json::json!({
+                "name": "work",
+                "created_at": long_ago,
+                "updated_at": long_ago,
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let before = Utc::now();
+        rename_in(&storage, "work", "work-eu").unwrap();
+
+        let profile = storage.get_profile("work-eu").unwrap().unwrap();
+        assert_eq!(profile.created_at, long_ago);
+        assert!(profile.updated_at >= before, "{}", profile.updated_at);
+    }
+}
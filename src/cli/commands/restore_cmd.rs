@@ -0,0 +1,65 @@
+use crate::config::{secrets, Storage};
+use crate::error::{Error, Result};
+use colored::Colorize;
+use inquire::Confirm;
+
+/// Restore the config directory from a `remove --all` backup, or list the
+/// backups when no timestamp is given.
+///
+/// # Errors
+///
+/// Returns an error if the backup does not exist, user confirmation fails, or files cannot be copied.
+pub fn restore_backup(timestamp: Option<&str>) -> Result<()> {
+    let storage = Storage::new_exclusive()?;
+
+    let Some(timestamp) = timestamp else {
+        let backups = storage.list_backups()?;
+        if backups.is_empty() {
+            println!("{}", "No backups found.".yellow());
+            return Ok(());
+        }
+        println!("{}", "Available backups:".bold());
+        for name in backups.iter().rev() {
+            println!("  {name}");
+        }
+        return Ok(());
+    };
+
+    if !storage.list_backups()?.iter().any(|name| name == timestamp) {
+        return Err(Error::ConfigError(format!(
+            "Backup not found: {timestamp} (run 'ccuse restore' to list backups)"
+        )));
+    }
+
+    let confirm = Confirm::new(&format!(
+        "Restore backup '{timestamp}'? Profiles with the same names will be overwritten."
+    ))
+    .with_default(false)
+    .prompt()?;
+
+    if !confirm {
+        println!("{}", "Restore cancelled.".yellow());
+        return Ok(());
+    }
+
+    storage.restore_backup(timestamp)?;
+
+    println!(
+        "{}",
+        format!("Backup '{timestamp}' restored successfully.").green()
+    );
+
+    // Keyring entries are deleted with their profiles and are not part of backups
+    if storage
+        .load_profiles()?
+        .iter()
+        .any(|profile| secrets::has_sentinels(&profile.env))
+    {
+        eprintln!(
+            "{} some restored profiles keep secrets in the OS keyring; set them again if they were removed",
+            "Warning:".yellow()
+        );
+    }
+
+    Ok(())
+}
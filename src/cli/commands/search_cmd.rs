@@ -0,0 +1,115 @@
+use crate::config::sorting::sort_profiles_by_display_name;
+use crate::config::{Profile, Storage};
+use crate::error::Result;
+use colored::Colorize;
+
+/// The env value `ccuse search` matches besides env keys
+const BASE_URL_KEY: &str = "ANTHROPIC_BASE_URL";
+
+/// Find profiles whose name, display name, category, env keys or base URL
+/// contain `query`, ignoring case.
+///
+/// With `env_only`, only env keys and the base URL are searched.
+///
+/// # Errors
+///
+/// Returns an error if profiles cannot be loaded from storage.
+pub fn search_profiles(query: &str, env_only: bool) -> Result<()> {
+    let storage = Storage::new()?;
+    let profiles = storage.load_profiles()?;
+    let mut profiles: Vec<&Profile> = profiles.iter().collect();
+    sort_profiles_by_display_name(&mut profiles);
+
+    let query = query.to_lowercase();
+    let results: Vec<_> = profiles
+        .into_iter()
+        .map(|profile| (profile, matching_fields(profile, &query, env_only)))
+        .filter(|(_, fields)| !fields.is_empty())
+        .collect();
+
+    if results.is_empty() {
+        println!("{}", "No profiles match the query.".yellow());
+        return Ok(());
+    }
+
+    for (profile, fields) in results {
+        let name = profile.display_name.as_ref().unwrap_or(&profile.name);
+        println!("  {}", name.green());
+        for (field, value) in fields {
+            println!("    {field}: {}", highlight(value, &query));
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Fields of `profile` containing the lowercased `query`, as (label, value) pairs.
+fn matching_fields<'a>(
+    profile: &'a Profile,
+    query: &str,
+    env_only: bool,
+) -> Vec<(&'static str, &'a str)> {
+    let contains = |text: &str| text.to_lowercase().contains(query);
+    let mut fields = Vec::new();
+
+    if !env_only {
+        if contains(&profile.name) {
+            fields.push(("Name", profile.name.as_str()));
+        }
+        if let Some(display_name) = profile.display_name.as_deref().filter(|d| contains(d)) {
+            fields.push(("Display name", display_name));
+        }
+        if let Some(category) = profile.category.as_deref().filter(|c| contains(c)) {
+            fields.push(("Category", category));
+        }
+    }
+
+    // Only keys are shown for other env vars, so secret values never are
+    let mut keys: Vec<&String> = profile.env.keys().filter(|key| contains(key)).collect();
+    keys.sort();
+    fields.extend(keys.into_iter().map(|key| ("Env", key.as_str())));
+
+    if let Some(base_url) = profile.env.get(BASE_URL_KEY).filter(|url| contains(url)) {
+        fields.push((BASE_URL_KEY, base_url.as_str()));
+    }
+
+    fields
+}
+
+/// `text` with every case-insensitive occurrence of the lowercased `query` highlighted.
+fn highlight(text: &str, query: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    'outer: while !rest.is_empty() {
+        for (start, _) in rest.char_indices() {
+            let tail = &rest[start..];
+            if let Some(len) = match_len(tail, query) {
+                out.push_str(&rest[..start]);
+                out.push_str(&tail[..len].yellow().bold().to_string());
+                rest = &tail[len..];
+                continue 'outer;
+            }
+        }
+        out.push_str(rest);
+        break;
+    }
+
+    out
+}
+
+/// Byte length of the prefix of `text` that lowercases to `query`, if any.
+fn match_len(text: &str, query: &str) -> Option<usize> {
+    let mut lowered = String::new();
+    for (index, c) in text.char_indices() {
+        lowered.extend(c.to_lowercase());
+        if lowered == query {
+            return Some(index + c.len_utf8());
+        }
+        if !query.starts_with(&lowered) {
+            return None;
+        }
+    }
+    None
+}
@@ -0,0 +1,118 @@
+use crate::config::secrets::shown_value;
+use crate::config::{Profile, ProfileSource, Storage};
+use crate::error::{Error, Result};
+use colored::Colorize;
+
+/// Print every field of a single profile.
+///
+/// Secret-looking env values are masked unless `reveal` is set.
+///
+/// # Errors
+///
+/// Returns an error if the profile does not exist or cannot be loaded.
+pub fn show_profile(name: &str, reveal: bool) -> Result<()> {
+    let storage = Storage::new()?;
+    let profile = storage
+        .get_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+
+    print_summary(&profile, reveal);
+    Ok(())
+}
+
+fn print_summary(profile: &Profile, reveal: bool) {
+    let none = || "(none)".dimmed().to_string();
+
+    println!("{}", profile.name.green().bold());
+    println!(
+        "  Display name: {}",
+        profile.display_name.clone().unwrap_or_else(none)
+    );
+    let source = match &profile.source {
+        Some(ProfileSource::CcSwitch) => "ccswitch".cyan().to_string(),
+        Some(ProfileSource::Manual) => "manual".blue().to_string(),
+        None => none(),
+    };
+    println!("  Source: {source}");
+    println!(
+        "  Category: {}",
+        profile.category.clone().unwrap_or_else(none)
+    );
+    if profile.tags.is_empty() {
+        println!("  Tags: {}", none());
+    } else {
+        println!("  Tags: {}", profile.tags.join(", "));
+    }
+    if profile.launch_args.is_empty() {
+        println!("  Launch args: {}", none());
+    } else {
+        println!("  Launch args: {}", profile.launch_args.join(" "));
+    }
+    println!("  Created: {}", profile.created_at.to_rfc3339());
+    println!("  Updated: {}", profile.updated_at.to_rfc3339());
+    println!(
+        "  API timeout: {}",
+        profile
+            .api_timeout_ms
+            .map_or_else(none, |ms| format!("{ms}ms"))
+    );
+
+    println!();
+    println!("{}", "Environment variables:".bold());
+    if profile.env.is_empty() {
+        println!("  {}", none());
+    }
+    let mut keys: Vec<&String> = profile.env.keys().collect();
+    keys.sort();
+    for key in keys {
+        let shown = shown_value(key, &profile.env[key], reveal);
+        println!("  {}={shown}", key.yellow());
+    }
+
+    println!();
+    println!("{}", "Permissions:".bold());
+    let permissions = &profile.permissions;
+    println!(
+        "  Enabled: {}",
+        permissions
+            .enabled
+            .map_or_else(none, |enabled| enabled.to_string())
+    );
+    match &permissions.mcp {
+        Some(servers) if !servers.is_empty() => {
+            println!("  MCP servers:");
+            for server in servers {
+                let state = match server.enabled {
+                    Some(false) => "disabled".red(),
+                    _ => "enabled".green(),
+                };
+                println!("    {} ({state})", server.name);
+            }
+        }
+        _ => println!("  MCP servers: {}", none()),
+    }
+    match &permissions.command {
+        Some(commands) if !commands.is_empty() => {
+            println!("  Commands: {}", commands.join(", "));
+        }
+        _ => println!("  Commands: {}", none()),
+    }
+
+    println!();
+    println!("{}", "Plugins:".bold());
+    match &profile.enabled_plugins {
+        Some(plugins) if !plugins.is_empty() => {
+            let mut names: Vec<&String> = plugins.keys().collect();
+            names.sort();
+            for plugin in names {
+                let state = if plugins[plugin] {
+                    "enabled".green()
+                } else {
+                    "disabled".red()
+                };
+                println!("  {plugin} ({state})");
+            }
+        }
+        _ => println!("  {}", none()),
+    }
+}
@@ -0,0 +1,213 @@
+use crate::config::state::HISTORY_LIMIT;
+use crate::config::{Profile, ProfileSource, Storage, UsageRecord};
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Category key for profiles without one, as in `ccuse list --group-by-category`
+const UNCATEGORIZED: &str = "(uncategorized)";
+
+/// Resource group key for launches without one
+const NO_RESOURCE_GROUP: &str = "(none)";
+
+/// Counts over all stored profiles, as printed by `ccuse stats`.
+#[derive(Debug, Serialize)]
+struct ProfileStats {
+    total: usize,
+    /// `ccswitch`, `manual` or `none`
+    by_source: BTreeMap<&'static str, usize>,
+    by_category: BTreeMap<String, usize>,
+    /// Profiles setting `api_timeout_ms`
+    custom_timeout: usize,
+    /// Profiles with at least one plugin enabled
+    plugins_enabled: usize,
+    oldest: Option<DatedProfile>,
+    newest: Option<DatedProfile>,
+}
+
+#[derive(Debug, Serialize)]
+struct DatedProfile {
+    name: String,
+    created_at: DateTime<Utc>,
+}
+
+impl ProfileStats {
+    fn compute(profiles: &[Profile]) -> Self {
+        let mut by_source = BTreeMap::new();
+        let mut by_category = BTreeMap::new();
+        for profile in profiles {
+            let source = match profile.source {
+                Some(ProfileSource::CcSwitch) => "ccswitch",
+                Some(ProfileSource::Manual) => "manual",
+                None => "none",
+            };
+            *by_source.entry(source).or_insert(0) += 1;
+            let category = profile
+                .category
+                .clone()
+                .unwrap_or_else(|| UNCATEGORIZED.into());
+            *by_category.entry(category).or_insert(0) += 1;
+        }
+
+        let dated = |profile: &Profile| DatedProfile {
+            name: profile.name.clone(),
+            created_at: profile.created_at,
+        };
+
+        Self {
+            total: profiles.len(),
+            by_source,
+            by_category,
+            custom_timeout: profiles
+                .iter()
+                .filter(|p| p.api_timeout_ms.is_some())
+                .count(),
+            plugins_enabled: profiles
+                .iter()
+                .filter(|p| {
+                    p.enabled_plugins
+                        .as_ref()
+                        .is_some_and(|plugins| plugins.values().any(|enabled| *enabled))
+                })
+                .count(),
+            oldest: profiles.iter().min_by_key(|p| p.created_at).map(dated),
+            newest: profiles.iter().max_by_key(|p| p.created_at).map(dated),
+        }
+    }
+}
+
+/// Number of recorded launches per resource group.
+fn launches_by_resource_group(history: &[UsageRecord]) -> BTreeMap<String, usize> {
+    let mut by_group = BTreeMap::new();
+    for record in history {
+        let group = record
+            .resource_group
+            .clone()
+            .unwrap_or_else(|| NO_RESOURCE_GROUP.into());
+        *by_group.entry(group).or_insert(0) += 1;
+    }
+    by_group
+}
+
+/// Print an overview of the stored profiles, or of the recorded launches
+/// per resource group if `by_resource_group` is set.
+///
+/// # Errors
+///
+/// Returns an error if the profiles or the launch history cannot be loaded.
+pub fn show_stats(json: bool, by_resource_group: bool) -> Result<()> {
+    if by_resource_group {
+        return show_resource_group_stats(json);
+    }
+
+    let stats = ProfileStats::compute(&Storage::new()?.load_profiles()?);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    if stats.total == 0 {
+        println!("{}", "No profiles found. Run 'ccuse update' to sync from CC-Switch or 'ccuse add' to create one.".yellow());
+        return Ok(());
+    }
+
+    println!("{} {}", "Profiles:".bold(), stats.total);
+
+    println!("\n{}", "By source:".bold());
+    for (source, count) in &stats.by_source {
+        println!("  {source:<16} {count}");
+    }
+    println!("\n{}", "By category:".bold());
+    for (category, count) in &stats.by_category {
+        println!("  {category:<16} {count}");
+    }
+
+    println!();
+    println!("  Custom API timeout: {}", stats.custom_timeout);
+    println!("  Plugins enabled:    {}", stats.plugins_enabled);
+    for (label, dated) in [("Oldest:", &stats.oldest), ("Newest:", &stats.newest)] {
+        if let Some(dated) = dated {
+            println!(
+                "  {label:<19} {} ({})",
+                dated.name,
+                dated.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn show_resource_group_stats(json: bool) -> Result<()> {
+    let by_group = launches_by_resource_group(&Storage::new()?.load_history()?);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&by_group)?);
+        return Ok(());
+    }
+
+    if by_group.is_empty() {
+        println!(
+            "{}",
+            "No launches recorded yet. Run 'ccuse use <name>' to launch a profile.".yellow()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        format!("Launches by resource group (last {HISTORY_LIMIT} launches):").bold()
+    );
+    for (group, count) in &by_group {
+        println!("  {group:<16} {count}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launch(name: &str, group: Option<&str>) -> UsageRecord {
+        UsageRecord {
+            name: name.to_string(),
+            timestamp: Utc::now(),
+            resource_group: group.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn launches_are_counted_per_resource_group() {
+        let history = [
+            launch("work", Some("team-a")),
+            launch("home", None),
+            launch("work", Some("team-a")),
+            launch("ci", Some("team-b")),
+        ];
+
+        let by_group = launches_by_resource_group(&history);
+
+        assert_eq!(
+            by_group.into_iter().collect::<Vec<_>>(),
+            [
+                (NO_RESOURCE_GROUP.to_string(), 1),
+                ("team-a".to_string(), 2),
+                ("team-b".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn history_without_resource_groups_still_loads() {
+        let history: Vec<UsageRecord> =
+            serde_json::from_str(r#"[{"name":"old","timestamp":"2024-01-01T00:00:00Z"}]"#).unwrap();
+
+        let by_group = launches_by_resource_group(&history);
+
+        assert_eq!(by_group[NO_RESOURCE_GROUP], 1);
+    }
+}
@@ -1,29 +1,142 @@
-use crate::config::{Profile, ProfileSource, Storage};
+use crate::cli::commands::completions_cmd::install_static_completions;
+use crate::cli::UpdateArgs;
+use crate::config::validation::validate_profile_name;
+use crate::config::{DisplayNameStrategy, Profile, ProfileSource, Storage};
 use crate::db::CcSwitchDb;
-use crate::error::Result;
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::Entry;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+/// Fields that change on every sync and are left out of dry-run diffs.
+const IGNORED_FIELDS: [&str; 2] = ["created_at", "updated_at"];
+
+/// One field that differs between a stored profile and its CC-Switch version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileFieldChange {
+    /// Top-level profile field, or `env.<KEY>` for a single env var
+    pub field: String,
+    /// Stored value; `None` for an env var that does not exist yet
+    pub old: Option<Value>,
+    /// CC-Switch value; `None` for an env var that would be removed
+    pub new: Option<Value>,
+}
+
+/// Changes `update` would make to one stored profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileUpdate {
+    pub name: String,
+    pub changes: Vec<ProfileFieldChange>,
+}
+
+/// A CC-Switch provider `update` leaves out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedProvider {
+    /// Name of the provider in CC-Switch
+    pub name: String,
+    pub reason: String,
+}
+
+/// What `update --merge` did to the env of one CC-Switch profile.
+#[derive(Debug, Clone, Default)]
+struct EnvMerge {
+    name: String,
+    /// Keys whose CC-Switch value differs from the stored one
+    updated: Vec<String>,
+    /// Keys only CC-Switch has
+    added: Vec<String>,
+    /// Keys only the stored profile has, kept as they are
+    kept: Vec<String>,
+}
+
+/// Preview produced by `ccuse update --dry-run`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunReport {
+    pub timestamp: DateTime<Utc>,
+    /// CC-Switch profiles that do not exist locally yet
+    pub profiles_to_add: Vec<Profile>,
+    pub profiles_to_update: Vec<ProfileUpdate>,
+    pub profiles_unchanged: Vec<String>,
+    #[serde(default)]
+    pub providers_skipped: Vec<SkippedProvider>,
+}
+
+impl DryRunReport {
+    #[must_use]
+    pub fn has_changes(&self) -> bool {
+        !self.profiles_to_add.is_empty() || !self.profiles_to_update.is_empty()
+    }
+}
 
 /// Update profiles from CC-Switch database.
 ///
+/// A `sync_display_names` strategy is persisted as the new default; otherwise
+/// the one saved in the global config is used. With `output_on_update`, the
+/// static completion script is regenerated afterwards. `dry_run` only
+/// previews the changes and `apply_diff_file` applies a saved preview.
+/// With `merge`, env vars that only exist in a stored CC-Switch profile are
+/// kept instead of being dropped.
+///
 /// # Errors
 ///
 /// Returns an error if CC-Switch database cannot be accessed or profiles cannot be saved.
-pub fn update_profiles() -> Result<()> {
-    let storage = Storage::new()?;
+pub fn update_profiles(update_args: &UpdateArgs) -> Result<()> {
+    let storage = Storage::new_exclusive()?;
 
-    if !CcSwitchDb::exists() {
-        println!(
-            "{}",
-            "CC-Switch database not found. No profiles to update.".yellow()
-        );
+    if let Some(path) = &update_args.apply_diff_file {
+        apply_diff_file(&storage, path)?;
+        if update_args.output_on_update {
+            install_static_completions(None)?;
+        }
         return Ok(());
     }
 
-    let ccswitch = CcSwitchDb::new()?;
-    let new_profiles = ccswitch.get_profiles()?;
+    let mut config = storage.load_config()?;
+    if let Some(strategy) = update_args.sync_display_names {
+        if strategy != config.ccswitch_display_name_strategy && !update_args.dry_run {
+            config.ccswitch_display_name_strategy = strategy;
+            storage.save_config(&config)?;
+        }
+    }
+    let strategy = update_args
+        .sync_display_names
+        .unwrap_or(config.ccswitch_display_name_strategy);
 
-    if new_profiles.is_empty() {
-        println!("{}", "No profiles found in CC-Switch database.".yellow());
+    let Some((mut new_profiles, skipped)) = load_ccswitch_profiles(
+        strategy,
+        &update_args.app_type,
+        update_args.db_path.as_deref(),
+    )?
+    else {
+        return Ok(());
+    };
+    keep_local_fields(&storage, &mut new_profiles)?;
+    let merges = if update_args.merge {
+        merge_local_env(&storage, &mut new_profiles)?
+    } else {
+        Vec::new()
+    };
+
+    // Also gives the counts reported after a real sync
+    let mut report = build_dry_run_report(&storage, new_profiles.clone())?;
+    report.providers_skipped = skipped;
+
+    if update_args.dry_run {
+        print_dry_run_report(&report);
+        print_env_merges(&merges);
+
+        if let Some(path) = &update_args.output_diff_file {
+            fs::write(path, serde_json::to_string_pretty(&report)?)?;
+            println!("Diff written to {}", path.display().to_string().cyan());
+        }
+        if update_args.fail_on_changes && report.has_changes() {
+            std::process::exit(1);
+        }
         return Ok(());
     }
 
@@ -41,25 +154,14 @@ pub fn update_profiles() -> Result<()> {
     let mut updated_profiles = manual_profiles;
 
     for new_profile in new_profiles {
-        // Replace spaces with underscores in the name for easier input
-        let name_with_underscores = new_profile.name.replace(' ', "_");
-
         // Check if profile from same source exists, update or add
         if let Some(idx) = updated_profiles
             .iter()
-            .position(|p| p.name == new_profile.name || p.name == name_with_underscores)
+            .position(|p| p.name == new_profile.name)
         {
-            // Update existing - also replace spaces in name
-            let mut updated_profile = new_profile;
-            updated_profile.name = name_with_underscores.clone();
-            updated_profile.display_name = Some(name_with_underscores);
-            updated_profiles[idx] = updated_profile;
+            updated_profiles[idx] = new_profile;
         } else {
-            // Add new with underscores instead of spaces
-            let mut updated_profile = new_profile;
-            updated_profile.name = name_with_underscores.clone();
-            updated_profile.display_name = Some(name_with_underscores);
-            updated_profiles.push(updated_profile);
+            updated_profiles.push(new_profile);
         }
     }
 
@@ -68,11 +170,489 @@ pub fn update_profiles() -> Result<()> {
     println!(
         "{}",
         format!(
-            "Updated {} profiles from CC-Switch.",
-            updated_profiles.len()
+            "Synced CC-Switch: {} added, {} updated, {} unchanged, {} skipped.",
+            report.profiles_to_add.len(),
+            report.profiles_to_update.len(),
+            report.profiles_unchanged.len(),
+            report.providers_skipped.len()
         )
         .green()
     );
+    print_skipped_providers(&report.providers_skipped);
+    print_env_merges(&merges);
 
+    if update_args.output_on_update {
+        install_static_completions(None)?;
+    }
+
+    Ok(())
+}
+
+/// Keep the tags and launch args of stored profiles, which CC-Switch does not know about.
+fn keep_local_fields(storage: &Storage, new_profiles: &mut [Profile]) -> Result<()> {
+    for new_profile in new_profiles {
+        if let Some(stored) = storage.get_profile(&new_profile.name)? {
+            new_profile.add_tags(stored.tags);
+            new_profile.launch_args = stored.launch_args;
+        }
+    }
     Ok(())
 }
+
+/// Carry env vars that only exist locally over into the CC-Switch profiles.
+///
+/// Only profiles stored with a CC-Switch source are merged. Keys CC-Switch
+/// sets take its value; keys it does not know are kept.
+fn merge_local_env(storage: &Storage, new_profiles: &mut [Profile]) -> Result<Vec<EnvMerge>> {
+    let mut merges = Vec::new();
+
+    for new_profile in new_profiles {
+        let Some(stored) = storage.get_profile(&new_profile.name)? else {
+            continue;
+        };
+        if stored.source != Some(ProfileSource::CcSwitch) {
+            continue;
+        }
+
+        let mut merge = EnvMerge {
+            name: new_profile.name.clone(),
+            ..EnvMerge::default()
+        };
+        for (key, value) in &new_profile.env {
+            match stored.env.get(key) {
+                None => merge.added.push(key.clone()),
+                Some(old) if old != value => merge.updated.push(key.clone()),
+                Some(_) => {}
+            }
+        }
+        for (key, value) in stored.env {
+            if let Entry::Vacant(entry) = new_profile.env.entry(key) {
+                merge.kept.push(entry.key().clone());
+                entry.insert(value);
+            }
+        }
+
+        merge.updated.sort();
+        merge.added.sort();
+        merge.kept.sort();
+        merges.push(merge);
+    }
+
+    Ok(merges)
+}
+
+fn print_env_merges(merges: &[EnvMerge]) {
+    if merges.is_empty() {
+        return;
+    }
+    println!();
+    println!("{}", "Merged env:".bold());
+    for merge in merges {
+        println!("  {}", merge.name);
+        // Only keys are shown, env values are often secrets
+        for (marker, keys) in [
+            ("~".yellow(), &merge.updated),
+            ("+".green(), &merge.added),
+            ("=".dimmed(), &merge.kept),
+        ] {
+            for key in keys {
+                println!("      {marker} {key}");
+            }
+        }
+        if merge.updated.is_empty() && merge.added.is_empty() && merge.kept.is_empty() {
+            println!("      {}", "no env changes".dimmed());
+        }
+    }
+}
+
+/// Read the CC-Switch profiles of `app_type` with names normalized for the
+/// command line, along with the providers left out.
+///
+/// When several providers normalize to the same name, the oldest keeps it
+/// and the others are skipped, so the result does not depend on the order
+/// of the database rows. Returns `None`, after telling the user, when there
+/// is nothing to sync. A database path given explicitly must exist.
+fn load_ccswitch_profiles(
+    strategy: DisplayNameStrategy,
+    app_type: &str,
+    db_path: Option<&Path>,
+) -> Result<Option<(Vec<Profile>, Vec<SkippedProvider>)>> {
+    if CcSwitchDb::override_path(db_path).is_none() && !CcSwitchDb::exists(None) {
+        println!(
+            "{}",
+            "CC-Switch database not found. No profiles to update.".yellow()
+        );
+        return Ok(None);
+    }
+
+    let ccswitch = CcSwitchDb::open(db_path)?;
+    let read = ccswitch.get_profiles_for(app_type)?;
+    let new_profiles = read.profiles;
+    let mut skipped: Vec<SkippedProvider> = read
+        .skipped
+        .into_iter()
+        .map(|(name, reason)| SkippedProvider { name, reason })
+        .collect();
+
+    if new_profiles.is_empty() {
+        println!(
+            "{}",
+            format!("No {app_type} profiles found in CC-Switch database.").yellow()
+        );
+        print_skipped_providers(&skipped);
+        return Ok(None);
+    }
+
+    let mut profiles: Vec<Profile> = Vec::with_capacity(new_profiles.len());
+    // CC-Switch names of `profiles`, for collision messages
+    let mut original_names: Vec<String> = Vec::with_capacity(new_profiles.len());
+
+    for mut new_profile in new_profiles {
+        // Replace spaces with underscores in the name for easier input
+        let name_with_underscores = new_profile.name.replace(' ', "_");
+
+        // parse_provider_config keeps the original CC-Switch name as display_name
+        let original_name = new_profile
+            .display_name
+            .take()
+            .unwrap_or_else(|| new_profile.name.clone());
+
+        // A CC-Switch name must not escape the config directory either
+        if let Err(e) = validate_profile_name(&name_with_underscores) {
+            skipped.push(SkippedProvider {
+                name: original_name,
+                reason: e.to_string(),
+            });
+            continue;
+        }
+        if let Some(idx) = profiles
+            .iter()
+            .position(|p| p.name == name_with_underscores)
+        {
+            skipped.push(SkippedProvider {
+                reason: format!(
+                    "'{}' also becomes '{name_with_underscores}' and was added first",
+                    original_names[idx]
+                ),
+                name: original_name,
+            });
+            continue;
+        }
+
+        new_profile.display_name =
+            Some(strategy.display_name(&original_name, &name_with_underscores));
+        new_profile.name = name_with_underscores;
+        original_names.push(original_name);
+        profiles.push(new_profile);
+    }
+
+    Ok(Some((profiles, skipped)))
+}
+
+/// Compare the CC-Switch profiles with what is stored.
+fn build_dry_run_report(storage: &Storage, new_profiles: Vec<Profile>) -> Result<DryRunReport> {
+    let mut report = DryRunReport {
+        timestamp: Utc::now(),
+        profiles_to_add: Vec::new(),
+        profiles_to_update: Vec::new(),
+        profiles_unchanged: Vec::new(),
+        providers_skipped: Vec::new(),
+    };
+
+    for new_profile in new_profiles {
+        match storage.get_profile(&new_profile.name)? {
+            None => report.profiles_to_add.push(new_profile),
+            Some(stored) => {
+                let changes = diff_profiles(&stored, &new_profile)?;
+                if changes.is_empty() {
+                    report.profiles_unchanged.push(new_profile.name);
+                } else {
+                    report.profiles_to_update.push(ProfileUpdate {
+                        name: new_profile.name,
+                        changes,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Field-by-field differences from `old` to `new`, with env vars compared per key.
+///
+/// # Errors
+///
+/// Returns an error if a profile cannot be serialized.
+pub fn diff_profiles(old: &Profile, new: &Profile) -> Result<Vec<ProfileFieldChange>> {
+    let old = profile_fields(old)?;
+    let new = profile_fields(new)?;
+    let mut changes = Vec::new();
+
+    let fields: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for field in fields {
+        if IGNORED_FIELDS.contains(&field.as_str()) {
+            continue;
+        }
+        let (old_value, new_value) = (old.get(field), new.get(field));
+
+        if field == "env" {
+            let empty = serde_json::Map::new();
+            let old_env = old_value.and_then(Value::as_object).unwrap_or(&empty);
+            let new_env = new_value.and_then(Value::as_object).unwrap_or(&empty);
+            let keys: BTreeSet<&String> = old_env.keys().chain(new_env.keys()).collect();
+            for key in keys {
+                if old_env.get(key) != new_env.get(key) {
+                    changes.push(ProfileFieldChange {
+                        field: format!("env.{key}"),
+                        old: old_env.get(key).cloned(),
+                        new: new_env.get(key).cloned(),
+                    });
+                }
+            }
+        } else if old_value != new_value {
+            changes.push(ProfileFieldChange {
+                field: field.clone(),
+                old: old_value.cloned(),
+                new: new_value.cloned(),
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+fn profile_fields(profile: &Profile) -> Result<serde_json::Map<String, Value>> {
+    match serde_json::to_value(profile)? {
+        Value::Object(fields) => Ok(fields),
+        _ => Err(Error::ConfigError(
+            "Profile did not serialize to an object".into(),
+        )),
+    }
+}
+
+fn print_dry_run_report(report: &DryRunReport) {
+    println!("{}", "Dry run: no profiles were changed.".bold());
+    println!();
+
+    for profile in &report.profiles_to_add {
+        println!("  {} {} (new)", "+".green(), profile.name.green());
+    }
+    for update in &report.profiles_to_update {
+        println!("  {} {}", "~".yellow(), update.name.yellow());
+        for change in &update.changes {
+            // Env values are often secrets, so only their keys are shown
+            let detail = match (&change.old, &change.new) {
+                _ if change.field.starts_with("env.") => String::new(),
+                (Some(old), Some(new)) => format!(": {old} → {new}"),
+                _ => String::new(),
+            };
+            let marker = match (&change.old, &change.new) {
+                (None, _) => "+".green(),
+                (_, None) => "-".red(),
+                _ => "~".yellow(),
+            };
+            println!("      {marker} {}{detail}", change.field);
+        }
+    }
+    for name in &report.profiles_unchanged {
+        println!("  {} {name}", "=".dimmed());
+    }
+
+    println!();
+    println!(
+        "{} to add, {} to update, {} unchanged, {} skipped",
+        report.profiles_to_add.len(),
+        report.profiles_to_update.len(),
+        report.profiles_unchanged.len(),
+        report.providers_skipped.len()
+    );
+    print_skipped_providers(&report.providers_skipped);
+}
+
+fn print_skipped_providers(skipped: &[SkippedProvider]) {
+    if skipped.is_empty() {
+        return;
+    }
+    eprintln!("{} skipped CC-Switch providers:", "Warning:".yellow());
+    for provider in skipped {
+        eprintln!("  {}: {}", provider.name, provider.reason);
+    }
+}
+
+/// Apply the changes listed in a dry-run diff file.
+///
+/// Profiles to add are only created if they still do not exist, and a field
+/// is only changed if it still has the value recorded in the diff; anything
+/// else is reported and skipped.
+fn apply_diff_file(storage: &Storage, path: &Path) -> Result<()> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| Error::ConfigError(format!("Cannot read {}: {e}", path.display())))?;
+    let report: DryRunReport = serde_json::from_str(&content)
+        .map_err(|e| Error::ConfigError(format!("{} is not a diff file: {e}", path.display())))?;
+
+    let mut skipped = 0;
+
+    for profile in &report.profiles_to_add {
+        if storage.profile_exists_fast(&profile.name)? {
+            eprintln!(
+                "{} '{}' already exists, not adding it",
+                "Warning:".yellow(),
+                profile.name
+            );
+            skipped += 1;
+            continue;
+        }
+        storage.add_profile(profile.clone())?;
+        println!("  {} {}", "+".green(), profile.name);
+    }
+
+    for update in &report.profiles_to_update {
+        let Some(stored) = storage.get_profile(&update.name)? else {
+            eprintln!(
+                "{} '{}' no longer exists, skipping its changes",
+                "Warning:".yellow(),
+                update.name
+            );
+            skipped += update.changes.len();
+            continue;
+        };
+
+        let mut fields = profile_fields(&stored)?;
+        let mut applied = 0;
+        for change in &update.changes {
+            // A JSON null reads back as None, so treat both as "unset"
+            let current = field_value(&fields, &change.field).filter(|v| !v.is_null());
+            if current != change.old.as_ref().filter(|v| !v.is_null()) {
+                eprintln!(
+                    "{} {}.{} changed since the diff was written, skipping it",
+                    "Warning:".yellow(),
+                    update.name,
+                    change.field
+                );
+                skipped += 1;
+                continue;
+            }
+            set_field_value(&mut fields, &change.field, change.new.clone());
+            applied += 1;
+        }
+
+        if applied > 0 {
+            let profile: Profile = serde_json::from_value(Value::Object(fields))?;
+            storage.update_profile(profile)?;
+            println!("  {} {} ({applied} change(s))", "~".yellow(), update.name);
+        }
+    }
+
+    if skipped == 0 {
+        println!("{}", format!("✓ Applied {}", path.display()).green());
+    } else {
+        println!(
+            "{}",
+            format!(
+                "Applied {} with {skipped} change(s) skipped",
+                path.display()
+            )
+            .yellow()
+        );
+    }
+
+    Ok(())
+}
+
+fn field_value<'a>(fields: &'a serde_json::Map<String, Value>, field: &str) -> Option<&'a Value> {
+    match field.strip_prefix("env.") {
+        Some(key) => fields.get("env").and_then(|env| env.get(key)),
+        None => fields.get(field),
+    }
+}
+
+fn set_field_value(fields: &mut serde_json::Map<String, Value>, field: &str, value: Option<Value>) {
+    let Some(key) = field.strip_prefix("env.") else {
+        fields.insert(field.to_string(), value.unwrap_or(Value::Null));
+        return;
+    };
+
+    let env = fields
+        .entry("env")
+        .or_insert_with(|| Value::Object(serde_json::Map::new()));
+    if let Some(env) = env.as_object_mut() {
+        match value {
+            Some(value) => {
+                env.insert(key.to_string(), value);
+            }
+            None => {
+                env.remove(key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    /// A CC-Switch database whose provider names need and do not need normalizing.
+    fn fixture_db(dir: &Path) -> std::path::PathBuf {
+        let path = dir.join("cc-switch.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"CREATE TABLE providers (id text, app_type text, name text, settings_config text, created_at integer);
+               INSERT INTO providers VALUES
+                 ('p1', 'claude', 'Work Proxy', '{"env":{}}', 1700000000000),
+                 ('p2', 'claude', 'personal', '{"env":{}}', 1700000001000);"#,
+        )
+        .unwrap();
+        path
+    }
+
+    fn names(strategy: DisplayNameStrategy) -> Vec<(String, String)> {
+        let dir = tempfile::tempdir().unwrap();
+        let db = fixture_db(dir.path());
+        let (profiles, skipped) = load_ccswitch_profiles(strategy, "claude", Some(&db))
+            .unwrap()
+            .unwrap();
+        assert!(skipped.is_empty());
+        let mut names: Vec<_> = profiles
+            .into_iter()
+            .map(|p| (p.name, p.display_name.unwrap()))
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn pairs(expected: [(&str, &str); 2]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(name, display)| ((*name).to_string(), (*display).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn keep_original_displays_the_ccswitch_name() {
+        assert_eq!(
+            names(DisplayNameStrategy::KeepOriginal),
+            pairs([("Work_Proxy", "Work Proxy"), ("personal", "personal")])
+        );
+    }
+
+    #[test]
+    fn use_normalized_displays_the_profile_name() {
+        assert_eq!(
+            names(DisplayNameStrategy::UseNormalized),
+            pairs([("Work_Proxy", "Work_Proxy"), ("personal", "personal")])
+        );
+    }
+
+    #[test]
+    fn both_displays_original_and_normalized() {
+        assert_eq!(
+            names(DisplayNameStrategy::Both),
+            pairs([
+                ("Work_Proxy", "Work Proxy (Work_Proxy)"),
+                ("personal", "personal")
+            ])
+        );
+    }
+}
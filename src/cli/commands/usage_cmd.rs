@@ -1,29 +1,255 @@
+use crate::config::storage::write_atomic;
+use crate::config::Storage;
 use crate::error::{Error, Result};
-use std::process::Command;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
 
-pub fn run_ccusage(args: &[String]) -> Result<()> {
-    // First try bunx
-    let mut bunx_cmd = Command::new("bunx");
-    bunx_cmd.arg("ccusage@latest");
-    bunx_cmd.args(args);
+/// How long `usage --cache` reuses a cached result by default
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 3600;
 
-    if bunx_cmd.status()?.success() {
+/// Environment variable pinning the ccusage version bunx/npx run
+pub const CCUSAGE_VERSION_ENV: &str = "CCUSE_CCUSAGE_VERSION";
+
+/// ccusage version bunx/npx run when none is pinned
+const DEFAULT_CCUSAGE_VERSION: &str = "latest";
+
+/// A cached ccusage run, stored as `cache/ccusage-<hash>.json` keyed by the
+/// ccusage version and normalized arguments.
+#[derive(Serialize, Deserialize)]
+struct CachedUsage {
+    args: Vec<String>,
+    created_at: DateTime<Utc>,
+    stdout: String,
+}
+
+/// How `run_ccusage` uses the ccusage cache.
+#[derive(Debug, Clone, Copy)]
+pub enum UsageCache {
+    /// Always run ccusage, without touching the cache
+    Off,
+    /// Reuse a cached result younger than the TTL, otherwise run and cache
+    ReadWrite(Duration),
+    /// Only print a cached result, of any age
+    Offline,
+}
+
+/// Which ccusage `run_ccusage` runs.
+#[derive(Debug, Clone)]
+pub struct CcusageRunner {
+    /// Version bunx/npx run, e.g. `latest` or `15.2.0`
+    pub version: String,
+    /// Run a `ccusage` found in PATH instead of bunx/npx
+    pub prefer_local: bool,
+}
+
+impl CcusageRunner {
+    /// Runner for `version`, falling back to `CCUSE_CCUSAGE_VERSION` and then
+    /// `latest`.
+    ///
+    /// A local install is only preferred when no specific version is asked
+    /// for, since its version cannot be chosen.
+    #[must_use]
+    pub fn new(version: Option<String>, no_local: bool) -> Self {
+        let version = version
+            .or_else(|| std::env::var(CCUSAGE_VERSION_ENV).ok())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| DEFAULT_CCUSAGE_VERSION.to_string());
+        let prefer_local = !no_local && version == DEFAULT_CCUSAGE_VERSION;
+        Self {
+            version,
+            prefer_local,
+        }
+    }
+
+    /// Run ccusage: the local install if preferred and found, otherwise bunx,
+    /// falling back to npx. stdout is captured when `capture` is set.
+    fn run(&self, args: &[String], capture: bool) -> Result<Output> {
+        let stdout = || {
+            if capture {
+                Stdio::piped()
+            } else {
+                Stdio::inherit()
+            }
+        };
+        let package = format!("ccusage@{}", self.version);
+
+        if self.prefer_local {
+            if let Ok(local) = which::which("ccusage") {
+                return Ok(Command::new(local)
+                    .args(args)
+                    .stdin(Stdio::inherit())
+                    .stdout(stdout())
+                    .stderr(Stdio::inherit())
+                    .output()?);
+            }
+        }
+
+        // First try bunx
+        let mut bunx_cmd = Command::new("bunx");
+        bunx_cmd.arg(&package);
+        bunx_cmd.args(args);
+        bunx_cmd
+            .stdin(Stdio::inherit())
+            .stdout(stdout())
+            .stderr(Stdio::inherit());
+
+        if let Ok(output) = bunx_cmd.output() {
+            if output.status.success() {
+                return Ok(output);
+            }
+        }
+
+        // Fallback to npx
+        let mut npx_cmd = Command::new("npx");
+        npx_cmd.arg("-y");
+        npx_cmd.arg(&package);
+        npx_cmd.args(args);
+        npx_cmd
+            .stdin(Stdio::inherit())
+            .stdout(stdout())
+            .stderr(Stdio::inherit());
+
+        Ok(npx_cmd.output()?)
+    }
+}
+
+/// Run ccusage with `runner`.
+///
+/// # Errors
+///
+/// Returns an error if ccusage fails, or with `UsageCache::Offline` if there
+/// is no cached result for these arguments.
+pub fn run_ccusage(args: &[String], cache: UsageCache, runner: &CcusageRunner) -> Result<()> {
+    if matches!(cache, UsageCache::Off) {
+        let status = runner.run(args, false)?.status;
+        if !status.success() {
+            return Err(exit_error(status.code()));
+        }
         return Ok(());
     }
 
-    // Fallback to npx
-    let mut npx_cmd = Command::new("npx");
-    npx_cmd.arg("-y");
-    npx_cmd.arg("ccusage@latest");
-    npx_cmd.args(args);
-
-    let status = npx_cmd.status()?;
-    if !status.success() {
-        return Err(Error::CcusageError(format!(
-            "ccusage failed with exit code: {}",
-            status.code().unwrap_or(1)
-        )));
+    let path = cache_path(&Storage::new()?, &runner.version, &normalize_args(args));
+    let cached = read_cache(&path);
+
+    match (cache, cached) {
+        (UsageCache::Offline, Some(cached)) => {
+            print_cached(&cached)?;
+            eprintln!(
+                "{}",
+                format!(
+                    "(cached ccusage output from {})",
+                    cached.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+                )
+                .dimmed()
+            );
+            Ok(())
+        }
+        (UsageCache::Offline, None) => Err(Error::CcusageError(
+            "no cached result for these arguments; run 'ccuse usage --cache' while online first"
+                .into(),
+        )),
+        (UsageCache::ReadWrite(ttl), Some(cached)) if is_fresh(&cached, ttl) => {
+            print_cached(&cached)
+        }
+        _ => {
+            let output = runner.run(args, true)?;
+            if !output.status.success() {
+                return Err(exit_error(output.status.code()));
+            }
+            let cached = CachedUsage {
+                args: args.to_vec(),
+                created_at: Utc::now(),
+                stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            };
+            // A cache that cannot be written should not hide the report
+            if let Err(e) = write_cache(&path, &cached) {
+                eprintln!(
+                    "{} could not cache ccusage output: {e}",
+                    "Warning:".yellow()
+                );
+            }
+            print_cached(&cached)
+        }
+    }
+}
+
+fn exit_error(code: Option<i32>) -> Error {
+    Error::CcusageError(format!(
+        "ccusage failed with exit code: {}",
+        code.unwrap_or(1)
+    ))
+}
+
+/// Cache key form of `args`: `--flag=value` is split into `--flag value` and
+/// options (with the values that follow them) are sorted after the leading
+/// positional arguments, so equivalent invocations share a cache entry.
+fn normalize_args(args: &[String]) -> Vec<String> {
+    let mut positionals = Vec::new();
+    let mut options: Vec<Vec<String>> = Vec::new();
+
+    for arg in args {
+        let parts = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                vec![flag.to_string(), value.to_string()]
+            }
+            _ => vec![arg.clone()],
+        };
+        for part in parts {
+            if part.starts_with('-') {
+                options.push(vec![part]);
+            } else if let Some(option) = options.last_mut() {
+                option.push(part);
+            } else {
+                positionals.push(part);
+            }
+        }
+    }
+
+    options.sort();
+    positionals
+        .into_iter()
+        .chain(options.into_iter().flatten())
+        .collect()
+}
+
+/// Cache file for `args`; pinned versions get their own entries.
+fn cache_path(storage: &Storage, version: &str, args: &[String]) -> PathBuf {
+    let key = std::iter::once(version)
+        .chain(args.iter().map(String::as_str))
+        .collect::<Vec<_>>();
+    let hash = format!("{:x}", Sha256::digest(key.join("\0").as_bytes()));
+    storage
+        .cache_dir()
+        .join(format!("ccusage-{}.json", &hash[..16]))
+}
+
+/// The cached result at `path`; unreadable entries count as missing.
+fn read_cache(path: &Path) -> Option<CachedUsage> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_cache(path: &Path, cached: &CachedUsage) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
     }
+    write_atomic(path, serde_json::to_string_pretty(cached)?)
+}
+
+fn is_fresh(cached: &CachedUsage, ttl: Duration) -> bool {
+    (Utc::now() - cached.created_at)
+        .to_std()
+        .is_ok_and(|age| age < ttl)
+}
 
+fn print_cached(cached: &CachedUsage) -> Result<()> {
+    io::stdout().lock().write_all(cached.stdout.as_bytes())?;
     Ok(())
 }
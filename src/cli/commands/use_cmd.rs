@@ -1,24 +1,598 @@
-use crate::claude::Launcher;
-use crate::config::Storage;
-use crate::error::Result;
+use crate::claude::env_trace::{EnvResolutionTrace, Phase};
+use crate::claude::launcher::CLEAN_ENV_KEEP;
+use crate::claude::limits;
+use crate::claude::overrides::{
+    apply_experimental_flags, apply_model_override, apply_port_forward, inject_resource_group,
+    validate_log_level, PortForwardMapping, DEFAULT_RESOURCE_GROUP_ENV_KEY,
+};
+use crate::claude::ports::ServiceWait;
+use crate::claude::process::{is_success_exit_code, TimeoutPolicy, DEFAULT_TIMEOUT_GRACE};
+use crate::claude::pty::TypedPrompt;
+use crate::claude::redaction::{read_patterns_file, LogRedactor};
+use crate::claude::timing::StartupTimer;
+use crate::claude::{LaunchOptions, Launcher};
+use crate::cli::commands::default_cmd::resolve_profile_name;
+use crate::cli::env_overrides::collect_env_overrides;
+use crate::cli::UseArgs;
+use crate::config::template::{expand_templates, substitute_from_parent};
+use crate::config::validation::validate_profile_env;
+use crate::config::Signal;
+use crate::config::{secrets, Profile, Storage};
+use crate::error::{Error, Result};
+use crate::proxy::{api_log_path, new_session_id, ApiProxy};
+use crate::telemetry;
+use colored::Colorize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::time::Duration;
 
-/// Use a profile to launch Claude Code.
+/// Endpoint Claude Code uses when a profile does not set `ANTHROPIC_BASE_URL`.
+const DEFAULT_API_BASE_URL: &str = "https://api.anthropic.com";
+
+/// Use a profile to launch Claude Code, falling back to the default profile
+/// when `use_args.name` is not given.
 ///
 /// # Errors
 ///
 /// Returns an error if profile does not exist or Claude Code fails to launch.
-pub fn use_profile(name: &str, bypass: bool, args: &[String]) -> Result<()> {
+pub fn use_profile(use_args: &UseArgs, verbose: bool) -> Result<()> {
+    let tags = use_args
+        .metric_tags
+        .iter()
+        .map(|tag| telemetry::parse_metric_tag(tag))
+        .collect::<Result<Vec<_>>>()?;
+
+    let name = resolve_profile_name(&Storage::new()?, use_args.name.as_deref())?;
+
+    let span = telemetry::session_span(&name, &tags);
+    let result = span.in_scope(|| run_session(use_args, &name, verbose));
+    if let Ok(Some(code)) = &result {
+        span.record("exit_code", *code);
+    }
+    // End the span before exporting so it is included in the flush
+    drop(span);
+    telemetry::shutdown();
+
+    // Background and new-terminal sessions have no exit code to check
+    let Some(code) = result? else {
+        return Ok(());
+    };
+
+    if use_args.capture_exit_code {
+        std::process::exit(code);
+    }
+
+    if !is_success_exit_code(code, &use_args.success_exit_codes) {
+        return Err(Error::ClaudeExitError(code));
+    }
+
+    Ok(())
+}
+
+/// Resolve the profile and run Claude, returning its exit code when ccuse
+/// waited for it.
+fn run_session(use_args: &UseArgs, name: &str, verbose: bool) -> Result<Option<i32>> {
+    if use_args.measure_startup || use_args.measure_startup_json {
+        StartupTimer::enable(use_args.measure_startup_json);
+    }
+
     let storage = Storage::new()?;
+    let config = storage.load_config()?;
 
-    let profile = storage
-        .get_profile(name)?
-        .ok_or_else(|| crate::error::Error::ProfileNotFound(name.into()))?;
+    // Parents given with --inherit-parent-profile come first, left to right
+    let chain: Vec<&str> = use_args
+        .inherit_parent_profile
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(name))
+        .collect();
+    let mut profile = compose_profiles(&storage, &chain)?;
+    StartupTimer::checkpoint("profile loaded");
+
+    let mut trace = EnvResolutionTrace::default();
+    trace_inheritance(&storage, &chain, &profile, &mut trace)?;
+
+    // Resolve ${KEY} references between the profile's own env vars first,
+    // so --env-substitute only sees what the profile leaves undefined
+    let unexpanded = profile.env.clone();
+    profile.env = expand_templates(&profile.env);
+
+    if use_args.env_substitute {
+        let parent_env: HashMap<String, String> = env::vars().collect();
+        profile.env = substitute_from_parent(&profile.env, &parent_env, use_args.warn_undefined);
+    }
+    trace.record_changes(&Phase::TemplateExpansion, &unexpanded, &profile.env);
+
+    // A composed profile gets its own settings file so nothing stored is
+    // modified; the launcher brings its env up to date with the options below
+    let settings_json = if chain.len() > 1 || profile.extends.is_some() {
+        Some(serde_json::to_string_pretty(&profile)?)
+    } else {
+        None
+    };
+
+    let before_options = profile.env.clone();
 
-    println!(
-        "Using profile: {}",
-        profile.display_name.as_ref().unwrap_or(&profile.name)
+    if let Some(mapping) = use_args
+        .port_forward
+        .as_deref()
+        .or(profile.port_forward.as_deref())
+    {
+        apply_port_forward(&mut profile.env, &mapping.parse::<PortForwardMapping>()?)?;
+    }
+
+    // CLI flags add to the profile's; --env can still override the result
+    let experimental_flags: Vec<String> = profile
+        .experimental_flags
+        .iter()
+        .chain(&use_args.experimental_flags)
+        .cloned()
+        .collect();
+    apply_experimental_flags(
+        &mut profile.env,
+        &experimental_flags,
+        use_args.no_experimental_flags,
+    )?;
+
+    if let Some(model) = &use_args.model_override {
+        apply_model_override(&mut profile.env, model);
+    }
+    trace.record_changes(&Phase::CliOverride, &before_options, &profile.env);
+
+    let mut env_overrides = collect_env_overrides(
+        &use_args.env,
+        use_args.env_from_json.as_deref(),
+        use_args.env_from_json_file.as_deref(),
+    )?;
+
+    // Keep the proxy alive until Claude exits
+    let _api_proxy = if use_args.capture_api_calls {
+        let upstream = env_overrides
+            .get("ANTHROPIC_BASE_URL")
+            .or_else(|| profile.env.get("ANTHROPIC_BASE_URL"))
+            .map_or(DEFAULT_API_BASE_URL, String::as_str)
+            .to_string();
+        let session_id = new_session_id();
+        let log_path = api_log_path(storage.config_dir(), &session_id);
+        let proxy = ApiProxy::start(&upstream, log_path.clone())?;
+        env_overrides.insert("ANTHROPIC_BASE_URL".to_string(), proxy.url().to_string());
+        eprintln!(
+            "Capturing API calls to {} (view with 'ccuse api-log {session_id}')",
+            log_path.display()
+        );
+        Some(proxy)
+    } else {
+        None
+    };
+
+    let resource_group = use_args
+        .resource_group
+        .clone()
+        .or_else(|| profile.resource_group.clone());
+    inject_resource_group(
+        &mut env_overrides,
+        resource_group.as_deref(),
+        config
+            .resource_group_env_key
+            .as_deref()
+            .unwrap_or(DEFAULT_RESOURCE_GROUP_ENV_KEY),
     );
-    Launcher::launch(&profile, bypass, args)?;
+
+    // CLI flags win over the profile's stored timeout behaviour
+    let timeout = use_args.timeout.map(|secs| TimeoutPolicy {
+        timeout: Duration::from_secs(secs),
+        action: use_args
+            .timeout_action
+            .or(profile.timeout_action)
+            .unwrap_or_default(),
+        signal: use_args
+            .timeout_signal
+            .or(profile.timeout_signal)
+            .unwrap_or(Signal::Term),
+        grace: use_args
+            .timeout_grace
+            .map_or(DEFAULT_TIMEOUT_GRACE, Duration::from_secs),
+    });
+
+    StartupTimer::checkpoint("env resolved");
+
+    let prompt_text = match (&use_args.stdin_prompt, &use_args.stdin_prompt_file) {
+        (Some(text), _) => Some(text.clone()),
+        (None, Some(path)) => Some(fs::read_to_string(path)?),
+        (None, None) => None,
+    };
+    let typed_prompt = prompt_text
+        .map(|text| TypedPrompt::new(text, use_args.typing_speed, use_args.typing_delay));
+
+    let log_level = use_args
+        .log_level
+        .clone()
+        .or_else(|| profile.log_level.clone());
+    if let Some(level) = &log_level {
+        validate_log_level(level)?;
+    }
+
+    let tee_output = use_args
+        .tee_output
+        .clone()
+        .or_else(|| profile.tee_output_file.clone());
+    // The tee file is always redacted; the flags only add patterns
+    let log_redactor = if tee_output.is_some() {
+        let mut patterns = profile.log_redact_patterns.clone();
+        patterns.extend(use_args.log_redact_patterns.iter().cloned());
+        if let Some(path) = &use_args.log_redact_patterns_file {
+            patterns.extend(read_patterns_file(path)?);
+        }
+        Some(LogRedactor::new(&patterns)?)
+    } else {
+        None
+    };
+
+    // Only a directly spawned Claude can be limited; the CLI flag conflicts
+    // with the other modes, so only a profile default can end up here
+    let limitable = !(use_args.background
+        || use_args.new_terminal
+        || tee_output.is_some()
+        || typed_prompt.is_some());
+    let mut cpu_limit = use_args.cpu_limit.or(profile.cpu_limit_percent);
+    if let Some(percent) = cpu_limit {
+        if !limitable {
+            eprintln!(
+                "{} cpu_limit_percent is ignored with --background, --new-terminal, --tee-output and --stdin-prompt",
+                "Warning:".yellow()
+            );
+            cpu_limit = None;
+        } else if limits::is_priority_only() {
+            eprintln!(
+                "{} a CPU limit of {percent}% only lowers Claude's priority on this platform, it is not a hard cap",
+                "Warning:".yellow()
+            );
+        }
+    }
+
+    let mut wait_ports = profile.wait_for_ports.clone();
+    for port in &use_args.wait_for_port {
+        if !wait_ports.contains(port) {
+            wait_ports.push(*port);
+        }
+    }
+    let wait_for =
+        (!wait_ports.is_empty() || !use_args.wait_for_url.is_empty()).then(|| ServiceWait {
+            ports: wait_ports,
+            urls: use_args.wait_for_url.clone(),
+            timeout: Duration::from_secs(use_args.port_timeout),
+            fail_if_unavailable: use_args.fail_if_port_unavailable,
+        });
+
+    let opts = LaunchOptions {
+        bypass: use_args.bypass,
+        detach: use_args.background,
+        dry_run: use_args.dry_run,
+        notify: use_args.notify || config.notify_on_exit,
+        timeout,
+        env_overrides,
+        clean_env: use_args.clean_env.then(|| {
+            let mut keep: Vec<String> = CLEAN_ENV_KEEP.map(String::from).into();
+            keep.extend(use_args.keep_env.iter().cloned());
+            keep
+        }),
+        log_level,
+        trace: use_args.trace,
+        debug_mcp: use_args.debug_mcp,
+        keep_existing_log_level: use_args.no_override_existing_log_level,
+        tunnel_cmd: use_args.tunnel_cmd.clone(),
+        tee_output,
+        tee_append: use_args.tee_append,
+        log_redactor,
+        wait_for,
+        cpu_limit,
+        typed_prompt,
+        settings_path: None,
+        settings_json,
+        claude_version: use_args
+            .claude_version
+            .clone()
+            .or_else(|| profile.claude_version.clone()),
+        strict_version: use_args.strict_version,
+        claude_path: use_args.claude_path.clone(),
+        args: use_args.args.clone(),
+    };
+
+    if use_args.env_diff_report || use_args.env_diff_report_only {
+        let (_, launch_trace) = Launcher::resolve_env(&profile, &opts);
+        trace.extend(launch_trace);
+        trace.print_report();
+        if use_args.env_diff_report_only {
+            return Ok(None);
+        }
+    }
+
+    if use_args.print_settings {
+        Launcher::print_settings(&profile, &opts, verbose)?;
+    }
+
+    if use_args.dry_run {
+        Launcher::print_launch_plan(&profile, &opts, verbose)?;
+        return Ok(None);
+    }
+
+    if chain.len() > 1 {
+        println!("Using: {} (merged)", chain.join(" → "));
+    } else {
+        println!(
+            "Using profile: {}",
+            profile.display_name.as_ref().unwrap_or(&profile.name)
+        );
+    }
+
+    // Release the shared lock first: recording the launch takes it exclusively
+    drop(storage);
+    match Storage::new_exclusive() {
+        Ok(storage) => record_launch(
+            &storage,
+            &profile,
+            name,
+            resource_group.as_deref(),
+            use_args.set_default,
+        ),
+        Err(e) => eprintln!("{} launch not recorded: {e}", "Warning:".yellow()),
+    }
+    // The storage lock is released here, so other commands can run during the session
+
+    if use_args.background {
+        let pid = Launcher::launch_background(&profile, &opts)?;
+        println!("Claude started in the background (PID {pid})");
+        return Ok(None);
+    }
+    if use_args.new_terminal {
+        Launcher::launch_in_new_terminal(&profile, &opts)?;
+        return Ok(None);
+    }
+
+    let status = Launcher::launch(&profile, &opts)?;
+
+    // A signal-terminated Claude has no exit code; report it as a generic failure
+    Ok(Some(status.code().unwrap_or(1)))
+}
+
+/// Record the launch of `name` as the last used profile and in the history,
+/// tagged with its resource group, and make it the default if `set_default`
+/// is set.
+///
+/// These read-modify-write state.json and history.json, so `storage` must
+/// hold the lock exclusively or concurrent launches lose updates. Failures
+/// only warn, since the session can start anyway.
+fn record_launch(
+    storage: &Storage,
+    profile: &Profile,
+    name: &str,
+    resource_group: Option<&str>,
+    set_default: bool,
+) {
+    if let Err(e) = storage.save_last_used(name) {
+        eprintln!(
+            "{} could not record last used profile: {e}",
+            "Warning:".yellow()
+        );
+    }
+    if let Err(e) = storage.record_usage(name, resource_group) {
+        eprintln!(
+            "{} could not record the launch in the history: {e}",
+            "Warning:".yellow()
+        );
+    }
+    if set_default {
+        // Set before launching, so a failed session still keeps it
+        match validate_profile_env(profile).and_then(|()| storage.save_default_profile(Some(name)))
+        {
+            Ok(()) => println!("{} '{name}' is now the default profile", "→".cyan()),
+            Err(e) => eprintln!("{} default profile not changed: {e}", "Warning:".yellow()),
+        }
+    }
+}
+
+/// Record the env vars a composed profile takes from its parents rather than
+/// from the profile being used (the last name in `chain`).
+fn trace_inheritance(
+    storage: &Storage,
+    chain: &[&str],
+    composed: &Profile,
+    trace: &mut EnvResolutionTrace,
+) -> Result<()> {
+    let Some((name, parents)) = chain.split_last() else {
+        return Ok(());
+    };
+    let mut own = storage
+        .get_profile(name)?
+        .ok_or_else(|| Error::ProfileNotFound((*name).into()))?;
+    // Compare against the env as compose_profiles sees it
+    if !parents.is_empty() {
+        secrets::qualify_sentinels(name, &mut own.env);
+    }
+    let empty = HashMap::new();
+    trace.record_changes(&Phase::BaseEnv, &empty, &own.env);
+
+    let mut parent_envs = Vec::with_capacity(parents.len());
+    for parent in parents {
+        let mut profile = storage
+            .get_profile(parent)?
+            .ok_or_else(|| Error::ProfileNotFound((*parent).into()))?;
+        secrets::qualify_sentinels(parent, &mut profile.env);
+        parent_envs.push((*parent, profile.env));
+    }
+
+    let mut keys: Vec<&String> = composed.env.keys().collect();
+    keys.sort();
+    for key in keys {
+        let value = &composed.env[key];
+        if own.env.get(key) == Some(value) {
+            continue;
+        }
+        // Later parents override earlier ones, so the last match is the source
+        let Some((parent, _)) = parent_envs
+            .iter()
+            .rev()
+            .find(|(_, env)| env.get(key) == Some(value))
+        else {
+            continue;
+        };
+        trace.record(
+            Phase::InheritedFrom((*parent).to_string()),
+            key,
+            own.env.get(key).map(String::as_str),
+            value,
+        );
+    }
 
     Ok(())
 }
+
+/// Load `names` in order, each with its `extends` chain flattened, and merge
+/// each onto the previous one, so later profiles override earlier ones.
+///
+/// # Errors
+///
+/// Returns `Error::ProfileNotFound` for the first profile in the chain that does not exist.
+pub fn compose_profiles(storage: &Storage, names: &[&str]) -> Result<Profile> {
+    let mut composed: Option<Profile> = None;
+
+    for name in names {
+        let mut profile = storage
+            .resolve_profile(name)?
+            .ok_or_else(|| Error::ProfileNotFound((*name).into()))?;
+        // The merged profile launches under the last name; keep keyring
+        // references pointing at the profile that owns each secret
+        if names.len() > 1 {
+            secrets::qualify_sentinels(name, &mut profile.env);
+        }
+        composed = Some(match composed {
+            Some(parent) => parent.merge_with(&profile),
+            None => profile,
+        });
+    }
+
+    composed.ok_or_else(|| Error::ConfigError("No profile to compose".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(storage: &Storage, name: &str, env: &[(&str, &str)]) {
+        storage
+            .add_profile(Profile {
+                name: name.to_string(),
+                env: env
+                    .iter()
+                    .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                    .collect(),
+                ..Profile::default()
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn composed_env_shows_parents_overridden_by_children() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        add(
+            &storage,
+            "base",
+            &[
+                ("ANTHROPIC_BASE_URL", "https://base.example.com"),
+                ("ANTHROPIC_MODEL", "sonnet"),
+                ("API_TIMEOUT_MS", "1000"),
+            ],
+        );
+        add(
+            &storage,
+            "team",
+            &[("ANTHROPIC_MODEL", "opus"), ("API_TIMEOUT_MS", "2000")],
+        );
+        add(&storage, "mine", &[("API_TIMEOUT_MS", "3000")]);
+        let stored = fs::read_to_string(dir.path().join("base/settings.json")).unwrap();
+
+        let composed = compose_profiles(&storage, &["base", "team", "mine"]).unwrap();
+
+        assert_eq!(composed.name, "mine");
+        assert_eq!(
+            composed.env["ANTHROPIC_BASE_URL"],
+            "https://base.example.com"
+        );
+        assert_eq!(composed.env["ANTHROPIC_MODEL"], "opus");
+        assert_eq!(composed.env["API_TIMEOUT_MS"], "3000");
+        // Composing is for the session only
+        assert_eq!(
+            fs::read_to_string(dir.path().join("base/settings.json")).unwrap(),
+            stored
+        );
+        assert_eq!(storage.get_profile("mine").unwrap().unwrap().env.len(), 1);
+    }
+
+    #[test]
+    fn composing_a_missing_profile_is_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        add(&storage, "mine", &[]);
+
+        let result = compose_profiles(&storage, &["missing", "mine"]);
+
+        assert!(matches!(result, Err(Error::ProfileNotFound(name)) if name == "missing"));
+    }
+
+    #[test]
+    fn trace_inheritance_names_the_parent_of_each_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        add(
+            &storage,
+            "base",
+            &[
+                ("ANTHROPIC_BASE_URL", "https://base.example.com"),
+                ("ANTHROPIC_MODEL", "sonnet"),
+            ],
+        );
+        add(&storage, "team", &[("ANTHROPIC_MODEL", "opus")]);
+        add(
+            &storage,
+            "mine",
+            &[("ANTHROPIC_BASE_URL", "https://mine.example.com")],
+        );
+        let chain = ["base", "team", "mine"];
+        let composed = compose_profiles(&storage, &chain).unwrap();
+
+        let mut trace = EnvResolutionTrace::default();
+        trace_inheritance(&storage, &chain, &composed, &mut trace).unwrap();
+
+        let steps: Vec<_> = trace
+            .steps
+            .iter()
+            .map(|s| {
+                (
+                    s.phase.clone(),
+                    s.key.as_str(),
+                    s.before.as_deref(),
+                    s.after.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (
+                    Phase::BaseEnv,
+                    "ANTHROPIC_BASE_URL",
+                    None,
+                    "https://mine.example.com"
+                ),
+                (
+                    Phase::InheritedFrom("team".into()),
+                    "ANTHROPIC_MODEL",
+                    None,
+                    "opus"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use crate::config::validation::validate_profile_env;
+use crate::config::Storage;
+use crate::error::{Error, Result};
+use colored::Colorize;
+
+/// Check one profile, or every profile, and print what is wrong with each.
+///
+/// Exits with code 1 when any profile is invalid.
+///
+/// # Errors
+///
+/// Returns `Error::ProfileNotFound` if `name` is given and does not exist, or
+/// an error if the profiles cannot be listed.
+pub fn validate_profiles(name: Option<&str>) -> Result<()> {
+    let storage = Storage::new()?;
+
+    let names = match name {
+        Some(name) => {
+            if !storage.profile_exists_fast(name)? {
+                return Err(Error::ProfileNotFound(name.into()));
+            }
+            vec![name.to_string()]
+        }
+        None => {
+            let mut names = storage.profile_dir_names()?;
+            names.retain(|name| storage.settings_file_exists(name));
+            names.sort();
+            names
+        }
+    };
+
+    if names.is_empty() {
+        println!("{}", "No profiles to validate.".yellow());
+        return Ok(());
+    }
+
+    let mut invalid = 0;
+    for name in &names {
+        let problems = profile_problems(&storage, name);
+        if problems.is_empty() {
+            println!("  {} {name}", "✓".green());
+            continue;
+        }
+        invalid += 1;
+        println!("  {} {name}", "✗".red());
+        for problem in problems {
+            println!("      - {problem}");
+        }
+    }
+
+    println!();
+    if invalid > 0 {
+        println!(
+            "{}",
+            format!("{invalid} of {} profile(s) invalid.", names.len()).red()
+        );
+        std::process::exit(1);
+    }
+    println!("{}", format!("✓ {} profile(s) valid.", names.len()).green());
+    Ok(())
+}
+
+/// Everything wrong with the stored profile `name`; empty when it is valid.
+///
+/// A profile whose settings do not parse reports only that.
+pub fn profile_problems(storage: &Storage, name: &str) -> Vec<String> {
+    let profile = match storage.get_profile(name) {
+        Ok(Some(profile)) => profile,
+        Ok(None) => return vec!["settings file not found".into()],
+        Err(e) => return vec![format!("settings do not parse: {e}")],
+    };
+
+    let mut problems = Vec::new();
+    if profile.name != name {
+        problems.push(format!(
+            "the name field is '{}' but the directory is '{name}'",
+            profile.name
+        ));
+    }
+    if profile.env.is_empty() && profile.extends.is_none() {
+        problems.push("env is empty".into());
+    }
+    if let Err(e) = validate_profile_env(&profile) {
+        problems.push(e.to_string());
+    }
+    if profile.extends.is_some() {
+        if let Err(e) = storage.resolve_profile(name) {
+            problems.push(e.to_string());
+        }
+    }
+    problems
+}
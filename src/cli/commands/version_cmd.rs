@@ -0,0 +1,59 @@
+use crate::config::Storage;
+use crate::error::Result;
+use crate::update;
+use chrono::DateTime;
+use colored::Colorize;
+use serde_json::json;
+
+/// Print version and build information, optionally checking GitHub for a newer release.
+///
+/// # Errors
+///
+/// Returns an error if the config directory cannot be resolved or the update check fails.
+pub fn show_version(check_update: bool, json: bool) -> Result<()> {
+    let current = update::current_version()?;
+    let target = env!("CCUSE_BUILD_TARGET");
+    let built_at = env!("CCUSE_BUILD_TIMESTAMP")
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0));
+    let config_dir = Storage::new()?.config_dir().to_path_buf();
+
+    let latest = if check_update {
+        Some(update::get_latest_github_version()?)
+    } else {
+        None
+    };
+
+    if json {
+        let mut output = json!({
+            "version": current.to_string(),
+            "target": target,
+            "built_at": built_at.map(|t| t.to_rfc3339()),
+            "config_dir": config_dir,
+        });
+        if let Some(latest) = &latest {
+            output["latest_version"] = json!(latest.to_string());
+            output["update_available"] = json!(*latest > current);
+        }
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("ccuse {}", current.to_string().bold());
+    println!("  Target: {target}");
+    if let Some(built_at) = built_at {
+        println!("  Built:  {}", built_at.format("%Y-%m-%d %H:%M:%S UTC"));
+    }
+    println!("  Config: {}", config_dir.display());
+
+    if let Some(latest) = latest {
+        if latest > current {
+            println!("\n{}", format!("Update available: {latest}").yellow());
+        } else {
+            println!("\n{}", "✓ ccuse is up to date".green());
+        }
+    }
+
+    Ok(())
+}
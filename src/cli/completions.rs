@@ -1,109 +1,175 @@
+use super::Args;
+use chrono::Utc;
+use clap::CommandFactory;
 use colored::Colorize;
 use inquire::Confirm;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-const ZSH_COMPLETION: &str = r#"#compdef ccuse
-
-# zsh completion for ccuse
-# Dynamic completion that fetches profile names from ccuse list
-
-local -a subcommands
-subcommands=(
-  'use:Use a profile to launch Claude Code'
-  'update:Update profiles from CC-Switch database'
-  'list:List all available profiles'
-  'add:Add a new profile interactively'
-  'remove:Remove a profile'
-  'rename:Rename a profile'
-  'config-dir:Show configuration directory'
-  'completions:Install shell completions'
-)
-
-# Get profile names dynamically from ccuse list
-local -a profiles
-profiles=(${${(f)"$(ccuse list 2>/dev/null | sed -n 's/^  \([^ ]*\).*/\1/p')"}:#})
-
-case "$words[1]" in
-  use|remove|rename)
-    _describe 'profile' profiles
-    ;;
-  *)
-    _describe 'command' subcommands
-    ;;
-esac
+/// Profile lookups in the snippets below, replaced by literal names for static completions
+const ZSH_DYNAMIC_PROFILES: &str =
+    r#"profiles=(${${(f)"$(ccuse list 2>/dev/null | sed -n 's/^  \([^ ]*\).*/\1/p')"}:#})"#;
+const BASH_DYNAMIC_PROFILES: &str =
+    r#"profiles=($(ccuse list 2>/dev/null | sed -n 's/^  \([^ ]*\).*/\1/p' | grep -v '^$'))"#;
+const FISH_DYNAMIC_PROFILES: &str =
+    r#"-a '(ccuse list 2>/dev/null | sed -n "s/^  \\([^ ]*\\).*/\\1/p")'"#;
+const NU_DYNAMIC_PROFILES: &str =
+    r"ccuse list | complete | get stdout | lines | parse --regex '^  (?<name>\S+)' | get name";
+const POWERSHELL_DYNAMIC_PROFILES: &str = r"$profiles = @(ccuse list 2>$null | ForEach-Object { if ($_ -match '^  (\S+)') { $Matches[1] } })";
+
+/// Start of the header line of static scripts, used to tell them apart when regenerating
+const STATIC_HEADER: &str = "# Generated by ccuse at ";
+
+/// Subcommands whose first argument is a profile name
+const PROFILE_SUBCOMMANDS: [&str; 12] = [
+    "use",
+    "default",
+    "edit",
+    "remove",
+    "rename",
+    "show",
+    "export",
+    "clone",
+    "env",
+    "exec",
+    "migrate-secrets",
+    "validate",
+];
+
+/// Wraps the generated `_ccuse` (renamed `_ccuse_clap`) to complete profile names.
+const ZSH_PROFILE_SNIPPET: &str = r#"
+_ccuse() {
+    if (( CURRENT == 3 )) && [[ $PREFIX != -* && ${words[2]} == (SUBCOMMANDS) ]]; then
+        local -a profiles
+        PROFILES
+        _describe 'profile' profiles
+    else
+        _ccuse_clap "$@"
+    fi
+}
 "#;
 
-const BASH_COMPLETION: &str = r#"_ccuse() {
-    local cur prev opts
-    COMPREPLY=()
-    cur="${COMP_WORDS[COMP_CWORD]}"
-    prev="${COMP_WORDS[COMP_CWORD-1]}"
-
-    local -a subcommands
-    subcommands=(
-        use
-        update
-        list
-        add
-        remove
-        rename
-        config-dir
-        completions
-    )
-
-    local -a profiles
-    profiles=($(ccuse list 2>/dev/null | sed -n 's/^  \([^ ]*\).*/\1/p' | grep -v '^$'))
-
-    case "${prev}" in
-        ccuse)
-            COMPREPLY=($(compgen -W "${subcommands[*]}" -- "${cur}"))
-            ;;
-        use|remove|rename)
-            COMPREPLY=($(compgen -W "${profiles[*]}" -- "${cur}"))
-            ;;
-    esac
-
-    return 0
+/// Replaces the generated registration with one that completes profile names first.
+const BASH_PROFILE_SNIPPET: &str = r#"
+_ccuse_profiles() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    if [[ ${COMP_CWORD} -eq 2 && ${cur} != -* ]]; then
+        case "${COMP_WORDS[1]}" in
+            SUBCOMMANDS)
+                local -a profiles
+                PROFILES
+                COMPREPLY=($(compgen -W "${profiles[*]}" -- "${cur}"))
+                return 0
+                ;;
+        esac
+    fi
+    _ccuse "$@"
 }
 
-complete -F _ccuse ccuse
+complete -F _ccuse_profiles -o bashdefault -o default ccuse
 "#;
 
-const FISH_COMPLETION: &str = r#"complete -c ccuse -f -n '__fish_use_subcommand' -a 'use' -d 'Use a profile to launch Claude Code'
-complete -c ccuse -f -n '__fish_use_subcommand' -a 'update' -d 'Update profiles from CC-Switch database'
-complete -c ccuse -f -n '__fish_use_subcommand' -a 'list' -d 'List all available profiles'
-complete -c ccuse -f -n '__fish_use_subcommand' -a 'add' -d 'Add a new profile interactively'
-complete -c ccuse -f -n '__fish_use_subcommand' -a 'remove' -d 'Remove a profile'
-complete -c ccuse -f -n '__fish_use_subcommand' -a 'rename' -d 'Rename a profile'
-complete -c ccuse -f -n '__fish_use_subcommand' -a 'config-dir' -d 'Show configuration directory'
-complete -c ccuse -f -n '__fish_use_subcommand' -a 'completions' -d 'Install shell completions'
+/// Fish merges completions, so profile names are simply added.
+const FISH_PROFILE_SNIPPET: &str = r#"
+complete -c ccuse -f -n '__fish_seen_subcommand_from SUBCOMMANDS' PROFILES
+"#;
 
-complete -c ccuse -f -n '__fish_seen_subcommand_from use remove rename' -a '(ccuse list 2>/dev/null | sed -n "s/^  \\([^ ]*\\).*/\\1/p")'
+/// Registers a completer that completes profile names first and otherwise
+/// defers to the generated one, stored in `$ccuseClapCompleter`.
+const POWERSHELL_PROFILE_SNIPPET: &str = r#"
+Register-ArgumentCompleter -Native -CommandName 'ccuse' -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $elements = $commandAst.CommandElements
+    $position = if ($wordToComplete) { $elements.Count - 1 } else { $elements.Count }
+    if ($position -eq 2 -and -not $wordToComplete.StartsWith('-') -and
+        $elements[1].ToString() -in @(SUBCOMMANDS)) {
+        PROFILES
+        $profiles.Where{ $_ -like "$wordToComplete*" } | ForEach-Object {
+            [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_)
+        }
+    } else {
+        & $ccuseClapCompleter $wordToComplete $commandAst $cursorPosition
+    }
+}.GetNewClosure()
+"#;
+
+/// Defines the profile completer inside the generated `completions` module.
+const NU_PROFILE_SNIPPET: &str = r#"
+  def "nu-complete ccuse profiles" [] {
+    PROFILES
+  }
 "#;
 
 pub struct CompletionInstaller;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Where and what [`CompletionInstaller`] writes for one shell.
+pub struct InstallPlan {
+    pub shell: Shell,
+    pub target_path: PathBuf,
+    /// `target_path`'s directory relative to the home directory, for display
+    pub rel_dir: &'static str,
+    /// Whether a completion file is already installed at `target_path`
+    pub exists: bool,
+    pub script: String,
+}
+
+impl InstallPlan {
+    /// Whether the installed file is a static script with profile names baked in.
+    #[must_use]
+    pub fn installed_is_static(&self) -> bool {
+        fs::read_to_string(&self.target_path).is_ok_and(|installed| {
+            // Zsh keeps `#compdef` on the first line, so the header is second
+            installed
+                .lines()
+                .take(2)
+                .any(|line| line.starts_with(STATIC_HEADER))
+        })
+    }
+
+    /// Write the script to the target file, creating its directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or file cannot be written.
+    pub fn write(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.target_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.target_path, &self.script)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum Shell {
     Zsh,
     Bash,
     Fish,
+    #[value(name = "powershell", alias = "pwsh")]
+    PowerShell,
+    #[value(name = "nu", alias = "nushell")]
+    Nu,
 }
 
 impl Shell {
     #[must_use]
     pub fn detect() -> Option<Self> {
-        let shell = std::env::var("SHELL").ok()?;
-        if shell.contains("zsh") {
-            Some(Self::Zsh)
-        } else if shell.contains("bash") {
-            Some(Self::Bash)
-        } else if shell.contains("fish") {
-            Some(Self::Fish)
-        } else {
-            None
+        if let Ok(shell) = std::env::var("SHELL") {
+            if shell.contains("zsh") {
+                return Some(Self::Zsh);
+            } else if shell.contains("bash") {
+                return Some(Self::Bash);
+            } else if shell.contains("fish") {
+                return Some(Self::Fish);
+            } else if Path::new(&shell)
+                .file_stem()
+                .is_some_and(|name| name == "nu")
+            {
+                return Some(Self::Nu);
+            }
         }
+        // PowerShell does not set SHELL but always sets PSModulePath
+        std::env::var_os("PSModulePath").map(|_| Self::PowerShell)
     }
 
     #[must_use]
@@ -112,15 +178,141 @@ impl Shell {
             Self::Zsh => "zsh",
             Self::Bash => "bash",
             Self::Fish => "fish",
+            Self::PowerShell => "powershell",
+            Self::Nu => "nu",
         }
     }
 
+    /// Completion script that looks up profile names with `ccuse list`.
     #[must_use]
-    pub fn completion(&self) -> &'static str {
+    pub fn completion(&self) -> String {
+        let lookup = match self {
+            Self::Zsh => ZSH_DYNAMIC_PROFILES,
+            Self::Bash => BASH_DYNAMIC_PROFILES,
+            Self::Fish => FISH_DYNAMIC_PROFILES,
+            Self::PowerShell => POWERSHELL_DYNAMIC_PROFILES,
+            Self::Nu => NU_DYNAMIC_PROFILES,
+        };
+        self.script(lookup)
+    }
+
+    /// Completion script with `profiles` baked in instead of calling `ccuse list`.
+    #[must_use]
+    pub fn completion_with_profiles(&self, profiles: &[&str]) -> String {
+        let header = format!(
+            "{STATIC_HEADER}{} — run ccuse completions to regenerate",
+            Utc::now().to_rfc3339()
+        );
+        let quoted: Vec<String> = profiles
+            .iter()
+            .map(|name| format!("'{}'", name.replace('\'', r"'\''")))
+            .collect();
+
+        match self {
+            Self::Zsh => {
+                let script = self.script(&format!("profiles=({})", quoted.join(" ")));
+                // #compdef must stay on the first line
+                script.replacen(
+                    "#compdef ccuse\n",
+                    &format!("#compdef ccuse\n{header}\n"),
+                    1,
+                )
+            }
+            Self::Bash => {
+                let script = self.script(&format!("profiles=({})", quoted.join(" ")));
+                format!("{header}\n{script}")
+            }
+            Self::Fish => {
+                let script = self.script(&format!("-a '{}'", profiles.join(" ")));
+                format!("{header}\n{script}")
+            }
+            Self::PowerShell => {
+                let quoted: Vec<String> = profiles
+                    .iter()
+                    .map(|name| format!("'{}'", name.replace('\'', "''")))
+                    .collect();
+                let script = self.script(&format!("$profiles = @({})", quoted.join(", ")));
+                format!("{header}\n{script}")
+            }
+            Self::Nu => {
+                let quoted: Vec<String> = profiles
+                    .iter()
+                    .map(|name| serde_json::Value::from(*name).to_string())
+                    .collect();
+                let script = self.script(&format!("[{}]", quoted.join(" ")));
+                format!("{header}\n{script}")
+            }
+        }
+    }
+
+    /// The clap-generated script for every subcommand and flag, followed by
+    /// the profile-name snippet using `profiles` to list profiles.
+    fn script(&self, profiles: &str) -> String {
+        let mut buffer = Vec::new();
+        let mut command = Args::command();
+        let shell = match self {
+            Self::Zsh => Some(clap_complete::Shell::Zsh),
+            Self::Bash => Some(clap_complete::Shell::Bash),
+            Self::Fish => Some(clap_complete::Shell::Fish),
+            Self::PowerShell => Some(clap_complete::Shell::PowerShell),
+            Self::Nu => None,
+        };
+        match shell {
+            Some(shell) => clap_complete::generate(shell, &mut command, "ccuse", &mut buffer),
+            // clap_complete has no Nushell generator of its own
+            None => clap_complete::generate(
+                clap_complete_nushell::Nushell,
+                &mut command,
+                "ccuse",
+                &mut buffer,
+            ),
+        }
+        let generated = String::from_utf8_lossy(&buffer);
+
+        let (generated, snippet) = match self {
+            // The file is autoloaded as `_ccuse`, so the wrapper takes that name
+            Self::Zsh => (
+                generated.replacen("\n_ccuse() {\n", "\n_ccuse_clap() {\n", 1),
+                ZSH_PROFILE_SNIPPET,
+            ),
+            Self::Bash => (generated.into_owned(), BASH_PROFILE_SNIPPET),
+            Self::Fish => (generated.into_owned(), FISH_PROFILE_SNIPPET),
+            // Keep the generated completer so the wrapper can fall back to it
+            Self::PowerShell => (
+                generated.replacen(
+                    "Register-ArgumentCompleter -Native -CommandName 'ccuse' -ScriptBlock {",
+                    "$ccuseClapCompleter = {",
+                    1,
+                ),
+                POWERSHELL_PROFILE_SNIPPET,
+            ),
+            Self::Nu => (
+                complete_nu_profiles(&generated, &command),
+                NU_PROFILE_SNIPPET,
+            ),
+        };
+        let subcommands = match self {
+            Self::Zsh | Self::Bash => PROFILE_SUBCOMMANDS.join("|"),
+            Self::Fish | Self::Nu => PROFILE_SUBCOMMANDS.join(" "),
+            Self::PowerShell => format!("'{}'", PROFILE_SUBCOMMANDS.join("', '")),
+        };
+        let snippet = snippet
+            .replace("SUBCOMMANDS", &subcommands)
+            .replace("PROFILES", profiles);
+
         match self {
-            Self::Zsh => ZSH_COMPLETION,
-            Self::Bash => BASH_COMPLETION,
-            Self::Fish => FISH_COMPLETION,
+            // The wrapper must exist before the generated tail calls `_ccuse`
+            Self::Zsh => match generated.rfind("\nif [ \"$funcstack[1]\" = \"_ccuse\" ]") {
+                Some(tail) => format!("{}{snippet}{}", &generated[..tail], &generated[tail..]),
+                None => format!("{generated}{snippet}"),
+            },
+            Self::Bash | Self::Fish | Self::PowerShell => format!("{generated}{snippet}"),
+            // The externs look the completer up inside the generated module
+            Self::Nu => generated.replacen(
+                "module completions {\n",
+                &format!("module completions {{\n{snippet}"),
+                1,
+            ),
         }
     }
 
@@ -136,6 +328,19 @@ impl Shell {
                 home.join(".config/fish/completions/ccuse.fish"),
                 "~/.config/fish/completions/",
             ),
+            // Next to the default $PROFILE
+            Self::PowerShell if cfg!(windows) => (
+                home.join("Documents/PowerShell/ccuse-completions.ps1"),
+                "~/Documents/PowerShell/",
+            ),
+            Self::PowerShell => (
+                home.join(".config/powershell/ccuse-completions.ps1"),
+                "~/.config/powershell/",
+            ),
+            Self::Nu => (
+                home.join(".config/nushell/completions/ccuse.nu"),
+                "~/.config/nushell/completions/",
+            ),
         }
     }
 
@@ -151,45 +356,135 @@ impl Shell {
                 path.display()
             ),
             Self::Fish => "# Fish completions are auto-loaded from ~/.config/fish/completions/".to_string(),
+            Self::PowerShell => format!(
+                "# Add to $PROFILE:\n. \"{}\"",
+                path.display()
+            ),
+            Self::Nu => format!(
+                "# Add to config.nu:\nsource {}",
+                path.display()
+            ),
         }
     }
 }
 
+/// Point the first positional of each profile subcommand's `extern` at the
+/// profile completer.
+fn complete_nu_profiles(generated: &str, command: &clap::Command) -> String {
+    let mut script = generated.to_string();
+    for name in PROFILE_SUBCOMMANDS {
+        let Some(id) = command
+            .find_subcommand(name)
+            .and_then(|sub| sub.get_positionals().next())
+            .map(clap::Arg::get_id)
+        else {
+            continue;
+        };
+        let Some(start) = script.find(&format!("export extern \"ccuse {name}\" [\n")) else {
+            continue;
+        };
+        let end = script[start..]
+            .find("\n  ]")
+            .map_or(script.len(), |end| start + end);
+        for param in [
+            format!("\n    {id}: string"),
+            format!("\n    {id}?: string"),
+        ] {
+            if let Some(at) = script[start..end].find(&param) {
+                let at = start + at + param.len();
+                script.insert_str(at, "@\"nu-complete ccuse profiles\"");
+                break;
+            }
+        }
+    }
+    script
+}
+
 impl CompletionInstaller {
-    /// Run the completion installer.
+    /// Work out where the completion script for `shell` (default: the
+    /// detected one) goes, without writing anything.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shell cannot be detected or the home directory cannot be found.
+    pub fn plan(
+        shell: Option<Shell>,
+    ) -> std::result::Result<InstallPlan, Box<dyn std::error::Error>> {
+        let shell = shell
+            .or_else(Shell::detect)
+            .ok_or("Unable to detect shell type; pass --shell")?;
+        let home = dirs::home_dir().ok_or("Cannot find home directory")?;
+        let (target_path, rel_dir) = shell.config_path(&home);
+
+        Ok(InstallPlan {
+            shell,
+            exists: target_path.exists(),
+            target_path,
+            rel_dir,
+            script: shell.completion(),
+        })
+    }
+
+    /// Write a static completion script for `shell` (default: the detected one), without prompting.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shell cannot be detected or the file cannot be written.
+    pub fn install_static(
+        shell: Option<Shell>,
+        profiles: &[&str],
+    ) -> std::result::Result<PathBuf, Box<dyn std::error::Error>> {
+        let mut plan = Self::plan(shell)?;
+        plan.script = plan.shell.completion_with_profiles(profiles);
+        plan.write()?;
+
+        Ok(plan.target_path)
+    }
+
+    /// Run the completion installer for `shell`, or the detected shell.
     ///
     /// # Errors
     ///
     /// Returns an error if shell cannot be detected, user confirmation fails, or completion file cannot be written.
-    pub fn run() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    pub fn run(shell: Option<Shell>) -> std::result::Result<(), Box<dyn std::error::Error>> {
         println!("\n{}", "Shell Completions Installation".bold().green());
         println!("{}", "═".repeat(50));
 
-        // Detect current shell
-        let shell = Shell::detect().ok_or("Unable to detect shell type")?;
-        println!("\nDetected shell: {}", shell.name().bold());
+        let detected = shell.is_none();
+        let plan = Self::plan(shell)?;
+        let shell = plan.shell;
+        if detected {
+            println!("\nDetected shell: {}", shell.name().bold());
+        } else {
+            println!("\nShell: {}", shell.name().bold());
+        }
 
         // Show available options
         println!("\nSupported shells:");
-        for (i, s) in [Shell::Zsh, Shell::Bash, Shell::Fish].iter().enumerate() {
+        for (i, s) in [
+            Shell::Zsh,
+            Shell::Bash,
+            Shell::Fish,
+            Shell::PowerShell,
+            Shell::Nu,
+        ]
+        .iter()
+        .enumerate()
+        {
             let marker = if *s == shell { " ✓" } else { "" };
             println!("  {}. {}{}", i + 1, s.name(), marker);
         }
 
-        // Get home directory
-        let home = dirs::home_dir().ok_or("Cannot find home directory")?;
-        let (target_path, rel_dir) = shell.config_path(&home);
-
         // Show what will be done
+        let target_path = &plan.target_path;
         println!("\n{}", "Installation plan:".bold());
         println!(
             "  - Target file: {}",
             target_path.display().to_string().cyan()
         );
-        println!("  - Directory: {}", rel_dir.cyan());
+        println!("  - Directory: {}", plan.rel_dir.cyan());
 
-        // Check if file already exists
-        let action = if target_path.exists() {
+        let action = if plan.exists {
             "update (overwrite)"
         } else {
             "create"
@@ -197,16 +492,14 @@ impl CompletionInstaller {
         println!("  - Action: {}", action.yellow());
 
         // Show preview of completion file
+        let script = &plan.script;
         println!("\n{}", "File preview (first 20 lines):".bold());
-        let preview: Vec<&str> = shell.completion().lines().take(20).collect();
-        for (i, line) in preview.iter().enumerate() {
+        for (i, line) in script.lines().take(20).enumerate() {
             println!("{:3}: {}", i + 1, line);
         }
-        if shell.completion().lines().count() > 20 {
-            println!(
-                "    ... ({} more lines)",
-                shell.completion().lines().count() - 20
-            );
+        let line_count = script.lines().count();
+        if line_count > 20 {
+            println!("    ... ({} more lines)", line_count - 20);
         }
 
         // Require confirmation
@@ -220,19 +513,14 @@ impl CompletionInstaller {
             return Ok(());
         }
 
-        // Create parent directory if needed
-        if let Some(parent) = target_path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)?;
-                println!(
-                    "\nCreated directory: {}",
-                    parent.display().to_string().cyan()
-                );
-            }
+        let new_dir = target_path.parent().filter(|parent| !parent.exists());
+        plan.write()?;
+        if let Some(parent) = new_dir {
+            println!(
+                "\nCreated directory: {}",
+                parent.display().to_string().cyan()
+            );
         }
-
-        // Write completion file
-        fs::write(&target_path, shell.completion())?;
         println!("\n{} Installed completions to:", "✓".green());
         println!("  {}", target_path.display().to_string().cyan());
 
@@ -252,9 +540,79 @@ impl CompletionInstaller {
                 println!("  Fish completions are auto-loaded.");
                 println!("  Restart your terminal.");
             }
+            Shell::PowerShell => {
+                println!("  Add to $PROFILE:");
+                println!("    . \"{}\"", target_path.display());
+            }
+            Shell::Nu => {
+                println!("  Add to config.nu ($nu.config-path):");
+                println!("    source {}", target_path.display());
+            }
         }
 
         println!();
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    fn dynamic_lookup(shell: Shell) -> &'static str {
+        match shell {
+            Shell::Zsh => ZSH_DYNAMIC_PROFILES,
+            Shell::Bash => BASH_DYNAMIC_PROFILES,
+            Shell::Fish => FISH_DYNAMIC_PROFILES,
+            Shell::PowerShell => POWERSHELL_DYNAMIC_PROFILES,
+            Shell::Nu => NU_DYNAMIC_PROFILES,
+        }
+    }
+
+    #[test]
+    fn static_completions_contain_each_profile_name() {
+        let profiles = ["work", "personal-proxy", "kimi_k2"];
+        for shell in Shell::value_variants() {
+            let script = shell.completion_with_profiles(&profiles);
+            for name in profiles {
+                assert!(script.contains(name), "{shell:?} script lacks {name}");
+            }
+            assert!(
+                !script.contains(dynamic_lookup(*shell)),
+                "{shell:?} static script still calls ccuse list"
+            );
+            assert!(script.contains(STATIC_HEADER), "{shell:?} lacks the header");
+        }
+    }
+
+    #[test]
+    fn dynamic_completions_look_up_profiles() {
+        for shell in Shell::value_variants() {
+            let script = shell.completion();
+            assert!(script.contains(dynamic_lookup(*shell)), "{shell:?}");
+            assert!(!script.contains(STATIC_HEADER), "{shell:?}");
+        }
+    }
+
+    #[test]
+    fn zsh_header_follows_compdef() {
+        let script = Shell::Zsh.completion_with_profiles(&["work"]);
+        let mut lines = script.lines();
+        assert_eq!(lines.next(), Some("#compdef ccuse"));
+        assert!(lines.next().unwrap().starts_with(STATIC_HEADER));
+        assert!(script.contains("profiles=('work')"));
+    }
+
+    #[test]
+    fn quotes_in_profile_names_are_escaped() {
+        let bash = Shell::Bash.completion_with_profiles(&["it's"]);
+        assert!(bash.contains(r"profiles=('it'\''s')"));
+
+        let powershell = Shell::PowerShell.completion_with_profiles(&["it's"]);
+        assert!(powershell.contains("$profiles = @('it''s')"));
+
+        let nu = Shell::Nu.completion_with_profiles(&["say \"hi\""]);
+        assert!(nu.contains(r#"["say \"hi\""]"#));
+    }
+}
@@ -0,0 +1,75 @@
+use crate::error::{Error, Result};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Get the system's default text editor
+#[must_use]
+pub fn get_editor() -> String {
+    // Try environment variables first
+    if let Ok(editor) = env::var("VISUAL") {
+        return editor;
+    }
+    if let Ok(editor) = env::var("EDITOR") {
+        return editor;
+    }
+
+    // Platform-specific defaults
+    #[cfg(target_os = "windows")]
+    {
+        "notepad.exe".to_string()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        // -W waits for TextEdit to quit and -n starts a new instance so it
+        // does, even when TextEdit is already open
+        "open -W -n -e".to_string()
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        // Try common editors on Linux
+        for editor in ["nano", "vim", "vi"] {
+            if which::which(editor).is_ok() {
+                return editor.to_string();
+            }
+        }
+        "vi".to_string()
+    }
+}
+
+/// Open `path` in the user's editor, wait for it to close, and report
+/// whether the file's content changed.
+///
+/// The editor setting may include arguments, e.g. `code --wait`.
+///
+/// # Errors
+///
+/// Returns `Error::EditorLaunchError` if the editor cannot be started,
+/// `Error::EditorExitError` if it exits unsuccessfully, or an error if the
+/// file cannot be read afterwards.
+pub fn edit_file(path: &Path) -> Result<bool> {
+    let before = fs::read(path).ok();
+
+    let editor = get_editor();
+    let mut parts = editor.split_whitespace();
+    let Some(cmd) = parts.next() else {
+        return Err(Error::EditorLaunchError(
+            editor,
+            "the editor command is empty".into(),
+        ));
+    };
+
+    let status = Command::new(cmd)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| Error::EditorLaunchError(editor.clone(), e.to_string()))?;
+
+    if !status.success() {
+        // An editor killed by a signal has no exit code
+        return Err(Error::EditorExitError(editor, status.code().unwrap_or(1)));
+    }
+
+    Ok(before.as_deref() != Some(fs::read(path)?.as_slice()))
+}
@@ -0,0 +1,66 @@
+use super::completions::Shell;
+use std::collections::HashMap;
+
+/// Shell syntax used by `ccuse env <name>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportShell {
+    Bash,
+    Zsh,
+    Fish,
+    #[value(name = "powershell", alias = "pwsh")]
+    PowerShell,
+}
+
+impl ExportShell {
+    /// Guess the calling shell, falling back to bash syntax.
+    #[must_use]
+    pub fn detect() -> Self {
+        match Shell::detect() {
+            Some(Shell::Zsh) => Self::Zsh,
+            Some(Shell::Bash) => Self::Bash,
+            Some(Shell::Fish) => Self::Fish,
+            Some(Shell::PowerShell) => Self::PowerShell,
+            Some(Shell::Nu) | None => Self::Bash,
+        }
+    }
+
+    /// One line that sets `key` to `value` in this shell, quoted so the
+    /// value is taken literally.
+    #[must_use]
+    pub fn export_line(self, key: &str, value: &str) -> String {
+        match self {
+            Self::Bash | Self::Zsh => {
+                format!(
+                    "export {key}=\"{}\"",
+                    escape(value, '\\', &['\\', '"', '$', '`'])
+                )
+            }
+            Self::Fish => format!("set -x {key} '{}'", escape(value, '\\', &['\\', '\''])),
+            Self::PowerShell => {
+                format!("$env:{key}=\"{}\"", escape(value, '`', &['`', '"', '$']))
+            }
+        }
+    }
+}
+
+/// Export lines for every var in `env`, sorted by key.
+#[must_use]
+pub fn export_lines(env: &HashMap<String, String>, shell: ExportShell) -> Vec<String> {
+    let mut keys: Vec<&String> = env.keys().collect();
+    keys.sort();
+    keys.into_iter()
+        .map(|key| shell.export_line(key, &env[key]))
+        .collect()
+}
+
+/// Prefix every character of `value` found in `special` with `escape_char`.
+fn escape(value: &str, escape_char: char, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push(escape_char);
+        }
+        escaped.push(c);
+    }
+    escaped
+}
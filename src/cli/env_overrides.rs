@@ -0,0 +1,139 @@
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Parse a single `KEY=VALUE` override.
+///
+/// # Errors
+///
+/// Returns an error if the value has no `=` or the key is empty.
+pub fn parse_env_pair(pair: &str) -> Result<(String, String)> {
+    match pair.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(Error::ConfigError(format!(
+            "Invalid --env value '{pair}': expected KEY=VALUE"
+        ))),
+    }
+}
+
+/// Parse a JSON object of string values into env overrides.
+///
+/// # Errors
+///
+/// Returns an error if the input is not a JSON object whose values are all strings.
+pub fn parse_env_json(json: &str) -> Result<HashMap<String, String>> {
+    serde_json::from_str::<HashMap<String, String>>(json).map_err(|_| {
+        Error::ConfigError("--env-from-json requires a JSON object with string values".into())
+    })
+}
+
+/// Read a JSON object of string values from a file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or does not contain a string-map JSON object.
+pub fn parse_env_json_file(path: &Path) -> Result<HashMap<String, String>> {
+    let content = fs::read_to_string(path)?;
+    parse_env_json(&content)
+}
+
+/// Collect all ad-hoc env overrides in precedence order.
+///
+/// `--env KEY=VALUE` pairs are applied first, then `--env-from-json-file`,
+/// then `--env-from-json`, so the inline JSON wins on conflicts.
+///
+/// # Errors
+///
+/// Returns an error if any override fails to parse.
+pub fn collect_env_overrides(
+    pairs: &[String],
+    json: Option<&str>,
+    json_file: Option<&Path>,
+) -> Result<HashMap<String, String>> {
+    let mut overrides = HashMap::new();
+
+    for pair in pairs {
+        let (key, value) = parse_env_pair(pair)?;
+        overrides.insert(key, value);
+    }
+
+    if let Some(path) = json_file {
+        overrides.extend(parse_env_json_file(path)?);
+    }
+
+    if let Some(json) = json {
+        overrides.extend(parse_env_json(json)?);
+    }
+
+    Ok(overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_json_object_of_strings() {
+        let env =
+            parse_env_json(r#"{"ANTHROPIC_MODEL":"claude-3-haiku","ANTHROPIC_TIMEOUT":"30000"}"#)
+                .unwrap();
+        assert_eq!(env.len(), 2);
+        assert_eq!(env["ANTHROPIC_MODEL"], "claude-3-haiku");
+        assert_eq!(env["ANTHROPIC_TIMEOUT"], "30000");
+    }
+
+    #[test]
+    fn rejects_json_that_is_not_an_object() {
+        for json in [r#"["A","B"]"#, r#""A=B""#, "42", "null", "{"] {
+            assert!(
+                matches!(parse_env_json(json), Err(Error::ConfigError(_))),
+                "{json}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_non_string_values() {
+        for json in [
+            r#"{"TIMEOUT":30000}"#,
+            r#"{"DEBUG":true}"#,
+            r#"{"KEY":null}"#,
+            r#"{"KEY":{"nested":"x"}}"#,
+        ] {
+            assert!(
+                matches!(parse_env_json(json), Err(Error::ConfigError(_))),
+                "{json}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_env_pairs() {
+        assert_eq!(
+            parse_env_pair("URL=http://a/?x=1").unwrap(),
+            ("URL".to_string(), "http://a/?x=1".to_string())
+        );
+        assert_eq!(parse_env_pair("EMPTY=").unwrap().1, "");
+        assert!(parse_env_pair("NOVALUE").is_err());
+        assert!(parse_env_pair("=value").is_err());
+    }
+
+    #[test]
+    fn inline_json_wins_over_file_and_pairs() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("env.json");
+        fs::write(&file, r#"{"A":"file","B":"file"}"#).unwrap();
+
+        let env = collect_env_overrides(
+            &["A=pair".to_string(), "C=pair".to_string()],
+            Some(r#"{"B":"inline"}"#),
+            Some(&file),
+        )
+        .unwrap();
+
+        assert_eq!(env["A"], "file");
+        assert_eq!(env["B"], "inline");
+        assert_eq!(env["C"], "pair");
+    }
+}
@@ -1,6 +1,12 @@
 pub mod args;
 pub mod commands;
 pub mod completions;
+pub mod editor;
+pub mod env_export;
+pub mod env_overrides;
 
-pub use args::{Args, Commands};
+pub use args::{
+    AddArgs, ApiLogCommands, Args, Commands, EnvCommands, ExperimentalFlagsCommands, ListArgs,
+    ProfileCommands, UpdateArgs, UseArgs,
+};
 pub use completions::CompletionInstaller;
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+/// Global ccuse settings stored in `<config_dir>/config.json`.
+///
+/// These are persistent defaults that apply to every profile; command-line
+/// flags always take precedence over them.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Config {
+    /// Send a desktop notification whenever a Claude session ends
+    #[serde(default)]
+    pub notify_on_exit: bool,
+
+    /// Store newly written profiles as gzip-compressed settings.json.gz
+    #[serde(default)]
+    pub compress_profiles: bool,
+
+    /// Env var used to pass the session's resource group [default: ANTHROPIC_RESOURCE_GROUP]
+    #[serde(default)]
+    pub resource_group_env_key: Option<String>,
+
+    /// How `ccuse update` derives display names from CC-Switch names
+    #[serde(default)]
+    pub ccswitch_display_name_strategy: DisplayNameStrategy,
+}
+
+/// How a CC-Switch provider name maps onto a profile's `display_name`.
+///
+/// The profile `name` is always the normalized form (spaces replaced by
+/// underscores) so it can be typed on the command line.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum DisplayNameStrategy {
+    /// Display the original CC-Switch name, e.g. "Work Proxy"
+    #[default]
+    KeepOriginal,
+    /// Display the normalized name, e.g. "Work_Proxy"
+    UseNormalized,
+    /// Display both when they differ, e.g. "Work Proxy (Work_Proxy)"
+    Both,
+}
+
+impl DisplayNameStrategy {
+    /// Display name for a profile imported as `normalized` from `original`.
+    #[must_use]
+    pub fn display_name(self, original: &str, normalized: &str) -> String {
+        match self {
+            Self::KeepOriginal => original.to_string(),
+            Self::UseNormalized => normalized.to_string(),
+            Self::Both if original == normalized => original.to_string(),
+            Self::Both => format!("{original} ({normalized})"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_name_shapes() {
+        let shapes: Vec<_> = [
+            DisplayNameStrategy::KeepOriginal,
+            DisplayNameStrategy::UseNormalized,
+            DisplayNameStrategy::Both,
+        ]
+        .into_iter()
+        .map(|strategy| strategy.display_name("Work Proxy", "Work_Proxy"))
+        .collect();
+        assert_eq!(
+            shapes,
+            ["Work Proxy", "Work_Proxy", "Work Proxy (Work_Proxy)"]
+        );
+    }
+
+    #[test]
+    fn both_shows_a_name_without_spaces_once() {
+        assert_eq!(
+            DisplayNameStrategy::Both.display_name("work", "work"),
+            "work"
+        );
+    }
+
+    #[test]
+    fn strategy_defaults_to_keep_original() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(
+            config.ccswitch_display_name_strategy,
+            DisplayNameStrategy::KeepOriginal
+        );
+
+        let config: Config =
+            serde_json::from_str(r#"{"ccswitch_display_name_strategy":"use-normalized"}"#).unwrap();
+        assert_eq!(
+            config.ccswitch_display_name_strategy,
+            DisplayNameStrategy::UseNormalized
+        );
+    }
+}
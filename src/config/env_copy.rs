@@ -0,0 +1,268 @@
+use crate::config::{secrets, Storage};
+use crate::error::{Error, Result};
+use inquire::Confirm;
+
+/// What to do when a copied env var already exists in the destination.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum MergeStrategy {
+    /// Replace the destination value
+    #[default]
+    Overwrite,
+    /// Keep the destination value
+    SkipExisting,
+    /// Prompt for each conflicting key
+    Ask,
+}
+
+/// Outcome of [`copy_env_vars`].
+#[derive(Debug, Default)]
+pub struct CopyResult {
+    /// Keys written to the destination
+    pub copied: Vec<String>,
+    /// Keys left unchanged because the destination already had them
+    pub skipped: Vec<String>,
+    /// Keys removed from the source (`remove_from_src` only)
+    pub removed: Vec<String>,
+}
+
+/// Copy env vars from profile `src` to profile `dst`.
+///
+/// Copies `keys`, or every env var of `src` when `None`. Keys that already
+/// exist in `dst` are handled according to `strategy`. With
+/// `remove_from_src`, copied keys are deleted from `src` afterwards; skipped
+/// keys stay where they are.
+///
+/// # Errors
+///
+/// Returns [`Error::ProfileNotFound`] if either profile does not exist, and
+/// an error if a requested key is not set in `src` or a profile cannot be saved.
+pub fn copy_env_vars(
+    storage: &Storage,
+    src: &str,
+    dst: &str,
+    keys: Option<&[&str]>,
+    strategy: MergeStrategy,
+    remove_from_src: bool,
+) -> Result<CopyResult> {
+    if src == dst {
+        return Err(Error::ConfigError(
+            "Source and destination profile are the same".into(),
+        ));
+    }
+
+    let mut source = storage
+        .get_profile(src)?
+        .ok_or_else(|| Error::ProfileNotFound(src.into()))?;
+    let mut dest = storage
+        .get_profile(dst)?
+        .ok_or_else(|| Error::ProfileNotFound(dst.into()))?;
+
+    let mut keys: Vec<String> = match keys {
+        Some(keys) => {
+            if let Some(missing) = keys.iter().find(|key| !source.env.contains_key(**key)) {
+                return Err(Error::ConfigError(format!(
+                    "'{missing}' is not set in profile '{src}'"
+                )));
+            }
+            keys.iter().map(|key| (*key).to_string()).collect()
+        }
+        None => source.env.keys().cloned().collect(),
+    };
+    keys.sort();
+    keys.dedup();
+
+    // Keyring references must keep pointing at the source's entries
+    let mut values = source.env.clone();
+    secrets::qualify_sentinels(src, &mut values);
+
+    let mut result = CopyResult::default();
+    for key in keys {
+        let value = values[&key].clone();
+
+        let write = match (dest.env.get(&key), strategy) {
+            (None, _) | (Some(_), MergeStrategy::Overwrite) => true,
+            (Some(existing), _) if *existing == value => true,
+            (Some(_), MergeStrategy::SkipExisting) => false,
+            (Some(_), MergeStrategy::Ask) => {
+                Confirm::new(&format!("'{key}' already exists in '{dst}'. Overwrite it?"))
+                    .with_default(false)
+                    .prompt()?
+            }
+        };
+
+        if write {
+            dest.env.insert(key.clone(), value);
+            if remove_from_src {
+                source.env.remove(&key);
+                result.removed.push(key.clone());
+            }
+            result.copied.push(key);
+        } else {
+            result.skipped.push(key);
+        }
+    }
+
+    storage.update_profile(dest)?;
+    if !result.removed.is_empty() {
+        storage.update_profile(source)?;
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Profile;
+
+    fn add(storage: &Storage, name: &str, env: &[(&str, &str)]) {
+        storage
+            .add_profile(Profile {
+                name: name.into(),
+                env: env
+                    .iter()
+                    .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                    .collect(),
+                ..Profile::default()
+            })
+            .unwrap();
+    }
+
+    fn env_keys(storage: &Storage, name: &str) -> Vec<String> {
+        let mut keys: Vec<_> = storage
+            .get_profile(name)
+            .unwrap()
+            .unwrap()
+            .env
+            .into_keys()
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    fn fixture(dir: &std::path::Path) -> Storage {
+        let storage = Storage::in_dir(dir);
+        add(
+            &storage,
+            "src",
+            &[
+                ("ANTHROPIC_AUTH_TOKEN", "sk-src"),
+                ("ANTHROPIC_BASE_URL", "https://src.example.com"),
+                ("ANTHROPIC_MODEL", "opus"),
+            ],
+        );
+        add(
+            &storage,
+            "dst",
+            &[("ANTHROPIC_BASE_URL", "https://dst.example.com")],
+        );
+        storage
+    }
+
+    #[test]
+    fn copies_the_given_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = fixture(dir.path());
+
+        let result = copy_env_vars(
+            &storage,
+            "src",
+            "dst",
+            Some(&["ANTHROPIC_MODEL", "ANTHROPIC_BASE_URL"]),
+            MergeStrategy::Overwrite,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(result.copied, ["ANTHROPIC_BASE_URL", "ANTHROPIC_MODEL"]);
+        assert!(result.skipped.is_empty() && result.removed.is_empty());
+        let dst = storage.get_profile("dst").unwrap().unwrap();
+        assert_eq!(dst.env["ANTHROPIC_BASE_URL"], "https://src.example.com");
+        assert_eq!(dst.env["ANTHROPIC_MODEL"], "opus");
+        assert!(!dst.env.contains_key("ANTHROPIC_AUTH_TOKEN"));
+        assert_eq!(env_keys(&storage, "src").len(), 3);
+    }
+
+    #[test]
+    fn skip_existing_keeps_destination_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = fixture(dir.path());
+
+        let result = copy_env_vars(
+            &storage,
+            "src",
+            "dst",
+            None,
+            MergeStrategy::SkipExisting,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(result.copied, ["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_MODEL"]);
+        assert_eq!(result.skipped, ["ANTHROPIC_BASE_URL"]);
+        let dst = storage.get_profile("dst").unwrap().unwrap();
+        assert_eq!(dst.env["ANTHROPIC_BASE_URL"], "https://dst.example.com");
+    }
+
+    #[test]
+    fn move_removes_copied_keys_from_the_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = fixture(dir.path());
+
+        let result = copy_env_vars(
+            &storage,
+            "src",
+            "dst",
+            None,
+            MergeStrategy::SkipExisting,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(result.removed, ["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_MODEL"]);
+        // Read back from disk: the moved keys are gone, the skipped one stays
+        let src = Storage::in_dir(dir.path())
+            .get_profile("src")
+            .unwrap()
+            .unwrap();
+        assert!(!src.env.contains_key("ANTHROPIC_AUTH_TOKEN"));
+        assert!(!src.env.contains_key("ANTHROPIC_MODEL"));
+        assert_eq!(src.env["ANTHROPIC_BASE_URL"], "https://src.example.com");
+        assert_eq!(
+            env_keys(&storage, "dst"),
+            [
+                "ANTHROPIC_AUTH_TOKEN",
+                "ANTHROPIC_BASE_URL",
+                "ANTHROPIC_MODEL"
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_profiles_and_keys_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = fixture(dir.path());
+        let copy = |src, dst, keys: Option<&[&str]>| {
+            copy_env_vars(&storage, src, dst, keys, MergeStrategy::Overwrite, true)
+        };
+
+        assert!(
+            matches!(copy("missing", "dst", None), Err(Error::ProfileNotFound(n)) if n == "missing")
+        );
+        assert!(
+            matches!(copy("src", "missing", None), Err(Error::ProfileNotFound(n)) if n == "missing")
+        );
+        assert!(matches!(
+            copy("src", "dst", Some(&["ANTHROPIC_MODEL", "NOT_SET"])),
+            Err(Error::ConfigError(_))
+        ));
+        assert!(matches!(
+            copy("src", "src", None),
+            Err(Error::ConfigError(_))
+        ));
+
+        // Nothing was copied or moved
+        assert_eq!(env_keys(&storage, "src").len(), 3);
+        assert_eq!(env_keys(&storage, "dst"), ["ANTHROPIC_BASE_URL"]);
+    }
+}
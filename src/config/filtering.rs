@@ -0,0 +1,222 @@
+use crate::config::Profile;
+use crate::error::{Error, Result};
+use regex::Regex;
+
+/// A pattern used to match env var keys or values.
+#[derive(Debug, Clone)]
+pub enum EnvFilter {
+    /// Whole-string match (case-insensitive for keys, exact for values)
+    Exact(String),
+    /// Substring match
+    Contains(String),
+    /// Regular expression match
+    Regex(Regex),
+}
+
+impl EnvFilter {
+    /// Build a regex filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression.
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Regex::new(pattern)
+            .map(Self::Regex)
+            .map_err(|e| Error::ConfigError(format!("Invalid regex '{pattern}': {e}")))
+    }
+
+    #[must_use]
+    pub fn matches_key(&self, key: &str) -> bool {
+        match self {
+            Self::Exact(expected) => key.eq_ignore_ascii_case(expected),
+            Self::Contains(needle) => key.to_lowercase().contains(&needle.to_lowercase()),
+            Self::Regex(re) => re.is_match(key),
+        }
+    }
+
+    #[must_use]
+    pub fn matches_value(&self, value: &str) -> bool {
+        match self {
+            Self::Exact(expected) => value == expected,
+            Self::Contains(needle) => value.contains(needle.as_str()),
+            Self::Regex(re) => re.is_match(value),
+        }
+    }
+}
+
+/// Compile a shell-style glob, where `*` matches any run of characters and
+/// `?` a single one, into a regex matching whole strings.
+///
+/// # Errors
+///
+/// Currently infallible, since every other character is matched literally;
+/// returns `Result` like [`EnvFilter::regex`].
+pub fn glob_regex(pattern: &str) -> Result<Regex> {
+    let mut regex = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).map_err(|e| Error::ConfigError(format!("Invalid pattern '{pattern}': {e}")))
+}
+
+/// Return the env entries of a profile that satisfy both filters.
+///
+/// A missing filter matches everything.
+#[must_use]
+pub fn matching_env_entries<'a>(
+    profile: &'a Profile,
+    key_pattern: Option<&EnvFilter>,
+    value_pattern: Option<&EnvFilter>,
+) -> Vec<(&'a String, &'a String)> {
+    let mut entries: Vec<_> = profile
+        .env
+        .iter()
+        .filter(|(k, _)| key_pattern.map_or(true, |f| f.matches_key(k)))
+        .filter(|(_, v)| value_pattern.map_or(true, |f| f.matches_value(v)))
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// Keep only profiles that define at least one env var matching the filters.
+///
+/// Without filters every profile is kept, including those with no env vars.
+#[must_use]
+pub fn filter_by_env<'a>(
+    profiles: &'a [Profile],
+    key_pattern: Option<&EnvFilter>,
+    value_pattern: Option<&EnvFilter>,
+) -> Vec<&'a Profile> {
+    if key_pattern.is_none() && value_pattern.is_none() {
+        return profiles.iter().collect();
+    }
+    profiles
+        .iter()
+        .filter(|p| !matching_env_entries(p, key_pattern, value_pattern).is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, env: &[(&str, &str)]) -> Profile {
+        Profile {
+            name: name.to_string(),
+            env: env
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                .collect(),
+            ..Profile::default()
+        }
+    }
+
+    fn names(profiles: &[&Profile]) -> Vec<String> {
+        profiles.iter().map(|p| p.name.clone()).collect()
+    }
+
+    fn fixtures() -> Vec<Profile> {
+        vec![
+            profile(
+                "work",
+                &[
+                    ("ANTHROPIC_BASE_URL", "https://work.example.com"),
+                    ("ANTHROPIC_MODEL", "opus"),
+                ],
+            ),
+            profile(
+                "typo",
+                &[("ANTHROPIC_BASE_ULR", "https://work.example.com")],
+            ),
+            profile(
+                "home",
+                &[("anthropic_base_url", "https://home.example.com")],
+            ),
+        ]
+    }
+
+    #[test]
+    fn no_filters_keep_every_profile() {
+        let mut profiles = fixtures();
+        profiles.push(profile("empty", &[]));
+        assert_eq!(
+            names(&filter_by_env(&profiles, None, None)),
+            ["work", "typo", "home", "empty"]
+        );
+
+        let key = EnvFilter::Contains("ANTHROPIC".to_string());
+        assert!(!names(&filter_by_env(&profiles, Some(&key), None)).contains(&"empty".into()));
+    }
+
+    #[test]
+    fn exact_keys_match_case_insensitively() {
+        let profiles = fixtures();
+        let key = EnvFilter::Exact("Anthropic_Base_Url".to_string());
+        assert_eq!(
+            names(&filter_by_env(&profiles, Some(&key), None)),
+            ["work", "home"]
+        );
+    }
+
+    #[test]
+    fn exact_values_match_whole_strings() {
+        let profiles = fixtures();
+        let value = EnvFilter::Exact("https://work.example.com".to_string());
+        assert_eq!(
+            names(&filter_by_env(&profiles, None, Some(&value))),
+            ["work", "typo"]
+        );
+        let partial = EnvFilter::Exact("work.example.com".to_string());
+        assert!(filter_by_env(&profiles, None, Some(&partial)).is_empty());
+    }
+
+    #[test]
+    fn contains_matches_substrings() {
+        let profiles = fixtures();
+        let key = EnvFilter::Contains("base_u".to_string());
+        assert_eq!(
+            names(&filter_by_env(&profiles, Some(&key), None)),
+            ["work", "typo", "home"]
+        );
+        let value = EnvFilter::Contains("home.".to_string());
+        assert_eq!(
+            names(&filter_by_env(&profiles, None, Some(&value))),
+            ["home"]
+        );
+    }
+
+    #[test]
+    fn regex_matches_keys() {
+        let profiles = fixtures();
+        let key = EnvFilter::regex("^ANTHROPIC_BASE_U(RL|LR)$").unwrap();
+        assert_eq!(
+            names(&filter_by_env(&profiles, Some(&key), None)),
+            ["work", "typo"]
+        );
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error() {
+        assert!(matches!(
+            EnvFilter::regex("ANTHROPIC_("),
+            Err(Error::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn key_and_value_must_match_the_same_entry() {
+        let profiles = fixtures();
+        let key = EnvFilter::Exact("ANTHROPIC_MODEL".to_string());
+        let value = EnvFilter::Contains("example".to_string());
+        assert!(filter_by_env(&profiles, Some(&key), Some(&value)).is_empty());
+
+        let entries = matching_env_entries(&profiles[0], None, Some(&value));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "ANTHROPIC_BASE_URL");
+    }
+}
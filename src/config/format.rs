@@ -0,0 +1,273 @@
+use crate::config::Profile;
+use crate::error::{Error, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// File formats accepted by `ccuse import`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImportFormat {
+    /// Detect the format from the file content
+    Auto,
+    /// A single profile as a JSON object
+    Json,
+    /// Several profiles as a JSON array
+    JsonArray,
+    /// `KEY=VALUE` lines, imported as the profile env
+    #[value(name = "dotenv")]
+    DotEnv,
+    /// A profile as TOML, optionally under a `[profile]` table
+    Toml,
+    /// Could not be detected
+    #[value(skip)]
+    Unknown,
+}
+
+impl fmt::Display for ImportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Auto => "auto",
+            Self::Json => "json",
+            Self::JsonArray => "json-array",
+            Self::DotEnv => "dotenv",
+            Self::Toml => "toml",
+            Self::Unknown => "unknown",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Version written to `ccuse_export_version` by `ccuse export`.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// A profile as written by `ccuse export`.
+///
+/// The profile fields are flattened next to `ccuse_export_version`, so the
+/// file is also a plain profile that `ccuse import` reads as JSON.
+#[derive(Debug, Serialize)]
+pub struct ProfileExport<'a> {
+    pub ccuse_export_version: u32,
+    #[serde(flatten)]
+    pub profile: &'a Profile,
+}
+
+impl<'a> ProfileExport<'a> {
+    #[must_use]
+    pub fn new(profile: &'a Profile) -> Self {
+        Self {
+            ccuse_export_version: EXPORT_VERSION,
+            profile,
+        }
+    }
+}
+
+/// Guess the format of an import file from its content.
+///
+/// TOML section headers (`[profile]`, `[env]`) are checked before the JSON
+/// array case because both start with `[`.
+#[must_use]
+pub fn detect_import_format(content: &str) -> ImportFormat {
+    let trimmed = content.trim_start();
+
+    let has_toml_section = content
+        .lines()
+        .map(str::trim)
+        .any(|line| line == "[profile]" || line == "[env]");
+
+    if has_toml_section {
+        ImportFormat::Toml
+    } else if trimmed.starts_with('{') {
+        ImportFormat::Json
+    } else if trimmed.starts_with('[') {
+        ImportFormat::JsonArray
+    } else if trimmed.starts_with('#') || (content.contains('=') && !content.contains('{')) {
+        ImportFormat::DotEnv
+    } else {
+        ImportFormat::Unknown
+    }
+}
+
+/// Parse import file content into profiles.
+///
+/// Profiles without a `name` are named `fallback_name` (usually the file stem).
+///
+/// # Errors
+///
+/// Returns an error if the format is unknown or the content does not parse.
+pub fn parse_import(
+    content: &str,
+    format: ImportFormat,
+    fallback_name: &str,
+) -> Result<Vec<Profile>> {
+    let values = match format {
+        ImportFormat::Json => vec![serde_json::from_str::<Value>(content)?],
+        ImportFormat::JsonArray => serde_json::from_str::<Vec<Value>>(content)?,
+        ImportFormat::Toml => {
+            let mut value: Value = toml::from_str(content)
+                .map_err(|e| Error::ConfigError(format!("Invalid TOML: {e}")))?;
+            // Allow the profile fields to live under a [profile] table
+            if let Some(mut profile) = value.get_mut("profile").map(Value::take) {
+                if let (Some(env), Some(obj)) = (value.get("env"), profile.as_object_mut()) {
+                    obj.entry("env").or_insert_with(|| env.clone());
+                }
+                value = profile;
+            }
+            vec![value]
+        }
+        ImportFormat::DotEnv => {
+            vec![serde_json::json!({ "env": parse_dotenv(content) })]
+        }
+        ImportFormat::Auto | ImportFormat::Unknown => {
+            return Err(Error::ConfigError(
+                "Could not detect import format; use --format with one of: json, json-array, dotenv, toml".into(),
+            ))
+        }
+    };
+
+    values
+        .into_iter()
+        .map(|mut value| {
+            if let Some(obj) = value.as_object_mut() {
+                obj.entry("name")
+                    .or_insert_with(|| Value::String(fallback_name.to_string()));
+            }
+            Ok(serde_json::from_value(value)?)
+        })
+        .collect()
+}
+
+/// Parse `KEY=VALUE` lines, skipping comments and `export` prefixes.
+fn parse_dotenv(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_each_format() {
+        assert_eq!(
+            detect_import_format(r#"  {"name":"a"}"#),
+            ImportFormat::Json
+        );
+        assert_eq!(
+            detect_import_format(r#"[{"name":"a"},{"name":"b"}]"#),
+            ImportFormat::JsonArray
+        );
+        assert_eq!(
+            detect_import_format("ANTHROPIC_BASE_URL=https://api.example.com\n"),
+            ImportFormat::DotEnv
+        );
+        assert_eq!(
+            detect_import_format("[profile]\nname = \"a\"\n"),
+            ImportFormat::Toml
+        );
+        assert_eq!(
+            detect_import_format("just some text"),
+            ImportFormat::Unknown
+        );
+        assert_eq!(detect_import_format("  \n"), ImportFormat::Unknown);
+    }
+
+    #[test]
+    fn toml_sections_are_not_json_arrays() {
+        assert_eq!(
+            detect_import_format("[env]\nANTHROPIC_MODEL = \"opus\"\n"),
+            ImportFormat::Toml
+        );
+        // A section further down still makes it TOML
+        assert_eq!(
+            detect_import_format("name = \"a\"\n\n  [env]  \nKEY = \"v\"\n"),
+            ImportFormat::Toml
+        );
+    }
+
+    #[test]
+    fn commented_toml_is_not_dotenv() {
+        assert_eq!(
+            detect_import_format("# exported profile\n[profile]\nname = \"a\"\n"),
+            ImportFormat::Toml
+        );
+    }
+
+    #[test]
+    fn json_containing_equals_signs_is_json() {
+        assert_eq!(
+            detect_import_format(r#"{"env":{"URL":"https://a.example.com/?x=1"}}"#),
+            ImportFormat::Json
+        );
+        // A JSON string that looks like a TOML header is not on its own line
+        assert_eq!(
+            detect_import_format("[\n  {\"name\": \"[env]\"}\n]"),
+            ImportFormat::JsonArray
+        );
+    }
+
+    #[test]
+    fn commented_dotenv_may_contain_braces() {
+        assert_eq!(
+            detect_import_format("# proxy settings\nHEADERS={\"x\":\"1\"}\n"),
+            ImportFormat::DotEnv
+        );
+    }
+
+    #[test]
+    fn parses_dotenv_with_quotes_comments_and_export() {
+        let content = "# comment\nexport A=\"quoted value\"\nB='single'\n\nC = plain \nD=a=b\n";
+        let profiles = parse_import(content, ImportFormat::DotEnv, "fallback").unwrap();
+
+        assert_eq!(profiles.len(), 1);
+        let env = &profiles[0].env;
+        assert_eq!(profiles[0].name, "fallback");
+        assert_eq!(env["A"], "quoted value");
+        assert_eq!(env["B"], "single");
+        assert_eq!(env["C"], "plain");
+        assert_eq!(env["D"], "a=b");
+    }
+
+    #[test]
+    fn parses_toml_profile_table_with_env() {
+        let content =
+            "[profile]\nname = \"work\"\ncategory = \"job\"\n\n[env]\nANTHROPIC_MODEL = \"opus\"\n";
+        let profiles = parse_import(content, ImportFormat::Toml, "fallback").unwrap();
+
+        assert_eq!(profiles[0].name, "work");
+        assert_eq!(profiles[0].category.as_deref(), Some("job"));
+        assert_eq!(profiles[0].env["ANTHROPIC_MODEL"], "opus");
+    }
+
+    #[test]
+    fn json_array_profiles_without_names_get_the_fallback() {
+        let content = r#"[{"name":"a"},{"env":{"K":"v"}}]"#;
+        let profiles = parse_import(content, ImportFormat::JsonArray, "file").unwrap();
+
+        let names: Vec<_> = profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["a", "file"]);
+    }
+
+    #[test]
+    fn unknown_format_is_an_error_listing_the_formats() {
+        let Err(Error::ConfigError(message)) = parse_import("text", ImportFormat::Unknown, "x")
+        else {
+            panic!("expected a config error");
+        };
+        assert!(message.contains("json, json-array, dotenv, toml"));
+    }
+}
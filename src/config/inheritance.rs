@@ -0,0 +1,54 @@
+use crate::config::{secrets, Profile};
+use crate::error::{Error, Result};
+
+/// Flatten the `extends` chain of `profile`, looking parents up with `lookup`.
+///
+/// Each profile is merged onto its parent with [`Profile::merge_with`], so
+/// the closest profile wins. Keyring references of parents are qualified
+/// with their owner, since the result launches under `profile`'s name. The
+/// result keeps `profile`'s own `extends`.
+///
+/// # Errors
+///
+/// Returns a `ConfigError` if a parent does not exist or the chain loops
+/// back on itself, or any error from `lookup`.
+pub fn resolve_inheritance(
+    profile: Profile,
+    mut lookup: impl FnMut(&str) -> Result<Option<Profile>>,
+) -> Result<Profile> {
+    let mut chain = vec![profile.name.clone()];
+    let mut parents = Vec::new();
+    let mut next = profile.extends.clone();
+
+    while let Some(parent_name) = next {
+        if chain.contains(&parent_name) {
+            chain.push(parent_name);
+            return Err(Error::ConfigError(format!(
+                "Profile inheritance cycle: {}",
+                chain.join(" → ")
+            )));
+        }
+
+        let Some(mut parent) = lookup(&parent_name)? else {
+            return Err(Error::ConfigError(format!(
+                "Profile '{}' extends '{parent_name}', which does not exist",
+                chain.last().map_or("", String::as_str)
+            )));
+        };
+        secrets::qualify_sentinels(&parent_name, &mut parent.env);
+
+        next = parent.extends.clone();
+        chain.push(parent_name);
+        parents.push(parent);
+    }
+
+    // Merge from the root down
+    match parents
+        .into_iter()
+        .rev()
+        .reduce(|parent, child| parent.merge_with(&child))
+    {
+        Some(base) => Ok(base.merge_with(&profile)),
+        None => Ok(profile),
+    }
+}
@@ -0,0 +1,55 @@
+use crate::error::Result;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::TempPath;
+
+/// A settings file passed to Claude Code with `--settings`.
+///
+/// Copies made for a single launch hold the profile's secrets, so they are
+/// private temporary files: a random name, created exclusively with mode
+/// 0600 on Unix, and removed when the value is dropped.
+#[derive(Debug)]
+pub enum LaunchSettings {
+    /// A file that stays after the launch, such as the profile's own settings.json
+    Persistent(PathBuf),
+    /// A private copy, removed on drop
+    Temporary(TempPath),
+}
+
+impl LaunchSettings {
+    /// Write `content` to a new private temporary file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written.
+    pub fn temporary(content: &str) -> Result<Self> {
+        let mut file = tempfile::Builder::new()
+            .prefix("ccuse-")
+            .suffix(".json")
+            .tempfile()?;
+        file.write_all(content.as_bytes())?;
+        file.flush()?;
+        Ok(Self::Temporary(file.into_temp_path()))
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Persistent(path) => path,
+            Self::Temporary(path) => path,
+        }
+    }
+
+    /// The temporary file, if this is one, kept on disk after the value is
+    /// dropped, for sessions that outlive ccuse and remove it themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can no longer be kept.
+    pub fn into_kept_temporary(self) -> Result<Option<PathBuf>> {
+        match self {
+            Self::Persistent(_) => Ok(None),
+            Self::Temporary(path) => Ok(Some(path.keep().map_err(|e| e.error)?)),
+        }
+    }
+}
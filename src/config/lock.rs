@@ -0,0 +1,100 @@
+use crate::error::{Error, Result};
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for another ccuse process to release the lock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The lock this process holds, shared by every `Storage` so that nested
+/// instances do not wait on their own process.
+static HELD: Mutex<Option<Weak<StorageLock>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// Read-only commands; any number may run at once
+    Shared,
+    /// Commands that modify the profile store
+    Exclusive,
+}
+
+/// Advisory lock on `<config dir>/.lock`, released once the last `Storage`
+/// holding it is dropped.
+#[derive(Debug)]
+pub struct StorageLock {
+    file: File,
+    exclusive: AtomicBool,
+}
+
+impl StorageLock {
+    /// Lock the profile store in `config_dir`, waiting up to `LOCK_TIMEOUT`
+    /// for other ccuse processes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Locked` on timeout, or an error if the lock file cannot be opened.
+    pub fn acquire(config_dir: &Path, mode: LockMode) -> Result<Arc<Self>> {
+        let path = config_dir.join(".lock");
+        let mut held = HELD.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(lock) = held.as_ref().and_then(Weak::upgrade) {
+            if mode == LockMode::Exclusive && !lock.exclusive.load(Ordering::SeqCst) {
+                // Not every platform converts a held lock in place, so re-lock
+                FileExt::unlock(&lock.file)?;
+                if let Err(e) = wait_for_lock(&lock.file, mode, &path) {
+                    // The other holders in this process still rely on the shared lock
+                    wait_for_lock(&lock.file, LockMode::Shared, &path)?;
+                    return Err(e);
+                }
+                lock.exclusive.store(true, Ordering::SeqCst);
+            }
+            return Ok(lock);
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        wait_for_lock(&file, mode, &path)?;
+
+        let lock = Arc::new(Self {
+            file,
+            exclusive: AtomicBool::new(mode == LockMode::Exclusive),
+        });
+        *held = Some(Arc::downgrade(&lock));
+        Ok(lock)
+    }
+}
+
+impl Drop for StorageLock {
+    fn drop(&mut self) {
+        FileExt::unlock(&self.file).ok();
+    }
+}
+
+fn wait_for_lock(file: &File, mode: LockMode, path: &Path) -> Result<()> {
+    let started = Instant::now();
+    loop {
+        let attempt = match mode {
+            LockMode::Shared => FileExt::try_lock_shared(file),
+            LockMode::Exclusive => FileExt::try_lock_exclusive(file),
+        };
+        match attempt {
+            Ok(()) => return Ok(()),
+            Err(e) if e.kind() == fs2::lock_contended_error().kind() => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        if started.elapsed() >= LOCK_TIMEOUT {
+            return Err(Error::Locked(path.to_path_buf()));
+        }
+        thread::sleep(RETRY_INTERVAL);
+    }
+}
@@ -1,5 +1,22 @@
+pub mod app;
+pub mod env_copy;
+pub mod filtering;
+pub mod format;
+pub mod inheritance;
+pub mod launch_settings;
+pub mod lock;
+pub mod orphans;
+pub mod presets;
 pub mod profile;
+pub mod secrets;
+pub mod sorting;
+pub mod state;
 pub mod storage;
+pub mod template;
+pub mod validation;
 
-pub use profile::{McpPermission, Permissions, Profile, ProfileSource};
+pub use app::{Config, DisplayNameStrategy};
+pub use launch_settings::LaunchSettings;
+pub use profile::{McpPermission, Permissions, Profile, ProfileSource, Signal, TimeoutAction};
+pub use state::{State, UsageRecord};
 pub use storage::Storage;
@@ -0,0 +1,160 @@
+use crate::config::Storage;
+use crate::error::Result;
+use std::path::PathBuf;
+
+/// Inconsistencies between profile directories and their settings.json files.
+#[derive(Debug, Default)]
+pub struct OrphanReport {
+    /// Profile directories that have no settings.json
+    pub missing_files: Vec<String>,
+    /// Directories whose settings.json does not load as a profile of the same name
+    pub orphaned_dirs: Vec<PathBuf>,
+}
+
+impl OrphanReport {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.missing_files.is_empty() && self.orphaned_dirs.is_empty()
+    }
+}
+
+/// Scan the config directory for broken profile layouts.
+///
+/// A directory is reported in `missing_files` when it has no settings.json,
+/// and in `orphaned_dirs` when its settings.json fails to parse or names a
+/// different profile than the directory it lives in.
+///
+/// # Errors
+///
+/// Returns an error if the config directory cannot be read.
+pub fn find_orphaned_profiles(storage: &Storage) -> Result<OrphanReport> {
+    let mut report = OrphanReport::default();
+
+    for name in storage.profile_dir_names()? {
+        if !storage.settings_file_exists(&name) {
+            report.missing_files.push(name);
+            continue;
+        }
+
+        let consistent =
+            matches!(storage.get_profile(&name), Ok(Some(profile)) if profile.name == name);
+        if !consistent {
+            report
+                .orphaned_dirs
+                .push(storage.profile_settings_dir(&name));
+        }
+    }
+
+    report.missing_files.sort();
+    report.orphaned_dirs.sort();
+
+    Ok(report)
+}
+
+/// Where a profile name that has no profile directory is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameReference {
+    /// The default profile in state.json
+    DefaultProfile,
+    /// The last used profile in state.json
+    LastUsed,
+    /// The `extends` parent of this profile
+    ExtendedBy(String),
+}
+
+/// A profile name that is used somewhere but has no profile directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingName {
+    pub name: String,
+    pub referenced_by: NameReference,
+}
+
+/// Profile directories and profile names that do not match up, as found by
+/// [`Storage::find_inconsistencies`].
+#[derive(Debug, Default)]
+pub struct Inconsistencies {
+    /// Directories that hold no loadable profile of their own name
+    pub orphans: OrphanReport,
+    /// Names in state.json or `extends` that have no profile directory
+    pub dangling_names: Vec<DanglingName>,
+}
+
+impl Inconsistencies {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.orphans.is_empty() && self.dangling_names.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Profile;
+    use std::fs;
+
+    fn add(storage: &Storage, name: &str) {
+        storage
+            .add_profile(Profile {
+                name: name.to_string(),
+                ..Profile::default()
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn healthy_profiles_are_not_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        add(&storage, "plain");
+        add(&storage, "packed");
+        storage.compress_profile("packed").unwrap();
+
+        assert!(find_orphaned_profiles(&storage).unwrap().is_empty());
+    }
+
+    #[test]
+    fn directories_without_settings_are_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        add(&storage, "plain");
+        fs::create_dir(dir.path().join("zeta")).unwrap();
+        fs::create_dir(dir.path().join("alpha")).unwrap();
+
+        let report = find_orphaned_profiles(&storage).unwrap();
+
+        assert_eq!(report.missing_files, ["alpha", "zeta"]);
+        assert!(report.orphaned_dirs.is_empty());
+    }
+
+    #[test]
+    fn unparseable_and_misnamed_settings_are_orphaned_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        add(&storage, "plain");
+        add(&storage, "copied");
+        let moved = fs::read_to_string(storage.profile_settings_path("copied")).unwrap();
+        fs::create_dir(dir.path().join("renamed")).unwrap();
+        fs::write(storage.profile_settings_path("renamed"), moved).unwrap();
+        fs::create_dir(dir.path().join("broken")).unwrap();
+        fs::write(storage.profile_settings_path("broken"), "not json").unwrap();
+
+        let report = find_orphaned_profiles(&storage).unwrap();
+
+        assert!(report.missing_files.is_empty());
+        assert_eq!(
+            report.orphaned_dirs,
+            [dir.path().join("broken"), dir.path().join("renamed")]
+        );
+    }
+
+    #[test]
+    fn reserved_and_hidden_directories_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        fs::create_dir(dir.path().join("backups")).unwrap();
+        fs::create_dir(dir.path().join("cache")).unwrap();
+        fs::create_dir(dir.path().join(".draft")).unwrap();
+
+        assert!(find_orphaned_profiles(&storage).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,44 @@
+/// A built-in starting point for `ccuse add --preset`.
+#[derive(Debug, Clone, Copy)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Env vars pre-filled in the editor template; empty values are left for the user
+    pub env: &'static [(&'static str, &'static str)],
+}
+
+/// Presets available to `ccuse add --preset`.
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "anthropic",
+        description: "Anthropic API",
+        env: &[
+            ("ANTHROPIC_AUTH_TOKEN", ""),
+            ("ANTHROPIC_BASE_URL", "https://api.anthropic.com"),
+        ],
+    },
+    Preset {
+        name: "openrouter",
+        description: "OpenRouter",
+        env: &[
+            ("ANTHROPIC_AUTH_TOKEN", ""),
+            ("ANTHROPIC_BASE_URL", "https://openrouter.ai/api"),
+            ("ANTHROPIC_MODEL", ""),
+        ],
+    },
+    Preset {
+        name: "deepseek",
+        description: "DeepSeek Anthropic-compatible API",
+        env: &[
+            ("ANTHROPIC_AUTH_TOKEN", ""),
+            ("ANTHROPIC_BASE_URL", "https://api.deepseek.com/anthropic"),
+            ("ANTHROPIC_MODEL", "deepseek-chat"),
+        ],
+    },
+];
+
+/// Look up a preset by name (case-insensitive).
+#[must_use]
+pub fn find_preset(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
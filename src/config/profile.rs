@@ -1,20 +1,58 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Profile {
     pub name: String,
     pub display_name: Option<String>,
+    #[serde(default)]
     pub env: HashMap<String, String>,
+    #[serde(default)]
     pub permissions: Permissions,
     pub enabled_plugins: Option<HashMap<String, bool>>,
     pub always_thinking_enabled: Option<bool>,
     pub api_timeout_ms: Option<u64>,
     pub category: Option<String>,
+    /// Free-form labels, unlike the single `category`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     #[serde(default)]
     pub source: Option<ProfileSource>,
+    #[serde(default)]
+    pub timeout_action: Option<TimeoutAction>,
+    #[serde(default)]
+    pub timeout_signal: Option<Signal>,
+    #[serde(default)]
+    pub log_level: Option<String>,
+    #[serde(default)]
+    pub resource_group: Option<String>,
+    #[serde(default)]
+    pub port_forward: Option<String>,
+    #[serde(default)]
+    pub tee_output_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub experimental_flags: Vec<String>,
+    /// Claude Code arguments passed on every launch, before `--dangerously-skip-permissions` and the user's own
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub launch_args: Vec<String>,
+    #[serde(default)]
+    pub template_source: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub log_redact_patterns: Vec<String>,
+    #[serde(default)]
+    pub cpu_limit_percent: Option<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wait_for_ports: Vec<u16>,
+    #[serde(default)]
+    pub claude_version: Option<String>,
+    /// Profile whose env and settings this one inherits and overrides
+    #[serde(default)]
+    pub extends: Option<String>,
+    #[serde(default = "Utc::now")]
     pub created_at: DateTime<Utc>,
+    #[serde(default = "Utc::now")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -25,6 +63,34 @@ pub enum ProfileSource {
     Manual,
 }
 
+/// What to do when a session exceeds its `--timeout`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimeoutAction {
+    /// Kill Claude immediately
+    #[default]
+    Kill,
+    /// Send `timeout_signal`, then kill after the grace period
+    Signal,
+}
+
+/// Signals that can be sent to Claude on timeout.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, clap::ValueEnum)]
+pub enum Signal {
+    #[serde(rename = "SIGTERM")]
+    #[value(name = "SIGTERM", alias = "TERM")]
+    Term,
+    #[serde(rename = "SIGINT")]
+    #[value(name = "SIGINT", alias = "INT")]
+    Int,
+    #[serde(rename = "SIGHUP")]
+    #[value(name = "SIGHUP", alias = "HUP")]
+    Hup,
+    #[serde(rename = "SIGQUIT")]
+    #[value(name = "SIGQUIT", alias = "QUIT")]
+    Quit,
+}
+
 impl Default for Profile {
     fn default() -> Self {
         Self {
@@ -36,13 +102,143 @@ impl Default for Profile {
             always_thinking_enabled: None,
             api_timeout_ms: None,
             category: None,
+            tags: Vec::new(),
             source: None,
+            timeout_action: None,
+            timeout_signal: None,
+            log_level: None,
+            resource_group: None,
+            port_forward: None,
+            tee_output_file: None,
+            experimental_flags: Vec::new(),
+            launch_args: Vec::new(),
+            template_source: None,
+            log_redact_patterns: Vec::new(),
+            cpu_limit_percent: None,
+            wait_for_ports: Vec::new(),
+            claude_version: None,
+            extends: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
     }
 }
 
+impl Profile {
+    /// Combine this profile as the parent with `child` layered on top.
+    ///
+    /// Env vars and plugins are merged key by key with the child winning;
+    /// other optional settings fall back to the parent when the child leaves
+    /// them unset. Name, source, `extends` and timestamps are the child's.
+    #[must_use]
+    pub fn merge_with(&self, child: &Profile) -> Profile {
+        let mut env = self.env.clone();
+        env.extend(child.env.clone());
+
+        let enabled_plugins = match (&self.enabled_plugins, &child.enabled_plugins) {
+            (Some(parent), Some(child)) => {
+                let mut plugins = parent.clone();
+                plugins.extend(child.clone());
+                Some(plugins)
+            }
+            (parent, child) => child.clone().or_else(|| parent.clone()),
+        };
+
+        let mut experimental_flags = self.experimental_flags.clone();
+        for flag in &child.experimental_flags {
+            if !experimental_flags.contains(flag) {
+                experimental_flags.push(flag.clone());
+            }
+        }
+
+        let mut wait_for_ports = self.wait_for_ports.clone();
+        for port in &child.wait_for_ports {
+            if !wait_for_ports.contains(port) {
+                wait_for_ports.push(*port);
+            }
+        }
+
+        // Arguments are not deduplicated; the child's come last so they win
+        let mut launch_args = self.launch_args.clone();
+        launch_args.extend(child.launch_args.iter().cloned());
+
+        let mut tags = self.tags.clone();
+        tags.extend(child.tags.iter().filter(|tag| !self.has_tag(tag)).cloned());
+
+        let mut log_redact_patterns = self.log_redact_patterns.clone();
+        for pattern in &child.log_redact_patterns {
+            if !log_redact_patterns.contains(pattern) {
+                log_redact_patterns.push(pattern.clone());
+            }
+        }
+
+        Profile {
+            env,
+            permissions: Permissions {
+                enabled: child.permissions.enabled.or(self.permissions.enabled),
+                mcp: child
+                    .permissions
+                    .mcp
+                    .clone()
+                    .or_else(|| self.permissions.mcp.clone()),
+                command: child
+                    .permissions
+                    .command
+                    .clone()
+                    .or_else(|| self.permissions.command.clone()),
+            },
+            enabled_plugins,
+            always_thinking_enabled: child
+                .always_thinking_enabled
+                .or(self.always_thinking_enabled),
+            api_timeout_ms: child.api_timeout_ms.or(self.api_timeout_ms),
+            category: child.category.clone().or_else(|| self.category.clone()),
+            tags,
+            timeout_action: child.timeout_action.or(self.timeout_action),
+            timeout_signal: child.timeout_signal.or(self.timeout_signal),
+            log_level: child.log_level.clone().or_else(|| self.log_level.clone()),
+            resource_group: child
+                .resource_group
+                .clone()
+                .or_else(|| self.resource_group.clone()),
+            port_forward: child
+                .port_forward
+                .clone()
+                .or_else(|| self.port_forward.clone()),
+            tee_output_file: child
+                .tee_output_file
+                .clone()
+                .or_else(|| self.tee_output_file.clone()),
+            experimental_flags,
+            launch_args,
+            log_redact_patterns,
+            cpu_limit_percent: child.cpu_limit_percent.or(self.cpu_limit_percent),
+            wait_for_ports,
+            claude_version: child
+                .claude_version
+                .clone()
+                .or_else(|| self.claude_version.clone()),
+            ..child.clone()
+        }
+    }
+
+    /// Whether the profile has `tag`, ignoring case.
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Add `tags`, trimmed, skipping empty ones and ones the profile already has.
+    pub fn add_tags<S: AsRef<str>>(&mut self, tags: impl IntoIterator<Item = S>) {
+        for tag in tags {
+            let tag = tag.as_ref().trim();
+            if !tag.is_empty() && !self.has_tag(tag) {
+                self.tags.push(tag.to_string());
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Permissions {
     pub enabled: Option<bool>,
@@ -57,3 +253,126 @@ pub struct McpPermission {
     pub name: String,
     pub enabled: Option<bool>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, env: &[(&str, &str)]) -> Profile {
+        Profile {
+            name: name.to_string(),
+            env: env
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                .collect(),
+            ..Profile::default()
+        }
+    }
+
+    #[test]
+    fn merge_keeps_parent_env_with_child_overrides() {
+        let base = profile(
+            "base",
+            &[
+                ("ANTHROPIC_BASE_URL", "https://base.example.com"),
+                ("ANTHROPIC_MODEL", "sonnet"),
+            ],
+        );
+        let child = profile(
+            "child",
+            &[("ANTHROPIC_MODEL", "opus"), ("API_TIMEOUT_MS", "600000")],
+        );
+
+        let merged = base.merge_with(&child);
+
+        assert_eq!(merged.env["ANTHROPIC_BASE_URL"], "https://base.example.com");
+        assert_eq!(merged.env["ANTHROPIC_MODEL"], "opus");
+        assert_eq!(merged.env["API_TIMEOUT_MS"], "600000");
+        assert_eq!(merged.env.len(), 3);
+    }
+
+    #[test]
+    fn merge_falls_back_to_parent_settings_and_keeps_child_identity() {
+        let mut base = profile("base", &[]);
+        base.api_timeout_ms = Some(1000);
+        base.category = Some("work".into());
+        base.experimental_flags = vec!["fast_mode".into()];
+        base.tags = vec!["Team".into()];
+        base.launch_args = vec!["--verbose".into()];
+        base.extends = Some("root".into());
+
+        let mut child = profile("child", &[]);
+        child.api_timeout_ms = Some(2000);
+        child.experimental_flags = vec!["fast_mode".into(), "tools".into()];
+        child.tags = vec!["team".into(), "cheap".into()];
+        child.launch_args = vec!["--model".into(), "opus".into()];
+        child.display_name = Some("Child".into());
+
+        let merged = base.merge_with(&child);
+
+        assert_eq!(merged.api_timeout_ms, Some(2000));
+        assert_eq!(merged.category.as_deref(), Some("work"));
+        assert_eq!(merged.experimental_flags, ["fast_mode", "tools"]);
+        assert_eq!(merged.tags, ["Team", "cheap"]);
+        assert_eq!(merged.launch_args, ["--verbose", "--model", "opus"]);
+        assert_eq!(merged.name, "child");
+        assert_eq!(merged.display_name.as_deref(), Some("Child"));
+        assert_eq!(merged.extends, None);
+        assert_eq!(merged.created_at, child.created_at);
+    }
+
+    #[test]
+    fn merge_keeps_parent_timeout_action_unless_child_sets_one() {
+        let mut base = profile("base", &[]);
+        base.timeout_action = Some(TimeoutAction::Signal);
+        base.timeout_signal = Some(Signal::Int);
+        let mut child = profile("child", &[]);
+
+        let merged = base.merge_with(&child);
+        assert_eq!(merged.timeout_action, Some(TimeoutAction::Signal));
+        assert_eq!(merged.timeout_signal, Some(Signal::Int));
+
+        child.timeout_action = Some(TimeoutAction::Kill);
+        assert_eq!(
+            base.merge_with(&child).timeout_action,
+            Some(TimeoutAction::Kill)
+        );
+    }
+
+    #[test]
+    fn add_tags_trims_and_skips_empty_and_duplicate_tags() {
+        let mut profile = profile("work", &[]);
+        profile.tags = vec!["Work".into()];
+
+        profile.add_tags([" cheap ", "", "   ", "work", "gpt-compat", "CHEAP"]);
+
+        assert_eq!(profile.tags, ["Work", "cheap", "gpt-compat"]);
+        assert!(profile.has_tag("GPT-Compat"));
+        assert!(!profile.has_tag("gpt"));
+    }
+
+    #[test]
+    fn profiles_without_tags_still_load() {
+        let profile: Profile = serde_json::from_str(r#"{"name":"old","env":{}}"#).unwrap();
+        assert!(profile.tags.is_empty());
+    }
+
+    #[test]
+    fn empty_lists_are_left_out_of_saved_profiles() {
+        let mut profile = profile("work", &[]);
+        let saved = serde_json::to_value(&profile).unwrap();
+        for key in [
+            "tags",
+            "experimental_flags",
+            "launch_args",
+            "log_redact_patterns",
+            "wait_for_ports",
+        ] {
+            assert!(saved.get(key).is_none(), "{key} in {saved}");
+        }
+
+        profile.tags = vec!["cheap".into()];
+        let saved = serde_json::to_value(&profile).unwrap();
+        assert_eq!(saved["tags"], serde_json::json!(["cheap"]));
+    }
+}
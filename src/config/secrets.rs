@@ -0,0 +1,278 @@
+use crate::config::Profile;
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+
+/// Prefix of env values that live in the OS keyring, e.g. `keyring:ANTHROPIC_AUTH_TOKEN`.
+///
+/// The rest names the keyring entry: `KEY` for the profile's own secret, or
+/// `PROFILE/KEY` for one owned by another profile (composed or copied env).
+pub const KEYRING_PREFIX: &str = "keyring:";
+
+/// Keyring service all ccuse secrets are stored under.
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "ccuse";
+
+/// Characters left visible at the start and end of a value by [`mask`].
+const VISIBLE_PREFIX: usize = 3;
+const VISIBLE_SUFFIX: usize = 2;
+
+/// Values this short are hidden entirely by [`mask`].
+const MIN_PARTIAL_MASK_LEN: usize = 8;
+
+/// Words in an env var name that mark its value as a secret.
+const SECRET_KEY_MARKERS: [&str; 4] = ["TOKEN", "KEY", "SECRET", "PASSWORD"];
+
+/// Hide the middle of `value`, e.g. `sk-ant-abc123` -> `sk-***23`.
+///
+/// Values of eight characters or fewer are hidden entirely so nothing meaningful leaks.
+#[must_use]
+pub fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= MIN_PARTIAL_MASK_LEN {
+        return "*".repeat(chars.len().max(1));
+    }
+    let prefix: String = chars[..VISIBLE_PREFIX].iter().collect();
+    let suffix: String = chars[chars.len() - VISIBLE_SUFFIX..].iter().collect();
+    format!("{prefix}***{suffix}")
+}
+
+/// Whether an env var name looks like it holds a credential, e.g. `ANTHROPIC_AUTH_TOKEN`.
+#[must_use]
+pub fn is_secret_key(key: &str) -> bool {
+    let key = key.to_uppercase();
+    SECRET_KEY_MARKERS.iter().any(|marker| key.contains(marker))
+}
+
+/// An env value as ccuse prints it: masked if `key` looks like a secret,
+/// unless `reveal` is set.
+///
+/// Keyring sentinels are shown as-is, since they are not secrets themselves.
+#[must_use]
+pub fn shown_value(key: &str, value: &str, reveal: bool) -> String {
+    if reveal || !is_secret_key(key) || value.starts_with(KEYRING_PREFIX) {
+        value.to_string()
+    } else {
+        mask(value)
+    }
+}
+
+/// Sentinel stored in settings.json in place of `key`'s value.
+#[must_use]
+pub fn sentinel(key: &str) -> String {
+    format!("{KEYRING_PREFIX}{key}")
+}
+
+/// Keyring entry a sentinel refers to, as `(profile, key)`.
+fn parse_sentinel<'a>(profile_name: &'a str, value: &'a str) -> Option<(&'a str, &'a str)> {
+    let reference = value.strip_prefix(KEYRING_PREFIX)?;
+    Some(
+        reference
+            .split_once('/')
+            .unwrap_or((profile_name, reference)),
+    )
+}
+
+/// Whether any value in `env` is a keyring sentinel.
+#[must_use]
+pub fn has_sentinels(env: &HashMap<String, String>) -> bool {
+    env.values().any(|value| value.starts_with(KEYRING_PREFIX))
+}
+
+/// Replace keyring sentinels in `env` with the secrets they refer to.
+///
+/// # Errors
+///
+/// Returns an error if the keyring is unavailable or an entry is missing.
+pub fn resolve_sentinels(profile_name: &str, env: &mut HashMap<String, String>) -> Result<()> {
+    for value in env.values_mut() {
+        if let Some((owner, key)) = parse_sentinel(profile_name, value) {
+            *value = load_secret(owner, key)?;
+        }
+    }
+    Ok(())
+}
+
+/// Point `profile_name`'s own sentinels in `env` at that profile explicitly,
+/// so they still resolve once the env is merged into or copied to another profile.
+pub fn qualify_sentinels(profile_name: &str, env: &mut HashMap<String, String>) {
+    for value in env.values_mut() {
+        if let Some(key) = value.strip_prefix(KEYRING_PREFIX) {
+            if !key.contains('/') {
+                *value = format!("{KEYRING_PREFIX}{profile_name}/{key}");
+            }
+        }
+    }
+}
+
+/// Keys of `env` whose values are this profile's own keyring sentinels.
+fn own_sentinel_keys(env: &HashMap<String, String>) -> Vec<String> {
+    env.iter()
+        .filter_map(|(key, value)| {
+            let reference = value.strip_prefix(KEYRING_PREFIX)?;
+            (!reference.contains('/')).then(|| key.clone())
+        })
+        .collect()
+}
+
+/// Move the profile's plaintext secret env values into the keyring, leaving
+/// sentinels behind. The caller saves the profile.
+///
+/// Returns the keys that were moved.
+///
+/// # Errors
+///
+/// Returns an error if the keyring is unavailable or a secret cannot be stored.
+pub fn move_secrets_to_keyring(profile: &mut Profile) -> Result<Vec<String>> {
+    let mut moved = Vec::new();
+    for (key, value) in &mut profile.env {
+        if !is_secret_key(key) || value.is_empty() || value.starts_with(KEYRING_PREFIX) {
+            continue;
+        }
+        store_secret(&profile.name, key, value)?;
+        *value = sentinel(key);
+        moved.push(key.clone());
+    }
+    moved.sort();
+    Ok(moved)
+}
+
+/// Copy the keyring entries behind `env`'s own sentinels from profile `from` to `to`.
+///
+/// # Errors
+///
+/// Returns an error if the keyring is unavailable or an entry is missing.
+pub fn copy_secrets(from: &str, to: &str, env: &HashMap<String, String>) -> Result<()> {
+    for key in own_sentinel_keys(env) {
+        store_secret(to, &key, &load_secret(from, &key)?)?;
+    }
+    Ok(())
+}
+
+/// Delete the keyring entries behind `env`'s own sentinels.
+///
+/// # Errors
+///
+/// Returns an error if the keyring is unavailable or an entry cannot be deleted.
+pub fn delete_secrets(profile_name: &str, env: &HashMap<String, String>) -> Result<()> {
+    for key in own_sentinel_keys(env) {
+        delete_secret(profile_name, &key)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "keyring")]
+fn entry(profile_name: &str, key: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, &format!("{profile_name}/{key}"))
+        .map_err(|e| Error::KeyringError(e.to_string()))
+}
+
+#[cfg(feature = "keyring")]
+fn store_secret(profile_name: &str, key: &str, value: &str) -> Result<()> {
+    entry(profile_name, key)?
+        .set_password(value)
+        .map_err(|e| Error::KeyringError(format!("Cannot store {profile_name}/{key}: {e}")))
+}
+
+#[cfg(feature = "keyring")]
+fn load_secret(profile_name: &str, key: &str) -> Result<String> {
+    entry(profile_name, key)?
+        .get_password()
+        .map_err(|e| Error::KeyringError(format!("Cannot read {profile_name}/{key}: {e}")))
+}
+
+#[cfg(feature = "keyring")]
+fn delete_secret(profile_name: &str, key: &str) -> Result<()> {
+    match entry(profile_name, key)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(Error::KeyringError(format!(
+            "Cannot delete {profile_name}/{key}: {e}"
+        ))),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn store_secret(_profile_name: &str, _key: &str, _value: &str) -> Result<()> {
+    Err(keyring_disabled())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn load_secret(_profile_name: &str, _key: &str) -> Result<String> {
+    Err(keyring_disabled())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn delete_secret(_profile_name: &str, _key: &str) -> Result<()> {
+    Err(keyring_disabled())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_disabled() -> Error {
+    Error::KeyringError("ccuse was built without the `keyring` feature".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_hides_short_values_entirely() {
+        assert_eq!(mask(""), "*");
+        assert_eq!(mask("abcd"), "****");
+        assert_eq!(mask("12345678"), "********");
+    }
+
+    #[test]
+    fn mask_keeps_the_ends_of_long_values() {
+        assert_eq!(mask("123456789"), "123***89");
+
+        let token = format!("sk-{}", "a1b2c3d4e5".repeat(3) + "XYZ0123");
+        assert_eq!(token.len(), 40);
+        assert_eq!(mask(&token), "sk-***23");
+    }
+
+    #[test]
+    fn mask_counts_characters_not_bytes() {
+        assert_eq!(mask("ключ"), "****");
+        assert_eq!(mask("пароль-секрет"), "пар***ет");
+    }
+
+    #[test]
+    fn secret_keys_are_matched_by_name() {
+        for key in [
+            "ANTHROPIC_AUTH_TOKEN",
+            "ANTHROPIC_API_KEY",
+            "client_secret",
+            "Proxy_Password",
+        ] {
+            assert!(is_secret_key(key), "{key}");
+        }
+        for key in [
+            "ANTHROPIC_BASE_URL",
+            "ANTHROPIC_MODEL",
+            "API_TIMEOUT_MS",
+            "",
+        ] {
+            assert!(!is_secret_key(key), "{key}");
+        }
+    }
+
+    #[test]
+    fn shown_value_masks_only_unrevealed_secrets() {
+        let token = "sk-ant-api03-abcdef";
+        assert_eq!(
+            shown_value("ANTHROPIC_AUTH_TOKEN", token, false),
+            "sk-***ef"
+        );
+        assert_eq!(shown_value("ANTHROPIC_AUTH_TOKEN", token, true), token);
+        assert_eq!(
+            shown_value("ANTHROPIC_BASE_URL", "https://api.example.com", false),
+            "https://api.example.com"
+        );
+
+        let reference = sentinel("ANTHROPIC_AUTH_TOKEN");
+        assert_eq!(
+            shown_value("ANTHROPIC_AUTH_TOKEN", &reference, false),
+            reference
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use crate::config::{Profile, ProfileSource};
+use std::collections::BTreeMap;
+
+/// Order of the profiles printed by `ccuse list`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ProfileSort {
+    /// Display name, ignoring case
+    #[default]
+    Name,
+    /// Creation time, oldest first
+    Created,
+    /// Last update, oldest first
+    Updated,
+    /// CC-Switch profiles, then manual ones, then those without a source
+    Source,
+}
+
+/// How `ccuse list` prints the profiles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListFormat {
+    /// One multi-line entry per profile
+    #[default]
+    List,
+    /// One aligned row per profile
+    Table,
+    /// A JSON array of the stored profiles, with secret values masked
+    Json,
+}
+
+/// Group profiles by the preset they were created from.
+///
+/// Profiles without a `template_source` are grouped under `None`, which
+/// sorts before every named template.
+#[must_use]
+pub fn group_profiles_by_template<'a>(
+    profiles: impl IntoIterator<Item = &'a Profile>,
+) -> BTreeMap<Option<&'a str>, Vec<&'a Profile>> {
+    let mut groups: BTreeMap<Option<&str>, Vec<&Profile>> = BTreeMap::new();
+
+    for profile in profiles {
+        groups
+            .entry(profile.template_source.as_deref())
+            .or_default()
+            .push(profile);
+    }
+
+    groups
+}
+
+/// Group profiles by category.
+///
+/// Profiles without a `category` are grouped under `None`, which sorts
+/// before every named category.
+#[must_use]
+pub fn group_profiles_by_category<'a>(
+    profiles: impl IntoIterator<Item = &'a Profile>,
+) -> BTreeMap<Option<&'a str>, Vec<&'a Profile>> {
+    let mut groups: BTreeMap<Option<&str>, Vec<&Profile>> = BTreeMap::new();
+
+    for profile in profiles {
+        groups
+            .entry(profile.category.as_deref())
+            .or_default()
+            .push(profile);
+    }
+
+    groups
+}
+
+/// Sort profiles by `sort`, breaking ties by display name so the order is deterministic.
+pub fn sort_profiles(profiles: &mut [&Profile], sort: ProfileSort) {
+    sort_profiles_by_display_name(profiles);
+
+    // Stable sorts keep the display-name order within equal keys
+    match sort {
+        ProfileSort::Name => {}
+        ProfileSort::Created => profiles.sort_by_key(|p| p.created_at),
+        ProfileSort::Updated => profiles.sort_by_key(|p| p.updated_at),
+        ProfileSort::Source => profiles.sort_by_key(|p| match p.source {
+            Some(ProfileSource::CcSwitch) => 0,
+            Some(ProfileSource::Manual) => 1,
+            None => 2,
+        }),
+    }
+}
+
+/// Sort profiles by the name `ccuse list` shows: the display name when set,
+/// otherwise the profile name, ignoring case. Ties fall back to the profile name.
+pub fn sort_profiles_by_display_name(profiles: &mut [&Profile]) {
+    profiles.sort_by(|a, b| {
+        let key = |p: &Profile| p.display_name.as_ref().unwrap_or(&p.name).to_lowercase();
+        key(a).cmp(&key(b)).then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, template: Option<&str>) -> Profile {
+        Profile {
+            name: name.to_string(),
+            template_source: template.map(String::from),
+            ..Profile::default()
+        }
+    }
+
+    fn names(group: &[&Profile]) -> Vec<String> {
+        group.iter().map(|p| p.name.clone()).collect()
+    }
+
+    #[test]
+    fn groups_profiles_by_template_source() {
+        let profiles = [
+            profile("work", Some("openrouter")),
+            profile("manual", None),
+            profile("home", Some("anthropic")),
+            profile("proxy", Some("openrouter")),
+            profile("legacy", None),
+        ];
+
+        let groups = group_profiles_by_template(&profiles);
+
+        let shape: Vec<_> = groups
+            .iter()
+            .map(|(template, group)| (*template, names(group)))
+            .collect();
+        assert_eq!(
+            shape,
+            [
+                (None, names(&[&profiles[1], &profiles[4]])),
+                (Some("anthropic"), names(&[&profiles[2]])),
+                (Some("openrouter"), names(&[&profiles[0], &profiles[3]])),
+            ]
+        );
+    }
+
+    #[test]
+    fn grouping_no_profiles_gives_no_groups() {
+        assert!(group_profiles_by_template(&[]).is_empty());
+    }
+
+    #[test]
+    fn sorts_by_display_name_ignoring_case() {
+        let mut b = profile("b", None);
+        b.display_name = Some("alpha".into());
+        let profiles = [profile("Charlie", None), b, profile("beta", None)];
+        let mut sorted: Vec<_> = profiles.iter().collect();
+
+        sort_profiles(&mut sorted, ProfileSort::Name);
+
+        assert_eq!(names(&sorted), ["b", "beta", "Charlie"]);
+    }
+}
@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Number of launches kept in `history.json`; older ones are dropped.
+pub const HISTORY_LIMIT: usize = 50;
+
+/// Runtime state ccuse keeps between runs in `<config_dir>/state.json`.
+///
+/// Unlike [`Config`](super::Config), this holds which profiles to launch
+/// rather than how ccuse behaves; `last_used` is rewritten by `ccuse use` as
+/// a side effect.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct State {
+    /// Name of the profile most recently launched with `ccuse use`
+    #[serde(default)]
+    pub last_used: Option<String>,
+
+    /// Profile `ccuse use` launches when no name is given, set with `ccuse default`
+    #[serde(default)]
+    pub default_profile: Option<String>,
+}
+
+/// One launch recorded in `<config_dir>/history.json` by `ccuse use`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Name of the launched profile
+    pub name: String,
+    pub timestamp: DateTime<Utc>,
+    /// Resource group the session was tagged with, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_group: Option<String>,
+}
@@ -1,30 +1,134 @@
-use crate::config::Profile;
+pub mod migrations;
+
+use crate::config::inheritance::resolve_inheritance;
+use crate::config::lock::{LockMode, StorageLock};
+use crate::config::orphans::{
+    find_orphaned_profiles, DanglingName, Inconsistencies, NameReference,
+};
+use crate::config::state::HISTORY_LIMIT;
+use crate::config::validation::validate_profile_name;
+use crate::config::{secrets, Config, LaunchSettings, Profile, State, UsageRecord};
 use crate::error::{Error, Result};
+use chrono::Utc;
+use colored::Colorize;
 use directories::ProjectDirs;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use std::fs;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Write `content` to `path` through a temporary file in the same directory,
+/// so a crash mid-write leaves either the old or the new file, never a
+/// truncated one.
+///
+/// # Errors
+///
+/// Returns an error if the temporary file cannot be written or renamed over `path`.
+pub fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| Error::ConfigError(format!("Invalid file path: {}", path.display())))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".tmp-{}", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(content.as_ref())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+    if result.is_err() {
+        fs::remove_file(&temp_path).ok();
+    }
+    Ok(result?)
+}
+
+/// Environment variable that points ccuse at another configuration directory
+pub const CONFIG_DIR_ENV: &str = "CCUSE_CONFIG_DIR";
+
+/// Resolve the configuration directory: `CCUSE_CONFIG_DIR` when set and not
+/// empty, otherwise the platform default (e.g. `~/.config/ccuse`).
+///
+/// # Errors
+///
+/// Returns an error if the platform config directory cannot be determined.
+pub fn resolve_config_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(CONFIG_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        // Claude and its hooks may run from another working directory
+        return Ok(std::env::current_dir()?.join(dir));
+    }
+
+    let project_dirs = ProjectDirs::from("com", "ccuse", "ccuse")
+        .ok_or_else(|| Error::ConfigError("Failed to determine config directory".into()))?;
+    Ok(project_dirs.config_dir().to_path_buf())
+}
+
+/// Directory under the config directory holding `remove --all` backups
+pub const BACKUPS_DIR: &str = "backups";
+
+/// Directory under the config directory holding cached command output
+pub const CACHE_DIR: &str = "cache";
+
+/// Directories under the config directory that are not profiles
+pub(crate) const RESERVED_DIRS: [&str; 2] = [BACKUPS_DIR, CACHE_DIR];
+
+/// Number of backups kept before the oldest are pruned
+const MAX_BACKUPS: usize = 5;
 
 pub struct Storage {
     config_dir: PathBuf,
+    _lock: Option<Arc<StorageLock>>,
 }
 
 impl Storage {
-    /// Create a new Storage instance.
+    /// Create a new Storage instance holding a shared lock, for read-only commands.
     ///
     /// # Errors
     ///
-    /// Returns an error if config directory cannot be determined or created.
+    /// Returns an error if config directory cannot be determined or created,
+    /// or another ccuse command holds the lock for too long.
     pub fn new() -> Result<Self> {
-        let project_dirs = ProjectDirs::from("com", "ccuse", "ccuse")
-            .ok_or_else(|| Error::ConfigError("Failed to determine config directory".into()))?;
+        Self::open(LockMode::Shared)
+    }
+
+    /// Create a new Storage instance holding an exclusive lock, for commands
+    /// that modify profiles.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if config directory cannot be determined or created,
+    /// or another ccuse command holds the lock for too long.
+    pub fn new_exclusive() -> Result<Self> {
+        Self::open(LockMode::Exclusive)
+    }
+
+    /// A Storage for `config_dir` that holds no lock, for tests.
+    #[cfg(test)]
+    pub(crate) fn in_dir(config_dir: &Path) -> Self {
+        Self {
+            config_dir: config_dir.to_path_buf(),
+            _lock: None,
+        }
+    }
 
-        let config_dir = project_dirs.config_dir().to_path_buf();
+    fn open(mode: LockMode) -> Result<Self> {
+        let config_dir = resolve_config_dir()?;
 
         if !config_dir.exists() {
             fs::create_dir_all(&config_dir)?;
         }
 
-        Ok(Self { config_dir })
+        let lock = StorageLock::acquire(&config_dir, mode)?;
+        Ok(Self {
+            config_dir,
+            _lock: Some(lock),
+        })
     }
 
     #[must_use]
@@ -47,6 +151,131 @@ impl Storage {
             .join("settings.json")
     }
 
+    /// Get the compressed settings path for a specific profile
+    /// Path: ~/.config/ccuse/<profile-name>/settings.json.gz
+    #[must_use]
+    pub fn compressed_settings_path(&self, profile_name: &str) -> PathBuf {
+        self.profile_settings_dir(profile_name)
+            .join("settings.json.gz")
+    }
+
+    /// Check whether a profile is stored as settings.json.gz
+    #[must_use]
+    pub fn is_profile_compressed(&self, profile_name: &str) -> bool {
+        !self.profile_settings_path(profile_name).exists()
+            && self.compressed_settings_path(profile_name).exists()
+    }
+
+    /// Check whether a profile has a settings file in either format
+    #[must_use]
+    pub fn settings_file_exists(&self, profile_name: &str) -> bool {
+        self.profile_settings_path(profile_name).exists()
+            || self.compressed_settings_path(profile_name).exists()
+    }
+
+    /// Get the on-disk size of a profile's settings file in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile has no settings file.
+    pub fn profile_size_on_disk(&self, profile_name: &str) -> Result<u64> {
+        let path = if self.is_profile_compressed(profile_name) {
+            self.compressed_settings_path(profile_name)
+        } else {
+            self.profile_settings_path(profile_name)
+        };
+        Ok(fs::metadata(path)?.len())
+    }
+
+    /// Get a settings file that can be passed to Claude Code.
+    ///
+    /// Compressed profiles are decompressed into a private temporary file,
+    /// since Claude Code can only read plain JSON; it is removed when the
+    /// returned value is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile has no settings file or it cannot be decompressed.
+    pub fn launch_settings(&self, profile_name: &str) -> Result<LaunchSettings> {
+        if !self.is_profile_compressed(profile_name) {
+            return Ok(LaunchSettings::Persistent(
+                self.profile_settings_path(profile_name),
+            ));
+        }
+
+        LaunchSettings::temporary(&self.read_settings(profile_name)?)
+    }
+
+    /// Compress a profile's settings.json into settings.json.gz.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile does not exist or cannot be rewritten.
+    pub fn compress_profile(&self, profile_name: &str) -> Result<()> {
+        let plain = self.profile_settings_path(profile_name);
+        if !plain.exists() {
+            if self.is_profile_compressed(profile_name) {
+                return Ok(());
+            }
+            return Err(Error::ProfileNotFound(profile_name.into()));
+        }
+
+        let content = fs::read_to_string(&plain)?;
+        self.write_compressed(profile_name, &content)?;
+        fs::remove_file(plain)?;
+        Ok(())
+    }
+
+    /// Convert a compressed profile back to a plain settings.json.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile does not exist or cannot be rewritten.
+    pub fn decompress_profile(&self, profile_name: &str) -> Result<()> {
+        if !self.is_profile_compressed(profile_name) {
+            if self.profile_settings_path(profile_name).exists() {
+                return Ok(());
+            }
+            return Err(Error::ProfileNotFound(profile_name.into()));
+        }
+
+        let content = self.read_settings(profile_name)?;
+        write_atomic(&self.profile_settings_path(profile_name), content)?;
+        fs::remove_file(self.compressed_settings_path(profile_name))?;
+        Ok(())
+    }
+
+    /// Read the raw settings JSON of a profile, decompressing if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::ProfileNotFound` if the profile has no settings file,
+    /// or an error if it cannot be read.
+    pub fn read_settings(&self, profile_name: &str) -> Result<String> {
+        let plain = self.profile_settings_path(profile_name);
+        if plain.exists() {
+            return Ok(fs::read_to_string(plain)?);
+        }
+
+        let compressed = self.compressed_settings_path(profile_name);
+        if !compressed.exists() {
+            return Err(Error::ProfileNotFound(profile_name.into()));
+        }
+
+        let mut content = String::new();
+        GzDecoder::new(fs::File::open(compressed)?).read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    fn write_compressed(&self, profile_name: &str, content: &str) -> Result<()> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(content.as_bytes())?;
+        write_atomic(
+            &self.compressed_settings_path(profile_name),
+            encoder.finish()?,
+        )
+    }
+
     /// Ensure the profile settings directory exists and return the settings.json path
     ///
     /// # Errors
@@ -60,44 +289,254 @@ impl Storage {
         Ok(self.profile_settings_path(profile_name))
     }
 
+    /// Get the global config path
+    /// Path: ~/.config/ccuse/config.json
+    #[must_use]
+    pub fn config_path(&self) -> PathBuf {
+        self.config_dir.join("config.json")
+    }
+
+    /// Load the global config, falling back to defaults if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if config.json exists but cannot be read or deserialized.
+    pub fn load_config(&self) -> Result<Config> {
+        let path = self.config_path();
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        let config: Config = serde_json::from_str(&content)?;
+        Ok(config)
+    }
+
+    /// Save the global config.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if config cannot be serialized or written to file.
+    pub fn save_config(&self, config: &Config) -> Result<()> {
+        let content = serde_json::to_string_pretty(config)?;
+        write_atomic(&self.config_path(), content)?;
+        Ok(())
+    }
+
+    /// Get the runtime state path
+    /// Path: ~/.config/ccuse/state.json
+    #[must_use]
+    pub fn state_path(&self) -> PathBuf {
+        self.config_dir.join("state.json")
+    }
+
+    /// Load the runtime state, falling back to defaults if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if state.json exists but cannot be read or deserialized.
+    pub fn load_state(&self) -> Result<State> {
+        let path = self.state_path();
+        if !path.exists() {
+            return Ok(State::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        let state: State = serde_json::from_str(&content)?;
+        Ok(state)
+    }
+
+    /// Save the runtime state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if state cannot be serialized or written to file.
+    pub fn save_state(&self, state: &State) -> Result<()> {
+        let content = serde_json::to_string_pretty(state)?;
+        write_atomic(&self.state_path(), content)?;
+        Ok(())
+    }
+
+    /// Record `name` as the most recently used profile.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if state.json cannot be read or written.
+    pub fn save_last_used(&self, name: &str) -> Result<()> {
+        let mut state = self.load_state()?;
+        state.last_used = Some(name.to_string());
+        self.save_state(&state)
+    }
+
+    /// Name of the most recently used profile, if any profile has been used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if state.json exists but cannot be read or deserialized.
+    pub fn load_last_used(&self) -> Result<Option<String>> {
+        Ok(self.load_state()?.last_used)
+    }
+
+    /// Get the launch history path
+    /// Path: ~/.config/ccuse/history.json
+    #[must_use]
+    pub fn history_path(&self) -> PathBuf {
+        self.config_dir.join("history.json")
+    }
+
+    /// Append a launch of `name`, tagged with `resource_group`, to the
+    /// history, keeping the newest [`HISTORY_LIMIT`] records.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if history.json cannot be read or written.
+    pub fn record_usage(&self, name: &str, resource_group: Option<&str>) -> Result<()> {
+        let mut history = self.load_history()?;
+        history.push(UsageRecord {
+            name: name.to_string(),
+            timestamp: Utc::now(),
+            resource_group: resource_group.map(str::to_string),
+        });
+        let excess = history.len().saturating_sub(HISTORY_LIMIT);
+        history.drain(..excess);
+
+        let content = serde_json::to_string_pretty(&history)?;
+        write_atomic(&self.history_path(), content)?;
+        Ok(())
+    }
+
+    /// Recorded launches, oldest first; empty if nothing was recorded yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if history.json exists but cannot be read or deserialized.
+    pub fn load_history(&self) -> Result<Vec<UsageRecord>> {
+        let path = self.history_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Delete the launch history.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if history.json exists but cannot be removed.
+    pub fn clear_history(&self) -> Result<()> {
+        let path = self.history_path();
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Set the profile `ccuse use` launches without a name, or clear it with `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if state.json cannot be read or written.
+    pub fn save_default_profile(&self, name: Option<&str>) -> Result<()> {
+        let mut state = self.load_state()?;
+        state.default_profile = name.map(str::to_string);
+        self.save_state(&state)
+    }
+
+    /// Name of the default profile, if one has been set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if state.json exists but cannot be read or deserialized.
+    pub fn load_default_profile(&self) -> Result<Option<String>> {
+        Ok(self.load_state()?.default_profile)
+    }
+
     /// Load a single profile from its settings.json
     ///
     /// # Errors
     ///
     /// Returns an error if settings.json cannot be read or deserialized.
     fn load_profile_from_file(&self, name: &str) -> Result<Profile> {
-        let path = self.profile_settings_path(name);
-        if !path.exists() {
-            return Err(Error::ProfileNotFound(name.into()));
+        let content = self.read_settings(name)?;
+        let mut settings: serde_json::Value = serde_json::from_str(&content)?;
+        let migrated = migrations::migrate(&mut settings)?;
+        let profile: Profile = serde_json::from_value(settings)?;
+
+        // Write the upgraded layout back so the migration runs only once
+        if migrated {
+            if let Err(e) = self.save_migrated(name) {
+                eprintln!(
+                    "{} could not save the migrated settings of '{name}': {e}",
+                    "Warning:".yellow()
+                );
+            }
         }
-        let content = fs::read_to_string(&path)?;
-        let profile: Profile = serde_json::from_str(&content)?;
         Ok(profile)
     }
 
+    /// Rewrite a profile's settings in the current layout.
+    ///
+    /// Read-only commands load profiles too, so the lock is made exclusive
+    /// for the write, and the settings are read again under it in case
+    /// another process changed them in the meantime.
+    fn save_migrated(&self, name: &str) -> Result<()> {
+        let _lock = StorageLock::acquire(&self.config_dir, LockMode::Exclusive)?;
+        let mut settings: serde_json::Value = serde_json::from_str(&self.read_settings(name)?)?;
+        if migrations::migrate(&mut settings)? {
+            self.write_settings(name, &serde_json::to_string_pretty(&settings)?)?;
+        }
+        Ok(())
+    }
+
     /// Save a single profile to its settings.json
     ///
     /// # Errors
     ///
     /// Returns an error if profile cannot be serialized or written to file.
     fn save_profile_to_file(&self, profile: &Profile) -> Result<()> {
-        let path = self.ensure_profile_settings_dir(&profile.name)?;
-        let content = serde_json::to_string_pretty(profile)?;
-        fs::write(path, content)?;
+        if RESERVED_DIRS.contains(&profile.name.as_str()) {
+            return Err(Error::ConfigError(format!(
+                "'{}' is reserved for ccuse's own data; choose another profile name",
+                profile.name
+            )));
+        }
+        let started = Instant::now();
+        let mut settings = serde_json::to_value(profile)?;
+        settings[migrations::SCHEMA_VERSION_KEY] = migrations::CURRENT_SCHEMA_VERSION.into();
+        self.write_settings(&profile.name, &serde_json::to_string_pretty(&settings)?)?;
+        tracing::debug!(profile = %profile.name, elapsed = ?started.elapsed(), "saved profile");
         Ok(())
     }
 
-    /// Load all profiles from storage by scanning config directory.
+    /// Write raw settings JSON for a profile, compressing if needed
+    fn write_settings(&self, profile_name: &str, content: &str) -> Result<()> {
+        let path = self.ensure_profile_settings_dir(profile_name)?;
+
+        // Keep compressed profiles compressed, and compress new writes if configured
+        if self.is_profile_compressed(profile_name) || self.load_config()?.compress_profiles {
+            self.write_compressed(profile_name, content)?;
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        } else {
+            write_atomic(&path, content)?;
+        }
+        Ok(())
+    }
+
+    /// List the names of all candidate profile directories in the config directory.
+    ///
+    /// Hidden directories are skipped. A directory is listed whether or not it
+    /// contains a settings.json.
     ///
     /// # Errors
     ///
-    /// Returns an error if profiles cannot be loaded.
-    pub fn load_profiles(&self) -> Result<Vec<Profile>> {
-        let mut profiles = Vec::new();
+    /// Returns an error if the config directory cannot be read.
+    pub fn profile_dir_names(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
 
         // Scan config directory for profile directories
         if !self.config_dir.exists() {
-            return Ok(profiles);
+            return Ok(names);
         }
 
         for entry in fs::read_dir(&self.config_dir)? {
@@ -108,17 +547,34 @@ impl Storage {
                 continue;
             }
 
-            let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
 
-            // Skip hidden directories
-            if dir_name.starts_with('.') {
+            // Skip hidden directories and ccuse's own data
+            if dir_name.starts_with('.') || RESERVED_DIRS.contains(&dir_name) {
                 continue;
             }
 
+            names.push(dir_name.to_string());
+        }
+
+        Ok(names)
+    }
+
+    /// Load all profiles from storage by scanning config directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if profiles cannot be loaded.
+    pub fn load_profiles(&self) -> Result<Vec<Profile>> {
+        let started = Instant::now();
+        let mut profiles = Vec::new();
+
+        for dir_name in self.profile_dir_names()? {
             // Try to load profile from settings.json
-            let settings_path = path.join("settings.json");
-            if settings_path.exists() {
-                match self.load_profile_from_file(dir_name) {
+            if self.settings_file_exists(&dir_name) {
+                match self.load_profile_from_file(&dir_name) {
                     Ok(profile) => profiles.push(profile),
                     Err(e) => {
                         eprintln!("Warning: Failed to load profile '{}': {}", dir_name, e);
@@ -127,10 +583,15 @@ impl Storage {
             }
         }
 
+        tracing::debug!(count = profiles.len(), elapsed = ?started.elapsed(), "loaded profiles");
         Ok(profiles)
     }
 
-    /// Save profiles to storage.
+    /// Save profiles to storage as-is.
+    ///
+    /// Unlike [`Storage::add_profile`] and [`Storage::update_profile`], this
+    /// keeps each profile's `updated_at`, so a CC-Switch sync can store the
+    /// database's timestamps.
     ///
     /// # Errors
     ///
@@ -144,54 +605,193 @@ impl Storage {
         Ok(())
     }
 
+    /// Check whether a profile exists without loading or parsing it.
+    ///
+    /// Only the settings file is looked up, so a profile whose settings
+    /// fail to parse still counts as existing.
+    ///
+    /// # Errors
+    ///
+    /// Currently infallible; returns `Result` to match the other lookups.
+    pub fn profile_exists_fast(&self, name: &str) -> Result<bool> {
+        Ok(self.settings_file_exists(name))
+    }
+
     /// Get a profile by name.
     ///
     /// # Errors
     ///
     /// Returns an error if profile cannot be loaded.
     pub fn get_profile(&self, name: &str) -> Result<Option<Profile>> {
+        let started = Instant::now();
         // Try to load profile directly from settings.json
         match self.load_profile_from_file(name) {
-            Ok(profile) => Ok(Some(profile)),
+            Ok(profile) => {
+                tracing::debug!(profile = name, elapsed = ?started.elapsed(), "loaded profile");
+                Ok(Some(profile))
+            }
             Err(Error::ProfileNotFound(_)) => Ok(None),
             Err(e) => Err(e),
         }
     }
 
-    /// Add a new profile.
+    /// Scan for profile directories without a loadable profile, and for
+    /// profile names used in state.json or as an `extends` parent that have
+    /// no profile directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config directory or state.json cannot be read.
+    pub fn find_inconsistencies(&self) -> Result<Inconsistencies> {
+        let orphans = find_orphaned_profiles(self)?;
+        let mut dangling_names = Vec::new();
+
+        let state = self.load_state()?;
+        let state_names = [
+            (state.default_profile, NameReference::DefaultProfile),
+            (state.last_used, NameReference::LastUsed),
+        ];
+        for (name, referenced_by) in state_names {
+            if let Some(name) = name {
+                if !self.profile_dir_exists(&name) {
+                    dangling_names.push(DanglingName {
+                        name,
+                        referenced_by,
+                    });
+                }
+            }
+        }
+
+        for profile in self.load_profiles()? {
+            if let Some(parent) = profile.extends {
+                if !self.profile_dir_exists(&parent) {
+                    dangling_names.push(DanglingName {
+                        name: parent,
+                        referenced_by: NameReference::ExtendedBy(profile.name),
+                    });
+                }
+            }
+        }
+
+        Ok(Inconsistencies {
+            orphans,
+            dangling_names,
+        })
+    }
+
+    /// Remove a dangling name from where it is used: clear it in state.json,
+    /// or drop the `extends` of the profile that names it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if state.json or the extending profile cannot be
+    /// read or written.
+    pub fn drop_dangling_name(&self, dangling: &DanglingName) -> Result<()> {
+        match &dangling.referenced_by {
+            NameReference::DefaultProfile => self.save_default_profile(None),
+            NameReference::LastUsed => {
+                let mut state = self.load_state()?;
+                state.last_used = None;
+                self.save_state(&state)
+            }
+            NameReference::ExtendedBy(child) => {
+                let mut profile = self
+                    .get_profile(child)?
+                    .ok_or_else(|| Error::ProfileNotFound(child.clone()))?;
+                profile.extends = None;
+                self.update_profile(profile)
+            }
+        }
+    }
+
+    /// Whether `name` has a profile directory, loadable or not.
+    fn profile_dir_exists(&self, name: &str) -> bool {
+        validate_profile_name(name).is_ok() && self.profile_settings_dir(name).is_dir()
+    }
+
+    /// Get a profile by name with its `extends` chain flattened, as it is launched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a profile in the chain cannot be loaded, a parent
+    /// does not exist, or the chain is circular.
+    pub fn resolve_profile(&self, name: &str) -> Result<Option<Profile>> {
+        let Some(profile) = self.get_profile(name)? else {
+            return Ok(None);
+        };
+        resolve_inheritance(profile, |parent| self.get_profile(parent)).map(Some)
+    }
+
+    /// Add a new profile, setting its `updated_at` to now.
     ///
     /// # Errors
     ///
     /// Returns an error if profile already exists or cannot be saved.
-    pub fn add_profile(&self, profile: Profile) -> Result<()> {
-        // Check if profile already exists by trying to load it
-        if self.get_profile(&profile.name)?.is_some() {
+    pub fn add_profile(&self, mut profile: Profile) -> Result<()> {
+        // Check if profile already exists
+        if self.profile_exists_fast(&profile.name)? {
             return Err(Error::ProfileAlreadyExists(profile.name));
         }
 
         // Save profile to its settings.json
+        profile.updated_at = Utc::now();
         self.save_profile_to_file(&profile)?;
 
         Ok(())
     }
 
-    /// Update an existing profile.
+    /// Update an existing profile, setting its `updated_at` to now.
     ///
     /// # Errors
     ///
     /// Returns an error if profile does not exist or cannot be saved.
-    pub fn update_profile(&self, profile: Profile) -> Result<()> {
+    pub fn update_profile(&self, mut profile: Profile) -> Result<()> {
         // Check if profile exists
-        if self.get_profile(&profile.name)?.is_none() {
+        if !self.profile_exists_fast(&profile.name)? {
             return Err(Error::ProfileNotFound(profile.name));
         }
 
         // Update profile in its settings.json
+        profile.updated_at = Utc::now();
         self.save_profile_to_file(&profile)?;
 
         Ok(())
     }
 
+    /// Move a profile's plaintext secret env values into the OS keyring.
+    ///
+    /// Returns the keys that were moved; the profile is only rewritten if
+    /// there were any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile does not exist, the keyring is
+    /// unavailable, or the profile cannot be saved.
+    pub fn move_secrets_to_keyring(&self, name: &str) -> Result<Vec<String>> {
+        let mut profile = self
+            .get_profile(name)?
+            .ok_or_else(|| Error::ProfileNotFound(name.into()))?;
+
+        let moved = secrets::move_secrets_to_keyring(&mut profile)?;
+        if !moved.is_empty() {
+            self.update_profile(profile)?;
+        }
+        Ok(moved)
+    }
+
+    /// Delete a removed profile's keyring entries, if it has any.
+    ///
+    /// Failures only warn: the profile is going away either way.
+    fn forget_secrets(profile: &Profile) {
+        if let Err(e) = secrets::delete_secrets(&profile.name, &profile.env) {
+            eprintln!(
+                "{} keyring entries of '{}' were not deleted: {e}",
+                "Warning:".yellow(),
+                profile.name
+            );
+        }
+    }
+
     /// Remove a profile by name.
     ///
     /// # Errors
@@ -199,10 +799,14 @@ impl Storage {
     /// Returns an error if profile does not exist or cannot be removed.
     pub fn remove_profile(&self, name: &str) -> Result<()> {
         // Check if profile exists
-        if self.get_profile(name)?.is_none() {
+        if !self.profile_exists_fast(name)? {
             return Err(Error::ProfileNotFound(name.into()));
         }
 
+        if let Ok(Some(profile)) = self.get_profile(name) {
+            Self::forget_secrets(&profile);
+        }
+
         // Remove profile directory
         let profile_dir = self.profile_settings_dir(name);
         if profile_dir.exists() {
@@ -212,32 +816,610 @@ impl Storage {
         Ok(())
     }
 
-    /// Remove all profiles.
+    /// Remove all profiles, after backing up the config directory.
+    ///
+    /// Returns the path of the backup.
     ///
     /// # Errors
     ///
-    /// Returns an error if profiles cannot be removed.
-    pub fn remove_all_profiles(&self) -> Result<()> {
+    /// Returns an error if the backup cannot be written or profiles cannot be removed.
+    pub fn remove_all_profiles(&self) -> Result<PathBuf> {
         // Load all profiles first
         let profiles = self.load_profiles()?;
 
+        // Nothing is deleted unless the backup succeeded
+        let backup = self.backup_config_dir()?;
+
         // Remove all profile directories
         for profile in profiles {
+            Self::forget_secrets(&profile);
             let profile_dir = self.profile_settings_dir(&profile.name);
             if profile_dir.exists() {
                 fs::remove_dir_all(&profile_dir)?;
             }
         }
 
+        Ok(backup)
+    }
+
+    /// Get the cache directory
+    /// Path: ~/.config/ccuse/cache/
+    #[must_use]
+    pub fn cache_dir(&self) -> PathBuf {
+        self.config_dir.join(CACHE_DIR)
+    }
+
+    /// Get the backups directory
+    /// Path: ~/.config/ccuse/backups/
+    #[must_use]
+    pub fn backups_dir(&self) -> PathBuf {
+        self.config_dir.join(BACKUPS_DIR)
+    }
+
+    /// Copy the config directory into `backups/<timestamp>/` and prune all
+    /// but the newest `MAX_BACKUPS` backups.
+    ///
+    /// A second backup within the same second gets a `-01`, `-02`, ...
+    /// suffix instead of sharing the directory.
+    ///
+    /// Returns the path of the new backup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config directory cannot be copied.
+    pub fn backup_config_dir(&self) -> Result<PathBuf> {
+        let timestamp = Utc::now().format("%Y%m%d-%H%M%S").to_string();
+        fs::create_dir_all(self.backups_dir())?;
+
+        // Count up from the highest suffix taken this second, even if pruning
+        // freed a lower one, so the newest backup always sorts last
+        let mut suffix = self
+            .list_backups()?
+            .iter()
+            .filter_map(|name| match name.strip_prefix(timestamp.as_str()) {
+                Some("") => Some(0),
+                Some(rest) => rest.strip_prefix('-')?.parse::<u32>().ok(),
+                None => None,
+            })
+            .max()
+            .map(|taken| taken + 1);
+
+        // create_dir fails on an existing directory, so backups never merge
+        let backup = loop {
+            let backup = match suffix {
+                None => self.backups_dir().join(&timestamp),
+                Some(n) => self.backups_dir().join(format!("{timestamp}-{n:02}")),
+            };
+            match fs::create_dir(&backup) {
+                Ok(()) => break backup,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    suffix = Some(suffix.map_or(1, |n| n + 1));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+
+        for entry in fs::read_dir(&self.config_dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            // Skip the backups themselves, the cache, the lock file and in-flight writes
+            if RESERVED_DIRS.iter().any(|dir| name == *dir)
+                || name.to_string_lossy().starts_with('.')
+            {
+                continue;
+            }
+            copy_recursive(&entry.path(), &backup.join(name))?;
+        }
+
+        self.prune_backups()?;
+        Ok(backup)
+    }
+
+    /// Timestamps of the available backups, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backups directory cannot be read.
+    pub fn list_backups(&self) -> Result<Vec<String>> {
+        let dir = self.backups_dir();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.path().is_dir() {
+                if let Some(name) = entry.file_name().to_str() {
+                    backups.push(name.to_string());
+                }
+            }
+        }
+        // Timestamps sort chronologically as strings
+        backups.sort();
+        Ok(backups)
+    }
+
+    fn prune_backups(&self) -> Result<()> {
+        let backups = self.list_backups()?;
+        let excess = backups.len().saturating_sub(MAX_BACKUPS);
+        for name in &backups[..excess] {
+            fs::remove_dir_all(self.backups_dir().join(name))?;
+        }
+        Ok(())
+    }
+
+    /// Copy the profiles in backup `timestamp` back into the config directory.
+    ///
+    /// Profiles in the backup replace those with the same name; other
+    /// profiles are left alone. Only profile directories are restored:
+    /// config.json, state.json (with the default profile) and the history
+    /// are kept as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup does not exist or cannot be copied.
+    pub fn restore_backup(&self, timestamp: &str) -> Result<()> {
+        // Only names listed in backups/, so `..` and paths are rejected
+        if !self.list_backups()?.iter().any(|name| name == timestamp) {
+            return Err(Error::ConfigError(format!("Backup not found: {timestamp}")));
+        }
+        let backup = self.backups_dir().join(timestamp);
+
+        for entry in fs::read_dir(&backup)? {
+            let entry = entry?;
+            if !entry.path().is_dir() {
+                continue;
+            }
+            let target = self.config_dir.join(entry.file_name());
+            if target.is_dir() {
+                fs::remove_dir_all(&target)?;
+            }
+            copy_recursive(&entry.path(), &target)?;
+        }
         Ok(())
     }
 }
 
+/// Copy a file, or a directory with everything below it.
+fn copy_recursive(from: &Path, to: &Path) -> Result<()> {
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
 impl Default for Storage {
     fn default() -> Self {
         // Safe default - uses system temp directory
         Self {
             config_dir: std::env::temp_dir().join("ccuse"),
+            _lock: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn profile(name: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            ..Profile::default()
         }
     }
+
+    #[test]
+    fn backups_in_the_same_second_get_their_own_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        storage.add_profile(profile("work")).unwrap();
+
+        let first = storage.backup_config_dir().unwrap();
+        let second = storage.backup_config_dir().unwrap();
+        let third = storage.backup_config_dir().unwrap();
+
+        assert_ne!(first, second);
+        assert_ne!(second, third);
+        let names: Vec<_> = [&first, &second, &third]
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(storage.list_backups().unwrap(), names);
+    }
+
+    #[test]
+    fn backups_are_pruned_to_the_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        storage.add_profile(profile("work")).unwrap();
+
+        let backups: Vec<_> = (0..MAX_BACKUPS + 2)
+            .map(|_| storage.backup_config_dir().unwrap())
+            .collect();
+
+        let kept = storage.list_backups().unwrap();
+        assert_eq!(kept.len(), MAX_BACKUPS);
+        let newest = backups.last().unwrap().file_name().unwrap();
+        assert_eq!(kept.last().unwrap().as_str(), newest);
+    }
+
+    #[test]
+    fn restore_replaces_profiles_but_keeps_top_level_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        storage.add_profile(profile("work")).unwrap();
+        storage.add_profile(profile("home")).unwrap();
+        storage.save_default_profile(Some("work")).unwrap();
+
+        let backup = storage.remove_all_profiles().unwrap();
+        storage.add_profile(profile("new")).unwrap();
+        storage.save_default_profile(Some("new")).unwrap();
+
+        let timestamp = backup.file_name().unwrap().to_str().unwrap();
+        storage.restore_backup(timestamp).unwrap();
+
+        let mut names = storage.profile_dir_names().unwrap();
+        names.sort();
+        assert_eq!(names, ["home", "new", "work"]);
+        assert_eq!(
+            storage.load_default_profile().unwrap().as_deref(),
+            Some("new")
+        );
+    }
+
+    #[test]
+    fn restore_rejects_unknown_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        storage.backup_config_dir().unwrap();
+
+        assert!(storage.restore_backup("../backups").is_err());
+        assert!(storage.restore_backup("20000101-000000").is_err());
+    }
+
+    #[test]
+    fn consistent_config_has_no_inconsistencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        storage.add_profile(profile("base")).unwrap();
+        storage
+            .add_profile(Profile {
+                extends: Some("base".to_string()),
+                ..profile("child")
+            })
+            .unwrap();
+        storage.save_default_profile(Some("child")).unwrap();
+        storage.save_last_used("base").unwrap();
+
+        assert!(storage.find_inconsistencies().unwrap().is_empty());
+    }
+
+    #[test]
+    fn finds_orphaned_dirs_and_dangling_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        storage
+            .add_profile(Profile {
+                extends: Some("gone".to_string()),
+                ..profile("child")
+            })
+            .unwrap();
+        storage.save_default_profile(Some("removed")).unwrap();
+        storage.save_last_used("child").unwrap();
+        fs::create_dir(dir.path().join("empty")).unwrap();
+
+        let found = storage.find_inconsistencies().unwrap();
+
+        assert_eq!(found.orphans.missing_files, ["empty"]);
+        assert_eq!(
+            found.dangling_names,
+            [
+                DanglingName {
+                    name: "removed".to_string(),
+                    referenced_by: NameReference::DefaultProfile,
+                },
+                DanglingName {
+                    name: "gone".to_string(),
+                    referenced_by: NameReference::ExtendedBy("child".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_directory_with_broken_settings_is_not_a_dangling_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        fs::create_dir(dir.path().join("broken")).unwrap();
+        fs::write(dir.path().join("broken/settings.json"), "{").unwrap();
+        storage.save_last_used("broken").unwrap();
+
+        let found = storage.find_inconsistencies().unwrap();
+
+        assert_eq!(found.orphans.orphaned_dirs, [dir.path().join("broken")]);
+        assert!(found.dangling_names.is_empty());
+    }
+
+    #[test]
+    fn dropping_dangling_names_clears_state_and_extends() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        storage
+            .add_profile(Profile {
+                extends: Some("gone".to_string()),
+                ..profile("child")
+            })
+            .unwrap();
+        storage.save_default_profile(Some("removed")).unwrap();
+        storage.save_last_used("removed").unwrap();
+
+        for dangling in storage.find_inconsistencies().unwrap().dangling_names {
+            storage.drop_dangling_name(&dangling).unwrap();
+        }
+
+        assert!(storage.find_inconsistencies().unwrap().is_empty());
+        assert_eq!(storage.load_default_profile().unwrap(), None);
+        assert_eq!(storage.load_last_used().unwrap(), None);
+        let child = storage.get_profile("child").unwrap().unwrap();
+        assert_eq!(child.extends, None);
+    }
+
+    #[test]
+    fn profile_exists_fast_matches_get_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        storage.add_profile(profile("plain")).unwrap();
+        storage.add_profile(profile("packed")).unwrap();
+        storage.compress_profile("packed").unwrap();
+        fs::create_dir(dir.path().join("empty")).unwrap();
+
+        for name in ["plain", "packed", "empty", "missing"] {
+            assert_eq!(
+                storage.profile_exists_fast(name).unwrap(),
+                storage.get_profile(name).unwrap().is_some(),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn profile_exists_fast_counts_unparseable_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        fs::create_dir(dir.path().join("broken")).unwrap();
+        fs::write(dir.path().join("broken/settings.json"), "{").unwrap();
+
+        assert!(storage.profile_exists_fast("broken").unwrap());
+        assert!(storage.get_profile("broken").is_err());
+    }
+
+    #[test]
+    #[ignore = "timing comparison, run with --ignored --nocapture"]
+    fn profile_exists_fast_is_faster_than_get_profile() {
+        const PROFILES: usize = 50;
+        const ROUNDS: usize = 20;
+
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        let names: Vec<String> = (0..PROFILES).map(|i| format!("profile-{i}")).collect();
+        for name in &names {
+            let mut env = HashMap::new();
+            env.insert(
+                "ANTHROPIC_BASE_URL".to_string(),
+                format!("https://{name}.example.com"),
+            );
+            env.insert("ANTHROPIC_AUTH_TOKEN".to_string(), "sk-test".repeat(8));
+            storage
+                .add_profile(Profile {
+                    env,
+                    ..profile(name)
+                })
+                .unwrap();
+        }
+
+        let started = Instant::now();
+        for _ in 0..ROUNDS {
+            for name in &names {
+                assert!(storage.get_profile(name).unwrap().is_some());
+            }
+        }
+        let full = started.elapsed();
+
+        let started = Instant::now();
+        for _ in 0..ROUNDS {
+            for name in &names {
+                assert!(storage.profile_exists_fast(name).unwrap());
+            }
+        }
+        let fast = started.elapsed();
+
+        println!(
+            "{PROFILES} profiles x {ROUNDS}: get_profile {full:?}, profile_exists_fast {fast:?}"
+        );
+        assert!(fast < full);
+    }
+
+    /// A profile with every field set, so a lossy round trip shows up.
+    fn full_profile(name: &str) -> Profile {
+        use crate::config::{McpPermission, Permissions, ProfileSource, Signal, TimeoutAction};
+        use chrono::TimeZone;
+
+        Profile {
+            name: name.to_string(),
+            display_name: Some("Full — ünïcode".to_string()),
+            env: HashMap::from([
+                (
+                    "ANTHROPIC_BASE_URL".to_string(),
+                    "https://api.example.com".to_string(),
+                ),
+                (
+                    "ANTHROPIC_AUTH_TOKEN".to_string(),
+                    "sk-test\n\"quoted\"".to_string(),
+                ),
+            ]),
+            permissions: Permissions {
+                enabled: Some(true),
+                mcp: Some(vec![McpPermission {
+                    name: "files".to_string(),
+                    enabled: Some(false),
+                }]),
+                command: Some(vec!["git status".to_string()]),
+            },
+            enabled_plugins: Some(HashMap::from([("lint".to_string(), true)])),
+            always_thinking_enabled: Some(true),
+            api_timeout_ms: Some(30_000),
+            category: Some("work".to_string()),
+            tags: vec!["a".to_string(), "b".to_string()],
+            source: Some(ProfileSource::Manual),
+            timeout_action: Some(TimeoutAction::Signal),
+            timeout_signal: Some(Signal::Hup),
+            log_level: Some("debug".to_string()),
+            resource_group: Some("team-a".to_string()),
+            port_forward: Some("8080:https://api.example.com".to_string()),
+            tee_output_file: Some(PathBuf::from("/tmp/claude.log")),
+            experimental_flags: vec!["fast_mode".to_string()],
+            launch_args: vec!["--verbose".to_string()],
+            template_source: Some("proxy".to_string()),
+            log_redact_patterns: vec!["sk-[a-z]+".to_string()],
+            cpu_limit_percent: Some(50),
+            wait_for_ports: vec![8080, 9090],
+            claude_version: Some("1.2.3".to_string()),
+            extends: Some("base".to_string()),
+            created_at: Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap(),
+            updated_at: Utc.with_ymd_and_hms(2024, 6, 7, 8, 9, 10).unwrap(),
+        }
+    }
+
+    #[test]
+    fn compress_decompress_round_trip_preserves_all_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        storage.save_profile_to_file(&full_profile("full")).unwrap();
+        let original = storage.read_settings("full").unwrap();
+        let loaded = serde_json::to_value(storage.get_profile("full").unwrap()).unwrap();
+
+        storage.compress_profile("full").unwrap();
+        assert!(storage.is_profile_compressed("full"));
+        assert!(!storage.profile_settings_path("full").exists());
+        assert_eq!(storage.read_settings("full").unwrap(), original);
+        assert_eq!(
+            serde_json::to_value(storage.get_profile("full").unwrap()).unwrap(),
+            loaded
+        );
+
+        storage.decompress_profile("full").unwrap();
+        assert!(!storage.is_profile_compressed("full"));
+        assert!(!storage.compressed_settings_path("full").exists());
+        assert_eq!(
+            fs::read_to_string(storage.profile_settings_path("full")).unwrap(),
+            original
+        );
+        assert_eq!(
+            serde_json::to_value(storage.get_profile("full").unwrap()).unwrap(),
+            serde_json::to_value(full_profile("full")).unwrap()
+        );
+    }
+
+    #[test]
+    fn compressed_profiles_stay_compressed_when_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        storage.add_profile(profile("packed")).unwrap();
+        storage.compress_profile("packed").unwrap();
+        // Compressing twice is a no-op
+        storage.compress_profile("packed").unwrap();
+
+        let mut packed = storage.get_profile("packed").unwrap().unwrap();
+        packed.category = Some("moved".to_string());
+        storage.update_profile(packed).unwrap();
+
+        assert!(storage.is_profile_compressed("packed"));
+        let packed = storage.get_profile("packed").unwrap().unwrap();
+        assert_eq!(packed.category.as_deref(), Some("moved"));
+    }
+
+    #[test]
+    fn compressing_a_missing_profile_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+
+        assert!(matches!(
+            storage.compress_profile("missing"),
+            Err(Error::ProfileNotFound(_))
+        ));
+        assert!(matches!(
+            storage.decompress_profile("missing"),
+            Err(Error::ProfileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn compressed_profiles_launch_from_a_private_temporary_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        storage.add_profile(profile("plain")).unwrap();
+        storage.add_profile(profile("packed")).unwrap();
+        storage.compress_profile("packed").unwrap();
+
+        let plain = storage.launch_settings("plain").unwrap();
+        assert!(matches!(plain, LaunchSettings::Persistent(_)));
+        assert_eq!(plain.path(), storage.profile_settings_path("plain"));
+
+        let packed = storage.launch_settings("packed").unwrap();
+        assert!(matches!(packed, LaunchSettings::Temporary(_)));
+        let path = packed.path().to_path_buf();
+        assert!(!path.starts_with(dir.path()));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            storage.read_settings("packed").unwrap()
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        drop(packed);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn loading_a_v0_profile_migrates_it_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_dir(dir.path());
+        fs::create_dir(dir.path().join("legacy")).unwrap();
+        let settings_path = dir.path().join("legacy/settings.json");
+        fs::write(
+            &settings_path,
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/settings_v0.json"
+            )),
+        )
+        .unwrap();
+
+        let profiles = storage.load_profiles().unwrap();
+
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].env["API_TIMEOUT_MS"], "600000");
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+        assert_eq!(
+            saved[migrations::SCHEMA_VERSION_KEY],
+            migrations::CURRENT_SCHEMA_VERSION
+        );
+        assert_eq!(saved["enabled_plugins"]["linter"], true);
+        // Migrating is not an edit
+        assert_eq!(saved["updated_at"], "2024-05-01T12:00:00Z");
+    }
 }
@@ -0,0 +1,151 @@
+use crate::error::{Error, Result};
+use serde_json::{json, Map, Value};
+
+/// Field of settings.json holding the layout version of the stored profile
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Layout written by this version of ccuse
+pub const CURRENT_SCHEMA_VERSION: u64 = 1;
+
+/// Migrations in order; entry `n` upgrades version `n` to `n + 1`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); CURRENT_SCHEMA_VERSION as usize] = [v0_to_v1];
+
+/// Layout version of `settings`; files written before versioning are version 0.
+#[must_use]
+pub fn schema_version(settings: &Value) -> u64 {
+    settings
+        .get(SCHEMA_VERSION_KEY)
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+}
+
+/// Upgrade `settings` to the current layout in place.
+///
+/// Returns whether anything was migrated.
+///
+/// # Errors
+///
+/// Returns an error if `settings` is not a JSON object or was written by a newer ccuse.
+pub fn migrate(settings: &mut Value) -> Result<bool> {
+    let version = schema_version(settings);
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(Error::ConfigError(format!(
+            "settings.json has schema version {version}, but this ccuse only supports up to {CURRENT_SCHEMA_VERSION}; upgrade ccuse"
+        )));
+    }
+    if version == CURRENT_SCHEMA_VERSION {
+        return Ok(false);
+    }
+
+    let object = settings
+        .as_object_mut()
+        .ok_or_else(|| Error::ConfigError("settings.json is not a JSON object".into()))?;
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(object);
+    }
+    object.insert(SCHEMA_VERSION_KEY.into(), CURRENT_SCHEMA_VERSION.into());
+    Ok(true)
+}
+
+/// Version 0 (before versioning) allowed `enabled_plugins` and
+/// `permissions.mcp` as plain lists of names, and env values that were
+/// numbers or booleans.
+fn v0_to_v1(settings: &mut Map<String, Value>) {
+    if let Some(Value::Array(names)) = settings.get("enabled_plugins") {
+        let plugins: Map<String, Value> = names
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|name| (name.to_string(), Value::Bool(true)))
+            .collect();
+        settings.insert("enabled_plugins".into(), Value::Object(plugins));
+    }
+
+    if let Some(Value::Array(servers)) = settings
+        .get_mut("permissions")
+        .and_then(|permissions| permissions.get_mut("mcp"))
+    {
+        for server in servers {
+            if let Some(name) = server.as_str().map(str::to_string) {
+                *server = json!({ "name": name, "enabled": true });
+            }
+        }
+    }
+
+    if let Some(Value::Object(env)) = settings.get_mut("env") {
+        for value in env.values_mut() {
+            if value.is_number() || value.is_boolean() {
+                *value = Value::String(value.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Profile;
+
+    /// settings.json as written before the layout was versioned.
+    const V0_FIXTURE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/settings_v0.json"
+    ));
+
+    #[test]
+    fn v0_fixture_loads_after_migration() {
+        let mut settings: Value = serde_json::from_str(V0_FIXTURE).unwrap();
+        assert_eq!(schema_version(&settings), 0);
+        assert!(serde_json::from_value::<Profile>(settings.clone()).is_err());
+
+        assert!(migrate(&mut settings).unwrap());
+        assert_eq!(schema_version(&settings), CURRENT_SCHEMA_VERSION);
+
+        let profile: Profile = serde_json::from_value(settings).unwrap();
+        assert_eq!(profile.name, "legacy");
+        assert_eq!(profile.env["API_TIMEOUT_MS"], "600000");
+        assert_eq!(profile.env["DISABLE_TELEMETRY"], "true");
+        assert_eq!(
+            profile.env["ANTHROPIC_BASE_URL"],
+            "https://proxy.example.com"
+        );
+        let plugins = profile.enabled_plugins.unwrap();
+        assert_eq!(plugins.len(), 2);
+        assert!(plugins["formatter"] && plugins["linter"]);
+        let mcp: Vec<_> = profile
+            .permissions
+            .mcp
+            .unwrap()
+            .into_iter()
+            .map(|server| (server.name, server.enabled))
+            .collect();
+        assert_eq!(
+            mcp,
+            [
+                ("filesystem".to_string(), Some(true)),
+                ("github".to_string(), Some(false))
+            ]
+        );
+    }
+
+    #[test]
+    fn current_layout_is_left_alone() {
+        let mut settings = json!({
+            "name": "work",
+            SCHEMA_VERSION_KEY: CURRENT_SCHEMA_VERSION,
+            "enabled_plugins": ["not", "migrated"],
+        });
+        let before = settings.clone();
+
+        assert!(!migrate(&mut settings).unwrap());
+        assert_eq!(settings, before);
+    }
+
+    #[test]
+    fn newer_layouts_and_non_objects_are_rejected() {
+        let mut newer = json!({ SCHEMA_VERSION_KEY: CURRENT_SCHEMA_VERSION + 1 });
+        assert!(migrate(&mut newer).is_err());
+
+        let mut list = json!(["not", "a", "profile"]);
+        assert!(migrate(&mut list).is_err());
+    }
+}
@@ -0,0 +1,321 @@
+use crate::config::Profile;
+use colored::Colorize;
+use regex::{Captures, Regex};
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Matches `${VAR}` template references between profile env vars.
+fn template_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("valid template pattern")
+    })
+}
+
+/// Matches `$VAR` and `${VAR}` references.
+fn var_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"\$(?:\{([A-Za-z_][A-Za-z0-9_]*)\}|([A-Za-z_][A-Za-z0-9_]*))")
+            .expect("valid variable pattern")
+    })
+}
+
+fn captured_name<'a>(caps: &'a Captures<'_>) -> &'a str {
+    caps.get(1)
+        .or_else(|| caps.get(2))
+        .map_or("", |m| m.as_str())
+}
+
+/// Expand `$VAR` / `${VAR}` references in profile env values using the parent
+/// shell environment.
+///
+/// Expansion is single-pass and non-recursive: substituted text is never
+/// re-scanned. References to variables missing from `parent_env` are left
+/// untouched, and reported on stderr when `warn_undefined` is set.
+#[must_use]
+pub fn substitute_from_parent(
+    profile_env: &HashMap<String, String>,
+    parent_env: &HashMap<String, String>,
+    warn_undefined: bool,
+) -> HashMap<String, String> {
+    profile_env
+        .iter()
+        .map(|(key, value)| {
+            let expanded = var_pattern().replace_all(value, |caps: &Captures<'_>| {
+                let name = captured_name(caps);
+                match parent_env.get(name) {
+                    Some(replacement) => replacement.clone(),
+                    None => {
+                        if warn_undefined {
+                            eprintln!(
+                                "{} ${name} referenced by {key} is not set in the environment",
+                                "Warning:".yellow()
+                            );
+                        }
+                        caps[0].to_string()
+                    }
+                }
+            });
+            (key.clone(), expanded.into_owned())
+        })
+        .collect()
+}
+
+/// Collect every `${KEY}` template variable referenced by env values.
+#[must_use]
+pub fn extract_template_vars(env: &HashMap<String, String>) -> HashSet<String> {
+    env.values()
+        .flat_map(|value| template_pattern().captures_iter(value))
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+/// Where a `${KEY}` template variable referenced by a profile is defined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateVarSource {
+    /// In the profile's own env
+    Own,
+    /// In the env of this `extends` ancestor, the closest one defining it
+    Inherited(String),
+    Undefined,
+}
+
+/// The template variables referenced anywhere in an `extends` chain, sorted,
+/// with where each one is defined.
+///
+/// `chain` starts with the profile itself, followed by its ancestors closest
+/// first. References are collected from the env the chain flattens to, so a
+/// parent value the profile overrides does not count.
+#[must_use]
+pub fn template_var_sources(chain: &[Profile]) -> Vec<(String, TemplateVarSource)> {
+    let mut flattened = HashMap::new();
+    for profile in chain.iter().rev() {
+        flattened.extend(profile.env.clone());
+    }
+
+    let mut vars: Vec<String> = extract_template_vars(&flattened).into_iter().collect();
+    vars.sort();
+
+    vars.into_iter()
+        .map(|var| {
+            let source = match chain.iter().position(|p| p.env.contains_key(&var)) {
+                Some(0) => TemplateVarSource::Own,
+                Some(i) => TemplateVarSource::Inherited(chain[i].name.clone()),
+                None => TemplateVarSource::Undefined,
+            };
+            (var, source)
+        })
+        .collect()
+}
+
+/// Expand `${KEY}` references using other vars from the same env.
+///
+/// References are resolved recursively so chains like `A=${B}`, `B=${C}`
+/// work; cycles and undefined keys are left unexpanded.
+#[must_use]
+pub fn expand_templates(env: &HashMap<String, String>) -> HashMap<String, String> {
+    fn expand(
+        key: &str,
+        env: &HashMap<String, String>,
+        resolving: &mut Vec<String>,
+    ) -> Option<String> {
+        let value = env.get(key)?;
+        if resolving.iter().any(|k| k == key) {
+            return None;
+        }
+
+        resolving.push(key.to_string());
+        let expanded = template_pattern()
+            .replace_all(value, |caps: &Captures<'_>| {
+                expand(&caps[1], env, resolving).unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned();
+        resolving.pop();
+
+        Some(expanded)
+    }
+
+    env.keys()
+        .map(|key| {
+            let value = expand(key, env, &mut Vec::new()).unwrap_or_default();
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn substitutes_both_reference_forms() {
+        let parent = env(&[("HOME", "/home/me"), ("USER", "me")]);
+        let profile = env(&[("CACHE", "$HOME/.cache/${USER}-claude")]);
+
+        let expanded = substitute_from_parent(&profile, &parent, false);
+
+        assert_eq!(expanded["CACHE"], "/home/me/.cache/me-claude");
+    }
+
+    #[test]
+    fn substitution_is_single_pass() {
+        let parent = env(&[("OUTER", "$INNER"), ("INNER", "deep")]);
+        let profile = env(&[("VALUE", "${OUTER}")]);
+
+        let expanded = substitute_from_parent(&profile, &parent, false);
+
+        assert_eq!(expanded["VALUE"], "$INNER");
+    }
+
+    #[test]
+    fn undefined_parent_vars_stay_unexpanded() {
+        let parent = env(&[("HOME", "/home/me")]);
+        let profile = env(&[
+            ("URL", "$MISSING_URL/v1"),
+            ("BRACED", "${MISSING}"),
+            ("COST", "$5"),
+        ]);
+
+        let expanded = substitute_from_parent(&profile, &parent, true);
+
+        assert_eq!(expanded["URL"], "$MISSING_URL/v1");
+        assert_eq!(expanded["BRACED"], "${MISSING}");
+        assert_eq!(expanded["COST"], "$5");
+    }
+
+    #[test]
+    fn templates_resolve_chains_between_profile_vars() {
+        let profile = env(&[
+            ("HOST", "api.example.com"),
+            ("BASE", "https://${HOST}"),
+            ("URL", "${BASE}/v1"),
+        ]);
+
+        let expanded = expand_templates(&profile);
+
+        assert_eq!(expanded["URL"], "https://api.example.com/v1");
+        assert_eq!(expanded["BASE"], "https://api.example.com");
+    }
+
+    #[test]
+    fn templates_leave_cycles_and_undefined_keys() {
+        let profile = env(&[("A", "${B}"), ("B", "${A}"), ("C", "${NOPE}-$A")]);
+
+        let expanded = expand_templates(&profile);
+
+        assert_eq!(expanded["A"], "${A}");
+        assert_eq!(expanded["B"], "${B}");
+        assert_eq!(expanded["C"], "${NOPE}-$A");
+    }
+
+    #[test]
+    fn templates_win_over_the_parent_environment() {
+        // The order `ccuse use --env-substitute` applies them in
+        let parent = env(&[("HOST", "parent.example.com"), ("NOPE", "from-parent")]);
+        let profile = env(&[
+            ("HOST", "profile.example.com"),
+            ("URL", "https://${HOST}/${NOPE}"),
+        ]);
+
+        let expanded = substitute_from_parent(&expand_templates(&profile), &parent, false);
+
+        assert_eq!(expanded["URL"], "https://profile.example.com/from-parent");
+    }
+
+    fn profile(name: &str, extends: Option<&str>, vars: &[(&str, &str)]) -> Profile {
+        Profile {
+            name: name.to_string(),
+            extends: extends.map(str::to_string),
+            env: env(vars),
+            ..Profile::default()
+        }
+    }
+
+    #[test]
+    fn extracts_each_referenced_var_once() {
+        let vars = extract_template_vars(&env(&[
+            ("URL", "https://${HOST}:${PORT}/${HOST}"),
+            ("PLAIN", "$HOME and ${lower_case}"),
+        ]));
+
+        let mut vars: Vec<_> = vars.into_iter().collect();
+        vars.sort();
+        assert_eq!(vars, ["HOST", "PORT", "lower_case"]);
+    }
+
+    #[test]
+    fn template_vars_are_traced_through_a_multi_level_chain() {
+        // child extends team, which extends base; each level references vars
+        let chain = [
+            profile(
+                "child",
+                Some("team"),
+                &[("MODEL", "${FAMILY}-latest"), ("REGION", "eu")],
+            ),
+            profile(
+                "team",
+                Some("base"),
+                &[("URL", "https://${HOST}/${REGION}"), ("FAMILY", "opus")],
+            ),
+            profile(
+                "base",
+                None,
+                &[("HOST", "api.example.com"), ("KEY", "${SECRET_SOURCE}")],
+            ),
+        ];
+
+        assert_eq!(
+            template_var_sources(&chain),
+            [
+                (
+                    "FAMILY".to_string(),
+                    TemplateVarSource::Inherited("team".to_string())
+                ),
+                (
+                    "HOST".to_string(),
+                    TemplateVarSource::Inherited("base".to_string())
+                ),
+                ("REGION".to_string(), TemplateVarSource::Own),
+                ("SECRET_SOURCE".to_string(), TemplateVarSource::Undefined),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_closest_definition_is_reported() {
+        let chain = [
+            profile("child", Some("base"), &[("URL", "${HOST}")]),
+            profile("middle", Some("base"), &[("HOST", "middle.example.com")]),
+            profile("base", None, &[("HOST", "base.example.com")]),
+        ];
+
+        assert_eq!(
+            template_var_sources(&chain),
+            [(
+                "HOST".to_string(),
+                TemplateVarSource::Inherited("middle".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn overridden_parent_references_do_not_count() {
+        let chain = [
+            profile(
+                "child",
+                Some("base"),
+                &[("URL", "https://fixed.example.com")],
+            ),
+            profile("base", None, &[("URL", "https://${HOST}")]),
+        ];
+
+        assert!(template_var_sources(&chain).is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::config::storage::RESERVED_DIRS;
+use crate::config::Profile;
+use crate::error::{Error, Result};
+use url::Url;
+
+/// Placeholder values from provider setup guides that get pasted verbatim
+const PLACEHOLDER_BASE_URLS: [&str; 1] = ["https://yourapi"];
+const PLACEHOLDER_AUTH_TOKENS: [&str; 1] = ["sk-apikey"];
+
+/// Longest profile name accepted, in characters
+pub const MAX_PROFILE_NAME_LEN: usize = 64;
+
+/// Characters Windows does not allow in file names, besides path separators
+const RESERVED_NAME_CHARS: [char; 7] = ['<', '>', ':', '"', '|', '?', '*'];
+
+/// Check that `name` can be used as a profile directory name.
+///
+/// Profile names become a single directory under the config directory, so
+/// path separators, `..`, leading dots (hidden directories are not
+/// profiles), control characters and characters Windows rejects are not
+/// allowed, and names are limited to `MAX_PROFILE_NAME_LEN` characters.
+/// The directories ccuse keeps its own data in cannot be profile names either.
+///
+/// # Errors
+///
+/// Returns a `ConfigError` describing why the name is rejected.
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    let invalid = |reason: &str| {
+        Err(Error::ConfigError(format!(
+            "Invalid profile name '{}': {reason}",
+            name.escape_debug()
+        )))
+    };
+
+    if name.trim().is_empty() {
+        return invalid("it is empty");
+    }
+    if name.chars().count() > MAX_PROFILE_NAME_LEN {
+        return invalid(&format!(
+            "it is longer than {MAX_PROFILE_NAME_LEN} characters"
+        ));
+    }
+    if name.contains(['/', '\\']) {
+        return invalid("it contains a path separator");
+    }
+    if name.contains("..") {
+        return invalid("it contains '..'");
+    }
+    if name.starts_with('.') {
+        return invalid("it starts with '.'");
+    }
+    if name.chars().any(char::is_control) {
+        return invalid("it contains control characters");
+    }
+    if let Some(c) = name.chars().find(|c| RESERVED_NAME_CHARS.contains(c)) {
+        return invalid(&format!("it contains '{c}'"));
+    }
+    if RESERVED_DIRS.contains(&name) {
+        return invalid("it is reserved for ccuse's own data");
+    }
+
+    Ok(())
+}
+
+/// Check that a profile's `ANTHROPIC_BASE_URL` is an absolute http(s) URL and
+/// that neither it nor `ANTHROPIC_AUTH_TOKEN` is a placeholder.
+///
+/// Missing and empty values pass, as do values with `${VAR}` references,
+/// which are only resolved at launch.
+///
+/// # Errors
+///
+/// Returns a `ConfigError` naming the offending field.
+pub fn validate_profile_env(profile: &Profile) -> Result<()> {
+    if let Some(base_url) = profile.env.get("ANTHROPIC_BASE_URL") {
+        validate_base_url(&profile.name, base_url.trim())?;
+    }
+
+    if let Some(token) = profile.env.get("ANTHROPIC_AUTH_TOKEN") {
+        if PLACEHOLDER_AUTH_TOKENS.contains(&token.trim()) {
+            return Err(Error::ConfigError(format!(
+                "ANTHROPIC_AUTH_TOKEN of profile '{}' is still the placeholder '{}'; set your API key",
+                profile.name,
+                token.trim()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_base_url(profile_name: &str, value: &str) -> Result<()> {
+    if value.is_empty() || value.contains("${") {
+        return Ok(());
+    }
+
+    if PLACEHOLDER_BASE_URLS.contains(&value.trim_end_matches('/')) {
+        return Err(Error::ConfigError(format!(
+            "ANTHROPIC_BASE_URL of profile '{profile_name}' is still the placeholder '{value}'; set your provider's API URL"
+        )));
+    }
+
+    let url = Url::parse(value).map_err(|e| {
+        Error::ConfigError(format!(
+            "ANTHROPIC_BASE_URL of profile '{profile_name}' is not a valid URL ('{value}'): {e}"
+        ))
+    })?;
+    if !matches!(url.scheme(), "http" | "https") || !url.has_host() {
+        return Err(Error::ConfigError(format!(
+            "ANTHROPIC_BASE_URL of profile '{profile_name}' must be an absolute http(s) URL, got '{value}'"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejection(name: &str) -> String {
+        match validate_profile_name(name) {
+            Err(Error::ConfigError(message)) => message,
+            other => panic!("{name:?} was not rejected: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn ordinary_names_are_accepted() {
+        let longest = "a".repeat(MAX_PROFILE_NAME_LEN);
+        for name in ["work", "Work_Proxy", "my-profile.v2", "工作", &longest] {
+            assert!(validate_profile_name(name).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn names_that_leave_the_config_dir_are_rejected() {
+        assert!(rejection("../evil").contains("path separator"));
+        assert!(rejection("..").contains("'..'"));
+        assert!(rejection("evil..").contains("'..'"));
+        assert!(rejection("a/b").contains("path separator"));
+        assert!(rejection("a\\b").contains("path separator"));
+        assert!(rejection("/etc").contains("path separator"));
+    }
+
+    #[test]
+    fn hidden_and_control_character_names_are_rejected() {
+        assert!(rejection(".hidden").contains("starts with '.'"));
+        for name in ["new\nline", "tab\there", "nul\0", "bell\u{7}", "del\u{7f}"] {
+            let message = rejection(name);
+            assert!(message.contains("control characters"), "{message}");
+            // The name is escaped so the message stays on one line
+            assert!(!message.contains(char::is_control), "{message}");
+        }
+    }
+
+    #[test]
+    fn empty_long_and_reserved_names_are_rejected() {
+        assert!(rejection("").contains("empty"));
+        assert!(rejection("   ").contains("empty"));
+        let too_long = "a".repeat(MAX_PROFILE_NAME_LEN + 1);
+        assert!(rejection(&too_long).contains("longer than"));
+        // Characters, not bytes, are counted
+        assert!(validate_profile_name(&"é".repeat(MAX_PROFILE_NAME_LEN)).is_ok());
+
+        assert!(rejection("backups").contains("reserved"));
+        assert!(rejection("cache").contains("reserved"));
+        assert!(rejection("a:b").contains("':'"));
+        assert!(rejection("what?").contains("'?'"));
+    }
+}
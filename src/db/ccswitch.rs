@@ -1,77 +1,250 @@
 use crate::config::{Profile, ProfileSource};
 use crate::error::{Error, Result};
 use chrono::{DateTime, Utc};
-use rusqlite::Connection;
-use serde::Deserialize;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// CC-Switch app type of Claude Code providers
+pub const DEFAULT_APP_TYPE: &str = "claude";
+
+/// How long a query waits for CC-Switch to release a write lock
+const BUSY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Environment variable pointing at a CC-Switch database outside `~/.cc-switch`
+pub const DB_PATH_ENV: &str = "CC_SWITCH_DB_PATH";
 
 pub struct CcSwitchDb {
     db_path: PathBuf,
 }
 
+/// A `providers` row exactly as stored in the CC-Switch database.
+#[derive(Debug, Clone, Serialize)]
+pub struct RawProfile {
+    pub id: String,
+    pub name: String,
+    pub app_type: String,
+    pub settings_config_json: String,
+    pub created_at_ms: i64,
+}
+
+/// Profiles read from the CC-Switch database, and the providers that could not be read.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderProfiles {
+    pub profiles: Vec<Profile>,
+    /// `(provider name, reason)` for every provider left out
+    pub skipped: Vec<(String, String)>,
+}
+
+impl RawProfile {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            app_type: row.get(2)?,
+            settings_config_json: row.get(3)?,
+            created_at_ms: row.get(4)?,
+        })
+    }
+}
+
 impl CcSwitchDb {
-    /// Create a new `CcSwitchDb` instance.
+    /// Create a new `CcSwitchDb` instance for the database at the default path.
     ///
     /// # Errors
     ///
     /// Returns an error if home directory cannot be found or CC-Switch database does not exist.
     pub fn new() -> Result<Self> {
-        let home = dirs::home_dir()
-            .ok_or_else(|| Error::ConfigError("Cannot find home directory".into()))?;
+        Self::open(None)
+    }
 
-        let db_path = home.join(".cc-switch").join("cc-switch.db");
+    /// Create a new `CcSwitchDb` instance for `db_path`, falling back to
+    /// `CC_SWITCH_DB_PATH` and then `~/.cc-switch/cc-switch.db`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if home directory cannot be found or CC-Switch database does not exist.
+    pub fn open(db_path: Option<&Path>) -> Result<Self> {
+        let db_path = Self::resolve_path(db_path)?;
 
         if !db_path.exists() {
-            return Err(Error::CcSwitchDbNotFound);
+            return Err(Error::CcSwitchDbNotFound(db_path));
         }
 
         Ok(Self { db_path })
     }
 
+    /// Whether the database `open(db_path)` would use exists.
+    #[must_use]
+    pub fn exists(db_path: Option<&Path>) -> bool {
+        Self::resolve_path(db_path).is_ok_and(|db_path| db_path.exists())
+    }
+
+    /// The path given by `db_path` or `CC_SWITCH_DB_PATH`, if any.
     #[must_use]
-    pub fn exists() -> bool {
-        dirs::home_dir().is_some_and(|home| {
-            let db_path = home.join(".cc-switch").join("cc-switch.db");
-            db_path.exists()
+    pub fn override_path(db_path: Option<&Path>) -> Option<PathBuf> {
+        db_path.map(Path::to_path_buf).or_else(|| {
+            std::env::var_os(DB_PATH_ENV)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
         })
     }
 
+    /// Open the database read-only, since ccuse never writes to it and
+    /// CC-Switch may be running at the same time.
+    fn connect(&self) -> Result<Connection> {
+        let conn = Connection::open_with_flags(
+            &self.db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        Ok(conn)
+    }
+
+    fn resolve_path(db_path: Option<&Path>) -> Result<PathBuf> {
+        if let Some(db_path) = Self::override_path(db_path) {
+            return Ok(db_path);
+        }
+
+        let home = dirs::home_dir()
+            .ok_or_else(|| Error::ConfigError("Cannot find home directory".into()))?;
+        Ok(home.join(".cc-switch").join("cc-switch.db"))
+    }
+
     /// Get all Claude profiles from CC-Switch database.
     ///
     /// # Errors
     ///
     /// Returns an error if database cannot be opened or queried.
-    pub fn get_profiles(&self) -> Result<Vec<Profile>> {
-        let conn = Connection::open(&self.db_path)?;
+    pub fn get_profiles(&self) -> Result<ProviderProfiles> {
+        self.get_profiles_for(DEFAULT_APP_TYPE)
+    }
+
+    /// Get all profiles of one app type (`claude`, `codex`, `gemini`, ...)
+    /// from CC-Switch database, oldest provider first. The app type is recorded
+    /// as the profile's category.
+    ///
+    /// Providers whose row or `settings_config` cannot be read are returned
+    /// in `skipped` instead of failing the whole read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database cannot be opened or queried.
+    pub fn get_profiles_for(&self, app_type: &str) -> Result<ProviderProfiles> {
+        let _span = tracing::debug_span!("ccswitch.get_profiles", app_type).entered();
+        let conn = self.connect()?;
 
         let mut stmt = conn.prepare(
             "SELECT id, name, settings_config, created_at
              FROM providers
-             WHERE app_type = 'claude'",
+             WHERE app_type = ?1
+             ORDER BY created_at, id",
         )?;
 
-        let profiles = stmt
-            .query_map([], |row| {
-                let id: String = row.get(0)?;
-                let name: String = row.get(1)?;
-                let settings_config: String = row.get(2)?;
-                let created_at: i64 = row.get(3)?;
-
-                Ok((id, name, settings_config, created_at))
-            })?
-            .filter_map(std::result::Result::ok)
-            .filter_map(|(id, name, settings_config, created_at)| {
-                Self::parse_provider_config(&id, &name, &settings_config, created_at).ok()
-            })
-            .collect();
+        let rows = stmt.query_map([app_type], |row| {
+            let id: String = row.get(0)?;
+            let name: String = row.get(1)?;
+            let settings_config: String = row.get(2)?;
+            let created_at: i64 = row.get(3)?;
+
+            Ok((id, name, settings_config, created_at))
+        })?;
 
-        Ok(profiles)
+        // Unreadable providers are skipped so the rest can still be synced
+        let mut row_count = 0;
+        let mut result = ProviderProfiles::default();
+        for row in rows {
+            row_count += 1;
+            let (id, name, settings_config, created_at) = match row {
+                Ok(row) => row,
+                Err(e) => {
+                    result
+                        .skipped
+                        .push((format!("(row {row_count})"), e.to_string()));
+                    continue;
+                }
+            };
+            match Self::parse_provider_config(&id, &name, app_type, &settings_config, created_at) {
+                Ok(profile) => result.profiles.push(profile),
+                Err(e) => result.skipped.push((name, e.to_string())),
+            }
+        }
+        tracing::debug!(
+            rows = row_count,
+            parsed = result.profiles.len(),
+            skipped = result.skipped.len(),
+            "read CC-Switch providers"
+        );
+
+        Ok(result)
+    }
+
+    /// List every provider row without parsing `settings_config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database cannot be opened or queried.
+    pub fn list_all_profiles_raw(&self) -> Result<Vec<RawProfile>> {
+        let conn = self.connect()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, name, app_type, settings_config, created_at
+             FROM providers
+             ORDER BY app_type, name",
+        )?;
+
+        let rows = stmt
+            .query_map([], RawProfile::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(rows)
+    }
+
+    /// Get a single provider row by its CC-Switch name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database cannot be opened or queried.
+    pub fn get_raw_profile_by_name(&self, name: &str) -> Result<Option<RawProfile>> {
+        let conn = self.connect()?;
+
+        let raw = conn
+            .query_row(
+                "SELECT id, name, app_type, settings_config, created_at
+                 FROM providers
+                 WHERE name = ?1",
+                [name],
+                RawProfile::from_row,
+            )
+            .optional()?;
+
+        Ok(raw)
+    }
+
+    /// List all table names in the database, for debugging schema differences.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database cannot be opened or queried.
+    pub fn list_tables(&self) -> Result<Vec<String>> {
+        let conn = self.connect()?;
+
+        let mut stmt =
+            conn.prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")?;
+
+        let tables = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        Ok(tables)
     }
 
     fn parse_provider_config(
         _id: &str,
         name: &str,
+        app_type: &str,
         settings_config: &str,
         created_at_ms: i64,
     ) -> Result<Profile> {
@@ -87,6 +260,9 @@ impl CcSwitchDb {
             always_thinking_enabled: Option<bool>,
             #[serde(rename = "apiTimeoutMs")]
             api_timeout_ms: Option<u64>,
+            /// Codex keeps its API key here rather than in `env`
+            #[serde(rename = "auth")]
+            auth: Option<HashMap<String, serde_json::Value>>,
         }
 
         let config: ProviderConfig = serde_json::from_str(settings_config).map_err(|e| {
@@ -102,18 +278,26 @@ impl CcSwitchDb {
         // Replace spaces with underscores in profile name
         let name = name.replace(' ', "_");
 
+        let mut env = config.env.unwrap_or_default();
+        for (key, value) in config.auth.unwrap_or_default() {
+            if let serde_json::Value::String(value) = value {
+                env.entry(key).or_insert(value);
+            }
+        }
+
         Ok(Profile {
             name,
             display_name: Some(original_name),
-            env: config.env.unwrap_or_default(),
+            env,
             permissions: config.permissions.unwrap_or_default(),
             enabled_plugins: config.enabled_plugins,
             always_thinking_enabled: config.always_thinking_enabled,
             api_timeout_ms: config.api_timeout_ms,
-            category: None,
+            category: Some(app_type.to_string()),
             source: Some(ProfileSource::CcSwitch),
             created_at: created_at_dt,
             updated_at: created_at_dt,
+            ..Profile::default()
         })
     }
 }
@@ -126,3 +310,153 @@ impl Default for CcSwitchDb {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A CC-Switch database with two Claude providers, one of them unparseable,
+    /// and one Codex provider.
+    fn fixture_db() -> (TempDir, CcSwitchDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cc-switch.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r#"CREATE TABLE providers (id text, app_type text, name text, settings_config text, created_at integer);
+               CREATE TABLE settings (key text, value text);
+               INSERT INTO providers VALUES
+                 ('p1', 'claude', 'Work Proxy', '{"env":{"ANTHROPIC_BASE_URL":"https://work.example.com"}}', 1700000000000),
+                 ('p2', 'claude', 'Broken', '{"env":', 1700000001000),
+                 ('p3', 'codex', 'Codex', '{"auth":{"OPENAI_API_KEY":"sk-codex"}}', 1700000002000);"#,
+        )
+        .unwrap();
+        drop(conn);
+
+        let db = CcSwitchDb::open(Some(&path)).unwrap();
+        (dir, db)
+    }
+
+    #[test]
+    fn lists_every_row_raw() {
+        let (_dir, db) = fixture_db();
+        let raw = db.list_all_profiles_raw().unwrap();
+
+        let rows: Vec<_> = raw
+            .iter()
+            .map(|r| (r.app_type.as_str(), r.name.as_str()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("claude", "Broken"),
+                ("claude", "Work Proxy"),
+                ("codex", "Codex")
+            ]
+        );
+        assert_eq!(raw[0].settings_config_json, r#"{"env":"#);
+        assert_eq!(raw[0].id, "p2");
+        assert_eq!(raw[0].created_at_ms, 1_700_000_001_000);
+    }
+
+    #[test]
+    fn gets_one_raw_row_by_name() {
+        let (_dir, db) = fixture_db();
+
+        let raw = db.get_raw_profile_by_name("Broken").unwrap().unwrap();
+        assert_eq!(raw.settings_config_json, r#"{"env":"#);
+        assert!(db.get_raw_profile_by_name("Missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn lists_tables() {
+        let (_dir, db) = fixture_db();
+        assert_eq!(db.list_tables().unwrap(), ["providers", "settings"]);
+    }
+
+    #[test]
+    fn unparseable_providers_are_skipped() {
+        let (_dir, db) = fixture_db();
+        let read = db.get_profiles().unwrap();
+
+        assert_eq!(read.profiles.len(), 1);
+        let profile = &read.profiles[0];
+        assert_eq!(profile.name, "Work_Proxy");
+        assert_eq!(profile.display_name.as_deref(), Some("Work Proxy"));
+        assert_eq!(
+            profile.env["ANTHROPIC_BASE_URL"],
+            "https://work.example.com"
+        );
+        assert_eq!(read.skipped.len(), 1);
+        assert_eq!(read.skipped[0].0, "Broken");
+    }
+
+    #[test]
+    fn codex_auth_keys_become_env() {
+        let (_dir, db) = fixture_db();
+        let read = db.get_profiles_for("codex").unwrap();
+
+        assert_eq!(read.profiles[0].env["OPENAI_API_KEY"], "sk-codex");
+        assert_eq!(read.profiles[0].category.as_deref(), Some("codex"));
+    }
+
+    #[test]
+    fn missing_database_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.db");
+        assert!(matches!(
+            CcSwitchDb::open(Some(&path)),
+            Err(Error::CcSwitchDbNotFound(p)) if p == path
+        ));
+    }
+
+    #[test]
+    fn concurrent_read_only_connections_do_not_conflict() {
+        let (_dir, db) = fixture_db();
+        let first = db.connect().unwrap();
+        let second = db.connect().unwrap();
+        // Both hold a read transaction at the same time
+        first
+            .execute_batch("BEGIN; SELECT count(*) FROM providers;")
+            .unwrap();
+        second
+            .execute_batch("BEGIN; SELECT count(*) FROM providers;")
+            .unwrap();
+
+        std::thread::scope(|scope| {
+            let readers: Vec<_> = (0..2)
+                .map(|_| scope.spawn(|| db.get_profiles().unwrap().profiles.len()))
+                .collect();
+            for reader in readers {
+                assert_eq!(reader.join().unwrap(), 1);
+            }
+        });
+
+        first.execute_batch("COMMIT").unwrap();
+        second.execute_batch("COMMIT").unwrap();
+    }
+
+    #[test]
+    fn connections_are_read_only() {
+        let (_dir, db) = fixture_db();
+        let conn = db.connect().unwrap();
+
+        assert!(conn.execute("DELETE FROM providers", []).is_err());
+        assert_eq!(db.list_all_profiles_raw().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn reads_wait_for_a_short_write_lock() {
+        let (_dir, db) = fixture_db();
+        let writer = Connection::open(&db.db_path).unwrap();
+        writer.execute_batch("BEGIN EXCLUSIVE").unwrap();
+
+        let release = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            writer.execute_batch("COMMIT").unwrap();
+        });
+        // Retried until the lock is released, well within BUSY_TIMEOUT
+        assert_eq!(db.get_profiles().unwrap().profiles.len(), 1);
+        release.join().unwrap();
+    }
+}
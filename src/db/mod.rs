@@ -1,3 +1,3 @@
 pub mod ccswitch;
 
-pub use ccswitch::CcSwitchDb;
+pub use ccswitch::{CcSwitchDb, ProviderProfiles, RawProfile, DEFAULT_APP_TYPE};
@@ -11,15 +11,21 @@ pub enum Error {
     #[error("Failed to read CC-Switch database: {0}")]
     CcSwitchReadError(String),
 
-    #[error("CC-Switch database not found")]
-    CcSwitchDbNotFound,
+    #[error("CC-Switch database not found at {}", .0.display())]
+    CcSwitchDbNotFound(std::path::PathBuf),
 
     #[error("Failed to find Claude Code executable")]
     ClaudeNotFound,
 
+    #[error("claude-{0} not found in PATH")]
+    ClaudeVersionNotFound(String),
+
     #[error("Failed to launch Claude Code: {0}")]
     LaunchError(String),
 
+    #[error("Claude Code exited with code {0}")]
+    ClaudeExitError(i32),
+
     #[error("Config error: {0}")]
     ConfigError(String),
 
@@ -37,6 +43,42 @@ pub enum Error {
 
     #[error("Failed to run ccusage: {0}")]
     CcusageError(String),
+
+    #[error("Failed to send notification: {0}")]
+    NotifyError(String),
+
+    #[error("Failed to tee output: {0}")]
+    TeeError(String),
+
+    #[error("Failed to check for updates: {0}")]
+    VersionCheckError(String),
+
+    #[error("API proxy error: {0}")]
+    ProxyError(String),
+
+    #[error("Failed to limit CPU usage: {0}")]
+    CpuLimitError(String),
+
+    #[error("Timed out waiting for port {0}")]
+    PortWaitTimeout(u16),
+
+    #[error("Timed out waiting for {0}")]
+    UrlWaitTimeout(String),
+
+    #[error("Keyring error: {0}")]
+    KeyringError(String),
+
+    #[error("Failed to open editor '{0}': {1}")]
+    EditorLaunchError(String, String),
+
+    #[error("Editor '{0}' exited with code {1}")]
+    EditorExitError(String, i32),
+
+    #[error("Failed to run '{0}': {1}")]
+    CommandError(String, String),
+
+    #[error("Another ccuse command is still using the profile store (lock: {})", .0.display())]
+    Locked(std::path::PathBuf),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -3,3 +3,6 @@ pub mod cli;
 pub mod config;
 pub mod db;
 pub mod error;
+pub mod proxy;
+pub mod telemetry;
+pub mod update;
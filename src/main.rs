@@ -1,40 +1,92 @@
+use ccuse::cli::commands::usage_cmd::{CcusageRunner, UsageCache};
 use ccuse::cli::commands::{
-    add_profile, list_profiles, remove_all_profiles, remove_profile, rename_profile, run_ccusage,
-    update_profiles, use_profile,
+    add_profile, audit_ccswitch, clone_profile, edit_profile, exec_with_profile, export_profile,
+    import_profile, install_static_completions, list_claude_versions, list_profiles,
+    migrate_secrets, print_completions, prune_profiles, regenerate_completions,
+    remove_all_profiles, remove_matching_profiles, remove_profile, rename_profile,
+    rename_profiles_by_regex, restore_backup, run_api_log_command, run_ccusage,
+    run_default_command, run_doctor, run_env_command, run_experimental_flags_command,
+    run_profile_command, search_profiles, show_config_dir, show_current_profile, show_history,
+    show_profile, show_stats, show_version, update_profiles, use_profile, validate_profiles,
 };
 use ccuse::cli::{Args, Commands, CompletionInstaller};
-use ccuse::config::Storage;
+use ccuse::config::storage::CONFIG_DIR_ENV;
+use ccuse::error::Error;
+use ccuse::telemetry;
 use clap::Parser;
 use colored::Colorize;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use std::env;
+use std::time::Duration;
 
 fn main() {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(EnvFilter::from_default_env())
-        .init();
-
     // Parse arguments
     let args = Args::parse();
 
-    // Set up verbose logging if requested
-    if args.verbose {
-        tracing::info!("Verbose mode enabled");
+    // Through the environment, so Claude, its hooks and the completion
+    // scripts that run ccuse see the same directory
+    if let Some(config_dir) = &args.config_dir {
+        env::set_var(CONFIG_DIR_ENV, config_dir);
+    }
+
+    // colored itself leaves out colors when stdout is not a terminal
+    let color = !args.no_color && env::var_os("NO_COLOR").map_or(true, |v| v.is_empty());
+    if !color {
+        colored::control::set_override(false);
     }
 
+    // Initialize logging, exporting spans when `use --otel` asks for it
+    let otel = matches!(&args.command, Commands::Use(use_args) if use_args.otel);
+    if let Err(e) = telemetry::init_tracing(otel, args.verbose, color) {
+        eprintln!("{} {e}", "Error:".red().bold());
+        std::process::exit(1);
+    }
+
+    tracing::debug!(version = env!("CARGO_PKG_VERSION"), "verbose mode enabled");
+
     // Run the appropriate command
     let result = match args.command {
-        Commands::Use { name, bypass, args } => use_profile(&name, bypass, &args),
+        Commands::Use(use_args) => use_profile(&use_args, args.verbose),
+
+        Commands::Update(update_args) => update_profiles(&update_args),
 
-        Commands::Update => update_profiles(),
+        Commands::List(list_args) => list_profiles(&list_args),
 
-        Commands::List => list_profiles(),
+        Commands::AuditCcSwitch {
+            profile_name,
+            json,
+            all_tables,
+        } => audit_ccswitch(profile_name.as_deref(), json, all_tables),
 
-        Commands::Add => add_profile(),
+        Commands::Add(add_args) => add_profile(&add_args),
 
-        Commands::Remove { name, all } => {
+        Commands::Import {
+            path,
+            format,
+            detect_only,
+            validate,
+            rename,
+            tags,
+            launch_args,
+        } => import_profile(
+            &path,
+            format,
+            detect_only,
+            validate,
+            rename.as_deref(),
+            &tags,
+            &launch_args,
+        ),
+
+        Commands::Remove {
+            name,
+            all,
+            category,
+            pattern,
+        } => {
             if all {
                 remove_all_profiles()
+            } else if category.is_some() || pattern.is_some() {
+                remove_matching_profiles(category.as_deref(), pattern.as_deref())
             } else if let Some(n) = name {
                 remove_profile(&n)
             } else {
@@ -43,27 +95,131 @@ fn main() {
             }
         }
 
-        Commands::Rename { old_name, new_name } => rename_profile(&old_name, &new_name),
+        Commands::Export {
+            name,
+            output,
+            redact_secrets,
+        } => export_profile(&name, output.as_deref(), redact_secrets),
 
-        Commands::ConfigDir => {
-            let storage = Storage::default();
-            println!("{}", storage.config_dir().display());
-            Ok(())
-        }
+        Commands::Show { name, reveal } => show_profile(&name, reveal),
+
+        Commands::Edit { name } => edit_profile(&name),
+
+        Commands::Search { query, env_only } => search_profiles(&query, env_only),
+
+        Commands::Rename {
+            old_name,
+            new_name,
+            regex,
+            replace,
+        } => match (old_name, new_name, regex, replace) {
+            (_, _, Some(pattern), Some(replacement)) => {
+                rename_profiles_by_regex(&pattern, &replacement)
+            }
+            (Some(old_name), Some(new_name), _, _) => rename_profile(&old_name, &new_name),
+            // clap requires either both names or --regex with --replace
+            _ => unreachable!("rename arguments are validated by clap"),
+        },
+
+        Commands::Clone { source, dest } => clone_profile(&source, &dest),
+
+        Commands::Profile { command } => run_profile_command(&command),
+
+        Commands::Env {
+            name,
+            format,
+            command,
+        } => run_env_command(name.as_deref(), format, command.as_ref()),
+
+        Commands::Exec { name, command } => exec_with_profile(&name, &command),
+
+        Commands::ExperimentalFlags { command } => run_experimental_flags_command(&command),
 
-        Commands::Completions => {
-            if let Err(e) = CompletionInstaller::run() {
+        Commands::ApiLog {
+            session_id,
+            command,
+        } => run_api_log_command(session_id.as_deref(), command.as_ref()),
+
+        Commands::MigrateSecrets { name } => migrate_secrets(name.as_deref()),
+
+        Commands::Current => show_current_profile(),
+
+        Commands::History { clear } => show_history(clear),
+
+        Commands::Stats {
+            json,
+            by_resource_group,
+        } => show_stats(json, by_resource_group),
+
+        Commands::Default { name, clear } => run_default_command(name.as_deref(), clear),
+
+        Commands::Prune { fix } => prune_profiles(fix),
+
+        Commands::Restore { timestamp } => restore_backup(timestamp.as_deref()),
+
+        Commands::ConfigDir { open } => show_config_dir(open),
+
+        Commands::Doctor => run_doctor(),
+
+        Commands::Validate { name } => validate_profiles(name.as_deref()),
+
+        Commands::Completions {
+            shell,
+            regenerate: true,
+            ..
+        } => regenerate_completions(shell),
+
+        Commands::Completions {
+            shell,
+            print: true,
+            generate_from_config,
+            ..
+        } => print_completions(shell, generate_from_config),
+
+        Commands::Completions {
+            shell,
+            generate_from_config: true,
+            ..
+        } => install_static_completions(shell),
+
+        Commands::Completions { shell, .. } => {
+            if let Err(e) = CompletionInstaller::run(shell) {
                 eprintln!("Error: {e}");
                 std::process::exit(1);
             }
             Ok(())
         }
 
-        Commands::Usage { args } => run_ccusage(&args),
+        Commands::Version { check_update, json } => show_version(check_update, json),
+
+        Commands::ListClaudeVersions => list_claude_versions(),
+
+        Commands::Usage {
+            cache,
+            cache_ttl,
+            offline,
+            ccusage_version,
+            no_local,
+            args,
+        } => {
+            let cache = if offline {
+                UsageCache::Offline
+            } else if cache {
+                UsageCache::ReadWrite(Duration::from_secs(cache_ttl))
+            } else {
+                UsageCache::Off
+            };
+            run_ccusage(&args, cache, &CcusageRunner::new(ccusage_version, no_local))
+        }
     };
 
-    if let Err(e) = result {
-        eprintln!("{} {e}", "Error:".red().bold());
-        std::process::exit(1);
+    match result {
+        Ok(()) => {}
+        // Claude has already reported its own failure; only pass the code on
+        Err(Error::ClaudeExitError(code)) => std::process::exit(code),
+        Err(e) => {
+            eprintln!("{} {e}", "Error:".red().bold());
+            std::process::exit(1);
+        }
     }
 }
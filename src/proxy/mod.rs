@@ -0,0 +1,287 @@
+#[cfg(feature = "api-capture")]
+mod server;
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "api-capture")]
+pub use server::ApiProxy;
+
+/// One proxied API request, stored as a line of `api-log-<session>.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiCallRecord {
+    pub timestamp: DateTime<Utc>,
+    pub method: String,
+    pub url: String,
+    /// HTTP status, or 502 when the upstream could not be reached
+    pub status: u16,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub input_tokens: Option<u64>,
+    #[serde(default)]
+    pub output_tokens: Option<u64>,
+}
+
+/// Aggregates shown by `ccuse api-log stats`.
+#[derive(Debug, Clone, Default)]
+pub struct ApiLogStats {
+    pub requests: usize,
+    pub errors: usize,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+}
+
+/// Identifier for a new capture session, e.g. `20250101-120000-4242`.
+#[must_use]
+pub fn new_session_id() -> String {
+    format!(
+        "{}-{}",
+        Utc::now().format("%Y%m%d-%H%M%S"),
+        std::process::id()
+    )
+}
+
+/// Path of the API log for `session_id`.
+#[must_use]
+pub fn api_log_path(config_dir: &Path, session_id: &str) -> PathBuf {
+    config_dir.join(format!("api-log-{session_id}.jsonl"))
+}
+
+/// Session ids of all API logs in `config_dir`, oldest first.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read.
+pub fn list_sessions(config_dir: &Path) -> Result<Vec<String>> {
+    let mut sessions: Vec<String> = fs::read_dir(config_dir)?
+        .filter_map(std::result::Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let id = name.strip_prefix("api-log-")?.strip_suffix(".jsonl")?;
+            Some(id.to_string())
+        })
+        .collect();
+    sessions.sort();
+    Ok(sessions)
+}
+
+/// Read every record of an API log.
+///
+/// # Errors
+///
+/// Returns an error if the log does not exist or a line is not a valid record.
+pub fn read_api_log(path: &Path) -> Result<Vec<ApiCallRecord>> {
+    let file = fs::File::open(path)
+        .map_err(|e| Error::ProxyError(format!("Cannot open {}: {e}", path.display())))?;
+
+    BufReader::new(file)
+        .lines()
+        .filter(|line| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?))
+        .collect()
+}
+
+/// Summarise an API log: totals and nearest-rank latency percentiles.
+#[must_use]
+pub fn compute_stats(records: &[ApiCallRecord]) -> ApiLogStats {
+    let mut durations: Vec<u64> = records.iter().map(|r| r.duration_ms).collect();
+    durations.sort_unstable();
+
+    let percentile = |p: usize| -> u64 {
+        if durations.is_empty() {
+            return 0;
+        }
+        let rank = ((p * durations.len() + 99) / 100).max(1);
+        durations[rank - 1]
+    };
+
+    ApiLogStats {
+        requests: records.len(),
+        errors: records.iter().filter(|r| r.status >= 400).count(),
+        request_bytes: records.iter().map(|r| r.request_bytes).sum(),
+        response_bytes: records.iter().map(|r| r.response_bytes).sum(),
+        input_tokens: records.iter().filter_map(|r| r.input_tokens).sum(),
+        output_tokens: records.iter().filter_map(|r| r.output_tokens).sum(),
+        p50_ms: percentile(50),
+        p90_ms: percentile(90),
+        p99_ms: percentile(99),
+    }
+}
+
+/// Extract token usage from an Anthropic Messages API response body.
+///
+/// Handles both plain JSON responses and `text/event-stream` bodies, where
+/// input tokens arrive in `message_start` and output tokens in `message_delta`.
+#[must_use]
+pub fn parse_token_usage(body: &[u8]) -> (Option<u64>, Option<u64>) {
+    let text = String::from_utf8_lossy(body);
+    let usage_of = |value: &serde_json::Value, key: &str| {
+        value
+            .get("usage")
+            .or_else(|| value.get("message").and_then(|m| m.get("usage")))
+            .and_then(|u| u.get(key))
+            .and_then(serde_json::Value::as_u64)
+    };
+
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(&text) {
+        return (
+            usage_of(&value, "input_tokens"),
+            usage_of(&value, "output_tokens"),
+        );
+    }
+
+    let mut input = None;
+    let mut output = None;
+    for data in text.lines().filter_map(|line| line.strip_prefix("data:")) {
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(data.trim()) else {
+            continue;
+        };
+        input = usage_of(&event, "input_tokens").or(input);
+        output = usage_of(&event, "output_tokens").or(output);
+    }
+    (input, output)
+}
+
+/// Stand-in for the capture proxy in builds without the `api-capture` feature.
+#[cfg(not(feature = "api-capture"))]
+pub struct ApiProxy {
+    _private: (),
+}
+
+#[cfg(not(feature = "api-capture"))]
+impl ApiProxy {
+    /// # Errors
+    ///
+    /// Always fails: this build does not include the `api-capture` feature.
+    pub fn start(_upstream: &str, _log_path: PathBuf) -> Result<Self> {
+        Err(Error::ProxyError(
+            "ccuse was built without the `api-capture` feature".into(),
+        ))
+    }
+
+    #[must_use]
+    pub fn url(&self) -> &str {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(status: u16, duration_ms: u64, tokens: Option<(u64, u64)>) -> ApiCallRecord {
+        ApiCallRecord {
+            timestamp: Utc::now(),
+            method: "POST".into(),
+            url: "https://api.example.com/v1/messages".into(),
+            status,
+            request_bytes: 100,
+            response_bytes: 1000,
+            duration_ms,
+            input_tokens: tokens.map(|(input, _)| input),
+            output_tokens: tokens.map(|(_, output)| output),
+        }
+    }
+
+    #[test]
+    fn stats_of_an_empty_log_are_zero() {
+        let stats = compute_stats(&[]);
+        assert_eq!(stats.requests, 0);
+        assert_eq!((stats.p50_ms, stats.p90_ms, stats.p99_ms), (0, 0, 0));
+    }
+
+    #[test]
+    fn stats_total_the_records() {
+        let records = [
+            record(200, 30, Some((10, 20))),
+            record(429, 5, None),
+            record(200, 70, Some((1, 2))),
+            record(502, 1, None),
+        ];
+        let stats = compute_stats(&records);
+        assert_eq!(stats.requests, 4);
+        assert_eq!(stats.errors, 2);
+        assert_eq!(stats.request_bytes, 400);
+        assert_eq!(stats.response_bytes, 4000);
+        assert_eq!((stats.input_tokens, stats.output_tokens), (11, 22));
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        // Out of order on purpose: 10, 20, ..., 100
+        let records: Vec<_> = [70, 10, 100, 40, 20, 90, 30, 60, 50, 80]
+            .into_iter()
+            .map(|ms| record(200, ms, None))
+            .collect();
+        let stats = compute_stats(&records);
+        assert_eq!((stats.p50_ms, stats.p90_ms, stats.p99_ms), (50, 90, 100));
+
+        let stats = compute_stats(&[record(200, 42, None)]);
+        assert_eq!((stats.p50_ms, stats.p90_ms, stats.p99_ms), (42, 42, 42));
+    }
+
+    #[test]
+    fn token_usage_from_a_json_response() {
+        let body = br#"{"id":"msg_1","usage":{"input_tokens":12,"output_tokens":34}}"#;
+        assert_eq!(parse_token_usage(body), (Some(12), Some(34)));
+    }
+
+    #[test]
+    fn token_usage_from_an_event_stream() {
+        let body = b"event: message_start\n\
+            data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":25,\"output_tokens\":1}}}\n\n\
+            event: content_block_delta\n\
+            data: {\"type\":\"content_block_delta\",\"delta\":{\"text\":\"hi\"}}\n\n\
+            event: ping\n\
+            data: not json\n\n\
+            event: message_delta\n\
+            data: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":15}}\n\n";
+        assert_eq!(parse_token_usage(body), (Some(25), Some(15)));
+    }
+
+    #[test]
+    fn token_usage_is_missing_without_usage() {
+        assert_eq!(parse_token_usage(b"{\"error\":{}}"), (None, None));
+        assert_eq!(parse_token_usage(b"<html>bad gateway</html>"), (None, None));
+        assert_eq!(parse_token_usage(b""), (None, None));
+    }
+
+    #[test]
+    fn api_logs_round_trip_and_list_by_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let records = [record(200, 30, Some((10, 20))), record(500, 5, None)];
+        let lines: Vec<_> = records
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap())
+            .collect();
+        let path = api_log_path(dir.path(), "20250102-000000-2");
+        fs::write(&path, format!("{}\n\n{}\n", lines[0], lines[1])).unwrap();
+        fs::write(api_log_path(dir.path(), "20250101-000000-1"), "").unwrap();
+        fs::write(dir.path().join("config.json"), "{}").unwrap();
+
+        assert_eq!(
+            list_sessions(dir.path()).unwrap(),
+            ["20250101-000000-1", "20250102-000000-2"]
+        );
+        let read = read_api_log(&path).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].input_tokens, Some(10));
+        assert_eq!(read[1].status, 500);
+
+        assert!(matches!(
+            read_api_log(&api_log_path(dir.path(), "missing")),
+            Err(Error::ProxyError(_))
+        ));
+    }
+}
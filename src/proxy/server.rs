@@ -0,0 +1,416 @@
+use super::{parse_token_usage, ApiCallRecord};
+use crate::error::{Error, Result};
+use chrono::Utc;
+use reqwest::blocking::Client;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+/// Response bytes kept in memory per request to extract token usage.
+const USAGE_CAPTURE_LIMIT: usize = 1024 * 1024;
+
+/// Request headers not forwarded upstream: hop-by-hop headers, the local
+/// `Host`, and `Accept-Encoding` so the response body stays readable.
+const SKIPPED_REQUEST_HEADERS: [&str; 5] = [
+    "host",
+    "connection",
+    "content-length",
+    "transfer-encoding",
+    "accept-encoding",
+];
+
+/// Local reverse proxy that forwards Claude's API calls to the real endpoint
+/// and logs each one.
+///
+/// Claude is pointed at the proxy through `ANTHROPIC_BASE_URL`, so requests
+/// arrive as plain HTTP and are re-sent over HTTPS (rustls) without having
+/// to intercept TLS. The proxy runs on background threads for as long as
+/// the process lives.
+pub struct ApiProxy {
+    url: String,
+}
+
+struct ProxyState {
+    client: Client,
+    upstream: String,
+    log: Mutex<File>,
+}
+
+impl ApiProxy {
+    /// Bind to a free localhost port and start forwarding to `upstream`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the port cannot be bound, the log cannot be
+    /// created, or the HTTP client fails to initialise.
+    pub fn start(upstream: &str, log_path: PathBuf) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .map_err(|e| Error::ProxyError(format!("Cannot bind proxy port: {e}")))?;
+        let port = listener.local_addr()?.port();
+
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .map_err(|e| Error::ProxyError(format!("Cannot open {}: {e}", log_path.display())))?;
+
+        // Streaming responses can take minutes; never time out
+        let client = Client::builder()
+            .timeout(None)
+            .build()
+            .map_err(|e| Error::ProxyError(e.to_string()))?;
+
+        let state = Arc::new(ProxyState {
+            client,
+            upstream: upstream.trim_end_matches('/').to_string(),
+            log: Mutex::new(log),
+        });
+
+        thread::spawn(move || {
+            for stream in listener.incoming().filter_map(std::result::Result::ok) {
+                let state = Arc::clone(&state);
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream, &state) {
+                        tracing::warn!("API proxy: {e}");
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            url: format!("http://127.0.0.1:{port}"),
+        })
+    }
+
+    /// Base URL Claude should use instead of the real endpoint.
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+/// Proxy a single request; the connection is closed afterwards.
+fn handle_connection(stream: TcpStream, state: &ProxyState) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut client_stream = stream;
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(Error::ProxyError(format!(
+            "Malformed request line: {request_line:?}"
+        )));
+    };
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    };
+    let body = if header("transfer-encoding").is_some_and(|v| v.eq_ignore_ascii_case("chunked")) {
+        read_chunked(&mut reader)?
+    } else {
+        let length = header("content-length")
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        body
+    };
+
+    let url = format!("{}{path}", state.upstream);
+    let method_value = reqwest::Method::from_bytes(method.as_bytes())
+        .map_err(|e| Error::ProxyError(e.to_string()))?;
+    let mut request = state.client.request(method_value, &url);
+    for (name, value) in &headers {
+        if !SKIPPED_REQUEST_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            request = request.header(name, value);
+        }
+    }
+
+    let started = Instant::now();
+    let mut record = ApiCallRecord {
+        timestamp: Utc::now(),
+        method: method.to_string(),
+        url: url.clone(),
+        status: 502,
+        request_bytes: body.len() as u64,
+        response_bytes: 0,
+        duration_ms: 0,
+        input_tokens: None,
+        output_tokens: None,
+    };
+
+    let result = match request.body(body).send() {
+        Ok(response) => forward_response(response, &mut client_stream, &mut record),
+        Err(e) => {
+            let message = format!("ccuse proxy: upstream request failed: {e}");
+            write!(
+                client_stream,
+                "HTTP/1.1 502 Bad Gateway\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{message}",
+                message.len()
+            )
+            .map_err(Error::from)
+        }
+    };
+
+    record.duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+    if let Ok(mut log) = state.log.lock() {
+        writeln!(log, "{}", serde_json::to_string(&record)?)?;
+    }
+
+    result
+}
+
+/// Stream the upstream response back to Claude, recording its size and token usage.
+fn forward_response(
+    mut response: reqwest::blocking::Response,
+    client: &mut TcpStream,
+    record: &mut ApiCallRecord,
+) -> Result<()> {
+    let status = response.status();
+    record.status = status.as_u16();
+
+    write!(
+        client,
+        "HTTP/1.1 {} {}\r\n",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("")
+    )?;
+    for (name, value) in response.headers() {
+        let name = name.as_str();
+        if matches!(name, "connection" | "content-length" | "transfer-encoding") {
+            continue;
+        }
+        client.write_all(name.as_bytes())?;
+        client.write_all(b": ")?;
+        client.write_all(value.as_bytes())?;
+        client.write_all(b"\r\n")?;
+    }
+    // The body is delimited by closing the connection
+    client.write_all(b"Connection: close\r\n\r\n")?;
+
+    let mut captured = Vec::new();
+    let mut buf = [0; 8192];
+    loop {
+        let n = response.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        // Flush every chunk so server-sent events reach Claude immediately
+        client.write_all(&buf[..n])?;
+        client.flush()?;
+        record.response_bytes += n as u64;
+        if captured.len() < USAGE_CAPTURE_LIMIT {
+            captured.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    (record.input_tokens, record.output_tokens) = parse_token_usage(&captured);
+    Ok(())
+}
+
+/// Decode a `Transfer-Encoding: chunked` request body.
+fn read_chunked(reader: &mut impl BufRead) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let mut size_line = String::new();
+        reader.read_line(&mut size_line)?;
+        let size_hex = size_line.trim().split(';').next().unwrap_or("");
+        let size = usize::from_str_radix(size_hex, 16)
+            .map_err(|_| Error::ProxyError(format!("Invalid chunk size: {size_hex:?}")))?;
+
+        if size == 0 {
+            // Skip optional trailers up to the terminating blank line
+            let mut line = String::new();
+            while reader.read_line(&mut line)? > 0 && !line.trim().is_empty() {
+                line.clear();
+            }
+            return Ok(body);
+        }
+
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..])?;
+        let mut crlf = [0; 2];
+        reader.read_exact(&mut crlf)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn chunked(body: &[u8]) -> Result<Vec<u8>> {
+        read_chunked(&mut Cursor::new(body))
+    }
+
+    #[test]
+    fn reads_chunked_bodies() {
+        assert_eq!(
+            chunked(b"5\r\nhello\r\n7\r\n, world\r\n0\r\n\r\n").unwrap(),
+            b"hello, world"
+        );
+        assert_eq!(chunked(b"0\r\n\r\n").unwrap(), b"");
+    }
+
+    #[test]
+    fn chunk_sizes_are_hex_and_may_have_extensions() {
+        let body = format!("1A;name=value\r\n{}\r\n0\r\n\r\n", "x".repeat(26));
+        assert_eq!(chunked(body.as_bytes()).unwrap(), "x".repeat(26).as_bytes());
+    }
+
+    #[test]
+    fn trailers_after_the_last_chunk_are_skipped() {
+        let mut reader = Cursor::new(&b"3\r\nabc\r\n0\r\nX-Trailer: 1\r\n\r\nNEXT"[..]);
+        assert_eq!(read_chunked(&mut reader).unwrap(), b"abc");
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "NEXT");
+    }
+
+    #[test]
+    fn invalid_or_truncated_chunks_are_errors() {
+        assert!(matches!(
+            chunked(b"zz\r\nabc\r\n0\r\n\r\n"),
+            Err(Error::ProxyError(_))
+        ));
+        assert!(chunked(b"").is_err());
+        assert!(chunked(b"10\r\nshort").is_err());
+    }
+
+    /// Answer one request with a Messages API response, returning the
+    /// upstream URL and the request line and body it received.
+    fn mock_upstream() -> (String, thread::JoinHandle<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = r#"{"usage":{"input_tokens":7,"output_tokens":3}}"#;
+            let mut stream = stream;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+            (
+                request_line.trim_end().to_string(),
+                String::from_utf8(body).unwrap(),
+            )
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn forwards_requests_and_logs_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("api-log-test.jsonl");
+        let (upstream, server) = mock_upstream();
+        let proxy = ApiProxy::start(&format!("{upstream}/"), log_path.clone()).unwrap();
+
+        let address = proxy.url().trim_start_matches("http://");
+        let mut client = TcpStream::connect(address).unwrap();
+        let request_body = r#"{"model":"claude"}"#;
+        write!(
+            client,
+            "POST /v1/messages?beta=true HTTP/1.1\r\nHost: {address}\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{request_body}\r\n0\r\n\r\n",
+            request_body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+
+        let (request_line, forwarded_body) = server.join().unwrap();
+        assert_eq!(request_line, "POST /v1/messages?beta=true HTTP/1.1");
+        assert_eq!(forwarded_body, request_body);
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+        assert!(response.ends_with(r#"{"usage":{"input_tokens":7,"output_tokens":3}}"#));
+
+        // The record is written after the response is sent
+        let mut records = Vec::new();
+        for _ in 0..100 {
+            records = super::super::read_api_log(&log_path).unwrap();
+            if !records.is_empty() {
+                break;
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.method, "POST");
+        assert_eq!(record.url, format!("{upstream}/v1/messages?beta=true"));
+        assert_eq!(record.status, 200);
+        assert_eq!(record.request_bytes, request_body.len() as u64);
+        assert_eq!(
+            (record.input_tokens, record.output_tokens),
+            (Some(7), Some(3))
+        );
+    }
+
+    #[test]
+    fn unreachable_upstream_is_a_bad_gateway() {
+        // Bind and drop to get a port nothing listens on
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let dir = tempfile::tempdir().unwrap();
+        let proxy = ApiProxy::start(
+            &format!("http://127.0.0.1:{port}"),
+            dir.path().join("api-log-test.jsonl"),
+        )
+        .unwrap();
+
+        let mut client = TcpStream::connect(proxy.url().trim_start_matches("http://")).unwrap();
+        write!(client, "GET /v1/models HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(
+            response.starts_with("HTTP/1.1 502 Bad Gateway\r\n"),
+            "{response}"
+        );
+    }
+}
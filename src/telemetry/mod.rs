@@ -0,0 +1,298 @@
+use crate::error::{Error, Result};
+use std::io::IsTerminal;
+use tracing::Span;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+/// Standard OTLP variable that must point at the collector for `use --otel`.
+pub const OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+#[cfg(feature = "otel")]
+static PROVIDER: std::sync::OnceLock<opentelemetry_sdk::trace::TracerProvider> =
+    std::sync::OnceLock::new();
+
+/// Install the global tracing subscriber, with an OpenTelemetry layer when
+/// `otel` is set.
+///
+/// Events are printed to stderr. Unless `RUST_LOG` says otherwise, only
+/// warnings are shown, or everything from `DEBUG` up when `verbose` is set.
+/// With OpenTelemetry, ccuse's spans are recorded at `INFO` or lower.
+/// Events are colored only if `color` is set and stderr is a terminal.
+///
+/// # Errors
+///
+/// Returns an error if `otel` is set but the exporter cannot be set up or
+/// this build lacks the `otel` feature.
+pub fn init_tracing(otel: bool, verbose: bool, color: bool) -> Result<()> {
+    let default_level = if verbose {
+        LevelFilter::DEBUG
+    } else if otel {
+        LevelFilter::INFO
+    } else {
+        LevelFilter::WARN
+    };
+    let filter = EnvFilter::builder()
+        .with_default_directive(default_level.into())
+        .from_env_lossy();
+    let registry = tracing_subscriber::registry().with(filter).with(
+        tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .with_ansi(color && std::io::stderr().is_terminal())
+            .with_target(false)
+            .without_time(),
+    );
+
+    if !otel {
+        registry.init();
+        return Ok(());
+    }
+
+    #[cfg(feature = "otel")]
+    {
+        let tracer = setup_otel_tracer()?;
+        registry
+            .with(tracing_opentelemetry::layer().with_tracer(tracer))
+            .init();
+        Ok(())
+    }
+
+    #[cfg(not(feature = "otel"))]
+    {
+        registry.init();
+        Err(Error::ConfigError(
+            "--otel requires ccuse to be built with the `otel` feature".into(),
+        ))
+    }
+}
+
+/// Create an OTLP/HTTP tracer for the collector at `OTEL_EXPORTER_OTLP_ENDPOINT`.
+///
+/// Spans are exported as soon as they end, so nothing is lost when ccuse
+/// exits right after the session.
+///
+/// # Errors
+///
+/// Returns an error if the endpoint is not set or the exporter cannot be built.
+#[cfg(feature = "otel")]
+pub fn setup_otel_tracer() -> Result<opentelemetry_sdk::trace::Tracer> {
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::KeyValue;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use opentelemetry_sdk::Resource;
+
+    if std::env::var_os(OTLP_ENDPOINT_ENV).is_none() {
+        return Err(Error::ConfigError(format!(
+            "--otel requires {OTLP_ENDPOINT_ENV} to be set"
+        )));
+    }
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()
+        .map_err(|e| Error::ConfigError(format!("Cannot create OTLP exporter: {e}")))?;
+
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter)
+        .with_resource(Resource::new([
+            KeyValue::new("service.name", "ccuse"),
+            KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+        ]))
+        .build();
+    let tracer = provider.tracer("ccuse");
+    PROVIDER.set(provider).ok();
+
+    Ok(tracer)
+}
+
+/// Flush and stop the OpenTelemetry exporter, if one was set up.
+pub fn shutdown() {
+    #[cfg(feature = "otel")]
+    if let Some(provider) = PROVIDER.get() {
+        if let Err(e) = provider.shutdown() {
+            eprintln!("Warning: failed to flush OpenTelemetry spans: {e}");
+        }
+    }
+}
+
+/// Parse a `--metric-tag` value.
+///
+/// # Errors
+///
+/// Returns an error if the value has no `=` or the key is empty.
+pub fn parse_metric_tag(tag: &str) -> Result<(String, String)> {
+    match tag.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(Error::ConfigError(format!(
+            "Invalid --metric-tag value '{tag}': expected KEY=VALUE"
+        ))),
+    }
+}
+
+/// Span covering one `ccuse use` session, tagged with `tags`.
+///
+/// Record the outcome with `span.record("exit_code", code)`.
+#[must_use]
+pub fn session_span(profile: &str, tags: &[(String, String)]) -> Span {
+    let span = tracing::info_span!("claude.session", profile, exit_code = tracing::field::Empty);
+
+    #[cfg(feature = "otel")]
+    {
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        for (key, value) in tags {
+            span.set_attribute(key.clone(), value.clone());
+        }
+    }
+    #[cfg(not(feature = "otel"))]
+    let _ = tags;
+
+    span
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn parses_metric_tags() {
+        assert_eq!(
+            parse_metric_tag("team=platform").unwrap(),
+            ("team".to_string(), "platform".to_string())
+        );
+        assert_eq!(
+            parse_metric_tag("query=a=b").unwrap(),
+            ("query".to_string(), "a=b".to_string())
+        );
+        assert_eq!(
+            parse_metric_tag("empty=").unwrap(),
+            ("empty".to_string(), String::new())
+        );
+        assert!(parse_metric_tag("no-separator").is_err());
+        assert!(parse_metric_tag("=value").is_err());
+    }
+
+    /// Records span names and the fields recorded on them.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<(String, String, String)>>>);
+
+    struct FieldVisitor<'a>(&'a Recorder, &'static str);
+
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0 .0.lock().unwrap().push((
+                self.1.to_string(),
+                field.name().to_string(),
+                format!("{value:?}"),
+            ));
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for Recorder
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            _id: &tracing::span::Id,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            attrs.record(&mut FieldVisitor(self, attrs.metadata().name()));
+        }
+
+        fn on_record(
+            &self,
+            id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let name = ctx.span(id).map_or("?", |span| span.name());
+            values.record(&mut FieldVisitor(self, name));
+        }
+    }
+
+    #[test]
+    fn session_span_records_profile_and_exit_code() {
+        let recorder = Recorder::default();
+        let subscriber = tracing_subscriber::registry().with(recorder.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = session_span("work", &[]);
+            span.record("exit_code", 3);
+        });
+
+        let fields = recorder.0.lock().unwrap().clone();
+        let field = |name: &str, value: &str| {
+            (
+                "claude.session".to_string(),
+                name.to_string(),
+                value.to_string(),
+            )
+        };
+        assert_eq!(
+            fields,
+            [field("profile", "\"work\""), field("exit_code", "3")]
+        );
+    }
+
+    #[cfg(feature = "otel")]
+    mod otel {
+        use super::super::*;
+        use opentelemetry::trace::TracerProvider as _;
+        use opentelemetry::{KeyValue, Value};
+        use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+        use opentelemetry_sdk::trace::TracerProvider;
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::sync::{Arc, Mutex};
+
+        /// Keeps exported spans in memory.
+        #[derive(Debug, Clone, Default)]
+        struct MemoryExporter(Arc<Mutex<Vec<SpanData>>>);
+
+        impl SpanExporter for MemoryExporter {
+            fn export(
+                &mut self,
+                batch: Vec<SpanData>,
+            ) -> Pin<Box<dyn Future<Output = ExportResult> + Send + 'static>> {
+                self.0.lock().unwrap().extend(batch);
+                Box::pin(std::future::ready(Ok(())))
+            }
+        }
+
+        #[test]
+        fn exported_session_span_carries_tags_and_exit_code() {
+            let exporter = MemoryExporter::default();
+            let provider = TracerProvider::builder()
+                .with_simple_exporter(exporter.clone())
+                .build();
+            let subscriber = tracing_subscriber::registry()
+                .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+            tracing::subscriber::with_default(subscriber, || {
+                let tags = [
+                    ("team".to_string(), "platform".to_string()),
+                    ("ticket".to_string(), "OPS-1".to_string()),
+                ];
+                let span = session_span("work", &tags);
+                span.record("exit_code", 0);
+            });
+
+            let spans = exporter.0.lock().unwrap();
+            assert_eq!(spans.len(), 1);
+            assert_eq!(spans[0].name, "claude.session");
+            let attribute = |key: &str| {
+                spans[0]
+                    .attributes
+                    .iter()
+                    .find(|KeyValue { key: k, .. }| k.as_str() == key)
+                    .map(|kv| kv.value.clone())
+            };
+            assert_eq!(attribute("profile"), Some(Value::from("work")));
+            assert_eq!(attribute("team"), Some(Value::from("platform")));
+            assert_eq!(attribute("ticket"), Some(Value::from("OPS-1")));
+            assert_eq!(attribute("exit_code"), Some(Value::I64(0)));
+        }
+    }
+}
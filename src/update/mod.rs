@@ -0,0 +1,166 @@
+use crate::error::{Error, Result};
+use semver::Version;
+
+/// GitHub API endpoint for the latest published ccuse release.
+pub const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/wjsoj/ccuse/releases/latest";
+
+/// Version of the running ccuse binary.
+///
+/// # Errors
+///
+/// Returns an error if `CARGO_PKG_VERSION` is not valid semver.
+pub fn current_version() -> Result<Version> {
+    Version::parse(env!("CARGO_PKG_VERSION"))
+        .map_err(|e| Error::VersionCheckError(format!("Invalid package version: {e}")))
+}
+
+/// Fetch the latest release version from GitHub.
+///
+/// # Errors
+///
+/// Returns an error if the request fails or the release tag is not valid semver.
+#[cfg(feature = "update-check")]
+pub fn get_latest_github_version() -> Result<Version> {
+    fetch_latest_version(LATEST_RELEASE_URL)
+}
+
+/// Fetch the release JSON at `url` and parse its version.
+#[cfg(feature = "update-check")]
+fn fetch_latest_version(url: &str) -> Result<Version> {
+    let body = reqwest::blocking::Client::new()
+        .get(url)
+        // GitHub rejects API requests without a User-Agent
+        .header("User-Agent", concat!("ccuse/", env!("CARGO_PKG_VERSION")))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .and_then(reqwest::blocking::Response::error_for_status)
+        .and_then(reqwest::blocking::Response::text)
+        .map_err(|e| Error::VersionCheckError(e.to_string()))?;
+
+    parse_release_version(&body)
+}
+
+/// Fetch the latest release version from GitHub.
+///
+/// # Errors
+///
+/// Always fails: this build does not include the `update-check` feature.
+#[cfg(not(feature = "update-check"))]
+pub fn get_latest_github_version() -> Result<Version> {
+    Err(Error::VersionCheckError(
+        "ccuse was built without the `update-check` feature".into(),
+    ))
+}
+
+/// Extract the version from a GitHub release JSON body, e.g. `{"tag_name": "v0.2.0"}`.
+///
+/// # Errors
+///
+/// Returns an error if the body has no `tag_name` or it is not valid semver.
+pub fn parse_release_version(body: &str) -> Result<Version> {
+    let release: serde_json::Value = serde_json::from_str(body)?;
+    let tag = release
+        .get("tag_name")
+        .and_then(serde_json::Value::as_str)
+        .ok_or_else(|| Error::VersionCheckError("Release has no tag_name".into()))?;
+
+    Version::parse(tag.trim_start_matches('v'))
+        .map_err(|e| Error::VersionCheckError(format!("Invalid release tag '{tag}': {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tag_with_and_without_v_prefix() {
+        assert_eq!(
+            parse_release_version(r#"{"tag_name": "v0.2.0", "name": "ccuse 0.2.0"}"#).unwrap(),
+            Version::new(0, 2, 0)
+        );
+        assert_eq!(
+            parse_release_version(r#"{"tag_name": "1.10.3"}"#).unwrap(),
+            Version::new(1, 10, 3)
+        );
+    }
+
+    #[test]
+    fn rejects_missing_or_invalid_tags() {
+        for body in [
+            r#"{"message": "Not Found"}"#,
+            r#"{"tag_name": null}"#,
+            r#"{"tag_name": "latest"}"#,
+            r#"{"tag_name": "v1.2"}"#,
+        ] {
+            assert!(
+                matches!(
+                    parse_release_version(body),
+                    Err(Error::VersionCheckError(_))
+                ),
+                "{body}"
+            );
+        }
+        assert!(parse_release_version("<html>").is_err());
+    }
+
+    #[test]
+    fn newer_release_compares_greater() {
+        let current = current_version().unwrap();
+        let newer = Version::new(current.major + 1, 0, 0);
+        assert!(newer > current);
+        assert!(Version::parse("0.0.1").unwrap() < current);
+    }
+
+    #[cfg(feature = "update-check")]
+    mod http {
+        use super::super::*;
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+        use std::thread::JoinHandle;
+
+        /// Serve one HTTP response on a local port, returning its URL and
+        /// the request line and headers the client sent.
+        fn serve_once(status: &str, body: &'static str) -> (String, JoinHandle<Vec<String>>) {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/releases/latest", listener.local_addr().unwrap());
+            let status = status.to_string();
+            let server = std::thread::spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                let request: Vec<String> = BufReader::new(stream.try_clone().unwrap())
+                    .lines()
+                    .map(std::result::Result::unwrap)
+                    .take_while(|line| !line.is_empty())
+                    .collect();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+                request
+            });
+            (url, server)
+        }
+
+        #[test]
+        fn fetches_latest_version_from_release_json() {
+            let (url, server) = serve_once("200 OK", r#"{"tag_name": "v9.8.7"}"#);
+            assert_eq!(fetch_latest_version(&url).unwrap(), Version::new(9, 8, 7));
+
+            let request = server.join().unwrap();
+            assert_eq!(request[0], "GET /releases/latest HTTP/1.1");
+            assert!(request.iter().any(|h| h
+                .eq_ignore_ascii_case(concat!("user-agent: ccuse/", env!("CARGO_PKG_VERSION")))));
+        }
+
+        #[test]
+        fn http_errors_are_version_check_errors() {
+            let (url, server) = serve_once("404 Not Found", r#"{"message": "Not Found"}"#);
+            assert!(matches!(
+                fetch_latest_version(&url),
+                Err(Error::VersionCheckError(_))
+            ));
+            server.join().unwrap();
+        }
+    }
+}
@@ -0,0 +1,805 @@
+//! End-to-end tests that run the built `ccuse` binary against a throwaway
+//! config directory and a stand-in `claude` script.
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+struct Sandbox {
+    dir: TempDir,
+}
+
+impl Sandbox {
+    fn new() -> Self {
+        Self {
+            dir: tempfile::tempdir().unwrap(),
+        }
+    }
+
+    fn config_dir(&self) -> PathBuf {
+        self.dir.path().join("config")
+    }
+
+    fn add_profile(&self, name: &str) {
+        self.add_profile_with(name, serde_json::json!({}));
+    }
+
+    /// Store a profile with `fields` on top of an empty env.
+    fn add_profile_with(&self, name: &str, fields: serde_json::Value) {
+        let profile_dir = self.config_dir().join(name);
+        fs::create_dir_all(&profile_dir).unwrap();
+        let mut settings = serde_json::json!({ "name": name, "env": {} });
+        for (key, value) in fields.as_object().unwrap() {
+            settings[key] = value.clone();
+        }
+        fs::write(profile_dir.join("settings.json"), settings.to_string()).unwrap();
+    }
+
+    fn fake_claude(&self, body: &str) -> PathBuf {
+        let path = self.dir.path().join("claude");
+        fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn ccuse(&self) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_ccuse"));
+        cmd.env("HOME", self.dir.path())
+            .env("CCUSE_CONFIG_DIR", self.config_dir())
+            .env("NO_COLOR", "1")
+            // A command that unexpectedly opens the editor fails instead of hanging
+            .env("VISUAL", "false")
+            .env_remove("CLAUDECODE")
+            .env_remove("RUST_LOG");
+        cmd
+    }
+}
+
+fn use_profile(sandbox: &Sandbox, name: &str, claude: &Path, extra: &[&str]) -> Command {
+    let mut cmd = sandbox.ccuse();
+    cmd.args(["use", name, "--claude-path"])
+        .arg(claude)
+        .args(extra);
+    cmd
+}
+
+#[test]
+fn measure_startup_json_records_every_checkpoint() {
+    let sandbox = Sandbox::new();
+    sandbox.add_profile("work");
+    let claude = sandbox.fake_claude("exit 0");
+
+    let output = use_profile(&sandbox, "work", &claude, &["--measure-startup-json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let report = stderr
+        .lines()
+        .find(|line| line.starts_with("{\"startup\""))
+        .unwrap_or_else(|| panic!("no startup report in: {stderr}"));
+    let report: serde_json::Value = serde_json::from_str(report).unwrap();
+    let entries = report["startup"].as_array().unwrap();
+
+    let labels: Vec<_> = entries
+        .iter()
+        .map(|e| e["label"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            "start",
+            "profile loaded",
+            "env resolved",
+            "launch entered",
+            "claude spawned"
+        ]
+    );
+    let cumulative: Vec<_> = entries
+        .iter()
+        .map(|e| e["cumulative_ms"].as_f64().unwrap())
+        .collect();
+    assert_eq!(cumulative[0], 0.0);
+    assert!(cumulative.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+/// Poll `condition` every 50ms for up to five seconds.
+fn wait_until(mut condition: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    condition()
+}
+
+/// Creates the file a stand-in Claude polls for, so it exits even if the test fails.
+struct StopFile(PathBuf);
+
+impl Drop for StopFile {
+    fn drop(&mut self) {
+        fs::write(&self.0, "").ok();
+    }
+}
+
+#[test]
+fn background_returns_while_claude_keeps_running() {
+    use nix::unistd::{getsid, Pid};
+
+    let sandbox = Sandbox::new();
+    sandbox.add_profile("work");
+    let pid_file = sandbox.dir.path().join("claude.pid");
+    let stop = StopFile(sandbox.dir.path().join("stop"));
+    // Claude must not hold ccuse's stdout open, or output() waits for it
+    let claude = sandbox.fake_claude(&format!(
+        "exec >/dev/null 2>&1\necho $$ > '{}'\nwhile [ ! -e '{}' ]; do sleep 0.05; done",
+        pid_file.display(),
+        stop.0.display()
+    ));
+
+    let output = use_profile(&sandbox, "work", &claude, &["--background"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+
+    assert!(wait_until(|| pid_file.exists()), "Claude never started");
+    let pid: i32 = fs::read_to_string(&pid_file)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(&format!("(PID {pid})")), "{stdout}");
+
+    // ccuse has exited; Claude is still running, in a session of its own
+    let pid = Pid::from_raw(pid);
+    assert!(is_running(pid), "Claude exited with ccuse");
+    assert_eq!(getsid(Some(pid)).unwrap(), pid);
+
+    drop(stop);
+    assert!(wait_until(|| !is_running(pid)), "Claude did not stop");
+}
+
+/// Whether `pid` is alive; an exited orphan waiting to be reaped is not.
+fn is_running(pid: nix::unistd::Pid) -> bool {
+    if nix::sys::signal::kill(pid, None).is_err() {
+        return false;
+    }
+    // The state follows the parenthesised command name in /proc/<pid>/stat
+    fs::read_to_string(format!("/proc/{pid}/stat")).map_or(true, |stat| {
+        !stat
+            .rsplit_once(") ")
+            .is_some_and(|(_, rest)| rest.starts_with('Z'))
+    })
+}
+
+#[test]
+fn list_by_template_prints_a_section_per_template() {
+    let sandbox = Sandbox::new();
+    sandbox.add_profile_with(
+        "work",
+        serde_json::json!({ "template_source": "openrouter" }),
+    );
+    sandbox.add_profile_with(
+        "home",
+        serde_json::json!({ "template_source": "anthropic" }),
+    );
+    sandbox.add_profile_with(
+        "proxy",
+        serde_json::json!({ "template_source": "openrouter" }),
+    );
+    sandbox.add_profile("manual");
+
+    let sections = |extra: &[&str]| {
+        let output = sandbox
+            .ccuse()
+            .args(["list", "--by-template", "--format", "table"])
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        // Each section is a header followed by a table of profile names
+        let mut sections: Vec<(String, Vec<String>)> = Vec::new();
+        for line in String::from_utf8(output.stdout).unwrap().lines() {
+            let first = line.split_whitespace().next().unwrap_or_default();
+            if line.is_empty() || first == "NAME" {
+                continue;
+            }
+            if line.starts_with("Template: ") || line == "No template" {
+                sections.push((line.to_string(), Vec::new()));
+            } else {
+                sections.last_mut().unwrap().1.push(first.to_string());
+            }
+        }
+        sections
+    };
+    let section = |header: &str, names: &[&str]| {
+        (
+            header.to_string(),
+            names.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        )
+    };
+
+    assert_eq!(
+        sections(&[]),
+        [
+            section("No template", &["manual"]),
+            section("Template: anthropic", &["home"]),
+            section("Template: openrouter", &["proxy", "work"]),
+        ]
+    );
+    assert_eq!(
+        sections(&["--template-only"]),
+        [
+            section("Template: anthropic", &["home"]),
+            section("Template: openrouter", &["proxy", "work"]),
+        ]
+    );
+}
+
+#[test]
+fn import_from_file_url_verifies_the_checksum() {
+    use sha2::{Digest, Sha256};
+
+    let sandbox = Sandbox::new();
+    fs::create_dir_all(sandbox.config_dir()).unwrap();
+    let content = r#"{"env":{"ANTHROPIC_BASE_URL":"https://team.example.com"}}"#;
+    let path = sandbox.dir.path().join("team.json");
+    fs::write(&path, content).unwrap();
+    let url = format!("file://{}", path.display());
+    let checksum = format!("{:x}", Sha256::digest(content.as_bytes()));
+
+    let import = |checksum: &str| {
+        sandbox
+            .ccuse()
+            .args([
+                "profile",
+                "import-from-url",
+                &url,
+                "--verify-checksum",
+                checksum,
+            ])
+            .output()
+            .unwrap()
+    };
+
+    let output = import(&"0".repeat(64));
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Checksum mismatch"));
+    assert!(!sandbox.config_dir().join("team").exists());
+
+    let output = import(&checksum.to_uppercase());
+    assert!(output.status.success(), "{output:?}");
+    let stored: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(sandbox.config_dir().join("team/settings.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(stored["name"], "team");
+    assert_eq!(
+        stored["env"]["ANTHROPIC_BASE_URL"],
+        "https://team.example.com"
+    );
+}
+
+/// A CC-Switch database holding `providers` as (name, settings JSON) pairs.
+fn ccswitch_db(path: &Path, providers: &[(&str, serde_json::Value)]) {
+    fs::remove_file(path).ok();
+    let conn = rusqlite::Connection::open(path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE providers (id text, app_type text, name text, settings_config text, created_at integer);",
+    )
+    .unwrap();
+    for (i, (name, settings)) in providers.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO providers VALUES (?1, 'claude', ?2, ?3, ?4)",
+            rusqlite::params![
+                format!("p{i}"),
+                name,
+                settings.to_string(),
+                1_700_000_000_000_i64 + i as i64
+            ],
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn update_diff_file_round_trips_through_apply() {
+    let sandbox = Sandbox::new();
+    let db = sandbox.dir.path().join("cc-switch.db");
+    let diff = sandbox.dir.path().join("diff.json");
+    let env = |url: &str| serde_json::json!({ "env": { "ANTHROPIC_BASE_URL": url } });
+    let update = |extra: &[&str]| {
+        sandbox
+            .ccuse()
+            .arg("update")
+            .arg("--db-path")
+            .arg(&db)
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+    let stored_url = |name: &str| {
+        let settings = sandbox.config_dir().join(name).join("settings.json");
+        let settings: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(settings).unwrap()).unwrap();
+        settings["env"]["ANTHROPIC_BASE_URL"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+
+    ccswitch_db(
+        &db,
+        &[
+            ("work", env("https://old.example.com")),
+            ("home", env("https://home.example.com")),
+        ],
+    );
+    let output = update(&[]);
+    assert!(output.status.success(), "{output:?}");
+
+    // CC-Switch moves one profile and gains another
+    ccswitch_db(
+        &db,
+        &[
+            ("work", env("https://new.example.com")),
+            ("home", env("https://home.example.com")),
+            ("extra", env("https://extra.example.com")),
+        ],
+    );
+    let diff_arg = diff.to_str().unwrap();
+    let output = update(&[
+        "--dry-run",
+        "--fail-on-changes",
+        "--output-diff-file",
+        diff_arg,
+    ]);
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+    assert_eq!(stored_url("work"), "https://old.example.com");
+    assert!(!sandbox.config_dir().join("extra").exists());
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&diff).unwrap()).unwrap();
+    assert_eq!(report["profiles_to_add"][0]["name"], "extra");
+    assert_eq!(report["profiles_unchanged"], serde_json::json!(["home"]));
+    assert_eq!(
+        report["profiles_to_update"],
+        serde_json::json!([{
+            "name": "work",
+            "changes": [{
+                "field": "env.ANTHROPIC_BASE_URL",
+                "old": "https://old.example.com",
+                "new": "https://new.example.com",
+            }],
+        }])
+    );
+
+    // Applying the saved diff makes the stored profiles match CC-Switch
+    let output = sandbox
+        .ccuse()
+        .args(["update", "--apply-diff-file"])
+        .arg(&diff)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(stored_url("work"), "https://new.example.com");
+    assert_eq!(stored_url("extra"), "https://extra.example.com");
+
+    let output = update(&["--dry-run", "--fail-on-changes"]);
+    assert!(output.status.success(), "{output:?}");
+}
+
+#[test]
+fn apply_diff_file_skips_fields_changed_since() {
+    let sandbox = Sandbox::new();
+    sandbox.add_profile_with(
+        "work",
+        serde_json::json!({ "env": { "ANTHROPIC_BASE_URL": "https://edited.example.com" } }),
+    );
+    let diff = sandbox.dir.path().join("diff.json");
+    fs::write(
+        &diff,
+        serde_json::json!({
+            "timestamp": "2026-01-01T00:00:00Z",
+            "profiles_to_add": [],
+            "profiles_to_update": [{
+                "name": "work",
+                "changes": [
+                    {
+                        "field": "env.ANTHROPIC_BASE_URL",
+                        "old": "https://old.example.com",
+                        "new": "https://new.example.com",
+                    },
+                    { "field": "env.API_TIMEOUT_MS", "old": null, "new": "600000" },
+                ],
+            }],
+            "profiles_unchanged": [],
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let output = sandbox
+        .ccuse()
+        .args(["update", "--apply-diff-file"])
+        .arg(&diff)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("changed since the diff was written"));
+
+    let settings: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(sandbox.config_dir().join("work/settings.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        settings["env"],
+        serde_json::json!({
+            "ANTHROPIC_BASE_URL": "https://edited.example.com",
+            "API_TIMEOUT_MS": "600000",
+        })
+    );
+}
+
+#[test]
+fn killing_a_write_never_leaves_invalid_json() {
+    let sandbox = Sandbox::new();
+    // Large enough that writing it takes a while
+    let env: serde_json::Map<_, _> = (0..20_000)
+        .map(|i| (format!("VAR_{i}"), serde_json::json!("x".repeat(200))))
+        .collect();
+    sandbox.add_profile_with("work", serde_json::json!({ "env": env }));
+    let settings = sandbox.config_dir().join("work/settings.json");
+    let load = || -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(&settings).unwrap()).unwrap()
+    };
+
+    let rename = |display_name: &str| {
+        sandbox
+            .ccuse()
+            .args(["profile", "rename-display", "work", display_name])
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap()
+    };
+    let started = Instant::now();
+    assert!(rename("Work").wait().unwrap().success());
+    let full_run = started.elapsed();
+
+    for attempt in 0..8u32 {
+        let mut child = rename(&format!("Work {attempt}"));
+        // Read while it writes, then kill it a little later each time
+        let deadline = Instant::now() + full_run * attempt / 6;
+        while Instant::now() < deadline && child.try_wait().unwrap().is_none() {
+            // Reading the whole file is too slow to catch a write in progress
+            assert_eq!(file_ends(&settings), (b'{', b'}'), "partial file seen");
+        }
+        child.kill().ok();
+        child.wait().unwrap();
+        assert_eq!(load()["env"].as_object().unwrap().len(), 20_000);
+    }
+
+    // Temporary files left by killed writes do not get in the way
+    assert!(rename("Renamed").wait().unwrap().success());
+    assert_eq!(load()["display_name"], "Renamed");
+}
+
+/// The first and last byte of the file at `path`.
+fn file_ends(path: &Path) -> (u8, u8) {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = fs::File::open(path).unwrap();
+    let (mut first, mut last) = ([0], [0]);
+    file.read_exact(&mut first).unwrap_or_default();
+    if file.seek(SeekFrom::End(-1)).is_ok() {
+        file.read_exact(&mut last).unwrap();
+    }
+    (first[0], last[0])
+}
+
+#[test]
+fn config_dir_comes_from_the_flag_then_the_env_var() {
+    let sandbox = Sandbox::new();
+    let home = sandbox.dir.path();
+    let config_dir = |cmd: &mut Command| {
+        let output = cmd.current_dir(home).arg("config-dir").output().unwrap();
+        assert!(output.status.success(), "{output:?}");
+        PathBuf::from(String::from_utf8(output.stdout).unwrap().trim())
+    };
+
+    // A relative path is taken from the working directory
+    let mut cmd = sandbox.ccuse();
+    cmd.env("CCUSE_CONFIG_DIR", "relative");
+    assert_eq!(config_dir(&mut cmd), home.join("relative"));
+
+    let mut cmd = sandbox.ccuse();
+    cmd.env("CCUSE_CONFIG_DIR", "relative")
+        .arg("--config-dir")
+        .arg(home.join("flag"));
+    assert_eq!(config_dir(&mut cmd), home.join("flag"));
+
+    // An empty variable is the same as an unset one
+    let mut cmd = sandbox.ccuse();
+    cmd.env("CCUSE_CONFIG_DIR", "")
+        .env_remove("XDG_CONFIG_HOME");
+    assert_eq!(config_dir(&mut cmd), home.join(".config/ccuse"));
+}
+
+#[test]
+fn config_dir_override_is_created_and_passed_to_claude() {
+    let sandbox = Sandbox::new();
+    let scratch = sandbox.dir.path().join("ci/scratch");
+    let seen = sandbox.dir.path().join("seen");
+    let claude = sandbox.fake_claude(&format!(
+        "printf %s \"$CCUSE_CONFIG_DIR\" > '{}'",
+        seen.display()
+    ));
+
+    let output = sandbox
+        .ccuse()
+        .env_remove("CCUSE_CONFIG_DIR")
+        .arg("--config-dir")
+        .arg(&scratch)
+        .args([
+            "add",
+            "--name",
+            "work",
+            "--base-url",
+            "https://work.example.com",
+            "--token",
+            "sk-test",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert!(scratch.join("work/settings.json").is_file());
+    assert!(!sandbox.config_dir().exists());
+
+    let output = sandbox
+        .ccuse()
+        .env_remove("CCUSE_CONFIG_DIR")
+        .arg("--config-dir")
+        .arg(&scratch)
+        .args(["use", "work", "--claude-path"])
+        .arg(&claude)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        fs::read_to_string(&seen).unwrap(),
+        scratch.to_str().unwrap()
+    );
+}
+
+#[test]
+fn traversal_names_are_rejected_before_touching_the_filesystem() {
+    let sandbox = Sandbox::new();
+    sandbox.add_profile("work");
+    let outside = sandbox.dir.path().join("evil");
+
+    for args in [
+        ["clone", "work", "../evil"],
+        ["rename", "work", "../evil"],
+        ["rename", "work", "backups"],
+    ] {
+        let output = sandbox.ccuse().args(args).output().unwrap();
+        assert!(!output.status.success(), "{args:?}");
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("Invalid profile name"),
+            "{output:?}"
+        );
+    }
+
+    assert!(!outside.exists());
+    assert!(!sandbox.config_dir().join("backups").exists());
+    assert!(sandbox.config_dir().join("work/settings.json").is_file());
+}
+
+#[test]
+fn list_tag_filters_require_every_tag() {
+    let sandbox = Sandbox::new();
+    sandbox.add_profile_with("work", serde_json::json!({ "tags": ["Work", "cheap"] }));
+    sandbox.add_profile_with("proxy", serde_json::json!({ "tags": ["work"] }));
+    sandbox.add_profile("home");
+
+    let listed = |tags: &[&str]| {
+        let mut cmd = sandbox.ccuse();
+        cmd.args(["list", "--format", "json"]);
+        for tag in tags {
+            cmd.args(["--tag", tag]);
+        }
+        let output = cmd.output().unwrap();
+        assert!(output.status.success(), "{output:?}");
+        let profiles: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+        let mut names: Vec<String> = profiles
+            .iter()
+            .map(|p| p["name"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    };
+
+    assert_eq!(listed(&[]), ["home", "proxy", "work"]);
+    assert_eq!(listed(&["WORK"]), ["proxy", "work"]);
+    assert_eq!(listed(&["work", "cheap"]), ["work"]);
+    assert!(listed(&["missing"]).is_empty());
+}
+
+/// The settings file a stand-in Claude gets from `ccuse use <name> <extra>`
+/// run with the extra environment `envs`.
+fn settings_seen_by_claude(
+    sandbox: &Sandbox,
+    name: &str,
+    extra: &[&str],
+    envs: &[(&str, &str)],
+) -> serde_json::Value {
+    let seen = sandbox.dir.path().join("seen.json");
+    // $1 is --settings, $2 the file
+    let claude = sandbox.fake_claude(&format!("cp \"$2\" '{}'", seen.display()));
+    let output = use_profile(sandbox, name, &claude, extra)
+        .envs(envs.iter().copied())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_str(&fs::read_to_string(seen).unwrap()).unwrap()
+}
+
+#[test]
+fn env_overrides_reach_the_settings_file() {
+    let sandbox = Sandbox::new();
+    sandbox.add_profile_with(
+        "work",
+        serde_json::json!({ "env": {
+            "ANTHROPIC_BASE_URL": "https://work.example.com",
+            "ANTHROPIC_MODEL": "sonnet",
+            "API_TIMEOUT_MS": "1000",
+        } }),
+    );
+    let settings = settings_seen_by_claude(
+        &sandbox,
+        "work",
+        &[
+            "--env",
+            "ANTHROPIC_BASE_URL=https://override.example.com",
+            "--env-from-json",
+            r#"{"ANTHROPIC_MODEL":"opus"}"#,
+        ],
+        &[],
+    );
+
+    assert_eq!(
+        settings["env"],
+        serde_json::json!({
+            "ANTHROPIC_BASE_URL": "https://override.example.com",
+            "ANTHROPIC_MODEL": "opus",
+            "API_TIMEOUT_MS": "1000",
+        })
+    );
+    let stored: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(sandbox.config_dir().join("work/settings.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        stored["env"]["ANTHROPIC_BASE_URL"],
+        "https://work.example.com"
+    );
+    assert_eq!(stored["env"]["ANTHROPIC_MODEL"], "sonnet");
+}
+
+#[test]
+fn env_substitute_reaches_the_settings_file() {
+    let sandbox = Sandbox::new();
+    sandbox.add_profile_with(
+        "ci",
+        serde_json::json!({ "env": {
+            "ANTHROPIC_AUTH_TOKEN": "$CI_TOKEN",
+            "ANTHROPIC_BASE_URL": "https://${CI_HOST}/v1",
+        } }),
+    );
+    let envs = [("CI_TOKEN", "sk-ci-token"), ("CI_HOST", "ci.example.com")];
+
+    let settings = settings_seen_by_claude(&sandbox, "ci", &["--env-substitute"], &envs);
+    assert_eq!(
+        settings["env"],
+        serde_json::json!({
+            "ANTHROPIC_AUTH_TOKEN": "sk-ci-token",
+            "ANTHROPIC_BASE_URL": "https://ci.example.com/v1",
+        })
+    );
+
+    // Without the flag the references are passed on as they are
+    let settings = settings_seen_by_claude(&sandbox, "ci", &[], &envs);
+    assert_eq!(settings["env"]["ANTHROPIC_AUTH_TOKEN"], "$CI_TOKEN");
+}
+
+#[test]
+fn expanded_templates_reach_the_settings_file() {
+    let sandbox = Sandbox::new();
+    sandbox.add_profile_with(
+        "base",
+        serde_json::json!({ "env": {
+            "HOST": "proxy.example.com",
+            "ANTHROPIC_BASE_URL": "https://${HOST}/v1",
+        } }),
+    );
+    sandbox.add_profile_with(
+        "child",
+        serde_json::json!({ "extends": "base", "env": { "HOST": "child.example.com" } }),
+    );
+
+    // Stored as is, and composed from a parent
+    for (name, url) in [
+        ("base", "https://proxy.example.com/v1"),
+        ("child", "https://child.example.com/v1"),
+    ] {
+        let settings = settings_seen_by_claude(&sandbox, name, &[], &[]);
+        assert_eq!(settings["env"]["ANTHROPIC_BASE_URL"], url, "{name}");
+    }
+}
+
+#[test]
+fn model_override_reaches_the_settings_file() {
+    let sandbox = Sandbox::new();
+    sandbox.add_profile_with(
+        "work",
+        serde_json::json!({ "env": {
+            "ANTHROPIC_BASE_URL": "https://work.example.com",
+            "ANTHROPIC_DEFAULT_OPUS_MODEL": "claude-opus-4-1",
+        } }),
+    );
+
+    let settings = settings_seen_by_claude(
+        &sandbox,
+        "work",
+        &["--model-override", "claude-opus-4-5"],
+        &[],
+    );
+
+    assert_eq!(
+        settings["env"]["ANTHROPIC_DEFAULT_OPUS_MODEL"],
+        "claude-opus-4-5"
+    );
+}
+
+#[test]
+fn port_forward_reaches_the_settings_file() {
+    let sandbox = Sandbox::new();
+    sandbox.add_profile_with(
+        "work",
+        serde_json::json!({ "env": { "ANTHROPIC_BASE_URL": "https://api.example.com/v1" } }),
+    );
+
+    let settings = settings_seen_by_claude(&sandbox, "work", &["--port-forward", "8443"], &[]);
+
+    assert_eq!(
+        settings["env"]["ANTHROPIC_BASE_URL"],
+        "https://localhost:8443/v1"
+    );
+    assert_eq!(
+        settings["env"]["CCUSE_ORIGINAL_BASE_URL"],
+        "https://api.example.com/v1"
+    );
+}
+
+#[cfg(feature = "api-capture")]
+#[test]
+fn capture_proxy_url_reaches_the_settings_file() {
+    let sandbox = Sandbox::new();
+    sandbox.add_profile_with(
+        "work",
+        serde_json::json!({ "env": { "ANTHROPIC_BASE_URL": "https://api.example.com" } }),
+    );
+
+    let settings = settings_seen_by_claude(&sandbox, "work", &["--capture-api-calls"], &[]);
+
+    let url = settings["env"]["ANTHROPIC_BASE_URL"].as_str().unwrap();
+    assert!(url.starts_with("http://127.0.0.1:"), "{url}");
+}